@@ -1,16 +1,16 @@
 use std::sync::Arc;
-use std::time::Duration;
 
-use valar::database::Database;
+// use valar::database::Database;
 // use valar::http::session::Session;
-use valar::services::cache::MemoryCache;
+use valar::services::cache::manager::CacheConfig;
+use valar::services::cache::CacheManager;
 use valar::services::Cacheable;
 // use valar::services::Service;
 // use valar::services::Singleton;
 
 pub struct App {
     // pub database: Database,
-    // pub cache: Arc<Cacheable>,
+    pub cache: Arc<Cacheable>,
 }
 
 // impl Singleton<Cacheable> for App {
@@ -20,9 +20,11 @@ pub struct App {
 // }
 
 impl App {
-    // fn cache() -> Arc<Cacheable> {
-    //     Arc::new(MemoryCache::new(Duration::from_secs(1)))
-    // }
+    fn cache() -> Arc<Cacheable> {
+        CacheConfig::from_env()
+            .and_then(|config| CacheManager::build(&config))
+            .expect("Unable to configure the cache")
+    }
 
     pub async fn create() -> Self {
         // let database = Database::connect("host=localhost
@@ -31,7 +33,7 @@ impl App {
 
         Self {
             // database,
-            // cache: Self::cache(),
+            cache: Self::cache(),
         }
     }
 
@@ -1,12 +1,10 @@
-use std::sync::Arc;
-
 use valar::http::Request;
 use valar::http::Response;
 use valar::http::Result;
 
 use crate::App;
 
-pub async fn show(request: Request<App>) -> Result {
+pub async fn show(_request: Request<App>) -> Result {
     let count = 1;
     // let app = request.app();
     // let session = request.session()?;
@@ -32,7 +30,7 @@ pub async fn show(request: Request<App>) -> Result {
         .into_ok()
 }
 
-pub async fn increment(request: Request<App>) -> Result {
+pub async fn increment(_request: Request<App>) -> Result {
     // let session = request.session()?;
 
     // let count: i32 = session
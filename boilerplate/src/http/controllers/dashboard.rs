@@ -1,36 +1,22 @@
 // use std::sync::Arc;
 
 // use serde::Serialize;
-// use tokio_postgres::Error as PGError;
 // use valar::database::builder::Whereable;
 // use valar::database::Database;
 // use valar::database::Executor;
-// use valar::database::Row;
+// use valar::database::FromRow;
 // use valar::http::Request;
 // use valar::http::Response;
 // use valar::http::Result;
 
 // use crate::App;
 
-// #[derive(Debug, Serialize)]
+// #[derive(Debug, Serialize, FromRow)]
 // struct User {
 //     id: i32,
 //     name: String,
 // }
 
-// impl TryFrom<Row> for User {
-//     type Error = PGError;
-
-//     fn try_from(row: Row) -> std::result::Result<Self,
-// Self::Error> {         let user = Self {
-//             id: row.try_get("id")?,
-//             name: row.try_get("name")?,
-//         };
-
-//         Ok(user)
-//     }
-// }
-
 // pub async fn index(request: Request<App>) -> Result {
 //     let rows: Vec<User> = Database::table("users")
 //         .select_all()
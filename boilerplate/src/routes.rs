@@ -1,11 +1,14 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use valar::http::middleware::Logger;
 use valar::http::middleware::Session;
+use valar::http::session::CacheStore;
 use valar::routing::route::Builder as Route;
 use valar::routing::router::Compiled;
 use valar::routing::router::Error;
 use valar::routing::Router;
+use valar::services::cache::MemoryCache;
 
 use crate::app::App;
 // use crate::http::controllers::dashboard;
@@ -21,8 +24,9 @@ impl App {
 
     pub fn router() -> Result<Arc<Router<Self, Compiled>>, Error> {
         let web = Self::web();
+        let sessions = CacheStore::new(Arc::new(MemoryCache::new(Duration::from_secs(60))));
 
-        let router = Router::from_iter([web.middleware(Session)]).middleware(Logger);
+        let router = Router::from_iter([web.middleware(Session::new(sessions))]).middleware(Logger);
         let router = Arc::new(router.compile()?);
 
         Ok(router)
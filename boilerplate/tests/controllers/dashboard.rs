@@ -1,8 +1,8 @@
-use std::sync::Arc;
+// use std::sync::Arc;
 
-use boilerplate::App;
-use valar::http::Request;
-use valar::http::Uri;
+// use boilerplate::App;
+// use valar::http::Request;
+// use valar::http::Uri;
 
 // #[tokio::test]
 // async fn it_has_a_homepage() {
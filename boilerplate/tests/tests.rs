@@ -1,2 +1,2 @@
 #[path = "controllers/dashboard.rs"]
-mod dashboard;
\ No newline at end of file
+mod dashboard;
@@ -0,0 +1,298 @@
+//! Attribute macros to declare Valar route handlers next
+//! to their paths, and derives for database models. Enable
+//! them with the `macros` feature of the `valar` crate.
+//!
+//! ```ignore
+//! use valar::get;
+//! use valar::http::Request;
+//! use valar::http::Response;
+//! use valar::http::Result;
+//! use valar::routes;
+//! use valar::routing::Router;
+//!
+//! #[get("/users/:id", name = "users.show")]
+//! async fn show(request: Request<App>) -> Result {
+//!     Response::ok().into_ok()
+//! }
+//!
+//! let router = Router::from_iter(routes![show]);
+//! ```
+use proc_macro::TokenStream;
+use proc_macro2::Ident;
+use proc_macro2::Span;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::parse::Parse;
+use syn::parse::ParseStream;
+use syn::parse_macro_input;
+use syn::punctuated::Punctuated;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Error;
+use syn::Fields;
+use syn::FnArg;
+use syn::GenericArgument;
+use syn::ItemFn;
+use syn::LitStr;
+use syn::Path;
+use syn::PathArguments;
+use syn::Token;
+use syn::Type;
+
+/// The arguments of a route attribute: the path and an
+/// optional route name.
+struct RouteArgs {
+    path: LitStr,
+    name: Option<LitStr>,
+}
+
+impl Parse for RouteArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path: LitStr = input.parse()?;
+        let mut name = None;
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+
+            if input.is_empty() {
+                break;
+            }
+
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+
+            match key.to_string().as_str() {
+                "name" => name = Some(value),
+                _ => return Err(Error::new(key.span(), "unknown route argument")),
+            }
+        }
+
+        Ok(Self { path, name })
+    }
+}
+
+/// Returns the application type of the handler, taken from
+/// its `Request<App>` argument.
+fn app_type(handler: &ItemFn) -> syn::Result<Type> {
+    let error = || {
+        Error::new_spanned(
+            &handler.sig,
+            "route handlers must take a single `Request<App>` argument",
+        )
+    };
+
+    let argument = match handler.sig.inputs.first() {
+        Some(FnArg::Typed(argument)) => argument,
+        _ => return Err(error()),
+    };
+
+    let path = match argument.ty.as_ref() {
+        Type::Path(path) => path,
+        _ => return Err(error()),
+    };
+
+    let segment = path.path.segments.last().ok_or_else(error)?;
+
+    let arguments = match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => arguments,
+        _ => return Err(error()),
+    };
+
+    match arguments.args.first() {
+        Some(GenericArgument::Type(app)) => Ok(app.clone()),
+        _ => Err(error()),
+    }
+}
+
+/// Expands a route attribute. The handler is kept as is
+/// and a module with the same name is generated next to
+/// it, holding the `route` function used by `routes!`.
+fn expand(method: &str, args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as RouteArgs);
+    let handler = parse_macro_input!(item as ItemFn);
+
+    let app = match app_type(&handler) {
+        Ok(app) => app,
+        Err(error) => return error.to_compile_error().into(),
+    };
+
+    let vis = &handler.vis;
+    let ident = &handler.sig.ident;
+    let path = &args.path;
+    let method = Ident::new(method, Span::call_site());
+
+    let name: TokenStream2 = match &args.name {
+        Some(name) => quote! { .name(#name) },
+        None => quote! {},
+    };
+
+    let expanded = quote! {
+        #handler
+
+        #[doc(hidden)]
+        #[allow(non_snake_case, unused_imports)]
+        #vis mod #ident {
+            use super::*;
+
+            pub fn route() -> ::valar::routing::route::Builder<#app> {
+                ::valar::routing::route::Builder::#method(#path, super::#ident) #name
+            }
+        }
+    };
+
+    expanded.into()
+}
+
+/// Declares a GET route handler. GET routes also answer
+/// HEAD requests.
+#[proc_macro_attribute]
+pub fn get(args: TokenStream, item: TokenStream) -> TokenStream {
+    expand("get", args, item)
+}
+
+/// Declares a POST route handler.
+#[proc_macro_attribute]
+pub fn post(args: TokenStream, item: TokenStream) -> TokenStream {
+    expand("post", args, item)
+}
+
+/// Declares a PUT route handler.
+#[proc_macro_attribute]
+pub fn put(args: TokenStream, item: TokenStream) -> TokenStream {
+    expand("put", args, item)
+}
+
+/// Declares a PATCH route handler.
+#[proc_macro_attribute]
+pub fn patch(args: TokenStream, item: TokenStream) -> TokenStream {
+    expand("patch", args, item)
+}
+
+/// Declares a DELETE route handler.
+#[proc_macro_attribute]
+pub fn delete(args: TokenStream, item: TokenStream) -> TokenStream {
+    expand("delete", args, item)
+}
+
+/// Declares a route handler that answers all methods.
+#[proc_macro_attribute]
+pub fn any(args: TokenStream, item: TokenStream) -> TokenStream {
+    expand("any", args, item)
+}
+
+/// Collects the routes of the given annotated handlers
+/// into a `Vec` of route builders, ready to be used with
+/// `Router::from_iter` or `Route::group`.
+#[proc_macro]
+pub fn routes(input: TokenStream) -> TokenStream {
+    let parser = Punctuated::<Path, Token![,]>::parse_terminated;
+    let handlers = parse_macro_input!(input with parser);
+    let handlers = handlers.iter();
+
+    let expanded = quote! {
+        ::std::vec![#(#handlers::route()),*]
+    };
+
+    expanded.into()
+}
+
+/// Returns the column a field is read from: the name set
+/// with `#[column("...")]`, if any.
+fn column(field: &syn::Field) -> syn::Result<Option<LitStr>> {
+    let mut column = None;
+
+    for attribute in &field.attrs {
+        if attribute.path().is_ident("column") {
+            column = Some(attribute.parse_args::<LitStr>()?);
+        }
+    }
+
+    Ok(column)
+}
+
+/// Expands the `FromRow` derive.
+fn expand_from_row(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                ident,
+                "FromRow can only be derived for structs",
+            ))
+        }
+    };
+
+    let body = match fields {
+        Fields::Named(fields) => {
+            let fields = fields
+                .named
+                .iter()
+                .map(|field| {
+                    let name = field.ident.as_ref().expect("named fields have a name");
+                    let column = match column(field)? {
+                        Some(column) => column,
+                        None => LitStr::new(&name.unraw().to_string(), name.span()),
+                    };
+
+                    Ok(quote! { #name: row.try_get(#column)? })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            quote! { Self { #(#fields),* } }
+        }
+        Fields::Unnamed(fields) => {
+            let fields = fields
+                .unnamed
+                .iter()
+                .enumerate()
+                .map(|(index, field)| match column(field)? {
+                    Some(column) => Ok(quote! { row.try_get(#column)? }),
+                    None => Ok(quote! { row.try_get(#index)? }),
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            quote! { Self(#(#fields),*) }
+        }
+        Fields::Unit => quote! { Self },
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::std::convert::TryFrom<::valar::database::Row> for #ident #ty_generics #where_clause {
+            type Error = ::valar::database::Error;
+
+            fn try_from(row: ::valar::database::Row) -> ::std::result::Result<Self, Self::Error> {
+                ::std::result::Result::Ok(#body)
+            }
+        }
+    })
+}
+
+/// Derives `TryFrom<Row>` for a struct, reading every field
+/// from the column with the same name. Use
+/// `#[column("...")]` to read a field from another column.
+/// Tuple structs read their fields by position.
+///
+/// ```ignore
+/// use valar::database::FromRow;
+///
+/// #[derive(FromRow)]
+/// struct User {
+///     id: i32,
+///     #[column("full_name")]
+///     name: String,
+/// }
+/// ```
+#[proc_macro_derive(FromRow, attributes(column))]
+pub fn from_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand_from_row(input) {
+        Ok(expanded) => expanded.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,41 @@
+use valar::database::Error;
+use valar::database::FromRow;
+use valar::database::Row;
+use valar::database::Value;
+
+#[derive(FromRow)]
+#[allow(dead_code)]
+struct User {
+    id: i32,
+    #[column("full_name")]
+    name: String,
+}
+
+#[derive(FromRow)]
+#[allow(dead_code)]
+struct Count(i64);
+
+#[derive(FromRow)]
+struct Token {
+    r#type: String,
+}
+
+fn from_row<T>()
+where
+    T: TryFrom<Row, Error = Error>,
+{
+}
+
+#[test]
+fn it_can_derive_from_row() {
+    from_row::<User>();
+    from_row::<Count>();
+}
+
+#[test]
+fn it_reads_raw_identifiers_without_their_prefix() {
+    let row = Row::new(["type"], [Value::Text("bearer".to_string())]);
+    let token = Token::try_from(row).unwrap();
+
+    assert_eq!(token.r#type, "bearer");
+}
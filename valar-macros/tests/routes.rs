@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use valar::get;
+use valar::http::Method;
+use valar::http::Request;
+use valar::http::Response;
+use valar::http::Result;
+use valar::http::Uri;
+use valar::post;
+use valar::routes;
+use valar::routing::route::Builder as Route;
+use valar::routing::Router;
+
+struct App;
+
+mod users {
+    use valar::get;
+    use valar::http::Request;
+    use valar::http::Response;
+    use valar::http::Result;
+
+    use super::App;
+
+    #[get("/users/:id", name = "users.show")]
+    pub async fn show(request: Request<App>) -> Result {
+        let id: String = request.parameter("id")?;
+
+        Response::ok().body(format!("user {id}")).into_ok()
+    }
+}
+
+#[get("/")]
+async fn home(_request: Request<App>) -> Result {
+    Response::ok().body("home").into_ok()
+}
+
+#[post("/users", name = "users.store")]
+async fn store(_request: Request<App>) -> Result {
+    Response::created().into_ok()
+}
+
+#[tokio::test]
+async fn it_can_collect_annotated_routes() {
+    let app = Arc::new(App);
+
+    let router = Router::from_iter([Route::group(routes![home, store, users::show])])
+        .compile()
+        .unwrap();
+
+    let home = router
+        .handle(Request::get(Uri::from_static("/")).build(app.clone()))
+        .await;
+    let user = router
+        .handle(Request::get(Uri::from_static("/users/5")).build(app.clone()))
+        .await;
+    let created = router
+        .handle(
+            Request::builder()
+                .method(Method::POST)
+                .uri(Uri::from_static("/users"))
+                .build(app),
+        )
+        .await;
+
+    assert_eq!(home.body(), "home");
+    assert_eq!(user.body(), "user 5");
+    assert_eq!(created.status().as_u16(), 201);
+    assert_eq!(router.url("users.show", [("id", "7")]).unwrap(), "/users/7");
+    assert_eq!(
+        router.url("users.store", [("a", "b")]).unwrap(),
+        "/users?a=b"
+    );
+}
@@ -1,44 +1,346 @@
+pub mod backend;
 pub mod builder;
+pub mod connection;
+pub mod error;
 pub mod executor;
+pub mod instrumentation;
+pub mod migrations;
+pub mod model;
+mod postgres;
 pub mod query;
+mod replicas;
+pub mod row;
+pub mod seeders;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+pub mod testing;
+#[cfg(feature = "postgres-tls")]
+pub mod tls;
+pub mod transaction;
+pub mod value;
 
-pub use tokio_postgres::types::ToSql;
-pub use tokio_postgres::Client;
-pub use tokio_postgres::Config;
-pub use tokio_postgres::Error as PGError;
-pub use tokio_postgres::Row;
+use std::time::Duration;
 
+use async_trait::async_trait;
+
+use crate::database::instrumentation::Instrumentation;
+use crate::database::postgres::Postgres;
+use crate::database::replicas::Replicas;
+
+#[cfg(feature = "macros")]
+pub use valar_macros::FromRow;
+
+pub use crate::database::backend::Backend;
 pub use crate::database::builder::QueryBuilder;
+use crate::database::builder::Table;
+pub use crate::database::connection::Connection;
+pub use crate::database::connection::Dialect;
+pub use crate::database::error::Error;
 pub use crate::database::executor::Executor;
+pub use crate::database::instrumentation::Query;
+pub use crate::database::model::Model;
 pub use crate::database::query::PendingQuery;
+pub use crate::database::replicas::Handle;
+pub use crate::database::row::ColumnIndex;
+pub use crate::database::row::Row;
+#[cfg(feature = "postgres-tls")]
+pub use crate::database::tls::Tls;
+pub use crate::database::transaction::Transaction;
+pub use crate::database::value::FromSql;
+pub use crate::database::value::Json;
+pub use crate::database::value::ToSql;
+pub use crate::database::value::Value;
 
 pub struct Database {
-    client: Client,
+    backend: Box<dyn Backend>,
+    replicas: Replicas,
+    instrumentation: Instrumentation,
 }
 
 impl Database {
-    pub async fn connect(url: &str) -> Result<Database, PGError> {
-        let (client, connection) = tokio_postgres::connect(url, tokio_postgres::NoTls).await?;
+    /// Connects to the database at the given connection
+    /// string. With the `postgres-tls` feature, TLS is used
+    /// as set by its `sslmode`.
+    pub async fn connect(url: &str) -> Result<Database, Error> {
+        Self::connect_with(url).await
+    }
+
+    #[cfg(not(feature = "postgres-tls"))]
+    async fn connect_with(url: &str) -> Result<Database, Error> {
+        let config: tokio_postgres::Config = url.parse()?;
+        let backend = Postgres::connect(move || {
+            let config = config.clone();
+
+            async move {
+                let (client, connection) = config.connect(tokio_postgres::NoTls).await?;
 
-        tokio::spawn(async move {
-            if let Err(e) = connection.await {
-                eprintln!("connection error: {}", e);
+                Ok(postgres::drive(client, connection))
             }
-        });
+        })
+        .await?;
+
+        Ok(Self::from_backend(backend))
+    }
+
+    #[cfg(feature = "postgres-tls")]
+    async fn connect_with(url: &str) -> Result<Database, Error> {
+        Self::connect_with_tls(url, Tls::new()).await
+    }
+
+    /// Connects to the database at the given connection
+    /// string with the given TLS settings, like a custom
+    /// root certificate.
+    #[cfg(feature = "postgres-tls")]
+    pub async fn connect_with_tls(url: &str, tls: Tls) -> Result<Database, Error> {
+        let config: tokio_postgres::Config = url.parse()?;
+        let connector = tls.connector();
+        let backend = Postgres::connect(move || {
+            let config = config.clone();
+            let connector = connector.clone();
+
+            async move {
+                let (client, connection) = config.connect(connector).await?;
+
+                Ok(postgres::drive(client, connection))
+            }
+        })
+        .await?;
+
+        Ok(Self::from_backend(backend))
+    }
+
+    /// Opens the SQLite database at the given path,
+    /// creating it when it does not exist. The path
+    /// `:memory:` opens a database held in memory.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::database::Database;
+    ///
+    /// # fn open() -> Result<(), valar::database::Error> {
+    /// let database = Database::connect_sqlite("database.sqlite")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "sqlite")]
+    pub fn connect_sqlite<P>(path: P) -> Result<Database, Error>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let connection = sqlite::Connection::open(path)?;
+
+        Ok(Self::from_backend(sqlite::Sqlite::new(connection)))
+    }
+
+    /// Runs statements through the given backend, like a
+    /// custom driver or a fake one in tests.
+    pub fn from_backend<B>(backend: B) -> Database
+    where
+        B: Backend + 'static,
+    {
+        Database {
+            backend: Box::new(backend),
+            replicas: Replicas::default(),
+            instrumentation: Instrumentation::default(),
+        }
+    }
+
+    /// Connects to the primary database at the given
+    /// connection string, along with its read replicas.
+    /// `SELECT` statements run on the replicas in turns,
+    /// while writes and transactions run on the primary.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use valar::database::Database;
+    ///
+    /// # async fn connect() -> Result<(), valar::database::Error> {
+    /// let database = Database::connect_with_replicas(
+    ///     "postgres://primary/valar",
+    ///     ["postgres://replica-1/valar", "postgres://replica-2/valar"],
+    /// )
+    /// .await?
+    /// .sticky(Duration::from_secs(1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect_with_replicas<I, S>(url: &str, replicas: I) -> Result<Database, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut database = Self::connect(url).await?;
+
+        for replica in replicas {
+            let replica = Self::connect(replica.as_ref()).await?;
+
+            database.replicas.push(replica.backend);
+        }
+
+        Ok(database)
+    }
+
+    /// Runs `SELECT` statements on the given backend too,
+    /// as a read replica of the database.
+    #[must_use]
+    pub fn replica<B>(mut self, backend: B) -> Self
+    where
+        B: Backend + 'static,
+    {
+        self.replicas.push(Box::new(backend));
+
+        self
+    }
+
+    /// Runs the reads of a [`Handle`] on the primary for
+    /// the given duration after its writes, so they see the
+    /// written rows even if the replicas lag behind.
+    #[must_use]
+    pub fn sticky(mut self, duration: Duration) -> Self {
+        self.replicas.sticky(duration);
+
+        self
+    }
 
-        Ok(Database { client })
+    /// Returns a handle whose reads stick to the primary
+    /// after its own writes, as set with
+    /// [`Database::sticky`].
+    pub fn handle(&self) -> Handle<'_> {
+        Handle::new(self)
     }
 
-    pub async fn connect_with(config: Config) -> Result<Database, PGError> {
-        let (client, connection) = config.connect(tokio_postgres::NoTls).await?;
+    /// Calls the listener after every statement ran against
+    /// the database or its transactions, with its bound
+    /// parameters, duration and number of rows.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::database::Database;
+    ///
+    /// # async fn connect() -> Result<(), valar::database::Error> {
+    /// let database = Database::connect("postgres://localhost/valar")
+    ///     .await?
+    ///     .on_query(|query| println!("{} took {:?}", query.statement, query.duration));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn on_query<F>(mut self, listener: F) -> Self
+    where
+        F: Fn(&Query<'_>) + Send + Sync + 'static,
+    {
+        self.instrumentation.listen(Box::new(listener));
+
+        self
+    }
+
+    /// Logs every statement at the debug level.
+    #[must_use]
+    pub fn log_queries(self) -> Self {
+        self.on_query(instrumentation::log)
+    }
+
+    /// Logs a warning for every statement that takes at
+    /// least the given duration.
+    #[must_use]
+    pub fn slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.instrumentation.slow_threshold(threshold);
 
-        tokio::spawn(async move {
-            if let Err(e) = connection.await {
-                eprintln!("connection error: {}", e);
+        self
+    }
+
+    /// Checks that the database and its replicas answer
+    /// statements. A lost connection is reopened first, so
+    /// it can back a readiness check.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use valar::database::Database;
+    /// use valar::http::Health;
+    ///
+    /// # async fn health() -> Result<(), valar::database::Error> {
+    /// let database = Arc::new(Database::connect("postgres://localhost/valar").await?);
+    ///
+    /// let health = Health::new().check("database", move || {
+    ///     let database = database.clone();
+    ///
+    ///     async move { database.ping().await.map_err(|error| error.to_string()) }
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn ping(&self) -> Result<(), Error> {
+        self.backend.ping().await?;
+
+        for replica in self.replicas.iter() {
+            replica.ping().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts a transaction. Statements ran against the
+    /// database wait until the transaction is committed
+    /// or rolled back.
+    pub async fn begin(&self) -> Result<Transaction<'_>, Error> {
+        let backend = self.backend.begin().await?;
+
+        Ok(Transaction::new(backend, &self.instrumentation))
+    }
+
+    /// Runs the callback in a transaction. The transaction
+    /// is committed when the callback succeeds and rolled
+    /// back when it fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::database::Database;
+    /// use valar::database::Executor;
+    /// use valar::database::Error;
+    ///
+    /// # async fn transfer(database: &Database) -> Result<(), Error> {
+    /// database
+    ///     .transaction(async |transaction| {
+    ///         Database::query("UPDATE accounts SET balance = balance - 10 WHERE id = 1")
+    ///             .execute(transaction)
+    ///             .await?;
+    ///
+    ///         Database::query("UPDATE accounts SET balance = balance + 10 WHERE id = 2")
+    ///             .execute(transaction)
+    ///             .await
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn transaction<F, T, E>(&self, callback: F) -> Result<T, E>
+    where
+        F: AsyncFnOnce(&Transaction<'_>) -> Result<T, E>,
+        E: From<Error>,
+    {
+        let transaction = self.begin().await?;
+
+        match callback(&transaction).await {
+            Ok(value) => {
+                transaction.commit().await?;
+
+                Ok(value)
             }
-        });
+            Err(error) => {
+                transaction.rollback().await?;
 
-        Ok(Database { client })
+                Err(error)
+            }
+        }
     }
 
     #[must_use]
@@ -51,12 +353,85 @@ impl Database {
 
     pub fn table<T>(table: T) -> QueryBuilder
     where
-        T: Into<String>,
+        T: Into<Table>,
     {
         QueryBuilder::table(table)
     }
 }
 
+impl Database {
+    /// Runs the statement on a replica when it only reads,
+    /// unless it must run on the primary.
+    async fn fetch_from(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+        primary: bool,
+    ) -> Result<Vec<Row>, Error> {
+        let backend = match primary {
+            true => &*self.backend,
+            false => self.replicas.reader(statement).unwrap_or(&*self.backend),
+        };
+
+        self.instrumentation
+            .observe(
+                statement,
+                parameters,
+                |rows| rows.len() as u64,
+                backend.fetch(statement, parameters),
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl Connection for Database {
+    fn dialect(&self) -> Dialect {
+        self.backend.dialect()
+    }
+
+    async fn fetch(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        self.fetch_from(statement, parameters, false).await
+    }
+
+    async fn execute(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        self.instrumentation
+            .observe(
+                statement,
+                parameters,
+                |rows| *rows,
+                self.backend.execute(statement, parameters),
+            )
+            .await
+    }
+
+    async fn copy_in(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<&(dyn ToSql + Sync)>],
+    ) -> Result<u64, Error> {
+        let statement = format!("COPY {table} ({})", columns.join(", "));
+
+        self.instrumentation
+            .observe(
+                &statement,
+                &[],
+                |rows| *rows,
+                self.backend.copy_in(table, columns, rows),
+            )
+            .await
+    }
+}
+
 pub trait ToPendingQuery {
-    fn to_pending_query(&self) -> PendingQuery;
+    fn to_pending_query(&self) -> Result<PendingQuery<'_>, Error>;
 }
@@ -0,0 +1,219 @@
+use async_trait::async_trait;
+
+use crate::database::Connection;
+use crate::database::Error;
+
+/// A database driver, like Postgres or SQLite. The
+/// database runs every statement through its backend, so
+/// a custom one can stand in for a real database in tests.
+///
+/// # Example
+///
+/// ```
+/// use async_trait::async_trait;
+/// use valar::database::backend::Backend;
+/// use valar::database::backend::TransactionBackend;
+/// use valar::database::Connection;
+/// use valar::database::Database;
+/// use valar::database::Dialect;
+/// use valar::database::Error;
+/// use valar::database::Row;
+/// use valar::database::ToSql;
+///
+/// struct Empty;
+///
+/// #[async_trait]
+/// impl Connection for Empty {
+///     fn dialect(&self) -> Dialect {
+///         Dialect::Postgres
+///     }
+///
+///     async fn fetch(&self, _: &str, _: &[&(dyn ToSql + Sync)]) -> Result<Vec<Row>, Error> {
+///         Ok(vec![])
+///     }
+///
+///     async fn execute(&self, _: &str, _: &[&(dyn ToSql + Sync)]) -> Result<u64, Error> {
+///         Ok(0)
+///     }
+/// }
+///
+/// #[async_trait]
+/// impl TransactionBackend for Empty {
+///     async fn commit(&self) -> Result<(), Error> {
+///         Ok(())
+///     }
+///
+///     async fn rollback(&self) -> Result<(), Error> {
+///         Ok(())
+///     }
+///
+///     fn abort(&self) {}
+/// }
+///
+/// #[async_trait]
+/// impl Backend for Empty {
+///     async fn begin(&self) -> Result<Box<dyn TransactionBackend + '_>, Error> {
+///         Ok(Box::new(Empty))
+///     }
+/// }
+///
+/// let database = Database::from_backend(Empty);
+/// ```
+#[async_trait]
+pub trait Backend: Connection + Send {
+    /// Starts a transaction and returns the connection it
+    /// runs on, which is held until the transaction ends.
+    async fn begin(&self) -> Result<Box<dyn TransactionBackend + '_>, Error>;
+
+    /// Checks that the database answers statements.
+    async fn ping(&self) -> Result<(), Error> {
+        self.fetch("SELECT 1", &[]).await?;
+
+        Ok(())
+    }
+}
+
+/// The connection an open transaction runs on.
+#[async_trait]
+pub trait TransactionBackend: Connection + Send {
+    /// Commits the changes of the transaction.
+    async fn commit(&self) -> Result<(), Error>;
+
+    /// Discards the changes of the transaction.
+    async fn rollback(&self) -> Result<(), Error>;
+
+    /// Discards the changes of the transaction without
+    /// waiting, as it is dropped before it finished.
+    fn abort(&self);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use crate::database::backend::Backend;
+    use crate::database::backend::TransactionBackend;
+    use crate::database::builder::Whereable;
+    use crate::database::Connection;
+    use crate::database::Database;
+    use crate::database::Dialect;
+    use crate::database::Error;
+    use crate::database::Executor;
+    use crate::database::Row;
+    use crate::database::ToSql;
+
+    #[derive(Clone, Default)]
+    struct Recorder {
+        statements: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn record(&self, statement: &str) {
+            self.statements.lock().unwrap().push(statement.to_string());
+        }
+    }
+
+    #[async_trait]
+    impl Connection for Recorder {
+        fn dialect(&self) -> Dialect {
+            Dialect::Postgres
+        }
+
+        async fn fetch(
+            &self,
+            statement: &str,
+            _: &[&(dyn ToSql + Sync)],
+        ) -> Result<Vec<Row>, Error> {
+            self.record(statement);
+
+            Ok(vec![])
+        }
+
+        async fn execute(&self, statement: &str, _: &[&(dyn ToSql + Sync)]) -> Result<u64, Error> {
+            self.record(statement);
+
+            Ok(1)
+        }
+    }
+
+    #[async_trait]
+    impl TransactionBackend for Recorder {
+        async fn commit(&self) -> Result<(), Error> {
+            self.record("COMMIT");
+
+            Ok(())
+        }
+
+        async fn rollback(&self) -> Result<(), Error> {
+            self.record("ROLLBACK");
+
+            Ok(())
+        }
+
+        fn abort(&self) {
+            self.record("ROLLBACK");
+        }
+    }
+
+    #[async_trait]
+    impl Backend for Recorder {
+        async fn begin(&self) -> Result<Box<dyn TransactionBackend + '_>, Error> {
+            self.record("BEGIN");
+
+            Ok(Box::new(self.clone()))
+        }
+    }
+
+    #[tokio::test]
+    async fn it_runs_statements_through_the_backend() {
+        let recorder = Recorder::default();
+        let database = Database::from_backend(recorder.clone());
+
+        database.ping().await.unwrap();
+
+        let users = Database::table("users")
+            .select(["id"])
+            .where_equal("name", &"Erik")
+            .raw_get(&database)
+            .await
+            .unwrap();
+
+        assert!(users.is_empty());
+
+        let transaction = database.begin().await.unwrap();
+
+        Database::query("DELETE FROM users")
+            .execute(&transaction)
+            .await
+            .unwrap();
+
+        drop(transaction);
+
+        database
+            .transaction(async |transaction| {
+                Database::query("UPDATE users SET name = $1")
+                    .with(&"Erik")
+                    .execute(transaction)
+                    .await
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *recorder.statements.lock().unwrap(),
+            [
+                "SELECT 1",
+                r#"SELECT "id" FROM "users" WHERE (("name" = $1))"#,
+                "BEGIN",
+                "DELETE FROM users",
+                "ROLLBACK",
+                "BEGIN",
+                "UPDATE users SET name = $1",
+                "COMMIT",
+            ]
+        );
+    }
+}
@@ -1,21 +1,27 @@
+pub mod identifiers;
 pub mod parameters;
 pub mod selects;
 pub mod wheres;
 
+use crate::database::Error;
+
+pub use identifiers::Column;
+pub use identifiers::Table;
 pub use parameters::Parameters;
 pub use selects::SelectQueryBuilder;
 pub use wheres::Operation;
+pub use wheres::Values;
 pub use wheres::Where;
 pub use wheres::Whereable;
 
 pub struct QueryBuilder {
-    table: String,
+    table: Table,
 }
 
 impl QueryBuilder {
     pub fn table<T>(table: T) -> Self
     where
-        T: Into<String>,
+        T: Into<Table>,
     {
         Self {
             table: table.into(),
@@ -25,7 +31,7 @@ impl QueryBuilder {
     #[must_use]
     pub fn select<'a, T, C>(self, columns: C) -> SelectQueryBuilder<'a>
     where
-        T: Into<String>,
+        T: Into<Column>,
         C: IntoIterator<Item = T>,
     {
         SelectQueryBuilder::new(self.table, columns)
@@ -35,8 +41,17 @@ impl QueryBuilder {
     pub fn select_all<'a>(self) -> SelectQueryBuilder<'a> {
         SelectQueryBuilder::new(self.table, ["*"])
     }
+
+    /// Selects a raw expression, like `count(*) as total`.
+    #[must_use]
+    pub fn select_raw<'a, E>(self, expression: E) -> SelectQueryBuilder<'a>
+    where
+        E: Into<String>,
+    {
+        SelectQueryBuilder::new(self.table, Vec::<Column>::new()).select_raw(expression)
+    }
 }
 
 pub trait ToSqlString<'a> {
-    fn to_sql_string(&self, parameters: &mut Parameters<'a>) -> String;
+    fn to_sql_string(&self, parameters: &mut Parameters<'a>) -> Result<String, Error>;
 }
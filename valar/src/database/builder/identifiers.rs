@@ -0,0 +1,213 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+use crate::database::Error;
+
+/// A table name, like `users` or `public.users`. Plain
+/// and dotted identifiers are quoted when written to a
+/// statement, folded to lowercase like unquoted names
+/// are. Anything else is written as is, so names given by
+/// the user must be created with [`Table::parse`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Table {
+    name: String,
+    raw: bool,
+}
+
+/// A column name, like `email`, `users.email` or `*`.
+/// Plain and dotted identifiers are quoted when written
+/// to a statement, folded to lowercase like unquoted
+/// names are. Expressions like `id AS uid` or `COUNT(*)`
+/// are written as is, so names given by the user must be
+/// created with [`Column::parse`].
+///
+/// # Example
+///
+/// ```
+/// use valar::database::builder::Column;
+///
+/// assert_eq!(Column::from("users.email").to_string(), "\"users\".\"email\"");
+/// assert_eq!(Column::from("COUNT(*)").to_string(), "COUNT(*)");
+/// assert!(Column::parse("email; DROP TABLE users").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Column {
+    name: String,
+    raw: bool,
+}
+
+impl Table {
+    /// Creates the table name, failing unless every part
+    /// of it is a plain identifier made of letters, digits
+    /// and underscores.
+    pub fn parse(name: &str) -> Result<Self, Error> {
+        match name.split('.').all(is_plain) {
+            true => Ok(Self {
+                name: name.to_string(),
+                raw: false,
+            }),
+            false => Err(Error::InvalidIdentifier(name.to_string())),
+        }
+    }
+
+    /// Creates a table expression written to statements
+    /// as is, without quoting it.
+    pub fn raw<N>(expression: N) -> Self
+    where
+        N: Into<String>,
+    {
+        Self {
+            name: expression.into(),
+            raw: true,
+        }
+    }
+
+    /// Returns the name, unquoted.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Column {
+    /// Creates the column name, failing unless every part
+    /// of it is a plain identifier made of letters, digits
+    /// and underscores, or its last part is `*`.
+    pub fn parse(name: &str) -> Result<Self, Error> {
+        let mut parts = name.rsplit('.');
+        let last = parts.next().unwrap_or_default();
+
+        match (last == "*" || is_plain(last)) && parts.all(is_plain) {
+            true => Ok(Self {
+                name: name.to_string(),
+                raw: false,
+            }),
+            false => Err(Error::InvalidIdentifier(name.to_string())),
+        }
+    }
+
+    /// Creates a column expression written to statements
+    /// as is, without quoting it.
+    pub fn raw<N>(expression: N) -> Self
+    where
+        N: Into<String>,
+    {
+        Self {
+            name: expression.into(),
+            raw: true,
+        }
+    }
+
+    /// Returns the name, unquoted.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.raw {
+            true => write!(f, "{}", self.name),
+            false => write!(f, "{}", quote(&self.name)),
+        }
+    }
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.raw {
+            true => write!(f, "{}", self.name),
+            false => write!(f, "{}", quote(&self.name)),
+        }
+    }
+}
+
+impl From<&str> for Table {
+    fn from(name: &str) -> Self {
+        Self::parse(name).unwrap_or_else(|_| Self::raw(name))
+    }
+}
+
+impl From<String> for Table {
+    fn from(name: String) -> Self {
+        Self::from(name.as_str())
+    }
+}
+
+impl From<&String> for Table {
+    fn from(name: &String) -> Self {
+        Self::from(name.as_str())
+    }
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        Self::parse(name).unwrap_or_else(|_| Self::raw(name))
+    }
+}
+
+impl From<String> for Column {
+    fn from(name: String) -> Self {
+        Self::from(name.as_str())
+    }
+}
+
+impl From<&String> for Column {
+    fn from(name: &String) -> Self {
+        Self::from(name.as_str())
+    }
+}
+
+/// Quotes every dot separated part of the name, folded
+/// to lowercase. A `*` is left as is.
+fn quote(name: &str) -> String {
+    name.split('.')
+        .map(|part| match part {
+            "*" => part.to_string(),
+            _ => format!("\"{}\"", part.to_ascii_lowercase()),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Whether the part is made of letters, digits and
+/// underscores, not starting with a digit.
+fn is_plain(part: &str) -> bool {
+    let mut characters = part.chars();
+
+    characters
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && characters.all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::builder::Column;
+    use crate::database::builder::Table;
+
+    #[test]
+    fn it_quotes_identifiers() {
+        assert_eq!(Table::from("users").to_string(), "\"users\"");
+        assert_eq!(Column::from("users.id").to_string(), "\"users\".\"id\"");
+        assert_eq!(Column::from("*").to_string(), "*");
+        assert_eq!(Column::from("users.*").to_string(), "\"users\".*");
+        assert_eq!(Column::from("createdAt").to_string(), "\"createdat\"");
+        assert_eq!(Column::from("id AS uid").to_string(), "id AS uid");
+        assert_eq!(Column::from("count(*)").to_string(), "count(*)");
+        assert_eq!(Column::raw("users.id").to_string(), "users.id");
+        assert_eq!(Table::from("users u").to_string(), "users u");
+    }
+
+    #[test]
+    fn it_validates_identifiers() {
+        assert!(Table::parse("public.users").is_ok());
+        assert!(Table::parse("users;").is_err());
+        assert!(Table::parse("*").is_err());
+        assert!(Column::parse("users.*").is_ok());
+        assert!(Column::parse("_created_at2").is_ok());
+        assert!(Column::parse("2fa").is_err());
+        assert!(Column::parse("users..id").is_err());
+        assert!(Column::parse("").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use tokio_postgres::types::ToSql;
+use crate::database::ToSql;
 
 #[derive(Default)]
 pub struct Parameters<'a> {
@@ -1,18 +1,32 @@
+use crate::database::builder::Column;
 use crate::database::builder::Parameters;
+use crate::database::builder::Table;
 use crate::database::builder::ToSqlString;
 use crate::database::builder::Where;
 use crate::database::builder::Whereable;
+use crate::database::Connection;
+use crate::database::Error;
 use crate::database::Executor;
+use crate::database::FromSql;
 use crate::database::PendingQuery;
+use crate::database::Row;
 use crate::database::ToPendingQuery;
-use tokio_postgres::types::ToSql;
+use crate::database::ToSql;
 
 pub struct SelectQueryBuilder<'a> {
-    table: String,
+    table: Table,
     columns: Vec<String>,
+    distinct: Distinct,
     wheres: Vec<Where<'a>>,
 }
 
+/// Whether the query drops duplicated rows.
+enum Distinct {
+    None,
+    All,
+    On(Vec<String>),
+}
+
 impl<'a> Whereable<'a> for SelectQueryBuilder<'a> {
     fn add_where(&mut self, condition: Where<'a>) {
         if self.wheres.is_empty() {
@@ -25,67 +39,403 @@ impl<'a> Whereable<'a> for SelectQueryBuilder<'a> {
 
 impl<'a> SelectQueryBuilder<'a> {
     #[must_use]
-    pub fn new<T, C>(table: String, columns: C) -> Self
+    pub fn new<T, C>(table: Table, columns: C) -> Self
     where
-        T: Into<String>,
+        T: Into<Column>,
         C: IntoIterator<Item = T>,
     {
         Self {
             table,
-            columns: columns.into_iter().map(|column| column.into()).collect(),
+            columns: columns
+                .into_iter()
+                .map(|column| column.into().to_string())
+                .collect(),
+            distinct: Distinct::None,
             wheres: vec![],
         }
     }
+
+    /// Drops duplicated rows from the result.
+    #[must_use]
+    pub fn distinct(mut self) -> Self {
+        self.distinct = Distinct::All;
+
+        self
+    }
+
+    /// Keeps only the first row of each group of rows
+    /// sharing the values of the given columns. This is
+    /// specific to Postgres.
+    #[must_use]
+    pub fn distinct_on<T, C>(mut self, columns: C) -> Self
+    where
+        T: Into<Column>,
+        C: IntoIterator<Item = T>,
+    {
+        self.distinct = Distinct::On(
+            columns
+                .into_iter()
+                .map(|column| column.into().to_string())
+                .collect(),
+        );
+
+        self
+    }
+
+    /// Adds a raw expression to the selected columns, like
+    /// `count(*) as total`.
+    #[must_use]
+    pub fn select_raw<E>(mut self, expression: E) -> Self
+    where
+        E: Into<String>,
+    {
+        self.columns.push(expression.into());
+
+        self
+    }
 }
 
-impl<'a> ToPendingQuery for SelectQueryBuilder<'a> {
-    fn to_pending_query(&self) -> PendingQuery {
+impl<'a> SelectQueryBuilder<'a> {
+    /// Returns the number of matching rows.
+    pub async fn count<C>(&self, connection: &C) -> Result<i64, Error>
+    where
+        C: Connection + ?Sized,
+    {
+        self.aggregate("COUNT(*)", connection).await
+    }
+
+    /// Returns the sum of the column over the matching
+    /// rows, which is `NULL` when no row matches.
+    pub async fn sum<T, C>(&self, column: &str, connection: &C) -> Result<Option<T>, Error>
+    where
+        T: FromSql,
+        C: Connection + ?Sized,
+    {
+        self.aggregate(&format!("SUM({})", Column::from(column)), connection)
+            .await
+    }
+
+    /// Returns the average of the column over the matching
+    /// rows, which is `NULL` when no row matches.
+    pub async fn avg<C>(&self, column: &str, connection: &C) -> Result<Option<f64>, Error>
+    where
+        C: Connection + ?Sized,
+    {
+        self.aggregate(
+            &format!("CAST(AVG({}) AS DOUBLE PRECISION)", Column::from(column)),
+            connection,
+        )
+        .await
+    }
+
+    /// Returns the smallest value of the column over the
+    /// matching rows, which is `NULL` when no row matches.
+    pub async fn min<T, C>(&self, column: &str, connection: &C) -> Result<Option<T>, Error>
+    where
+        T: FromSql,
+        C: Connection + ?Sized,
+    {
+        self.aggregate(&format!("MIN({})", Column::from(column)), connection)
+            .await
+    }
+
+    /// Returns the largest value of the column over the
+    /// matching rows, which is `NULL` when no row matches.
+    pub async fn max<T, C>(&self, column: &str, connection: &C) -> Result<Option<T>, Error>
+    where
+        T: FromSql,
+        C: Connection + ?Sized,
+    {
+        self.aggregate(&format!("MAX({})", Column::from(column)), connection)
+            .await
+    }
+
+    /// Runs the query selecting only the given aggregate
+    /// and returns its value.
+    async fn aggregate<T, C>(&self, expression: &str, connection: &C) -> Result<T, Error>
+    where
+        T: FromSql,
+        C: Connection + ?Sized,
+    {
+        let (statement, parameters) = self.aggregate_statement(expression)?;
+        let parameters: Vec<&(dyn ToSql + Sync)> = parameters.into();
+
+        connection
+            .fetch_one(&statement, &parameters)
+            .await?
+            .try_get(0)
+    }
+
+    /// Builds the statement selecting the aggregate. A
+    /// distinct query is aggregated over its distinct
+    /// rows.
+    fn aggregate_statement(&self, expression: &str) -> Result<(String, Parameters<'a>), Error> {
+        if let Distinct::None = self.distinct {
+            return self.statement(expression);
+        }
+
+        let (rows, parameters) = self.statement(&self.columns.join(", "))?;
+
+        Ok((
+            format!("SELECT {expression} FROM ({rows}) AS distinct_rows"),
+            parameters,
+        ))
+    }
+
+    /// Determines if the query selects the given column,
+    /// by name or through a `*`.
+    fn selects(&self, column: &Column) -> bool {
+        let column = column.to_string();
+
+        self.columns
+            .iter()
+            .any(|selected| *selected == column || selected == "*" || selected.ends_with(".*"))
+    }
+
+    /// Builds the statement selecting the given columns
+    /// from the matching rows.
+    fn statement(&self, columns: &str) -> Result<(String, Parameters<'a>), Error> {
         let mut parameters = Parameters::new();
-        let columns = self.columns.join(", ");
+        let statement = self.select(columns, &mut parameters)?;
+
+        Ok((statement, parameters))
+    }
+
+    /// Builds the statement selecting the given columns,
+    /// numbering its placeholders after the given
+    /// parameters so it can be nested in another query.
+    fn select(&self, columns: &str, parameters: &mut Parameters<'a>) -> Result<String, Error> {
         let table = &self.table;
-        let mut statement = format!("SELECT {columns} FROM {table}");
+        let distinct = match &self.distinct {
+            Distinct::None => String::new(),
+            Distinct::All => "DISTINCT ".to_string(),
+            Distinct::On(on) => format!("DISTINCT ON ({}) ", on.join(", ")),
+        };
+        let mut statement = format!("SELECT {distinct}{columns} FROM {table}");
 
         if !self.wheres.is_empty() {
             let wheres: Vec<String> = self
                 .wheres
                 .iter()
-                .map(|condition| condition.to_sql_string(&mut parameters))
-                .collect();
+                .map(|condition| condition.to_sql_string(parameters))
+                .collect::<Result<_, _>>()?;
             let wheres = wheres.join(" ");
 
             statement.push_str(&format!(" WHERE ({})", wheres));
         }
 
-        PendingQuery::new(statement).parameters_from(parameters)
+        Ok(statement)
     }
 }
 
-impl<'a> Executor<'a> for SelectQueryBuilder<'a> {
-    fn executor_parameters(&self) -> (String, Vec<&'a (dyn ToSql + Sync)>) {
-        let mut parameters = Parameters::new();
-        let columns = self.columns.join(", ");
-        let table = &self.table;
-        let mut statement = format!("SELECT {columns} FROM {table}");
+impl<'a> SelectQueryBuilder<'a> {
+    /// Runs the query in pages of the given size, calling
+    /// the callback with the rows of each page. Rows are
+    /// ordered by the given column, which should be unique,
+    /// like the primary key, so pages don't overlap. Pages
+    /// are taken with `LIMIT` and `OFFSET`, so rows
+    /// inserted or deleted meanwhile can shift them; prefer
+    /// [`SelectQueryBuilder::chunk_by_id`] when the
+    /// callback modifies the table.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::database::Database;
+    /// use valar::database::Error;
+    /// use valar::database::Row;
+    ///
+    /// # async fn report(database: &Database) -> Result<(), Error> {
+    /// Database::table("users")
+    ///     .select(["id", "email"])
+    ///     .chunk(1000, "id", database, async |rows: Vec<Row>| {
+    ///         println!("{} users", rows.len());
+    ///
+    ///         Ok::<_, Error>(())
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chunk<T, C, F, E>(
+        &self,
+        size: usize,
+        order: &str,
+        connection: &C,
+        mut callback: F,
+    ) -> Result<(), E>
+    where
+        T: TryFrom<Row>,
+        T::Error: Into<Error>,
+        C: Connection + ?Sized,
+        F: AsyncFnMut(Vec<T>) -> Result<(), E>,
+        E: From<Error>,
+    {
+        let size = size.max(1);
+        let order = Column::from(order);
+        let mut offset = 0;
 
-        if !self.wheres.is_empty() {
-            let wheres: Vec<String> = self
-                .wheres
-                .iter()
-                .map(|condition| condition.to_sql_string(&mut parameters))
-                .collect();
-            let wheres = wheres.join(" ");
+        loop {
+            let (mut statement, parameters) = self.statement(&self.columns.join(", "))?;
+            let parameters: Vec<&(dyn ToSql + Sync)> = parameters.into();
 
-            statement.push_str(&format!(" WHERE ({})", wheres));
+            statement.push_str(&format!(" ORDER BY {order} LIMIT {size} OFFSET {offset}"));
+
+            let rows = connection.fetch(&statement, &parameters).await?;
+            let count = rows.len();
+
+            if count > 0 {
+                callback(convert(rows)?).await?;
+            }
+
+            if count < size {
+                return Ok(());
+            }
+
+            offset += size;
+        }
+    }
+
+    /// Runs the query in pages of the given size, calling
+    /// the callback with the rows of each page. Pages are
+    /// taken after the largest value of the given integer
+    /// column seen so far, which should be unique, so the
+    /// callback can safely modify the table. The column is
+    /// added to the selected ones when it is missing.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::database::Database;
+    /// use valar::database::Error;
+    /// use valar::database::Executor;
+    /// use valar::database::Row;
+    ///
+    /// # async fn deactivate(database: &Database) -> Result<(), Error> {
+    /// Database::table("users")
+    ///     .select(["id"])
+    ///     .chunk_by_id(1000, "id", database, async |rows: Vec<Row>| {
+    ///         for row in rows {
+    ///             let id: i64 = row.get("id");
+    ///
+    ///             Database::query("UPDATE users SET active = false WHERE id = $1")
+    ///                 .with(&id)
+    ///                 .execute(database)
+    ///                 .await?;
+    ///         }
+    ///
+    ///         Ok::<_, Error>(())
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chunk_by_id<T, C, F, E>(
+        &self,
+        size: usize,
+        column: &str,
+        connection: &C,
+        mut callback: F,
+    ) -> Result<(), E>
+    where
+        T: TryFrom<Row>,
+        T::Error: Into<Error>,
+        C: Connection + ?Sized,
+        F: AsyncFnMut(Vec<T>) -> Result<(), E>,
+        E: From<Error>,
+    {
+        let size = size.max(1);
+        let name = column.rsplit('.').next().unwrap_or(column);
+        let column = Column::from(column);
+        let mut columns = self.columns.clone();
+        let mut last: Option<i64> = None;
+
+        if !self.selects(&column) {
+            columns.push(column.to_string());
+        }
+
+        loop {
+            let mut parameters = Parameters::new();
+            let mut statement = self.select(&columns.join(", "), &mut parameters)?;
+            let mut parameters: Vec<&(dyn ToSql + Sync)> = parameters.into();
+
+            if let Some(last) = &last {
+                parameters.push(last);
+
+                let keyword = match self.wheres.is_empty() {
+                    true => "WHERE",
+                    false => "AND",
+                };
+
+                statement.push_str(&format!(
+                    " {keyword} {column} > CAST(${} AS BIGINT)",
+                    parameters.len()
+                ));
+            }
+
+            statement.push_str(&format!(" ORDER BY {column} LIMIT {size}"));
+
+            let rows = connection.fetch(&statement, &parameters).await?;
+            let count = rows.len();
+
+            let Some(row) = rows.last() else {
+                return Ok(());
+            };
+
+            let key = row.try_get::<_, i64>(name)?;
+
+            callback(convert(rows)?).await?;
+
+            if count < size {
+                return Ok(());
+            }
+
+            last = Some(key);
         }
+    }
+}
+
+/// Converts the rows to the given type.
+fn convert<T>(rows: Vec<Row>) -> Result<Vec<T>, Error>
+where
+    T: TryFrom<Row>,
+    T::Error: Into<Error>,
+{
+    rows.into_iter()
+        .map(|row| T::try_from(row).map_err(Into::into))
+        .collect()
+}
 
-        (statement, parameters.into())
+impl<'a> ToSqlString<'a> for SelectQueryBuilder<'a> {
+    fn to_sql_string(&self, parameters: &mut Parameters<'a>) -> Result<String, Error> {
+        self.select(&self.columns.join(", "), parameters)
+    }
+}
+
+impl<'a> ToPendingQuery for SelectQueryBuilder<'a> {
+    fn to_pending_query(&self) -> Result<PendingQuery<'_>, Error> {
+        let (statement, parameters) = self.statement(&self.columns.join(", "))?;
+
+        Ok(PendingQuery::new(statement).parameters_from(parameters))
+    }
+}
+
+impl<'a> Executor<'a> for SelectQueryBuilder<'a> {
+    fn executor_parameters(&self) -> Result<(String, Vec<&'a (dyn ToSql + Sync)>), Error> {
+        let (statement, parameters) = self.statement(&self.columns.join(", "))?;
+
+        Ok((statement, parameters.into()))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use serde_json::json;
+
     use crate::database::builder::wheres::Whereable;
     use crate::database::builder::QueryBuilder;
+    use crate::database::Error;
+    use crate::database::Json;
     use crate::database::ToPendingQuery;
 
     #[test]
@@ -93,9 +443,10 @@ mod tests {
         let query = QueryBuilder::table("users")
             .select(["id"])
             .to_pending_query()
+            .unwrap()
             .to_string();
 
-        assert_eq!(query, "SELECT id FROM users");
+        assert_eq!(query, r#"SELECT "id" FROM "users""#);
     }
 
     #[test]
@@ -104,9 +455,10 @@ mod tests {
             .select(["id"])
             .where_equal("name", &"Erik")
             .to_pending_query()
+            .unwrap()
             .to_string();
 
-        assert_eq!(query, "SELECT id FROM users WHERE ((name = $1))");
+        assert_eq!(query, r#"SELECT "id" FROM "users" WHERE (("name" = $1))"#);
     }
 
     #[test]
@@ -116,12 +468,87 @@ mod tests {
             .where_equal("name", &"Erik")
             .where_equal("email", &"soc@erik.cat")
             .to_pending_query()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            query,
+            r#"SELECT "id" FROM "users" WHERE (("name" = $1) AND ("email" = $2))"#
+        );
+    }
+
+    #[test]
+    fn test_aggregate_select() {
+        let (statement, _) = QueryBuilder::table("users")
+            .select(["id"])
+            .where_greater_than("age", &18)
+            .statement("COUNT(*)")
+            .unwrap();
+
+        assert_eq!(
+            statement,
+            r#"SELECT COUNT(*) FROM "users" WHERE (("age" > $1))"#
+        );
+    }
+
+    #[test]
+    fn test_subquery_select() {
+        let admins = QueryBuilder::table("admins")
+            .select(["user_id"])
+            .where_equal("active", &true);
+        let posts = QueryBuilder::table("posts")
+            .select(["id"])
+            .where_greater_than("likes", &100);
+
+        let query = QueryBuilder::table("users")
+            .select(["id"])
+            .where_equal("name", &"Erik")
+            .where_in("id", admins)
+            .where_exists(posts)
+            .to_pending_query()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            query,
+            r#"SELECT "id" FROM "users" WHERE (("name" = $1) AND ("id" IN (SELECT "user_id" FROM "admins" WHERE (("active" = $2)))) AND (EXISTS (SELECT "id" FROM "posts" WHERE (("likes" > $3)))))"#
+        );
+    }
+
+    #[test]
+    fn test_raw_select() {
+        let email = "soc@erik.cat";
+        let query = QueryBuilder::table("users")
+            .select(["id"])
+            .select_raw("count(*) as total")
+            .where_equal("active", &true)
+            .where_raw("lower(email) = $? OR lower(name) = $?", [&email, &"erik"])
+            .to_pending_query()
+            .unwrap()
             .to_string();
 
         assert_eq!(
             query,
-            "SELECT id FROM users WHERE ((name = $1) AND (email = $2))"
+            r#"SELECT "id", count(*) as total FROM "users" WHERE (("active" = $1) AND (lower(email) = $2 OR lower(name) = $3))"#
         );
+
+        let missing = QueryBuilder::table("users")
+            .select(["id"])
+            .where_raw("lower(email) = $? OR lower(name) = $?", [&email])
+            .to_pending_query()
+            .map(|query| query.to_string());
+        let extra = QueryBuilder::table("users")
+            .select(["id"])
+            .where_exists(
+                QueryBuilder::table("posts")
+                    .select(["id"])
+                    .or_where_raw("likes > 100", [&email]),
+            )
+            .to_pending_query()
+            .map(|query| query.to_string());
+
+        assert!(matches!(missing, Err(Error::BindingMismatch(2, 1))));
+        assert!(matches!(extra, Err(Error::BindingMismatch(0, 1))));
     }
 
     #[test]
@@ -132,11 +559,76 @@ mod tests {
             .where_not_equal("email", &"soc@erik.cat")
             .where_between("age", &"18", &"30")
             .to_pending_query()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            query,
+            r#"SELECT "id" FROM "users" WHERE (("name" = $1) AND ("email" != $2) AND ("age" BETWEEN $3 AND $4))"#
+        );
+    }
+
+    #[test]
+    fn test_json_select() {
+        let plan = Json(json!({ "plan": "pro" }));
+        let query = QueryBuilder::table("teams")
+            .select(["id"])
+            .where_json_contains("meta", &plan)
+            .or_where_json_path_equal("meta", "$.owner's.name", &"Erik")
+            .to_pending_query()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            query,
+            r#"SELECT "id" FROM "teams" WHERE (("meta" @> $1) OR (jsonb_path_query_first("meta", '$.owner''s.name') #>> '{}' = $2))"#
+        );
+    }
+
+    #[test]
+    fn test_array_select() {
+        let tags = vec!["rust", "web"];
+        let query = QueryBuilder::table("posts")
+            .select(["id"])
+            .where_any("tags", &"rust")
+            .where_contains("tags", &tags)
+            .or_where_overlaps("labels", &tags)
+            .to_pending_query()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            query,
+            r#"SELECT "id" FROM "posts" WHERE (($1 = ANY("tags")) AND ("tags" @> $2) OR ("labels" && $3))"#
+        );
+    }
+
+    #[test]
+    fn test_distinct_select() {
+        let users = QueryBuilder::table("users")
+            .select(["email"])
+            .distinct()
+            .where_equal("active", &true);
+
+        assert_eq!(
+            users.to_pending_query().unwrap().to_string(),
+            r#"SELECT DISTINCT "email" FROM "users" WHERE (("active" = $1))"#
+        );
+        assert_eq!(
+            users.aggregate_statement("COUNT(*)").unwrap().0,
+            r#"SELECT COUNT(*) FROM (SELECT DISTINCT "email" FROM "users" WHERE (("active" = $1))) AS distinct_rows"#
+        );
+
+        let query = QueryBuilder::table("posts")
+            .select(["user_id", "title"])
+            .distinct_on(["user_id", "posts.category"])
+            .to_pending_query()
+            .unwrap()
             .to_string();
 
         assert_eq!(
             query,
-            "SELECT id FROM users WHERE ((name = $1) AND (email != $2) AND (age BETWEEN $3 AND $4))"
+            r#"SELECT DISTINCT ON ("user_id", "posts"."category") "user_id", "title" FROM "posts""#
         );
     }
 }
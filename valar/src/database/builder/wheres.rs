@@ -1,27 +1,82 @@
+use crate::database::builder::Column;
 use crate::database::builder::Parameters;
+use crate::database::builder::SelectQueryBuilder;
 use crate::database::builder::ToSqlString;
-use tokio_postgres::types::ToSql;
+use crate::database::Error;
+use crate::database::ToSql;
+
+/// The values a column is compared against, either a list
+/// of values or the rows of a subquery.
+pub enum Values<'a> {
+    List(Vec<&'a (dyn ToSql + Sync)>),
+    Query(Box<SelectQueryBuilder<'a>>),
+}
+
+impl<'a> From<Vec<&'a (dyn ToSql + Sync)>> for Values<'a> {
+    fn from(values: Vec<&'a (dyn ToSql + Sync)>) -> Self {
+        Self::List(values)
+    }
+}
+
+impl<'a> From<&[&'a (dyn ToSql + Sync)]> for Values<'a> {
+    fn from(values: &[&'a (dyn ToSql + Sync)]) -> Self {
+        Self::List(values.to_vec())
+    }
+}
+
+impl<'a, const N: usize> From<[&'a (dyn ToSql + Sync); N]> for Values<'a> {
+    fn from(values: [&'a (dyn ToSql + Sync); N]) -> Self {
+        Self::List(Vec::from(values))
+    }
+}
+
+impl<'a> From<SelectQueryBuilder<'a>> for Values<'a> {
+    fn from(query: SelectQueryBuilder<'a>) -> Self {
+        Self::Query(Box::new(query))
+    }
+}
+
+impl<'a> ToSqlString<'a> for Values<'a> {
+    fn to_sql_string(&self, parameters: &mut Parameters<'a>) -> Result<String, Error> {
+        match self {
+            Self::List(values) => Ok(values
+                .iter()
+                .map(|value| format!("${}", parameters.add(*value)))
+                .collect::<Vec<_>>()
+                .join(", ")),
+            Self::Query(query) => query.to_sql_string(parameters),
+        }
+    }
+}
 
 pub enum Operation<'a> {
-    Equal(String, &'a (dyn ToSql + Sync)),
-    NotEqual(String, &'a (dyn ToSql + Sync)),
-    GreaterThan(String, &'a (dyn ToSql + Sync)),
-    GreaterThanOrEqual(String, &'a (dyn ToSql + Sync)),
-    LessThan(String, &'a (dyn ToSql + Sync)),
-    LessThanOrEqual(String, &'a (dyn ToSql + Sync)),
-    Like(String, &'a (dyn ToSql + Sync)),
-    NotLike(String, &'a (dyn ToSql + Sync)),
-    In(String, Vec<&'a (dyn ToSql + Sync)>),
-    NotIn(String, Vec<&'a (dyn ToSql + Sync)>),
-    Between(String, &'a (dyn ToSql + Sync), &'a (dyn ToSql + Sync)),
-    NotBetween(String, &'a (dyn ToSql + Sync), &'a (dyn ToSql + Sync)),
-    IsNull(String),
-    IsNotNull(String),
+    Equal(Column, &'a (dyn ToSql + Sync)),
+    NotEqual(Column, &'a (dyn ToSql + Sync)),
+    GreaterThan(Column, &'a (dyn ToSql + Sync)),
+    GreaterThanOrEqual(Column, &'a (dyn ToSql + Sync)),
+    LessThan(Column, &'a (dyn ToSql + Sync)),
+    LessThanOrEqual(Column, &'a (dyn ToSql + Sync)),
+    Like(Column, &'a (dyn ToSql + Sync)),
+    NotLike(Column, &'a (dyn ToSql + Sync)),
+    In(Column, Values<'a>),
+    NotIn(Column, Values<'a>),
+    Between(Column, &'a (dyn ToSql + Sync), &'a (dyn ToSql + Sync)),
+    NotBetween(Column, &'a (dyn ToSql + Sync), &'a (dyn ToSql + Sync)),
+    IsNull(Column),
+    IsNotNull(Column),
+    Exists(Box<SelectQueryBuilder<'a>>),
+    NotExists(Box<SelectQueryBuilder<'a>>),
+    Any(Column, &'a (dyn ToSql + Sync)),
+    Contains(Column, &'a (dyn ToSql + Sync)),
+    Overlaps(Column, &'a (dyn ToSql + Sync)),
+    JsonContains(Column, &'a (dyn ToSql + Sync)),
+    JsonPathEqual(Column, String, &'a (dyn ToSql + Sync)),
+    Raw(String, Vec<&'a (dyn ToSql + Sync)>),
 }
 
 impl<'a> ToSqlString<'a> for Operation<'a> {
-    fn to_sql_string(&self, parameters: &mut Parameters<'a>) -> String {
-        match self {
+    fn to_sql_string(&self, parameters: &mut Parameters<'a>) -> Result<String, Error> {
+        let sql = match self {
             Self::Equal(column, value) => {
                 format!("{column} = ${}", parameters.add(*value))
             }
@@ -47,22 +102,10 @@ impl<'a> ToSqlString<'a> for Operation<'a> {
                 format!("{column} NOT LIKE ${}", parameters.add(*value))
             }
             Self::In(column, values) => {
-                let positions = values
-                    .iter()
-                    .map(|value| format!("${}", parameters.add(*value)))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-
-                format!("{column} IN ({positions})")
+                format!("{column} IN ({})", values.to_sql_string(parameters)?)
             }
             Self::NotIn(column, values) => {
-                let positions = values
-                    .iter()
-                    .map(|value| format!("${}", parameters.add(*value)))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-
-                format!("{column} NOT IN ({positions})")
+                format!("{column} NOT IN ({})", values.to_sql_string(parameters)?)
             }
             Self::Between(column, min, max) => {
                 let min_position = parameters.add(*min);
@@ -82,10 +125,60 @@ impl<'a> ToSqlString<'a> for Operation<'a> {
             Self::IsNotNull(column) => {
                 format!("{column} IS NOT NULL")
             }
-        }
+            Self::Exists(query) => {
+                format!("EXISTS ({})", query.to_sql_string(parameters)?)
+            }
+            Self::NotExists(query) => {
+                format!("NOT EXISTS ({})", query.to_sql_string(parameters)?)
+            }
+            Self::Any(column, value) => {
+                format!("${} = ANY({column})", parameters.add(*value))
+            }
+            Self::Contains(column, values) => {
+                format!("{column} @> ${}", parameters.add(*values))
+            }
+            Self::Overlaps(column, values) => {
+                format!("{column} && ${}", parameters.add(*values))
+            }
+            Self::JsonContains(column, value) => {
+                format!("{column} @> ${}", parameters.add(*value))
+            }
+            Self::JsonPathEqual(column, path, value) => {
+                format!(
+                    "jsonb_path_query_first({column}, {}) #>> '{{}}' = ${}",
+                    literal(path),
+                    parameters.add(*value)
+                )
+            }
+            Self::Raw(sql, bindings) => {
+                let placeholders = sql.matches("$?").count();
+
+                if placeholders != bindings.len() {
+                    return Err(Error::BindingMismatch(placeholders, bindings.len()));
+                }
+
+                let mut bindings = bindings.iter();
+                let mut fragments = sql.split("$?");
+                let mut sql = fragments.next().unwrap_or_default().to_string();
+
+                for (fragment, binding) in fragments.zip(&mut bindings) {
+                    sql.push_str(&format!("${}", parameters.add(*binding)));
+                    sql.push_str(fragment);
+                }
+
+                sql
+            }
+        };
+
+        Ok(sql)
     }
 }
 
+/// Quotes the value as a string literal.
+fn literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
 pub enum Where<'a> {
     And(Operation<'a>),
     Or(Operation<'a>),
@@ -103,12 +196,12 @@ impl<'a> Where<'a> {
 }
 
 impl<'a> ToSqlString<'a> for Where<'a> {
-    fn to_sql_string(&self, parameters: &mut Parameters<'a>) -> String {
-        match self {
-            Self::And(operation) => format!("AND ({})", operation.to_sql_string(parameters)),
-            Self::Or(operation) => format!("OR ({})", operation.to_sql_string(parameters)),
-            Self::Nop(operation) => format!("({})", operation.to_sql_string(parameters)),
-        }
+    fn to_sql_string(&self, parameters: &mut Parameters<'a>) -> Result<String, Error> {
+        Ok(match self {
+            Self::And(operation) => format!("AND ({})", operation.to_sql_string(parameters)?),
+            Self::Or(operation) => format!("OR ({})", operation.to_sql_string(parameters)?),
+            Self::Nop(operation) => format!("({})", operation.to_sql_string(parameters)?),
+        })
     }
 }
 
@@ -117,7 +210,7 @@ pub trait Whereable<'a>: Sized {
 
     fn where_equal<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::Equal(column.into(), value));
         self.add_where(condition);
@@ -127,7 +220,7 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_equal<C, V>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::Equal(column.into(), value));
         self.add_where(condition);
@@ -137,7 +230,7 @@ pub trait Whereable<'a>: Sized {
 
     fn where_not_equal<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::NotEqual(column.into(), value));
         self.add_where(condition);
@@ -147,7 +240,7 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_not_equal<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::NotEqual(column.into(), value));
         self.add_where(condition);
@@ -157,7 +250,7 @@ pub trait Whereable<'a>: Sized {
 
     fn where_greater_than<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::GreaterThan(column.into(), value));
         self.add_where(condition);
@@ -167,7 +260,7 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_greater_than<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::GreaterThan(column.into(), value));
         self.add_where(condition);
@@ -177,7 +270,7 @@ pub trait Whereable<'a>: Sized {
 
     fn where_greater_than_or_equal<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::GreaterThanOrEqual(column.into(), value));
         self.add_where(condition);
@@ -187,7 +280,7 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_greater_than_or_equal<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::GreaterThanOrEqual(column.into(), value));
         self.add_where(condition);
@@ -197,7 +290,7 @@ pub trait Whereable<'a>: Sized {
 
     fn where_less_than<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::LessThan(column.into(), value));
         self.add_where(condition);
@@ -207,7 +300,7 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_less_than<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::LessThan(column.into(), value));
         self.add_where(condition);
@@ -217,7 +310,7 @@ pub trait Whereable<'a>: Sized {
 
     fn where_less_than_or_equal<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::LessThanOrEqual(column.into(), value));
         self.add_where(condition);
@@ -227,7 +320,7 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_less_than_or_equal<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::LessThanOrEqual(column.into(), value));
         self.add_where(condition);
@@ -237,7 +330,7 @@ pub trait Whereable<'a>: Sized {
 
     fn where_like<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::Like(column.into(), value));
         self.add_where(condition);
@@ -247,7 +340,7 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_like<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::Like(column.into(), value));
         self.add_where(condition);
@@ -257,7 +350,7 @@ pub trait Whereable<'a>: Sized {
 
     fn where_not_like<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::NotLike(column.into(), value));
         self.add_where(condition);
@@ -267,7 +360,7 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_not_like<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::NotLike(column.into(), value));
         self.add_where(condition);
@@ -277,8 +370,8 @@ pub trait Whereable<'a>: Sized {
 
     fn where_in<C, V>(mut self, column: C, values: V) -> Self
     where
-        C: Into<String>,
-        V: Into<Vec<&'a (dyn ToSql + Sync)>>,
+        C: Into<Column>,
+        V: Into<Values<'a>>,
     {
         let condition = Where::And(Operation::In(column.into(), values.into()));
         self.add_where(condition);
@@ -288,8 +381,8 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_in<C, V>(mut self, column: C, values: V) -> Self
     where
-        C: Into<String>,
-        V: Into<Vec<&'a (dyn ToSql + Sync)>>,
+        C: Into<Column>,
+        V: Into<Values<'a>>,
     {
         let condition = Where::Or(Operation::In(column.into(), values.into()));
         self.add_where(condition);
@@ -299,8 +392,8 @@ pub trait Whereable<'a>: Sized {
 
     fn where_not_in<C, V>(mut self, column: C, values: V) -> Self
     where
-        C: Into<String>,
-        V: Into<Vec<&'a (dyn ToSql + Sync)>>,
+        C: Into<Column>,
+        V: Into<Values<'a>>,
     {
         let condition = Where::And(Operation::NotIn(column.into(), values.into()));
         self.add_where(condition);
@@ -310,8 +403,8 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_not_in<C, V>(mut self, column: C, values: V) -> Self
     where
-        C: Into<String>,
-        V: Into<Vec<&'a (dyn ToSql + Sync)>>,
+        C: Into<Column>,
+        V: Into<Values<'a>>,
     {
         let condition = Where::Or(Operation::NotIn(column.into(), values.into()));
         self.add_where(condition);
@@ -321,7 +414,7 @@ pub trait Whereable<'a>: Sized {
 
     fn where_null<C>(mut self, column: C) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::IsNull(column.into()));
         self.add_where(condition);
@@ -331,7 +424,7 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_null<C>(mut self, column: C) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::IsNull(column.into()));
         self.add_where(condition);
@@ -341,7 +434,7 @@ pub trait Whereable<'a>: Sized {
 
     fn where_not_null<C>(mut self, column: C) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::IsNotNull(column.into()));
         self.add_where(condition);
@@ -351,7 +444,7 @@ pub trait Whereable<'a>: Sized {
 
     fn or_where_not_null<C>(mut self, column: C) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::IsNotNull(column.into()));
         self.add_where(condition);
@@ -366,7 +459,7 @@ pub trait Whereable<'a>: Sized {
         end: &'a (dyn ToSql + Sync),
     ) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::Between(column.into(), start, end));
         self.add_where(condition);
@@ -381,7 +474,7 @@ pub trait Whereable<'a>: Sized {
         end: &'a (dyn ToSql + Sync),
     ) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::Between(column.into(), start, end));
         self.add_where(condition);
@@ -396,7 +489,7 @@ pub trait Whereable<'a>: Sized {
         end: &'a (dyn ToSql + Sync),
     ) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::And(Operation::NotBetween(column.into(), start, end));
         self.add_where(condition);
@@ -411,11 +504,195 @@ pub trait Whereable<'a>: Sized {
         end: &'a (dyn ToSql + Sync),
     ) -> Self
     where
-        C: Into<String>,
+        C: Into<Column>,
     {
         let condition = Where::Or(Operation::NotBetween(column.into(), start, end));
         self.add_where(condition);
 
         self
     }
+
+    fn where_exists(mut self, query: SelectQueryBuilder<'a>) -> Self {
+        let condition = Where::And(Operation::Exists(Box::new(query)));
+        self.add_where(condition);
+
+        self
+    }
+
+    fn or_where_exists(mut self, query: SelectQueryBuilder<'a>) -> Self {
+        let condition = Where::Or(Operation::Exists(Box::new(query)));
+        self.add_where(condition);
+
+        self
+    }
+
+    fn where_not_exists(mut self, query: SelectQueryBuilder<'a>) -> Self {
+        let condition = Where::And(Operation::NotExists(Box::new(query)));
+        self.add_where(condition);
+
+        self
+    }
+
+    fn or_where_not_exists(mut self, query: SelectQueryBuilder<'a>) -> Self {
+        let condition = Where::Or(Operation::NotExists(Box::new(query)));
+        self.add_where(condition);
+
+        self
+    }
+
+    /// Adds a condition matching rows whose array column
+    /// has the given value as one of its elements.
+    fn where_any<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
+    where
+        C: Into<Column>,
+    {
+        let condition = Where::And(Operation::Any(column.into(), value));
+        self.add_where(condition);
+
+        self
+    }
+
+    fn or_where_any<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
+    where
+        C: Into<Column>,
+    {
+        let condition = Where::Or(Operation::Any(column.into(), value));
+        self.add_where(condition);
+
+        self
+    }
+
+    /// Adds a condition matching rows whose array column
+    /// has every element of the given array, like
+    /// `vec!["rust", "web"]`.
+    fn where_contains<C>(mut self, column: C, values: &'a (dyn ToSql + Sync)) -> Self
+    where
+        C: Into<Column>,
+    {
+        let condition = Where::And(Operation::Contains(column.into(), values));
+        self.add_where(condition);
+
+        self
+    }
+
+    fn or_where_contains<C>(mut self, column: C, values: &'a (dyn ToSql + Sync)) -> Self
+    where
+        C: Into<Column>,
+    {
+        let condition = Where::Or(Operation::Contains(column.into(), values));
+        self.add_where(condition);
+
+        self
+    }
+
+    /// Adds a condition matching rows whose array column
+    /// has any element of the given array.
+    fn where_overlaps<C>(mut self, column: C, values: &'a (dyn ToSql + Sync)) -> Self
+    where
+        C: Into<Column>,
+    {
+        let condition = Where::And(Operation::Overlaps(column.into(), values));
+        self.add_where(condition);
+
+        self
+    }
+
+    fn or_where_overlaps<C>(mut self, column: C, values: &'a (dyn ToSql + Sync)) -> Self
+    where
+        C: Into<Column>,
+    {
+        let condition = Where::Or(Operation::Overlaps(column.into(), values));
+        self.add_where(condition);
+
+        self
+    }
+
+    /// Adds a condition matching rows whose JSONB column
+    /// contains the given JSON value, like
+    /// `Json(json!({ "plan": "pro" }))`.
+    fn where_json_contains<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
+    where
+        C: Into<Column>,
+    {
+        let condition = Where::And(Operation::JsonContains(column.into(), value));
+        self.add_where(condition);
+
+        self
+    }
+
+    fn or_where_json_contains<C>(mut self, column: C, value: &'a (dyn ToSql + Sync)) -> Self
+    where
+        C: Into<Column>,
+    {
+        let condition = Where::Or(Operation::JsonContains(column.into(), value));
+        self.add_where(condition);
+
+        self
+    }
+
+    /// Adds a condition matching rows whose JSONB column
+    /// has the given value at the JSON path, like
+    /// `$.plan`. The value is compared as text.
+    fn where_json_path_equal<C, P>(
+        mut self,
+        column: C,
+        path: P,
+        value: &'a (dyn ToSql + Sync),
+    ) -> Self
+    where
+        C: Into<Column>,
+        P: Into<String>,
+    {
+        let condition = Where::And(Operation::JsonPathEqual(column.into(), path.into(), value));
+        self.add_where(condition);
+
+        self
+    }
+
+    fn or_where_json_path_equal<C, P>(
+        mut self,
+        column: C,
+        path: P,
+        value: &'a (dyn ToSql + Sync),
+    ) -> Self
+    where
+        C: Into<Column>,
+        P: Into<String>,
+    {
+        let condition = Where::Or(Operation::JsonPathEqual(column.into(), path.into(), value));
+        self.add_where(condition);
+
+        self
+    }
+
+    /// Adds a raw condition. Every `$?` placeholder in it
+    /// is bound to the next value of the bindings, and
+    /// building the query fails when their counts differ.
+    fn where_raw<S, const N: usize>(mut self, sql: S, bindings: [&'a (dyn ToSql + Sync); N]) -> Self
+    where
+        S: Into<String>,
+    {
+        let condition = Where::And(Operation::Raw(sql.into(), Vec::from(bindings)));
+        self.add_where(condition);
+
+        self
+    }
+
+    /// Adds a raw condition, joined with `OR`. Every `$?`
+    /// placeholder in it is bound to the next value of the
+    /// bindings, and building the query fails when their
+    /// counts differ.
+    fn or_where_raw<S, const N: usize>(
+        mut self,
+        sql: S,
+        bindings: [&'a (dyn ToSql + Sync); N],
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let condition = Where::Or(Operation::Raw(sql.into(), Vec::from(bindings)));
+        self.add_where(condition);
+
+        self
+    }
 }
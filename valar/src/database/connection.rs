@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+
+use crate::database::builder::Column;
+use crate::database::builder::Table;
+use crate::database::Error;
+use crate::database::Row;
+use crate::database::ToSql;
+
+/// The SQL dialect spoken by a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Postgres,
+    Sqlite,
+}
+
+/// A connection statements can run against, either the
+/// database itself or an open transaction.
+#[async_trait]
+pub trait Connection: Sync {
+    /// Returns the SQL dialect of the connection.
+    fn dialect(&self) -> Dialect;
+
+    /// Runs the statement and returns the resulting rows.
+    async fn fetch(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error>;
+
+    /// Runs the statement and returns its only row. It
+    /// fails unless exactly one row is returned.
+    async fn fetch_one(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Row, Error> {
+        let mut rows = self.fetch(statement, parameters).await?.into_iter();
+
+        match (rows.next(), rows.next()) {
+            (Some(row), None) => Ok(row),
+            (None, _) => Err(Error::RowNotFound),
+            (Some(_), Some(_)) => Err(Error::TooManyRows),
+        }
+    }
+
+    /// Runs the statement and returns the number of rows
+    /// it modified.
+    async fn execute(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error>;
+
+    /// Inserts the rows into the given columns of the
+    /// table and returns the number of rows inserted. The
+    /// rows are inserted one by one, unless the connection
+    /// loads them in bulk, like Postgres does with `COPY`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::database::Connection;
+    /// use valar::database::Database;
+    /// use valar::database::ToSql;
+    ///
+    /// # async fn import(database: &Database) -> Result<(), valar::database::Error> {
+    /// let users = [("Erik", "soc@erik.cat"), ("Anna", "anna@example.com")];
+    /// let rows: Vec<Vec<&(dyn ToSql + Sync)>> = users
+    ///     .iter()
+    ///     .map(|(name, email)| vec![name as &(dyn ToSql + Sync), email])
+    ///     .collect();
+    ///
+    /// database.copy_in("users", &["name", "email"], &rows).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn copy_in(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<&(dyn ToSql + Sync)>],
+    ) -> Result<u64, Error> {
+        let statement = insert_statement(table, columns);
+        let mut inserted = 0;
+
+        for row in rows {
+            inserted += self.execute(&statement, row).await?;
+        }
+
+        Ok(inserted)
+    }
+}
+
+/// Returns the statement inserting one row into the given
+/// columns of the table.
+pub(crate) fn insert_statement(table: &str, columns: &[&str]) -> String {
+    let placeholders = (1..=columns.len())
+        .map(|position| format!("${position}"))
+        .collect::<Vec<_>>();
+
+    format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        Table::from(table),
+        quote_all(columns),
+        placeholders.join(", ")
+    )
+}
+
+/// Quotes the columns and joins them with commas.
+pub(crate) fn quote_all(columns: &[&str]) -> String {
+    columns
+        .iter()
+        .map(|column| Column::from(*column).to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
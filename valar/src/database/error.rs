@@ -0,0 +1,69 @@
+use std::convert::Infallible;
+use std::error::Error as StdError;
+
+use thiserror::Error as ThisError;
+
+/// An error returned by the database.
+#[derive(Debug, ThisError)]
+pub enum Error {
+    /// The backend failed to run the statement, like when
+    /// the connection is lost or a constraint is violated.
+    #[error(transparent)]
+    Backend(Box<dyn StdError + Send + Sync>),
+
+    /// The query returned no row where one was expected.
+    #[error("The query returned no rows")]
+    RowNotFound,
+
+    /// The query returned more rows than expected.
+    #[error("The query returned more than one row")]
+    TooManyRows,
+
+    /// The row has no column with the given name or at
+    /// the given position.
+    #[error("The row has no column {0}")]
+    ColumnNotFound(String),
+
+    /// A table or column name is not a plain identifier.
+    #[error("`{0}` is not a valid identifier")]
+    InvalidIdentifier(String),
+
+    /// A migration has ran but is not registered, so it
+    /// can't be reverted.
+    #[error("The migration {0} has ran but is not registered")]
+    MigrationNotFound(String),
+
+    /// The migration command is not one of `migrate`,
+    /// `rollback` or `status`.
+    #[error("Unknown migration command `{0}`, use migrate, rollback or status")]
+    UnknownCommand(String),
+
+    /// A raw condition has a different number of `$?`
+    /// placeholders than bindings.
+    #[error("The raw condition has {0} placeholders but {1} bindings")]
+    BindingMismatch(usize, usize),
+
+    /// A value could not be converted to or from the type
+    /// of the database.
+    #[error("{0}")]
+    Conversion(String),
+}
+
+impl From<Infallible> for Error {
+    fn from(error: Infallible) -> Self {
+        match error {}
+    }
+}
+
+impl From<tokio_postgres::Error> for Error {
+    fn from(error: tokio_postgres::Error) -> Self {
+        Self::Backend(Box::new(error))
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Backend(Box::new(error))
+    }
+}
@@ -1,48 +1,112 @@
 use async_trait::async_trait;
-use tokio_postgres::types::ToSql;
-use tokio_postgres::Error as PGError;
-use tokio_postgres::Row;
 
-use crate::database::Database;
+use crate::database::Connection;
+use crate::database::Error;
+use crate::database::FromSql;
+use crate::database::Row;
+use crate::database::ToSql;
 
 #[async_trait]
 pub trait Executor<'a> {
-    fn executor_parameters(&self) -> (String, Vec<&'a (dyn ToSql + Sync)>);
+    fn executor_parameters(&self) -> Result<(String, Vec<&'a (dyn ToSql + Sync)>), Error>;
 
-    async fn raw_get(&self, database: &Database) -> Result<Vec<Row>, PGError> {
-        let (statement, parameters) = &self.executor_parameters();
+    async fn raw_get<C>(&self, connection: &C) -> Result<Vec<Row>, Error>
+    where
+        C: Connection + ?Sized,
+    {
+        let (statement, parameters) = &self.executor_parameters()?;
 
-        database.client.query(statement, parameters).await
+        connection.fetch(statement, parameters).await
     }
 
-    async fn raw_first(&self, database: &Database) -> Result<Row, PGError> {
-        let (statement, parameters) = &self.executor_parameters();
+    async fn raw_first<C>(&self, connection: &C) -> Result<Row, Error>
+    where
+        C: Connection + ?Sized,
+    {
+        let (statement, parameters) = &self.executor_parameters()?;
 
-        database.client.query_one(statement, parameters).await
+        connection.fetch_one(statement, parameters).await
     }
 
-    async fn execute(&self, database: &Database) -> Result<u64, PGError> {
-        let (statement, parameters) = &self.executor_parameters();
+    async fn execute<C>(&self, connection: &C) -> Result<u64, Error>
+    where
+        C: Connection + ?Sized,
+    {
+        let (statement, parameters) = &self.executor_parameters()?;
 
-        database.client.execute(statement, parameters).await
+        connection.execute(statement, parameters).await
     }
 
-    async fn get<T, R>(&self, database: &Database) -> Result<R, PGError>
+    async fn get<T, R, C>(&self, connection: &C) -> Result<R, Error>
     where
-        T: TryFrom<Row, Error = PGError>,
+        T: TryFrom<Row, Error = Error>,
         R: FromIterator<T>,
+        C: Connection + ?Sized,
     {
-        self.raw_get(database)
+        self.raw_get(connection)
             .await?
             .into_iter()
             .map(|row| T::try_from(row))
             .collect()
     }
 
-    async fn first<T>(&self, database: &Database) -> Result<T, PGError>
+    async fn first<T, C>(&self, connection: &C) -> Result<T, Error>
+    where
+        T: TryFrom<Row, Error = Error>,
+        C: Connection + ?Sized,
+    {
+        T::try_from(self.raw_first(connection).await?)
+    }
+
+    /// Returns the value of the given column for every
+    /// row of the query.
+    async fn pluck<T, C>(&self, column: &str, connection: &C) -> Result<Vec<T>, Error>
+    where
+        T: FromSql,
+        C: Connection + ?Sized,
+    {
+        self.raw_get(connection)
+            .await?
+            .iter()
+            .map(|row| row.try_get(column))
+            .collect()
+    }
+
+    /// Returns the value of the given column for the only
+    /// row of the query.
+    async fn value<T, C>(&self, column: &str, connection: &C) -> Result<T, Error>
+    where
+        T: FromSql,
+        C: Connection + ?Sized,
+    {
+        self.raw_first(connection).await?.try_get(column)
+    }
+
+    /// Returns the only row of the query, or `None` when
+    /// no row matches.
+    async fn first_optional<T, C>(&self, connection: &C) -> Result<Option<T>, Error>
     where
-        T: TryFrom<Row, Error = PGError>,
+        T: TryFrom<Row, Error = Error>,
+        C: Connection + ?Sized,
     {
-        T::try_from(self.raw_first(database).await?)
+        match self.raw_first(connection).await {
+            Ok(row) => T::try_from(row).map(Some),
+            Err(Error::RowNotFound) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns whether the query matches any row, without
+    /// fetching them.
+    async fn exists<C>(&self, connection: &C) -> Result<bool, Error>
+    where
+        C: Connection + ?Sized,
+    {
+        let (statement, parameters) = &self.executor_parameters()?;
+
+        connection
+            .fetch_one(&format!("SELECT EXISTS({statement})"), parameters)
+            .await?
+            .try_get(0)
     }
 }
@@ -0,0 +1,164 @@
+use std::future::Future;
+use std::time::Duration;
+use std::time::Instant;
+
+use log::debug;
+use log::warn;
+
+use crate::database::Error;
+use crate::database::ToSql;
+
+/// A callback that receives every statement ran against
+/// the database.
+pub type Listener = Box<dyn Fn(&Query<'_>) + Send + Sync>;
+
+/// A statement ran against the database.
+#[derive(Debug)]
+pub struct Query<'a> {
+    /// The statement, with its placeholders.
+    pub statement: &'a str,
+
+    /// The debug representation of the bound parameters.
+    pub parameters: Vec<String>,
+
+    /// How long the statement took to run.
+    pub duration: Duration,
+
+    /// The number of rows returned or modified, which is
+    /// `None` when the statement failed.
+    pub rows: Option<u64>,
+}
+
+/// The listeners and the slow query threshold of a
+/// database.
+#[derive(Default)]
+pub(crate) struct Instrumentation {
+    listeners: Vec<Listener>,
+    slow_threshold: Option<Duration>,
+}
+
+impl Instrumentation {
+    pub(crate) fn listen(&mut self, listener: Listener) {
+        self.listeners.push(listener);
+    }
+
+    pub(crate) fn slow_threshold(&mut self, threshold: Duration) {
+        self.slow_threshold = Some(threshold);
+    }
+
+    /// Runs the statement, reporting it once it finishes.
+    /// The given closure counts the rows of its result.
+    pub(crate) async fn observe<T, F, R>(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+        rows: R,
+        future: F,
+    ) -> Result<T, Error>
+    where
+        F: Future<Output = Result<T, Error>>,
+        R: FnOnce(&T) -> u64,
+    {
+        if self.listeners.is_empty() && self.slow_threshold.is_none() {
+            return future.await;
+        }
+
+        let start = Instant::now();
+        let result = future.await;
+        let query = Query {
+            statement,
+            parameters: parameters
+                .iter()
+                .map(|parameter| format!("{parameter:?}"))
+                .collect(),
+            duration: start.elapsed(),
+            rows: result.as_ref().ok().map(rows),
+        };
+
+        for listener in &self.listeners {
+            listener(&query);
+        }
+
+        if self
+            .slow_threshold
+            .is_some_and(|threshold| query.duration >= threshold)
+        {
+            warn!(
+                "Slow query ({:.2}ms): {} {:?}",
+                query.duration.as_secs_f64() * 1000.0,
+                query.statement,
+                query.parameters
+            );
+        }
+
+        result
+    }
+}
+
+/// Logs the statement at the debug level.
+pub(crate) fn log(query: &Query<'_>) {
+    debug!(
+        "Query ({:.2}ms, {} rows): {} {:?}",
+        query.duration.as_secs_f64() * 1000.0,
+        query.rows.map_or("no".to_string(), |rows| rows.to_string()),
+        query.statement,
+        query.parameters
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use crate::database::instrumentation::Instrumentation;
+    use crate::database::Error;
+
+    #[tokio::test]
+    async fn it_reports_statements() {
+        let queries = Arc::new(Mutex::new(Vec::new()));
+        let mut instrumentation = Instrumentation::default();
+        let recorded = queries.clone();
+
+        instrumentation.listen(Box::new(move |query| {
+            recorded.lock().unwrap().push((
+                query.statement.to_string(),
+                query.parameters.clone(),
+                query.rows,
+            ));
+        }));
+        instrumentation.slow_threshold(Duration::from_secs(60));
+
+        let rows = instrumentation
+            .observe(
+                "SELECT * FROM users WHERE name = $1",
+                &[&"Erik"],
+                |rows: &Vec<i32>| rows.len() as u64,
+                async { Ok(vec![1, 2]) },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(rows, [1, 2]);
+
+        let failed = instrumentation
+            .observe("DELETE FROM users", &[], |rows| *rows, async {
+                Err::<u64, _>(Error::RowNotFound)
+            })
+            .await;
+
+        assert!(failed.is_err());
+        assert_eq!(
+            *queries.lock().unwrap(),
+            [
+                (
+                    "SELECT * FROM users WHERE name = $1".to_string(),
+                    vec!["\"Erik\"".to_string()],
+                    Some(2)
+                ),
+                ("DELETE FROM users".to_string(), vec![], None),
+            ]
+        );
+    }
+}
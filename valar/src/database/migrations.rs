@@ -0,0 +1,433 @@
+use async_trait::async_trait;
+use colored::Colorize;
+
+use crate::database::Connection;
+use crate::database::Database;
+use crate::database::Dialect;
+use crate::database::Error;
+use crate::database::Executor;
+use crate::database::Transaction;
+
+/// The key of the advisory lock held while migrating, so
+/// instances starting at the same time don't run the same
+/// migrations twice.
+const LOCK: i64 = 0x76616c6172;
+
+/// A change to the database schema. The migrations of a
+/// run share a transaction, so a failing migration leaves
+/// the schema as it was before the run.
+///
+/// # Example
+///
+/// ```no_run
+/// use async_trait::async_trait;
+/// use valar::database::migrations::Migration;
+/// use valar::database::Connection;
+/// use valar::database::Error;
+/// use valar::database::Transaction;
+///
+/// struct CreateUsersTable;
+///
+/// #[async_trait]
+/// impl Migration for CreateUsersTable {
+///     fn name(&self) -> &str {
+///         "2024_01_01_000000_create_users_table"
+///     }
+///
+///     async fn up(&self, transaction: &Transaction<'_>) -> Result<(), Error> {
+///         transaction
+///             .execute("CREATE TABLE users (id UUID PRIMARY KEY, name TEXT NOT NULL)", &[])
+///             .await?;
+///
+///         Ok(())
+///     }
+///
+///     async fn down(&self, transaction: &Transaction<'_>) -> Result<(), Error> {
+///         transaction.execute("DROP TABLE users", &[]).await?;
+///
+///         Ok(())
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait Migration: Send + Sync {
+    /// The unique name of the migration, recorded once it
+    /// has ran.
+    fn name(&self) -> &str;
+
+    /// Applies the migration.
+    async fn up(&self, transaction: &Transaction<'_>) -> Result<(), Error>;
+
+    /// Reverts the migration.
+    async fn down(&self, transaction: &Transaction<'_>) -> Result<(), Error>;
+}
+
+/// Whether a migration has ran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Status {
+    pub name: String,
+
+    /// The batch the migration ran in, if it has ran.
+    pub batch: Option<i32>,
+}
+
+/// Runs the migrations of the application and records the
+/// ones that have ran in a tracking table. The migrations
+/// run in the order they were registered, and every run
+/// groups the migrations it applies in a batch, which is
+/// what a rollback reverts. A run or rollback happens in
+/// a single transaction, so it applies either all of its
+/// migrations or none.
+///
+/// # Example
+///
+/// ```no_run
+/// # use async_trait::async_trait;
+/// # use valar::database::migrations::Migration;
+/// # use valar::database::Error;
+/// # use valar::database::Transaction;
+/// # struct CreateUsersTable;
+/// # #[async_trait]
+/// # impl Migration for CreateUsersTable {
+/// #     fn name(&self) -> &str { "create_users_table" }
+/// #     async fn up(&self, _: &Transaction<'_>) -> Result<(), Error> { Ok(()) }
+/// #     async fn down(&self, _: &Transaction<'_>) -> Result<(), Error> { Ok(()) }
+/// # }
+/// use valar::database::migrations::Migrator;
+/// use valar::database::Database;
+///
+/// # async fn migrate(database: &Database) -> Result<(), Error> {
+/// let migrator = Migrator::new().migration(CreateUsersTable);
+///
+/// migrator.run(database).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Migrator {
+    table: String,
+    migrations: Vec<Box<dyn Migration>>,
+}
+
+impl Default for Migrator {
+    fn default() -> Self {
+        Self {
+            table: "migrations".to_string(),
+            migrations: Vec::new(),
+        }
+    }
+}
+
+impl Migrator {
+    /// Creates a migrator tracking the migrations in the
+    /// `migrations` table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the table the migrations are tracked in.
+    pub fn table<T>(mut self, table: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.table = table.into();
+
+        self
+    }
+
+    /// Registers a migration.
+    pub fn migration<M>(mut self, migration: M) -> Self
+    where
+        M: Migration + 'static,
+    {
+        self.migrations.push(Box::new(migration));
+
+        self
+    }
+
+    /// Runs the migrations that have not ran yet, in a new
+    /// batch, and returns their names.
+    pub async fn run(&self, database: &Database) -> Result<Vec<String>, Error> {
+        let transaction = self.begin(database).await?;
+        let ran = self.ran(&transaction).await?;
+        let batch = ran.iter().map(|(_, batch)| *batch).max().unwrap_or(0) + 1;
+        let pending = self.pending(&ran);
+        let insert = format!("INSERT INTO {} (name, batch) VALUES ($1, $2)", self.table);
+
+        for migration in &pending {
+            migration.up(&transaction).await?;
+
+            Database::query(insert.as_str())
+                .with(&migration.name())
+                .with(&batch)
+                .execute(&transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(pending
+            .iter()
+            .map(|migration| migration.name().to_string())
+            .collect())
+    }
+
+    /// Reverts the migrations of the last batch, in the
+    /// reverse order they ran, and returns their names.
+    /// Nothing is reverted when one of them is no longer
+    /// registered.
+    pub async fn rollback(&self, database: &Database) -> Result<Vec<String>, Error> {
+        let transaction = self.begin(database).await?;
+        let ran = self.ran(&transaction).await?;
+        let Some(batch) = ran.iter().map(|(_, batch)| *batch).max() else {
+            transaction.commit().await?;
+
+            return Ok(Vec::new());
+        };
+        let delete = format!("DELETE FROM {} WHERE name = $1", self.table);
+        let mut reverted = Vec::new();
+        let last = ran
+            .iter()
+            .rev()
+            .filter(|(_, ran)| *ran == batch)
+            .map(|(name, _)| {
+                self.find(name)
+                    .map(|migration| (name, migration))
+                    .ok_or_else(|| Error::MigrationNotFound(name.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (name, migration) in last {
+            migration.down(&transaction).await?;
+
+            Database::query(delete.as_str())
+                .with(name)
+                .execute(&transaction)
+                .await?;
+
+            reverted.push(name.clone());
+        }
+
+        transaction.commit().await?;
+
+        Ok(reverted)
+    }
+
+    /// Returns whether each registered migration has ran.
+    pub async fn status(&self, database: &Database) -> Result<Vec<Status>, Error> {
+        let transaction = self.begin(database).await?;
+        let ran = self.ran(&transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(self
+            .migrations
+            .iter()
+            .map(|migration| Status {
+                name: migration.name().to_string(),
+                batch: ran
+                    .iter()
+                    .find(|(name, _)| name == migration.name())
+                    .map(|(_, batch)| *batch),
+            })
+            .collect())
+    }
+
+    /// Runs a migration command from the arguments of the
+    /// process, for apps shipping a small migration CLI:
+    /// `migrate`, `rollback` or `status`. Any other command
+    /// is an error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::database::migrations::Migrator;
+    /// use valar::database::Database;
+    ///
+    /// # async fn cli(database: &Database) -> Result<(), valar::database::Error> {
+    /// let command = std::env::args().nth(1).unwrap_or_default();
+    ///
+    /// Migrator::new().command(database, &command).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn command(&self, database: &Database, command: &str) -> Result<(), Error> {
+        match command {
+            "migrate" => {
+                let ran = self.run(database).await?;
+
+                if ran.is_empty() {
+                    println!("{}", "Nothing to migrate.".dimmed());
+                }
+
+                for name in ran {
+                    println!("{} {name}", "Migrated:".green().bold());
+                }
+            }
+            "rollback" => {
+                let reverted = self.rollback(database).await?;
+
+                if reverted.is_empty() {
+                    println!("{}", "Nothing to roll back.".dimmed());
+                }
+
+                for name in reverted {
+                    println!("{} {name}", "Rolled back:".yellow().bold());
+                }
+            }
+            "status" => {
+                for status in self.status(database).await? {
+                    match status.batch {
+                        Some(batch) => {
+                            println!("{} {} [{batch}]", "Ran".green().bold(), status.name)
+                        }
+                        None => println!("{} {}", "Pending".yellow().bold(), status.name),
+                    }
+                }
+            }
+            _ => return Err(Error::UnknownCommand(command.to_string())),
+        }
+
+        Ok(())
+    }
+
+    /// Starts the transaction every migration command runs
+    /// in, making sure the tracking table exists.
+    async fn begin<'a>(&self, database: &'a Database) -> Result<Transaction<'a>, Error> {
+        let transaction = database.begin().await?;
+
+        let create = match transaction.dialect() {
+            Dialect::Postgres => {
+                Database::query("SELECT pg_advisory_xact_lock($1)")
+                    .with(&LOCK)
+                    .execute(&transaction)
+                    .await?;
+
+                format!(
+                    "CREATE TABLE IF NOT EXISTS {} (\
+                        id SERIAL PRIMARY KEY, \
+                        name TEXT NOT NULL UNIQUE, \
+                        batch INTEGER NOT NULL, \
+                        migrated_at TIMESTAMPTZ NOT NULL DEFAULT now()\
+                    )",
+                    self.table
+                )
+            }
+            // SQLite transactions hold the write lock of the
+            // whole database, so no advisory lock is needed.
+            Dialect::Sqlite => format!(
+                "CREATE TABLE IF NOT EXISTS {} (\
+                    id INTEGER PRIMARY KEY AUTOINCREMENT, \
+                    name TEXT NOT NULL UNIQUE, \
+                    batch INTEGER NOT NULL, \
+                    migrated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP\
+                )",
+                self.table
+            ),
+        };
+
+        transaction.execute(&create, &[]).await?;
+
+        Ok(transaction)
+    }
+
+    /// Returns the name and batch of the migrations that
+    /// have ran, in the order they ran.
+    async fn ran(&self, transaction: &Transaction<'_>) -> Result<Vec<(String, i32)>, Error> {
+        let select = format!("SELECT name, batch FROM {} ORDER BY id", self.table);
+
+        transaction
+            .fetch(&select, &[])
+            .await?
+            .into_iter()
+            .map(|row| Ok((row.try_get(0)?, row.try_get(1)?)))
+            .collect()
+    }
+
+    /// Returns the registered migrations that have not ran.
+    fn pending(&self, ran: &[(String, i32)]) -> Vec<&dyn Migration> {
+        self.migrations
+            .iter()
+            .filter(|migration| !ran.iter().any(|(name, _)| name == migration.name()))
+            .map(Box::as_ref)
+            .collect()
+    }
+
+    /// Finds a registered migration by its name.
+    fn find(&self, name: &str) -> Option<&dyn Migration> {
+        self.migrations
+            .iter()
+            .find(|migration| migration.name() == name)
+            .map(Box::as_ref)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use crate::database::migrations::Migration;
+    use crate::database::migrations::Migrator;
+    #[cfg(feature = "sqlite")]
+    use crate::database::Database;
+    use crate::database::Error;
+    use crate::database::Transaction;
+
+    struct Named(&'static str);
+
+    #[async_trait]
+    impl Migration for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        async fn up(&self, _: &Transaction<'_>) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn down(&self, _: &Transaction<'_>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn it_can_find_pending_migrations() {
+        let migrator = Migrator::new()
+            .migration(Named("create_users_table"))
+            .migration(Named("create_posts_table"))
+            .migration(Named("add_email_to_users"));
+
+        let ran = vec![("create_posts_table".to_string(), 1)];
+        let pending: Vec<&str> = migrator
+            .pending(&ran)
+            .into_iter()
+            .map(|migration| migration.name())
+            .collect();
+
+        assert_eq!(pending, ["create_users_table", "add_email_to_users"]);
+        assert!(migrator.find("create_posts_table").is_some());
+        assert!(migrator.find("drop_users_table").is_none());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn it_rejects_unknown_commands_and_migrations() {
+        let database = Database::connect_sqlite(":memory:").unwrap();
+        let migrator = Migrator::new().migration(Named("create_users_table"));
+
+        assert!(matches!(
+            migrator.command(&database, "fresh").await,
+            Err(Error::UnknownCommand(command)) if command == "fresh"
+        ));
+
+        migrator.run(&database).await.unwrap();
+
+        assert!(matches!(
+            Migrator::new().rollback(&database).await,
+            Err(Error::MigrationNotFound(name)) if name == "create_users_table"
+        ));
+        assert_eq!(
+            migrator.rollback(&database).await.unwrap(),
+            ["create_users_table"]
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+
+use crate::database::builder::Column;
+use crate::database::builder::Table;
+use crate::database::builder::Whereable;
+use crate::database::connection::quote_all;
+use crate::database::Connection;
+use crate::database::Database;
+use crate::database::Error;
+use crate::database::Executor;
+use crate::database::Row;
+use crate::database::ToSql;
+
+/// A database table mapped to a struct, with helpers to
+/// load, save and delete its rows.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::database::Database;
+/// use valar::database::Model;
+/// use valar::database::Error;
+/// use valar::database::Row;
+/// use valar::database::ToSql;
+///
+/// struct User {
+///     id: i32,
+///     name: String,
+/// }
+///
+/// impl TryFrom<Row> for User {
+///     type Error = Error;
+///
+///     fn try_from(row: Row) -> Result<Self, Error> {
+///         Ok(Self {
+///             id: row.try_get("id")?,
+///             name: row.try_get("name")?,
+///         })
+///     }
+/// }
+///
+/// impl Model for User {
+///     const TABLE: &'static str = "users";
+///
+///     fn key(&self) -> &(dyn ToSql + Sync) {
+///         &self.id
+///     }
+///
+///     fn columns(&self) -> Vec<(&'static str, &(dyn ToSql + Sync))> {
+///         vec![("id", &self.id), ("name", &self.name)]
+///     }
+/// }
+///
+/// # async fn rename(database: &Database) -> Result<(), Error> {
+/// if let Some(mut user) = User::find(database, &1).await? {
+///     user.name = "Erik".to_string();
+///     user.save(database).await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait Model: TryFrom<Row, Error = Error> + Send + Sync {
+    /// The table the model is stored in.
+    const TABLE: &'static str;
+
+    /// The primary key column of the table.
+    const PRIMARY_KEY: &'static str = "id";
+
+    /// Returns the value of the primary key.
+    fn key(&self) -> &(dyn ToSql + Sync);
+
+    /// Returns the columns saved with the model and their
+    /// values, including the primary key.
+    fn columns(&self) -> Vec<(&'static str, &(dyn ToSql + Sync))>;
+
+    /// Returns every row of the table.
+    async fn all<C>(connection: &C) -> Result<Vec<Self>, Error>
+    where
+        C: Connection + ?Sized,
+    {
+        Database::table(Self::TABLE)
+            .select_all()
+            .get(connection)
+            .await
+    }
+
+    /// Returns the row with the given primary key, if any.
+    async fn find<C, K>(connection: &C, key: &K) -> Result<Option<Self>, Error>
+    where
+        C: Connection + ?Sized,
+        K: ToSql + Sync,
+    {
+        let rows = Database::table(Self::TABLE)
+            .select_all()
+            .where_equal(Self::PRIMARY_KEY, key)
+            .raw_get(connection)
+            .await?;
+
+        rows.into_iter().next().map(Self::try_from).transpose()
+    }
+
+    /// Inserts the model, or updates its row when one with
+    /// the same primary key exists.
+    async fn save<C>(&self, connection: &C) -> Result<(), Error>
+    where
+        C: Connection + ?Sized,
+    {
+        let columns = self.columns();
+        let names: Vec<&str> = columns.iter().map(|(name, _)| *name).collect();
+        let values: Vec<&(dyn ToSql + Sync)> = columns.iter().map(|(_, value)| *value).collect();
+
+        connection
+            .execute(&upsert(Self::TABLE, Self::PRIMARY_KEY, &names), &values)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the row of the model.
+    async fn delete<C>(&self, connection: &C) -> Result<(), Error>
+    where
+        C: Connection + ?Sized,
+    {
+        let statement = format!(
+            "DELETE FROM {} WHERE {} = $1",
+            Table::from(Self::TABLE),
+            Column::from(Self::PRIMARY_KEY)
+        );
+
+        connection.execute(&statement, &[self.key()]).await?;
+
+        Ok(())
+    }
+}
+
+/// Builds the statement inserting a row, or updating it
+/// when its primary key already exists.
+pub(crate) fn upsert(table: &str, primary_key: &str, columns: &[&str]) -> String {
+    let table = Table::from(table);
+    let placeholders: Vec<String> = (1..=columns.len())
+        .map(|position| format!("${position}"))
+        .collect();
+    let updates: Vec<String> = columns
+        .iter()
+        .filter(|column| **column != primary_key)
+        .map(|column| format!("{0} = EXCLUDED.{0}", Column::from(*column)))
+        .collect();
+    let primary_key = Column::from(primary_key);
+
+    let conflict = match updates.is_empty() {
+        true => "DO NOTHING".to_string(),
+        false => format!("DO UPDATE SET {}", updates.join(", ")),
+    };
+
+    format!(
+        "INSERT INTO {table} ({}) VALUES ({}) ON CONFLICT ({primary_key}) {conflict}",
+        quote_all(columns),
+        placeholders.join(", "),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::model::upsert;
+
+    #[test]
+    fn it_can_build_upserts() {
+        assert_eq!(
+            upsert("users", "id", &["id", "name", "email"]),
+            r#"INSERT INTO "users" ("id", "name", "email") VALUES ($1, $2, $3) ON CONFLICT ("id") DO UPDATE SET "name" = EXCLUDED."name", "email" = EXCLUDED."email""#
+        );
+        assert_eq!(
+            upsert("tags", "name", &["name"]),
+            r#"INSERT INTO "tags" ("name") VALUES ($1) ON CONFLICT ("name") DO NOTHING"#
+        );
+    }
+}
@@ -0,0 +1,484 @@
+use std::error::Error as StdError;
+use std::future::Future;
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use bytes::BytesMut;
+use futures_util::pin_mut;
+use futures_util::FutureExt;
+use futures_util::TryStreamExt;
+use log::warn;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::sync::RwLock;
+use tokio::sync::RwLockReadGuard;
+use tokio::sync::RwLockWriteGuard;
+use tokio_postgres::binary_copy::BinaryCopyInWriter;
+use tokio_postgres::types::to_sql_checked;
+use tokio_postgres::types::FromSql as PostgresFromSql;
+use tokio_postgres::types::IsNull;
+use tokio_postgres::types::Kind;
+use tokio_postgres::types::ToSql as PostgresToSql;
+use tokio_postgres::types::Type;
+use tokio_postgres::Client;
+
+use crate::database::backend::Backend;
+use crate::database::backend::TransactionBackend;
+use crate::database::builder::Table;
+use crate::database::connection::quote_all;
+use crate::database::Connection;
+use crate::database::Dialect;
+use crate::database::Error;
+use crate::database::Row;
+use crate::database::ToSql;
+use crate::database::Value;
+
+/// Opens a new connection to the database.
+type Connect =
+    Box<dyn Fn() -> Pin<Box<dyn Future<Output = Result<Client, Error>> + Send>> + Send + Sync>;
+
+/// The Postgres backend. Statements share the client,
+/// while a transaction holds it until it ends. When the
+/// connection is lost, the next statement opens a new one.
+pub(crate) struct Postgres {
+    client: RwLock<Client>,
+    connect: Connect,
+}
+
+impl Postgres {
+    /// Connects with the given callback, which is called
+    /// again whenever the connection has to be reopened.
+    pub(crate) async fn connect<F, Fut>(connect: F) -> Result<Self, Error>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Client, Error>> + Send + 'static,
+    {
+        let connect: Connect = Box::new(move || Box::pin(connect()));
+        let client = connect().await?;
+
+        Ok(Self {
+            client: RwLock::new(client),
+            connect,
+        })
+    }
+
+    /// Returns the client, reconnecting first when its
+    /// connection was closed.
+    async fn client(&self) -> Result<RwLockReadGuard<'_, Client>, Error> {
+        let client = self.client.read().await;
+
+        if !client.is_closed() {
+            return Ok(client);
+        }
+
+        drop(client);
+
+        Ok(self.reconnect().await?.downgrade())
+    }
+
+    /// Returns the client for exclusive use, reconnecting
+    /// first when its connection was closed.
+    async fn reconnect(&self) -> Result<RwLockWriteGuard<'_, Client>, Error> {
+        let mut client = self.client.write().await;
+
+        // Another statement may have reconnected while
+        // this one waited for the lock.
+        if client.is_closed() {
+            warn!("The database connection was closed, reconnecting");
+
+            *client = (self.connect)().await?;
+        }
+
+        Ok(client)
+    }
+}
+
+/// Drives the connection in the background and returns
+/// its client.
+pub(crate) fn drive<S, T>(client: Client, connection: tokio_postgres::Connection<S, T>) -> Client
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            eprintln!("connection error: {}", e);
+        }
+    });
+
+    client
+}
+
+#[async_trait]
+impl Connection for Postgres {
+    fn dialect(&self) -> Dialect {
+        Dialect::Postgres
+    }
+
+    async fn fetch(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        fetch(&*self.client().await?, statement, parameters).await
+    }
+
+    async fn execute(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        execute(&*self.client().await?, statement, parameters).await
+    }
+
+    async fn copy_in(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<&(dyn ToSql + Sync)>],
+    ) -> Result<u64, Error> {
+        copy_in(&*self.client().await?, table, columns, rows).await
+    }
+}
+
+#[async_trait]
+impl Backend for Postgres {
+    async fn begin(&self) -> Result<Box<dyn TransactionBackend + '_>, Error> {
+        let client = self.reconnect().await?;
+
+        client.batch_execute("BEGIN").await?;
+
+        Ok(Box::new(PostgresTransaction { client }))
+    }
+}
+
+/// Runs the statement and returns the resulting rows.
+async fn fetch(
+    client: &Client,
+    statement: &str,
+    parameters: &[&(dyn ToSql + Sync)],
+) -> Result<Vec<Row>, Error> {
+    let values = values(parameters);
+    let rows: Vec<_> = client
+        .query_raw(statement, &bind(&values))
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(rows.into_iter().map(Row::postgres).collect())
+}
+
+/// Runs the statement and returns the number of rows it
+/// modified.
+async fn execute(
+    client: &Client,
+    statement: &str,
+    parameters: &[&(dyn ToSql + Sync)],
+) -> Result<u64, Error> {
+    let values = values(parameters);
+
+    Ok(client.execute_raw(statement, &bind(&values)).await?)
+}
+
+/// Loads the rows into the table with the binary `COPY`
+/// protocol, which needs the types of the columns upfront.
+async fn copy_in(
+    client: &Client,
+    table: &str,
+    columns: &[&str],
+    rows: &[Vec<&(dyn ToSql + Sync)>],
+) -> Result<u64, Error> {
+    let table = Table::from(table);
+    let columns = quote_all(columns);
+    let select = client
+        .prepare(&format!("SELECT {columns} FROM {table} LIMIT 0"))
+        .await?;
+    let types = select
+        .columns()
+        .iter()
+        .map(|column| column.type_().clone())
+        .collect::<Vec<_>>();
+
+    let sink = client
+        .copy_in(&format!("COPY {table} ({columns}) FROM STDIN BINARY"))
+        .await?;
+    let writer = BinaryCopyInWriter::new(sink, &types);
+
+    pin_mut!(writer);
+
+    for row in rows {
+        let values = values(row);
+
+        writer.as_mut().write_raw(&bind(&values)).await?;
+    }
+
+    Ok(writer.finish().await?)
+}
+
+/// A Postgres transaction, holding the client.
+struct PostgresTransaction<'a> {
+    client: RwLockWriteGuard<'a, Client>,
+}
+
+#[async_trait]
+impl Connection for PostgresTransaction<'_> {
+    fn dialect(&self) -> Dialect {
+        Dialect::Postgres
+    }
+
+    async fn fetch(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        fetch(&self.client, statement, parameters).await
+    }
+
+    async fn execute(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        execute(&self.client, statement, parameters).await
+    }
+
+    async fn copy_in(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<&(dyn ToSql + Sync)>],
+    ) -> Result<u64, Error> {
+        copy_in(&self.client, table, columns, rows).await
+    }
+}
+
+#[async_trait]
+impl TransactionBackend for PostgresTransaction<'_> {
+    async fn commit(&self) -> Result<(), Error> {
+        Ok(self.client.batch_execute("COMMIT").await?)
+    }
+
+    async fn rollback(&self) -> Result<(), Error> {
+        Ok(self.client.batch_execute("ROLLBACK").await?)
+    }
+
+    fn abort(&self) {
+        // Polling the statement once queues it on the
+        // connection, and the connection runs statements
+        // in order, so the rollback happens before any
+        // statement sent once the connection is released.
+        let _ = self.client.batch_execute("ROLLBACK").now_or_never();
+    }
+}
+
+fn values(parameters: &[&(dyn ToSql + Sync)]) -> Vec<Value> {
+    parameters
+        .iter()
+        .map(|parameter| parameter.to_value())
+        .collect()
+}
+
+fn bind(values: &[Value]) -> Vec<Parameter<'_>> {
+    values.iter().map(Parameter).collect()
+}
+
+/// A value bound to a statement, written as the type
+/// Postgres expects for it.
+#[derive(Debug)]
+struct Parameter<'a>(&'a Value);
+
+impl PostgresToSql for Parameter<'_> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        match self.0 {
+            Value::Null => Ok(IsNull::Yes),
+            Value::Bool(boolean) => boolean.to_sql(ty, out),
+            Value::Int(integer) => match *ty {
+                Type::CHAR => i8::try_from(*integer)?.to_sql(ty, out),
+                Type::INT2 => i16::try_from(*integer)?.to_sql(ty, out),
+                Type::INT4 => i32::try_from(*integer)?.to_sql(ty, out),
+                Type::OID => u32::try_from(*integer)?.to_sql(ty, out),
+                Type::FLOAT4 => (*integer as f32).to_sql(ty, out),
+                Type::FLOAT8 => (*integer as f64).to_sql(ty, out),
+                _ => integer.to_sql(ty, out),
+            },
+            Value::Float(float) => match *ty {
+                Type::FLOAT4 => (*float as f32).to_sql(ty, out),
+                _ => float.to_sql(ty, out),
+            },
+            Value::Text(text) => text.as_str().to_sql(ty, out),
+            Value::Bytes(bytes) => bytes.as_slice().to_sql(ty, out),
+            Value::Json(json) => json.to_sql(ty, out),
+            Value::Array(values) => values
+                .iter()
+                .map(Parameter)
+                .collect::<Vec<_>>()
+                .to_sql(ty, out),
+        }
+    }
+
+    /// The type is checked once the value is written, as
+    /// it depends on the value.
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
+
+/// A value read from a row.
+struct Decoded(Value);
+
+impl<'a> PostgresFromSql<'a> for Decoded {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let value = match *ty {
+            Type::BOOL => Value::Bool(bool::from_sql(ty, raw)?),
+            Type::CHAR => Value::Int(i8::from_sql(ty, raw)? as i64),
+            Type::INT2 => Value::Int(i16::from_sql(ty, raw)? as i64),
+            Type::INT4 => Value::Int(i32::from_sql(ty, raw)? as i64),
+            Type::INT8 => Value::Int(i64::from_sql(ty, raw)?),
+            Type::OID => Value::Int(u32::from_sql(ty, raw)? as i64),
+            Type::FLOAT4 => Value::Float(f32::from_sql(ty, raw)? as f64),
+            Type::FLOAT8 => Value::Float(f64::from_sql(ty, raw)?),
+            Type::BYTEA => Value::Bytes(Vec::from_sql(ty, raw)?),
+            Type::JSON | Type::JSONB => Value::Json(serde_json::Value::from_sql(ty, raw)?),
+            _ if <&str as PostgresFromSql>::accepts(ty) => Value::Text(String::from_sql(ty, raw)?),
+            _ if matches!(ty.kind(), Kind::Array(_)) => Value::Array(
+                Vec::<Decoded>::from_sql(ty, raw)?
+                    .into_iter()
+                    .map(|decoded| decoded.0)
+                    .collect(),
+            ),
+            _ => return Err(format!("The type {ty} is not supported").into()),
+        };
+
+        Ok(Self(value))
+    }
+
+    fn from_sql_null(_: &Type) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        Ok(Self(Value::Null))
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+/// Reads the value of the column at the given position.
+pub(crate) fn decode(row: &tokio_postgres::Row, position: usize) -> Result<Value, Error> {
+    row.try_get::<_, Decoded>(position)
+        .map(|decoded| decoded.0)
+        .map_err(|error| match error.source() {
+            Some(source) => Error::Conversion(format!("{error}: {source}")),
+            None => Error::Conversion(error.to_string()),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::io::DuplexStream;
+    use tokio::task::JoinHandle;
+    use tokio_postgres::Config;
+    use tokio_postgres::NoTls;
+
+    use crate::database::postgres::drive;
+    use crate::database::postgres::Postgres;
+    use crate::database::Connection;
+
+    /// Appends a backend message with the given tag.
+    fn message(buffer: &mut Vec<u8>, tag: u8, body: &[u8]) {
+        buffer.push(tag);
+        buffer.extend((body.len() as u32 + 4).to_be_bytes());
+        buffer.extend(body);
+    }
+
+    /// Answers the startup and every statement like a
+    /// server updating a single row, until the stream ends.
+    async fn serve(mut stream: DuplexStream) -> std::io::Result<()> {
+        let length = stream.read_u32().await?;
+        let mut startup = vec![0; length as usize - 4];
+
+        stream.read_exact(&mut startup).await?;
+
+        let mut reply = Vec::new();
+
+        message(&mut reply, b'R', &0u32.to_be_bytes());
+        message(&mut reply, b'Z', b"I");
+        stream.write_all(&reply).await?;
+
+        loop {
+            let tag = stream.read_u8().await?;
+            let length = stream.read_u32().await?;
+            let mut body = vec![0; length as usize - 4];
+
+            stream.read_exact(&mut body).await?;
+
+            let mut reply = Vec::new();
+
+            match tag {
+                b'P' => message(&mut reply, b'1', &[]),
+                b'D' => {
+                    message(&mut reply, b't', &0u16.to_be_bytes());
+                    message(&mut reply, b'n', &[]);
+                }
+                b'B' => message(&mut reply, b'2', &[]),
+                b'E' => message(&mut reply, b'C', b"UPDATE 1\0"),
+                b'C' => message(&mut reply, b'3', &[]),
+                b'S' => message(&mut reply, b'Z', b"I"),
+                b'X' => return Ok(()),
+                _ => {}
+            }
+
+            stream.write_all(&reply).await?;
+        }
+    }
+
+    #[tokio::test]
+    async fn it_reconnects_when_the_connection_is_lost() {
+        let servers: Arc<Mutex<Vec<JoinHandle<_>>>> = Arc::default();
+        let backend = Postgres::connect({
+            let servers = servers.clone();
+
+            move || {
+                let (client, server) = tokio::io::duplex(1024);
+
+                servers.lock().unwrap().push(tokio::spawn(serve(server)));
+
+                async move {
+                    let (client, connection) = Config::new()
+                        .user("valar")
+                        .connect_raw(client, NoTls)
+                        .await?;
+
+                    Ok(drive(client, connection))
+                }
+            }
+        })
+        .await
+        .unwrap();
+        let statement = "UPDATE users SET active = true";
+
+        assert_eq!(backend.execute(statement, &[]).await.unwrap(), 1);
+
+        servers.lock().unwrap()[0].abort();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !backend.client.read().await.is_closed() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(backend.execute(statement, &[]).await.unwrap(), 1);
+        assert_eq!(servers.lock().unwrap().len(), 2);
+    }
+}
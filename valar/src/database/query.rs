@@ -1,6 +1,10 @@
-use tokio_postgres::types::ToSql;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
 
+use crate::database::Error;
 use crate::database::Executor;
+use crate::database::ToSql;
 
 pub struct PendingQuery<'a> {
     statement: String,
@@ -29,10 +33,7 @@ impl<'a> PendingQuery<'a> {
     }
 
     #[must_use]
-    pub fn parameters<const N: usize>(
-        mut self,
-        value: [&'a (dyn ToSql + Sync); N],
-    ) -> Self {
+    pub fn parameters<const N: usize>(mut self, value: [&'a (dyn ToSql + Sync); N]) -> Self {
         self.parameters = Vec::from(value);
 
         self
@@ -47,13 +48,13 @@ impl<'a> PendingQuery<'a> {
 }
 
 impl<'a> Executor<'a> for PendingQuery<'a> {
-    fn executor_parameters(&self) -> (String, Vec<&'a (dyn ToSql + Sync)>) {
-        (self.statement.clone(), self.parameters.clone())
+    fn executor_parameters(&self) -> Result<(String, Vec<&'a (dyn ToSql + Sync)>), Error> {
+        Ok((self.statement.clone(), self.parameters.clone()))
     }
 }
 
-impl<'a> ToString for PendingQuery<'a> {
-    fn to_string(&self) -> String {
-        self.statement.clone()
+impl Display for PendingQuery<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.statement)
     }
 }
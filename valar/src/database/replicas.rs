@@ -0,0 +1,421 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::database::Backend;
+use crate::database::Connection;
+use crate::database::Database;
+use crate::database::Dialect;
+use crate::database::Error;
+use crate::database::Row;
+use crate::database::ToSql;
+
+/// The read replicas of a database. Reads are spread
+/// across them in turns.
+#[derive(Default)]
+pub(crate) struct Replicas {
+    backends: Vec<Box<dyn Backend>>,
+    next: AtomicUsize,
+    sticky: Option<Duration>,
+}
+
+impl Replicas {
+    pub(crate) fn push(&mut self, backend: Box<dyn Backend>) {
+        self.backends.push(backend);
+    }
+
+    pub(crate) fn sticky(&mut self, duration: Duration) {
+        self.sticky = Some(duration);
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &dyn Backend> {
+        self.backends.iter().map(|backend| &**backend)
+    }
+
+    /// Returns the replica the statement should run on,
+    /// or `None` when it should run on the primary.
+    pub(crate) fn reader(&self, statement: &str) -> Option<&dyn Backend> {
+        if self.backends.is_empty() || !is_read(statement) {
+            return None;
+        }
+
+        let next = self.next.fetch_add(1, Ordering::Relaxed);
+
+        Some(&*self.backends[next % self.backends.len()])
+    }
+}
+
+/// A handle to a database remembering its own writes, so
+/// its reads stick to the primary for the duration set
+/// with [`Database::sticky`] after them, while the reads
+/// of other handles still go to the replicas. Use one per
+/// request or unit of work.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::database::Connection;
+/// use valar::database::Database;
+///
+/// # async fn example(database: &Database) -> Result<(), valar::database::Error> {
+/// let handle = database.handle();
+///
+/// handle.execute("UPDATE users SET active = true", &[]).await?;
+/// handle.fetch("SELECT * FROM users WHERE active", &[]).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Handle<'a> {
+    database: &'a Database,
+    last_write: Mutex<Option<Instant>>,
+}
+
+impl<'a> Handle<'a> {
+    pub(crate) fn new(database: &'a Database) -> Self {
+        Self {
+            database,
+            last_write: Mutex::default(),
+        }
+    }
+
+    /// Records that the handle wrote to the primary.
+    fn wrote(&self) {
+        *self.last_write() = Some(Instant::now());
+    }
+
+    /// Whether reads should stick to the primary.
+    fn is_sticking(&self) -> bool {
+        let Some(sticky) = self.database.replicas.sticky else {
+            return false;
+        };
+
+        self.last_write()
+            .is_some_and(|written| written.elapsed() < sticky)
+    }
+
+    fn last_write(&self) -> MutexGuard<'_, Option<Instant>> {
+        self.last_write
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[async_trait]
+impl Connection for Handle<'_> {
+    fn dialect(&self) -> Dialect {
+        self.database.dialect()
+    }
+
+    async fn fetch(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        let primary = self.is_sticking();
+
+        if !is_read(statement) {
+            self.wrote();
+        }
+
+        self.database
+            .fetch_from(statement, parameters, primary)
+            .await
+    }
+
+    async fn execute(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        self.wrote();
+
+        self.database.execute(statement, parameters).await
+    }
+
+    async fn copy_in(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<&(dyn ToSql + Sync)>],
+    ) -> Result<u64, Error> {
+        self.wrote();
+
+        self.database.copy_in(table, columns, rows).await
+    }
+}
+
+/// Whether the statement only reads, so a replica can
+/// run it. Selects that lock rows, create a table or call
+/// a function that is not known to only read run on the
+/// primary, as the function may write.
+pub(crate) fn is_read(statement: &str) -> bool {
+    let words = words(statement);
+
+    if !words
+        .first()
+        .is_some_and(|(word, _)| word.eq_ignore_ascii_case("SELECT"))
+    {
+        return false;
+    }
+
+    words.iter().enumerate().all(|(position, (word, call))| {
+        let word = word.to_ascii_lowercase();
+        let next = words
+            .get(position + 1)
+            .map(|(next, _)| next.to_ascii_lowercase());
+
+        match word.as_str() {
+            "into" => false,
+            "for" => !matches!(next.as_deref(), Some("update" | "share" | "no" | "key")),
+            _ if *call => READ_ONLY_CALLS.contains(&word.as_str()),
+            _ => true,
+        }
+    })
+}
+
+/// The words followed by a parenthesis that can't write:
+/// keywords and functions that only read.
+const READ_ONLY_CALLS: &[&str] = &[
+    "select",
+    "from",
+    "join",
+    "on",
+    "using",
+    "where",
+    "and",
+    "or",
+    "not",
+    "in",
+    "exists",
+    "any",
+    "all",
+    "some",
+    "as",
+    "values",
+    "over",
+    "filter",
+    "within",
+    "lateral",
+    "by",
+    "having",
+    "when",
+    "then",
+    "else",
+    "case",
+    "is",
+    "between",
+    "like",
+    "ilike",
+    "distinct",
+    "union",
+    "intersect",
+    "except",
+    "array",
+    "row",
+    "count",
+    "sum",
+    "avg",
+    "min",
+    "max",
+    "cast",
+    "coalesce",
+    "nullif",
+    "greatest",
+    "least",
+    "lower",
+    "upper",
+    "length",
+    "abs",
+    "round",
+    "floor",
+    "ceil",
+    "trim",
+    "concat",
+    "substring",
+    "date_trunc",
+    "extract",
+    "now",
+    "array_agg",
+    "string_agg",
+    "json_agg",
+    "jsonb_agg",
+    "json_build_object",
+    "jsonb_build_object",
+    "row_number",
+    "rank",
+    "to_char",
+];
+
+/// Splits the statement into its words, outside quotes,
+/// along with whether each is followed by a parenthesis.
+fn words(statement: &str) -> Vec<(&str, bool)> {
+    let mut words = Vec::new();
+    let mut characters = statement.char_indices().peekable();
+    let mut quote = None;
+    let mut start = None;
+
+    while let Some((index, character)) = characters.next() {
+        if let Some(open) = quote {
+            if character == open {
+                quote = None;
+            }
+
+            continue;
+        }
+
+        if character.is_alphanumeric() || character == '_' {
+            start.get_or_insert(index);
+
+            if characters
+                .peek()
+                .is_some_and(|(_, next)| next.is_alphanumeric() || *next == '_')
+            {
+                continue;
+            }
+
+            let word = &statement[start.take().unwrap_or(index)..index + character.len_utf8()];
+            let call = statement[index + character.len_utf8()..]
+                .trim_start()
+                .starts_with('(');
+
+            words.push((word, call));
+        } else if character == '\'' || character == '"' {
+            quote = Some(character);
+        }
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+
+    use crate::database::backend::TransactionBackend;
+    use crate::database::replicas::is_read;
+    use crate::database::Backend;
+    use crate::database::Connection;
+    use crate::database::Database;
+    use crate::database::Dialect;
+    use crate::database::Error;
+    use crate::database::Row;
+    use crate::database::ToSql;
+
+    #[derive(Clone, Default)]
+    struct Recorder {
+        statements: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn statements(&self) -> Vec<String> {
+            self.statements.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Connection for Recorder {
+        fn dialect(&self) -> Dialect {
+            Dialect::Postgres
+        }
+
+        async fn fetch(
+            &self,
+            statement: &str,
+            _: &[&(dyn ToSql + Sync)],
+        ) -> Result<Vec<Row>, Error> {
+            self.statements.lock().unwrap().push(statement.to_string());
+
+            Ok(vec![])
+        }
+
+        async fn execute(&self, statement: &str, _: &[&(dyn ToSql + Sync)]) -> Result<u64, Error> {
+            self.statements.lock().unwrap().push(statement.to_string());
+
+            Ok(1)
+        }
+    }
+
+    #[async_trait]
+    impl Backend for Recorder {
+        async fn begin(&self) -> Result<Box<dyn TransactionBackend + '_>, Error> {
+            Err(Error::Backend("The recorder runs no transactions".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn it_reads_from_replicas() {
+        let (primary, first, second) = (
+            Recorder::default(),
+            Recorder::default(),
+            Recorder::default(),
+        );
+        let database = Database::from_backend(primary.clone())
+            .replica(first.clone())
+            .replica(second.clone());
+
+        database.fetch("SELECT 1", &[]).await.unwrap();
+        database.fetch("SELECT 2", &[]).await.unwrap();
+        database
+            .fetch("INSERT INTO users DEFAULT VALUES RETURNING id", &[])
+            .await
+            .unwrap();
+        database.execute("DELETE FROM users", &[]).await.unwrap();
+        database.fetch("SELECT 3", &[]).await.unwrap();
+
+        assert_eq!(
+            primary.statements(),
+            [
+                "INSERT INTO users DEFAULT VALUES RETURNING id",
+                "DELETE FROM users"
+            ]
+        );
+        assert_eq!(first.statements(), ["SELECT 1", "SELECT 3"]);
+        assert_eq!(second.statements(), ["SELECT 2"]);
+    }
+
+    #[tokio::test]
+    async fn it_sticks_to_the_primary_after_writes() {
+        let (primary, replica) = (Recorder::default(), Recorder::default());
+        let database = Database::from_backend(primary.clone())
+            .replica(replica.clone())
+            .sticky(Duration::from_secs(60));
+
+        let (writer, reader) = (database.handle(), database.handle());
+
+        writer.fetch("SELECT 1", &[]).await.unwrap();
+        writer.execute("DELETE FROM users", &[]).await.unwrap();
+        writer.fetch("SELECT 2", &[]).await.unwrap();
+        reader.fetch("SELECT 3", &[]).await.unwrap();
+        database.fetch("SELECT 4", &[]).await.unwrap();
+
+        assert_eq!(primary.statements(), ["DELETE FROM users", "SELECT 2"]);
+        assert_eq!(replica.statements(), ["SELECT 1", "SELECT 3", "SELECT 4"]);
+    }
+
+    #[test]
+    fn it_can_detect_reads() {
+        assert!(is_read("SELECT * FROM users"));
+        assert!(is_read("  select id FROM users"));
+        assert!(!is_read("INSERT INTO users (name) VALUES ($1)"));
+        assert!(!is_read("UPDATE users SET name = $1 RETURNING id"));
+        assert!(!is_read("SEL"));
+        assert!(is_read("SELECT COUNT(*) FROM users WHERE id IN (1, 2)"));
+        assert!(is_read("SELECT name FROM users WHERE name = 'nextval(1)'"));
+        assert!(!is_read("SELECT * FROM users WHERE id = 1 FOR UPDATE"));
+        assert!(!is_read("select * from jobs for no key update skip locked"));
+        assert!(!is_read("SELECT * FROM users FOR SHARE"));
+        assert!(!is_read("SELECT nextval('users_id_seq')"));
+        assert!(!is_read("SELECT pg_advisory_lock (1)"));
+        assert!(!is_read("SELECT * INTO archived_users FROM users"));
+    }
+}
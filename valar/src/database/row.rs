@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use crate::database::postgres;
+use crate::database::Error;
+use crate::database::FromSql;
+use crate::database::Value;
+
+/// A row returned by the database.
+pub struct Row {
+    inner: Inner,
+}
+
+enum Inner {
+    Postgres(tokio_postgres::Row),
+    Values {
+        columns: Arc<[String]>,
+        values: Vec<Value>,
+    },
+}
+
+/// A column of a row, either its name or its position.
+pub trait ColumnIndex {
+    /// Returns the position of the column among the given
+    /// column names.
+    fn position(&self, columns: &[&str]) -> Option<usize>;
+
+    /// Describes the column in errors.
+    fn describe(&self) -> String;
+}
+
+impl ColumnIndex for usize {
+    fn position(&self, columns: &[&str]) -> Option<usize> {
+        (*self < columns.len()).then_some(*self)
+    }
+
+    fn describe(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ColumnIndex for str {
+    fn position(&self, columns: &[&str]) -> Option<usize> {
+        columns.iter().position(|column| *column == self)
+    }
+
+    fn describe(&self) -> String {
+        format!("`{self}`")
+    }
+}
+
+impl ColumnIndex for String {
+    fn position(&self, columns: &[&str]) -> Option<usize> {
+        self.as_str().position(columns)
+    }
+
+    fn describe(&self) -> String {
+        self.as_str().describe()
+    }
+}
+
+impl<T> ColumnIndex for &T
+where
+    T: ColumnIndex + ?Sized,
+{
+    fn position(&self, columns: &[&str]) -> Option<usize> {
+        (**self).position(columns)
+    }
+
+    fn describe(&self) -> String {
+        (**self).describe()
+    }
+}
+
+impl Row {
+    /// Creates a row with the given columns and values, in
+    /// the same order, like the rows of a custom backend.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use valar::database::Row;
+    /// use valar::database::Value;
+    ///
+    /// let row = Row::new(["name"], [Value::Text("Erik".to_string())]);
+    ///
+    /// assert_eq!(row.get::<_, String>("name"), "Erik");
+    /// ```
+    pub fn new<C, V>(columns: C, values: V) -> Self
+    where
+        C: IntoIterator,
+        C::Item: Into<String>,
+        V: IntoIterator<Item = Value>,
+    {
+        Self::with_columns(
+            columns.into_iter().map(Into::into).collect(),
+            values.into_iter().collect(),
+        )
+    }
+
+    /// Creates a row sharing the column names of the other
+    /// rows of its result.
+    pub(crate) fn with_columns(columns: Arc<[String]>, values: Vec<Value>) -> Self {
+        Self {
+            inner: Inner::Values { columns, values },
+        }
+    }
+
+    /// Wraps a row returned by Postgres, whose values are
+    /// decoded as they are read.
+    pub(crate) fn postgres(row: tokio_postgres::Row) -> Self {
+        Self {
+            inner: Inner::Postgres(row),
+        }
+    }
+
+    /// Returns the names of the columns of the row.
+    pub fn columns(&self) -> Vec<&str> {
+        match &self.inner {
+            Inner::Postgres(row) => row.columns().iter().map(|column| column.name()).collect(),
+            Inner::Values { columns, .. } => columns.iter().map(String::as_str).collect(),
+        }
+    }
+
+    /// Returns the number of columns of the row.
+    pub fn len(&self) -> usize {
+        self.columns().len()
+    }
+
+    /// Whether the row has no columns.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value of the given column, by name or
+    /// by position.
+    pub fn try_get<I, T>(&self, index: I) -> Result<T, Error>
+    where
+        I: ColumnIndex,
+        T: FromSql,
+    {
+        let position = index
+            .position(&self.columns())
+            .ok_or_else(|| Error::ColumnNotFound(index.describe()))?;
+
+        let value = match &self.inner {
+            Inner::Postgres(row) => postgres::decode(row, position)?,
+            Inner::Values { values, .. } => values
+                .get(position)
+                .cloned()
+                .ok_or_else(|| Error::ColumnNotFound(index.describe()))?,
+        };
+
+        T::from_value(value)
+    }
+
+    /// Returns the value of the given column, by name or
+    /// by position. It panics when the column does not
+    /// exist or its value can't be converted.
+    pub fn get<I, T>(&self, index: I) -> T
+    where
+        I: ColumnIndex,
+        T: FromSql,
+    {
+        match self.try_get(index) {
+            Ok(value) => value,
+            Err(error) => panic!("Failed to read the column: {error}"),
+        }
+    }
+}
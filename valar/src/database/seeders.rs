@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use thiserror::Error as ThisError;
+
+use crate::database::Connection;
+use crate::database::Database;
+use crate::database::Error as DatabaseError;
+
+/// The environment seeders assume when `VALAR_ENV` is not
+/// set, so they never run by accident on a server that
+/// didn't set it.
+const DEFAULT_ENVIRONMENT: &str = "production";
+
+#[derive(Debug, ThisError)]
+pub enum Error {
+    #[error("Seeders are disabled in the {0} environment")]
+    Environment(String),
+
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+}
+
+/// Populates the database with development or test data.
+/// Seeders can run other seeders from [`Seeder::seed`] to
+/// split the data by concern.
+///
+/// # Example
+///
+/// ```no_run
+/// use async_trait::async_trait;
+/// use valar::database::seeders::Seeder;
+/// use valar::database::Connection;
+/// use valar::database::Database;
+/// use valar::database::Executor;
+/// use valar::database::Error as DatabaseError;
+///
+/// struct UsersSeeder;
+///
+/// #[async_trait]
+/// impl Seeder for UsersSeeder {
+///     async fn seed(&self, connection: &dyn Connection) -> Result<(), DatabaseError> {
+///         Database::query("INSERT INTO users (name) VALUES ($1)")
+///             .with(&"Erik")
+///             .execute(connection)
+///             .await?;
+///
+///         Ok(())
+///     }
+/// }
+///
+/// # async fn seed(database: &Database) -> Result<(), valar::database::seeders::Error> {
+/// UsersSeeder.run(database).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait Seeder: Send + Sync {
+    /// Inserts the data of the seeder.
+    async fn seed(&self, connection: &dyn Connection) -> Result<(), DatabaseError>;
+
+    /// Whether the seeder may run in production. Seeders
+    /// only run outside of it by default.
+    fn production(&self) -> bool {
+        false
+    }
+
+    /// Runs the seeder in a transaction, so a failing
+    /// seeder leaves the database untouched. The seeder
+    /// refuses to run in production, as set with the
+    /// `VALAR_ENV` environment variable, unless it opts in.
+    /// An unset `VALAR_ENV` counts as production.
+    async fn run(&self, database: &Database) -> Result<(), Error> {
+        guard(self.production(), &environment())?;
+
+        database
+            .transaction(async |transaction| self.seed(transaction).await)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Returns the environment the application runs in, set
+/// with the `VALAR_ENV` environment variable, or
+/// production when it is not set.
+fn environment() -> String {
+    std::env::var("VALAR_ENV").unwrap_or_else(|_| DEFAULT_ENVIRONMENT.to_string())
+}
+
+/// Checks that a seeder may run in the given environment.
+fn guard(production: bool, environment: &str) -> Result<(), Error> {
+    if environment.eq_ignore_ascii_case("production") && !production {
+        return Err(Error::Environment(environment.to_string()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::seeders::guard;
+    use crate::database::seeders::Error;
+
+    #[test]
+    fn it_can_guard_the_production_environment() {
+        assert!(guard(false, "development").is_ok());
+        assert!(guard(false, "testing").is_ok());
+        assert!(guard(true, "production").is_ok());
+        assert!(matches!(
+            guard(false, "production"),
+            Err(Error::Environment(environment)) if environment == "production"
+        ));
+    }
+}
@@ -0,0 +1,637 @@
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+use async_trait::async_trait;
+use rusqlite::params_from_iter;
+use rusqlite::types::Value as SqliteValue;
+use tokio::sync::oneshot;
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::sync::MutexGuard as AsyncMutexGuard;
+
+use crate::database::backend::Backend;
+use crate::database::backend::TransactionBackend;
+use crate::database::connection::insert_statement;
+use crate::database::Connection as DatabaseConnection;
+use crate::database::Dialect;
+use crate::database::Error;
+use crate::database::Row;
+use crate::database::ToSql;
+use crate::database::Value;
+
+/// A call to run on the thread of the connection.
+type Job = Box<dyn FnOnce(&mut rusqlite::Connection) + Send>;
+
+/// A connection to an SQLite database. SQLite calls
+/// block and its connections can't be shared between
+/// threads, so the connection lives on a thread of its
+/// own and runs the calls sent to it, in order.
+pub(crate) struct Connection {
+    jobs: mpsc::Sender<Job>,
+}
+
+impl Connection {
+    /// Opens the database at the given path.
+    pub(crate) fn open<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut connection = rusqlite::Connection::open(path)?;
+        let (jobs, receiver) = mpsc::channel::<Job>();
+
+        thread::Builder::new()
+            .name("valar-sqlite".to_string())
+            .spawn(move || {
+                for job in receiver {
+                    job(&mut connection);
+                }
+            })
+            .map_err(|error| Error::Backend(Box::new(error)))?;
+
+        Ok(Self { jobs })
+    }
+
+    /// Runs the statements, which take no parameters.
+    pub(crate) async fn batch(&self, statements: &'static str) -> Result<(), Error> {
+        self.run(move |connection| Ok(connection.execute_batch(statements)?))
+            .await
+    }
+
+    /// Runs the statement and returns the resulting rows.
+    pub(crate) async fn fetch(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        let statement = translate(statement);
+        let values = encode_all(parameters)?;
+
+        self.run(move |connection| {
+            let mut statement = connection.prepare(&statement)?;
+            let columns: Arc<[String]> = statement
+                .column_names()
+                .into_iter()
+                .map(String::from)
+                .collect();
+            let mut rows = statement.query(params_from_iter(values))?;
+            let mut fetched = Vec::new();
+
+            while let Some(row) = rows.next()? {
+                let values = (0..columns.len())
+                    .map(|position| row.get::<_, SqliteValue>(position).map(decode))
+                    .collect::<Result<_, _>>()?;
+
+                fetched.push(Row::with_columns(columns.clone(), values));
+            }
+
+            Ok(fetched)
+        })
+        .await
+    }
+
+    /// Runs the statement and returns the number of rows
+    /// it modified.
+    pub(crate) async fn execute(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        let statement = translate(statement);
+        let values = encode_all(parameters)?;
+
+        self.run(move |connection| {
+            let mut statement = connection.prepare(&statement)?;
+
+            Ok(statement.execute(params_from_iter(values))? as u64)
+        })
+        .await
+    }
+
+    /// Inserts the rows with a single prepared statement,
+    /// in a savepoint so they are inserted all or none,
+    /// whether a transaction is open or not.
+    pub(crate) async fn copy_in(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<&(dyn ToSql + Sync)>],
+    ) -> Result<u64, Error> {
+        let statement = translate(&insert_statement(table, columns));
+        let rows = rows
+            .iter()
+            .map(|row| encode_all(row))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.run(move |connection| {
+            connection.execute_batch("SAVEPOINT copy_in")?;
+
+            let inserted = (|| {
+                let mut statement = connection.prepare(&statement)?;
+                let mut inserted = 0;
+
+                for row in rows {
+                    inserted += statement.execute(params_from_iter(row))? as u64;
+                }
+
+                Ok(inserted)
+            })();
+
+            match inserted {
+                Ok(_) => connection.execute_batch("RELEASE copy_in")?,
+                Err(_) => connection.execute_batch("ROLLBACK TO copy_in; RELEASE copy_in")?,
+            }
+
+            inserted
+        })
+        .await
+    }
+
+    /// Rolls back the open transaction without waiting.
+    /// Calls run in order, so it happens before any call
+    /// sent after it.
+    pub(crate) fn abort(&self) {
+        let _ = self.jobs.send(Box::new(|connection| {
+            let _ = connection.execute_batch("ROLLBACK");
+        }));
+    }
+
+    /// Runs the call on the thread of the connection and
+    /// waits for its result.
+    async fn run<F, T>(&self, call: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut rusqlite::Connection) -> Result<T, Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+
+        self.jobs
+            .send(Box::new(move |connection| {
+                let _ = sender.send(call(connection));
+            }))
+            .map_err(|_| stopped())?;
+
+        receiver.await.map_err(|_| stopped())?
+    }
+}
+
+/// The error of a connection whose thread stopped.
+fn stopped() -> Error {
+    Error::Backend("The SQLite connection thread stopped".into())
+}
+
+/// The SQLite backend. Statements take turns on the
+/// connection, and a transaction holds it until it ends.
+pub(crate) struct Sqlite {
+    connection: AsyncMutex<Connection>,
+}
+
+impl Sqlite {
+    pub(crate) fn new(connection: Connection) -> Self {
+        Self {
+            connection: AsyncMutex::new(connection),
+        }
+    }
+}
+
+#[async_trait]
+impl DatabaseConnection for Sqlite {
+    fn dialect(&self) -> Dialect {
+        Dialect::Sqlite
+    }
+
+    async fn fetch(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        self.connection
+            .lock()
+            .await
+            .fetch(statement, parameters)
+            .await
+    }
+
+    async fn execute(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        self.connection
+            .lock()
+            .await
+            .execute(statement, parameters)
+            .await
+    }
+
+    async fn copy_in(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<&(dyn ToSql + Sync)>],
+    ) -> Result<u64, Error> {
+        self.connection
+            .lock()
+            .await
+            .copy_in(table, columns, rows)
+            .await
+    }
+}
+
+#[async_trait]
+impl Backend for Sqlite {
+    /// It takes the write lock of the database right away,
+    /// so concurrent transactions wait instead of failing
+    /// once they first write.
+    async fn begin(&self) -> Result<Box<dyn TransactionBackend + '_>, Error> {
+        let connection = self.connection.lock().await;
+
+        connection.batch("BEGIN IMMEDIATE").await?;
+
+        Ok(Box::new(SqliteTransaction { connection }))
+    }
+}
+
+/// An SQLite transaction, holding the connection.
+struct SqliteTransaction<'a> {
+    connection: AsyncMutexGuard<'a, Connection>,
+}
+
+#[async_trait]
+impl DatabaseConnection for SqliteTransaction<'_> {
+    fn dialect(&self) -> Dialect {
+        Dialect::Sqlite
+    }
+
+    async fn fetch(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        self.connection.fetch(statement, parameters).await
+    }
+
+    async fn execute(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        self.connection.execute(statement, parameters).await
+    }
+
+    async fn copy_in(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<&(dyn ToSql + Sync)>],
+    ) -> Result<u64, Error> {
+        self.connection.copy_in(table, columns, rows).await
+    }
+}
+
+#[async_trait]
+impl TransactionBackend for SqliteTransaction<'_> {
+    async fn commit(&self) -> Result<(), Error> {
+        self.connection.batch("COMMIT").await
+    }
+
+    async fn rollback(&self) -> Result<(), Error> {
+        self.connection.batch("ROLLBACK").await
+    }
+
+    fn abort(&self) {
+        self.connection.abort();
+    }
+}
+
+/// Rewrites the `$1` placeholders of Postgres, which the
+/// query builder emits, into the `?1` ones of SQLite.
+/// Quoted strings and identifiers are left untouched.
+pub(crate) fn translate(statement: &str) -> String {
+    let mut translated = String::with_capacity(statement.len());
+    let mut characters = statement.chars().peekable();
+    let mut quote = None;
+
+    while let Some(character) = characters.next() {
+        match quote {
+            Some(open) if character == open => quote = None,
+            Some(_) => {}
+            None if character == '\'' || character == '"' => quote = Some(character),
+            None if character == '$' && characters.peek().is_some_and(char::is_ascii_digit) => {
+                translated.push('?');
+                continue;
+            }
+            None => {}
+        }
+
+        translated.push(character);
+    }
+
+    translated
+}
+
+fn encode_all(parameters: &[&(dyn ToSql + Sync)]) -> Result<Vec<SqliteValue>, Error> {
+    parameters
+        .iter()
+        .map(|parameter| encode(parameter.to_value()))
+        .collect()
+}
+
+/// Converts a value to an SQLite one. SQLite has no
+/// booleans or JSON, so they are stored as integers and
+/// text, and it has no arrays at all.
+fn encode(value: Value) -> Result<SqliteValue, Error> {
+    let value = match value {
+        Value::Null => SqliteValue::Null,
+        Value::Bool(boolean) => SqliteValue::Integer(boolean as i64),
+        Value::Int(integer) => SqliteValue::Integer(integer),
+        Value::Float(float) => SqliteValue::Real(float),
+        Value::Text(text) => SqliteValue::Text(text),
+        Value::Bytes(bytes) => SqliteValue::Blob(bytes),
+        Value::Json(json) => SqliteValue::Text(json.to_string()),
+        Value::Array(_) => {
+            return Err(Error::Conversion(format!(
+                "SQLite does not support the parameter {value:?}"
+            )))
+        }
+    };
+
+    Ok(value)
+}
+
+/// Converts an SQLite value to a database value.
+fn decode(value: SqliteValue) -> Value {
+    match value {
+        SqliteValue::Null => Value::Null,
+        SqliteValue::Integer(integer) => Value::Int(integer),
+        SqliteValue::Real(real) => Value::Float(real),
+        SqliteValue::Text(text) => Value::Text(text),
+        SqliteValue::Blob(blob) => Value::Bytes(blob),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::types::Value as SqliteValue;
+
+    use crate::database::builder::Whereable;
+    use crate::database::sqlite::decode;
+    use crate::database::sqlite::encode;
+    use crate::database::sqlite::translate;
+    use crate::database::Connection;
+    use crate::database::Database;
+    use crate::database::Error;
+    use crate::database::Executor;
+    use crate::database::Row;
+    use crate::database::ToSql;
+    use crate::database::Value;
+
+    #[test]
+    fn it_can_translate_placeholders() {
+        assert_eq!(
+            translate("SELECT * FROM users WHERE name = $1 AND email != '$2' AND \"$3\" = $10"),
+            "SELECT * FROM users WHERE name = ?1 AND email != '$2' AND \"$3\" = ?10"
+        );
+    }
+
+    #[test]
+    fn it_can_convert_values() {
+        assert_eq!(encode(42i32.to_value()).unwrap(), SqliteValue::Integer(42));
+        assert_eq!(encode(true.to_value()).unwrap(), SqliteValue::Integer(1));
+        assert_eq!(encode(1.5f64.to_value()).unwrap(), SqliteValue::Real(1.5));
+        assert_eq!(
+            encode("Erik".to_value()).unwrap(),
+            SqliteValue::Text("Erik".to_string())
+        );
+        assert_eq!(encode(None::<i64>.to_value()).unwrap(), SqliteValue::Null);
+        assert!(encode(vec!["rust", "web"].to_value()).is_err());
+
+        assert_eq!(decode(SqliteValue::Integer(42)), Value::Int(42));
+        assert_eq!(decode(SqliteValue::Real(1.5)), Value::Float(1.5));
+        assert_eq!(decode(SqliteValue::Null), Value::Null);
+    }
+
+    #[tokio::test]
+    async fn it_can_run_queries() {
+        let database = Database::connect_sqlite(":memory:").unwrap();
+
+        database.ping().await.unwrap();
+
+        database
+            .execute(
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, age INTEGER)",
+                &[],
+            )
+            .await
+            .unwrap();
+
+        Database::query("INSERT INTO users (name, age) VALUES ($1, $2), ($3, $4)")
+            .with(&"Erik")
+            .with(&30i64)
+            .with(&"Anna")
+            .with(&17i64)
+            .execute(&database)
+            .await
+            .unwrap();
+
+        let adults = Database::table("users")
+            .select(["name"])
+            .where_greater_than("age", &18i64);
+
+        assert_eq!(adults.count(&database).await.unwrap(), 1);
+
+        let row = adults.raw_first(&database).await.unwrap();
+
+        assert_eq!(row.get::<_, String>("name"), "Erik");
+
+        let transaction = database.begin().await.unwrap();
+
+        transaction.execute("DELETE FROM users", &[]).await.unwrap();
+        transaction.rollback().await.unwrap();
+
+        let users = Database::table("users").select(["id"]);
+
+        assert_eq!(users.count(&database).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_can_copy_rows() {
+        let database = Database::connect_sqlite(":memory:").unwrap();
+
+        database
+            .execute("CREATE TABLE users (name TEXT NOT NULL, age INTEGER)", &[])
+            .await
+            .unwrap();
+
+        let rows: Vec<Vec<&(dyn ToSql + Sync)>> =
+            vec![vec![&"Erik", &30i64], vec![&"Anna", &None::<i64>]];
+
+        assert_eq!(
+            database
+                .copy_in("users", &["name", "age"], &rows)
+                .await
+                .unwrap(),
+            2
+        );
+
+        let rows: Vec<Vec<&(dyn ToSql + Sync)>> =
+            vec![vec![&"Joan", &20i64], vec![&None::<String>, &20i64]];
+
+        assert!(database
+            .copy_in("users", &["name", "age"], &rows)
+            .await
+            .is_err());
+
+        let users = Database::table("users").select(["name"]);
+
+        assert_eq!(users.count(&database).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_can_chunk_rows() {
+        let database = Database::connect_sqlite(":memory:").unwrap();
+
+        database
+            .execute(
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)",
+                &[],
+            )
+            .await
+            .unwrap();
+
+        for name in ["Erik", "Anna", "Joan", "Marc", "Laia"] {
+            Database::query("INSERT INTO users (name) VALUES ($1)")
+                .with(&name)
+                .execute(&database)
+                .await
+                .unwrap();
+        }
+
+        let mut pages = Vec::new();
+
+        Database::table("users")
+            .select(["name"])
+            .chunk(2, "name", &database, async |rows: Vec<Row>| {
+                pages.push(
+                    rows.iter()
+                        .map(|row| row.get::<_, String>("name"))
+                        .collect::<Vec<_>>(),
+                );
+
+                Ok::<_, Error>(())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            pages,
+            [vec!["Anna", "Erik"], vec!["Joan", "Laia"], vec!["Marc"]]
+        );
+
+        let mut names = Vec::new();
+
+        Database::table("users")
+            .select(["name"])
+            .where_not_equal("name", &"Joan")
+            .chunk_by_id(2, "id", &database, async |rows: Vec<Row>| {
+                for row in rows {
+                    database
+                        .execute(
+                            "DELETE FROM users WHERE id = $1",
+                            &[&row.get::<_, i64>("id")],
+                        )
+                        .await?;
+
+                    names.push(row.get::<_, String>("name"));
+                }
+
+                Ok::<_, Error>(())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(names, ["Erik", "Anna", "Marc", "Laia"]);
+    }
+
+    struct User {
+        name: String,
+    }
+
+    impl TryFrom<Row> for User {
+        type Error = Error;
+
+        fn try_from(row: Row) -> Result<Self, Self::Error> {
+            Ok(Self {
+                name: row.try_get("name")?,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn it_can_check_rows() {
+        let database = Database::connect_sqlite(":memory:").unwrap();
+
+        database
+            .execute("CREATE TABLE users (name TEXT)", &[])
+            .await
+            .unwrap();
+        database
+            .execute("INSERT INTO users (name) VALUES ($1)", &[&"Erik"])
+            .await
+            .unwrap();
+
+        let erik = Database::table("users")
+            .select(["name"])
+            .where_equal("name", &"Erik");
+        let anna = Database::table("users")
+            .select(["name"])
+            .where_equal("name", &"Anna");
+
+        assert!(erik.exists(&database).await.unwrap());
+        assert!(!anna.exists(&database).await.unwrap());
+        let user: Option<User> = erik.first_optional(&database).await.unwrap();
+
+        assert_eq!(user.map(|user| user.name).as_deref(), Some("Erik"));
+
+        let user: Option<User> = anna.first_optional(&database).await.unwrap();
+
+        assert!(user.is_none());
+    }
+
+    #[tokio::test]
+    async fn it_can_pluck_columns() {
+        let database = Database::connect_sqlite(":memory:").unwrap();
+
+        database
+            .execute("CREATE TABLE users (name TEXT, age INTEGER)", &[])
+            .await
+            .unwrap();
+
+        for (name, age) in [("Erik", 30), ("Anna", 25)] {
+            database
+                .execute(
+                    "INSERT INTO users (name, age) VALUES ($1, $2)",
+                    &[&name, &age],
+                )
+                .await
+                .unwrap();
+        }
+
+        let names: Vec<String> = Database::table("users")
+            .select(["name"])
+            .pluck("name", &database)
+            .await
+            .unwrap();
+
+        assert_eq!(names, ["Erik", "Anna"]);
+
+        let age: i32 = Database::table("users")
+            .select(["age"])
+            .where_equal("name", &"Anna")
+            .value("age", &database)
+            .await
+            .unwrap();
+
+        assert_eq!(age, 25);
+    }
+}
@@ -0,0 +1,250 @@
+use std::ops::Deref;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use async_trait::async_trait;
+
+use crate::database::backend::Backend;
+use crate::database::backend::TransactionBackend;
+use crate::database::Connection;
+use crate::database::Database;
+use crate::database::Dialect;
+use crate::database::Error;
+use crate::database::Row;
+use crate::database::ToSql;
+
+/// A database for a test. Everything ran against it
+/// happens in a transaction that is rolled back once the
+/// test ends, so tests against a real database don't leak
+/// rows into each other. Transactions started by the code
+/// under test become savepoints of it.
+///
+/// The transaction is rolled back by
+/// [`TestDatabase::rollback`], or by the database itself
+/// when the connection closes as the test database is
+/// dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::database::testing::TestDatabase;
+/// use valar::database::Connection;
+/// use valar::database::Database;
+///
+/// # async fn test() -> Result<(), valar::database::Error> {
+/// let database = Database::connect("postgres://localhost/valar_test").await?;
+/// let database = TestDatabase::begin(database).await?;
+///
+/// database.execute("DELETE FROM users", &[]).await?;
+///
+/// database.rollback().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TestDatabase {
+    database: Database,
+}
+
+impl TestDatabase {
+    /// Starts the transaction of the test. Replicas of the
+    /// database are left out, as they can't see the rows
+    /// written during the test.
+    pub async fn begin(database: Database) -> Result<Self, Error> {
+        let backend = Testing {
+            backend: database.backend,
+            savepoints: AtomicUsize::new(0),
+        };
+
+        backend.execute("BEGIN", &[]).await?;
+
+        Ok(Self {
+            database: Database {
+                backend: Box::new(backend),
+                replicas: Default::default(),
+                instrumentation: database.instrumentation,
+            },
+        })
+    }
+
+    /// Discards everything ran against the database.
+    pub async fn rollback(self) -> Result<(), Error> {
+        self.database.backend.execute("ROLLBACK", &[]).await?;
+
+        Ok(())
+    }
+}
+
+impl Deref for TestDatabase {
+    type Target = Database;
+
+    fn deref(&self) -> &Self::Target {
+        &self.database
+    }
+}
+
+/// The backend of a test database, running every
+/// statement in the transaction of the test.
+struct Testing {
+    backend: Box<dyn Backend>,
+    savepoints: AtomicUsize,
+}
+
+#[async_trait]
+impl Connection for Testing {
+    fn dialect(&self) -> Dialect {
+        self.backend.dialect()
+    }
+
+    async fn fetch(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        self.backend.fetch(statement, parameters).await
+    }
+
+    async fn execute(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        self.backend.execute(statement, parameters).await
+    }
+
+    async fn copy_in(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<&(dyn ToSql + Sync)>],
+    ) -> Result<u64, Error> {
+        self.backend.copy_in(table, columns, rows).await
+    }
+}
+
+#[async_trait]
+impl Backend for Testing {
+    async fn begin(&self) -> Result<Box<dyn TransactionBackend + '_>, Error> {
+        let name = format!("test_{}", self.savepoints.fetch_add(1, Ordering::Relaxed));
+
+        self.backend
+            .execute(&format!("SAVEPOINT {name}"), &[])
+            .await?;
+
+        Ok(Box::new(Savepoint {
+            backend: &*self.backend,
+            name,
+        }))
+    }
+}
+
+/// A transaction started during a test.
+struct Savepoint<'a> {
+    backend: &'a dyn Backend,
+    name: String,
+}
+
+#[async_trait]
+impl Connection for Savepoint<'_> {
+    fn dialect(&self) -> Dialect {
+        self.backend.dialect()
+    }
+
+    async fn fetch(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        self.backend.fetch(statement, parameters).await
+    }
+
+    async fn execute(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        self.backend.execute(statement, parameters).await
+    }
+
+    async fn copy_in(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<&(dyn ToSql + Sync)>],
+    ) -> Result<u64, Error> {
+        self.backend.copy_in(table, columns, rows).await
+    }
+}
+
+#[async_trait]
+impl TransactionBackend for Savepoint<'_> {
+    async fn commit(&self) -> Result<(), Error> {
+        let release = format!("RELEASE SAVEPOINT {}", self.name);
+
+        self.backend.execute(&release, &[]).await?;
+
+        Ok(())
+    }
+
+    async fn rollback(&self) -> Result<(), Error> {
+        let rollback = format!("ROLLBACK TO SAVEPOINT {}", self.name);
+
+        self.backend.execute(&rollback, &[]).await?;
+        self.commit().await
+    }
+
+    /// The savepoint can't be rolled back without waiting,
+    /// so it is left open until the test is rolled back.
+    fn abort(&self) {}
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use crate::database::testing::TestDatabase;
+    use crate::database::Connection;
+    use crate::database::Database;
+    use crate::database::Error;
+
+    #[tokio::test]
+    async fn it_rolls_back_the_test() {
+        let database = Database::connect_sqlite(":memory:").unwrap();
+
+        database
+            .execute("CREATE TABLE users (name TEXT)", &[])
+            .await
+            .unwrap();
+
+        let database = TestDatabase::begin(database).await.unwrap();
+
+        database
+            .execute("INSERT INTO users (name) VALUES ($1)", &[&"Erik"])
+            .await
+            .unwrap();
+
+        let failed = database
+            .transaction(async |transaction| {
+                transaction
+                    .execute("INSERT INTO users (name) VALUES ($1)", &[&"Anna"])
+                    .await?;
+
+                Err::<(), _>(Error::RowNotFound)
+            })
+            .await;
+
+        assert!(failed.is_err());
+
+        database
+            .transaction(async |transaction| {
+                transaction
+                    .execute("INSERT INTO users (name) VALUES ($1)", &[&"Joan"])
+                    .await
+            })
+            .await
+            .unwrap();
+
+        let users = Database::table("users").select(["name"]);
+
+        assert_eq!(users.count(&*database).await.unwrap(), 2);
+
+        database.rollback().await.unwrap();
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::Error;
+use std::io::ErrorKind;
+use std::io::Result as IoResult;
+use std::path::Path;
+use std::sync::Arc;
+
+use rustls::ClientConfig;
+use rustls::RootCertStore;
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+/// The TLS settings of the database connection. Whether
+/// TLS is used depends on the `sslmode` of the connection
+/// string: `disable` never uses it, `prefer` (the default)
+/// uses it when the server supports it, and `require`
+/// refuses to connect without it. The certificate of the
+/// server is always verified.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::database::Database;
+/// use valar::database::Tls;
+///
+/// # async fn connect() -> Result<(), Box<dyn std::error::Error>> {
+/// let tls = Tls::new().root_certificate("certs/ca.pem")?;
+/// let url = "host=db.example.com user=valar sslmode=require";
+///
+/// let database = Database::connect_with_tls(url, tls).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tls {
+    roots: RootCertStore,
+}
+
+impl Default for Tls {
+    fn default() -> Self {
+        Self {
+            roots: RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            },
+        }
+    }
+}
+
+impl Tls {
+    /// Trusts the Mozilla root certificates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trusts no certificate until one is added with
+    /// [`Tls::root_certificate`], for servers using a
+    /// private certificate authority.
+    pub fn empty() -> Self {
+        Self {
+            roots: RootCertStore::empty(),
+        }
+    }
+
+    /// Trusts the certificates of the given PEM file, like
+    /// the certificate authority of a managed database.
+    pub fn root_certificate<P>(mut self, path: P) -> IoResult<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let pem = std::fs::read(path.as_ref())?;
+
+        for certificate in rustls_pemfile::certs(&mut pem.as_slice()) {
+            self.roots
+                .add(certificate?)
+                .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+        }
+
+        Ok(self)
+    }
+
+    /// Builds the connector used by the connection.
+    pub(crate) fn connector(self) -> MakeRustlsConnect {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .expect("The ring provider supports the default protocol versions")
+            .with_root_certificates(self.roots)
+            .with_no_client_auth();
+
+        MakeRustlsConnect::new(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use crate::database::Tls;
+
+    #[test]
+    fn it_can_load_root_certificates() {
+        let error = Tls::empty().root_certificate("missing/ca.pem").unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+        assert!(!Tls::new().roots.is_empty());
+        assert!(Tls::empty().roots.is_empty());
+    }
+}
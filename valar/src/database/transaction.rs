@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+
+use crate::database::backend::TransactionBackend;
+use crate::database::instrumentation::Instrumentation;
+use crate::database::Connection;
+use crate::database::Dialect;
+use crate::database::Error;
+use crate::database::Row;
+use crate::database::ToSql;
+
+/// A database transaction. It holds the connection until
+/// it is committed or rolled back, so every statement ran
+/// against it is part of the transaction. Dropping it
+/// without committing rolls it back.
+pub struct Transaction<'a> {
+    backend: Box<dyn TransactionBackend + 'a>,
+    instrumentation: &'a Instrumentation,
+    finished: bool,
+}
+
+impl<'a> Transaction<'a> {
+    /// Wraps the connection of a transaction the backend
+    /// started, reporting its statements like the database.
+    pub(crate) fn new(
+        backend: Box<dyn TransactionBackend + 'a>,
+        instrumentation: &'a Instrumentation,
+    ) -> Self {
+        Self {
+            backend,
+            instrumentation,
+            finished: false,
+        }
+    }
+
+    /// Commits the changes of the transaction.
+    pub async fn commit(mut self) -> Result<(), Error> {
+        self.finished = true;
+
+        self.backend.commit().await
+    }
+
+    /// Discards the changes of the transaction.
+    pub async fn rollback(mut self) -> Result<(), Error> {
+        self.finished = true;
+
+        self.backend.rollback().await
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.backend.abort();
+        }
+    }
+}
+
+#[async_trait]
+impl Connection for Transaction<'_> {
+    fn dialect(&self) -> Dialect {
+        self.backend.dialect()
+    }
+
+    async fn fetch(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        self.instrumentation
+            .observe(
+                statement,
+                parameters,
+                |rows| rows.len() as u64,
+                self.backend.fetch(statement, parameters),
+            )
+            .await
+    }
+
+    async fn execute(
+        &self,
+        statement: &str,
+        parameters: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        self.instrumentation
+            .observe(
+                statement,
+                parameters,
+                |rows| *rows,
+                self.backend.execute(statement, parameters),
+            )
+            .await
+    }
+
+    async fn copy_in(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<&(dyn ToSql + Sync)>],
+    ) -> Result<u64, Error> {
+        let statement = format!("COPY {table} ({})", columns.join(", "));
+
+        self.instrumentation
+            .observe(
+                &statement,
+                &[],
+                |rows| *rows,
+                self.backend.copy_in(table, columns, rows),
+            )
+            .await
+    }
+}
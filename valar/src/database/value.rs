@@ -0,0 +1,350 @@
+use std::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::database::Error;
+
+/// A value bound to a statement or read from a row. Every
+/// backend converts its own types from and to it, so the
+/// same code runs against any of them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+    Json(JsonValue),
+    Array(Vec<Value>),
+}
+
+/// A type that can be bound to a statement.
+///
+/// # Example
+///
+/// ```
+/// use valar::database::ToSql;
+/// use valar::database::Value;
+///
+/// #[derive(Debug)]
+/// struct Email(String);
+///
+/// impl ToSql for Email {
+///     fn to_value(&self) -> Value {
+///         Value::Text(self.0.to_lowercase())
+///     }
+/// }
+/// ```
+pub trait ToSql: Debug {
+    /// Converts the value to a database value.
+    fn to_value(&self) -> Value;
+}
+
+/// A type that can be read from a row.
+pub trait FromSql: Sized {
+    /// Converts the database value to the type.
+    fn from_value(value: Value) -> Result<Self, Error>;
+}
+
+/// A value stored as JSON, serialized with serde.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Json<T>(pub T);
+
+impl ToSql for Value {
+    fn to_value(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl<T> ToSql for &T
+where
+    T: ToSql + ?Sized,
+{
+    fn to_value(&self) -> Value {
+        (**self).to_value()
+    }
+}
+
+impl<T> ToSql for Option<T>
+where
+    T: ToSql,
+{
+    fn to_value(&self) -> Value {
+        match self {
+            Some(value) => value.to_value(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl ToSql for bool {
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl ToSql for i16 {
+    fn to_value(&self) -> Value {
+        Value::Int(*self as i64)
+    }
+}
+
+impl ToSql for i32 {
+    fn to_value(&self) -> Value {
+        Value::Int(*self as i64)
+    }
+}
+
+impl ToSql for i64 {
+    fn to_value(&self) -> Value {
+        Value::Int(*self)
+    }
+}
+
+impl ToSql for f32 {
+    fn to_value(&self) -> Value {
+        Value::Float(*self as f64)
+    }
+}
+
+impl ToSql for f64 {
+    fn to_value(&self) -> Value {
+        Value::Float(*self)
+    }
+}
+
+impl ToSql for str {
+    fn to_value(&self) -> Value {
+        Value::Text(self.to_string())
+    }
+}
+
+impl ToSql for String {
+    fn to_value(&self) -> Value {
+        Value::Text(self.clone())
+    }
+}
+
+impl ToSql for [u8] {
+    fn to_value(&self) -> Value {
+        Value::Bytes(self.to_vec())
+    }
+}
+
+impl ToSql for Vec<u8> {
+    fn to_value(&self) -> Value {
+        Value::Bytes(self.clone())
+    }
+}
+
+impl<T> ToSql for [T]
+where
+    T: ToSql,
+{
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(ToSql::to_value).collect())
+    }
+}
+
+impl<T> ToSql for Vec<T>
+where
+    T: ToSql,
+{
+    fn to_value(&self) -> Value {
+        self.as_slice().to_value()
+    }
+}
+
+impl ToSql for JsonValue {
+    fn to_value(&self) -> Value {
+        Value::Json(self.clone())
+    }
+}
+
+impl<T> ToSql for Json<T>
+where
+    T: Serialize + Debug,
+{
+    fn to_value(&self) -> Value {
+        match serde_json::to_value(&self.0) {
+            Ok(value) => Value::Json(value),
+            Err(_) => Value::Null,
+        }
+    }
+}
+
+impl FromSql for Value {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        Ok(value)
+    }
+}
+
+impl<T> FromSql for Option<T>
+where
+    T: FromSql,
+{
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+/// SQLite stores booleans as integers.
+impl FromSql for bool {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Bool(value) => Ok(value),
+            Value::Int(value) => Ok(value != 0),
+            value => Err(mismatch::<Self>(&value)),
+        }
+    }
+}
+
+impl FromSql for i16 {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        let integer = i64::from_value(value)?;
+
+        Self::try_from(integer).map_err(|_| mismatch::<Self>(&Value::Int(integer)))
+    }
+}
+
+impl FromSql for i32 {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        let integer = i64::from_value(value)?;
+
+        Self::try_from(integer).map_err(|_| mismatch::<Self>(&Value::Int(integer)))
+    }
+}
+
+impl FromSql for i64 {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Int(value) => Ok(value),
+            value => Err(mismatch::<Self>(&value)),
+        }
+    }
+}
+
+impl FromSql for f32 {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        f64::from_value(value).map(|value| value as f32)
+    }
+}
+
+impl FromSql for f64 {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Float(value) => Ok(value),
+            Value::Int(value) => Ok(value as f64),
+            value => Err(mismatch::<Self>(&value)),
+        }
+    }
+}
+
+impl FromSql for String {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Text(value) => Ok(value),
+            value => Err(mismatch::<Self>(&value)),
+        }
+    }
+}
+
+impl FromSql for Vec<u8> {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Bytes(value) => Ok(value),
+            value => Err(mismatch::<Self>(&value)),
+        }
+    }
+}
+
+impl<T> FromSql for Vec<T>
+where
+    T: FromSql,
+{
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Array(values) => values.into_iter().map(T::from_value).collect(),
+            value => Err(mismatch::<Self>(&value)),
+        }
+    }
+}
+
+/// SQLite stores JSON as text.
+impl FromSql for JsonValue {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Json(value) => Ok(value),
+            Value::Text(text) => {
+                serde_json::from_str(&text).map_err(|error| Error::Conversion(error.to_string()))
+            }
+            value => Err(mismatch::<Self>(&value)),
+        }
+    }
+}
+
+impl<T> FromSql for Json<T>
+where
+    T: DeserializeOwned,
+{
+    fn from_value(value: Value) -> Result<Self, Error> {
+        serde_json::from_value(JsonValue::from_value(value)?)
+            .map(Json)
+            .map_err(|error| Error::Conversion(error.to_string()))
+    }
+}
+
+/// Returns the error of a value that can't be converted
+/// to the given type.
+fn mismatch<T>(value: &Value) -> Error {
+    Error::Conversion(format!(
+        "Cannot convert the database value {value:?} to {}",
+        std::any::type_name::<T>()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::database::FromSql;
+    use crate::database::Json;
+    use crate::database::ToSql;
+    use crate::database::Value;
+
+    #[test]
+    fn it_can_convert_values() {
+        assert_eq!(42i32.to_value(), Value::Int(42));
+        assert_eq!("Erik".to_value(), Value::Text("Erik".to_string()));
+        assert_eq!(None::<i64>.to_value(), Value::Null);
+        assert_eq!(
+            vec!["rust", "web"].to_value(),
+            Value::Array(vec![
+                Value::Text("rust".to_string()),
+                Value::Text("web".to_string())
+            ])
+        );
+        assert_eq!(vec![1u8, 2].to_value(), Value::Bytes(vec![1, 2]));
+        assert_eq!(
+            Json(json!({ "plan": "pro" })).to_value(),
+            Value::Json(json!({ "plan": "pro" }))
+        );
+
+        assert_eq!(i32::from_value(Value::Int(42)).unwrap(), 42);
+        assert!(bool::from_value(Value::Int(1)).unwrap());
+        assert_eq!(Option::<String>::from_value(Value::Null).unwrap(), None);
+        assert_eq!(
+            Json::<Vec<i32>>::from_value(Value::Text("[1, 2]".to_string())).unwrap(),
+            Json(vec![1, 2])
+        );
+        assert!(i16::from_value(Value::Int(1 << 20)).is_err());
+        assert!(String::from_value(Value::Int(1)).is_err());
+        assert!(i64::from_value(Value::Null).is_err());
+    }
+}
@@ -0,0 +1,38 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+static DEBUG: AtomicBool = AtomicBool::new(false);
+
+/// Determines if the framework runs in debug mode. Debug
+/// mode makes some outputs friendlier to read at the cost
+/// of performance, for example pretty printed JSON
+/// responses. It is disabled by default.
+pub fn enabled() -> bool {
+    DEBUG.load(Ordering::Relaxed)
+}
+
+/// Enables or disables the debug mode globally. It should
+/// only be enabled while developing.
+///
+/// # Example
+///
+/// ```no_run
+/// valar::debug::set(true);
+///
+/// assert!(valar::debug::enabled());
+/// ```
+pub fn set(enabled: bool) {
+    DEBUG.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns the debug mode set with the `VALAR_DEBUG`
+/// environment variable, if any. `1`, `true`, `yes` and
+/// `on` enable it and any other value disables it.
+pub fn from_env() -> Option<bool> {
+    let value = std::env::var("VALAR_DEBUG").ok()?;
+
+    Some(matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    ))
+}
@@ -1,19 +1,31 @@
+pub mod body;
+pub mod cache_control;
 pub mod client;
 pub mod context;
 pub mod cookie;
+pub mod cookie_jar;
+pub mod error_page;
 pub mod headers;
+pub mod health;
 pub mod middleware;
 pub mod request;
 pub mod response;
 pub mod server;
+pub mod session;
+pub mod terminate;
+#[cfg(feature = "tower")]
+pub mod tower;
 
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 
+pub use body::Body;
 pub use client::Client;
 pub use cookie::Cookie;
+pub use cookie_jar::CookieJar;
 pub use headers::Headers;
+pub use health::Health;
 pub use http::Method;
 pub use http::StatusCode;
 pub use http::Uri;
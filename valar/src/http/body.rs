@@ -0,0 +1,168 @@
+use std::error::Error;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::future::Future;
+
+use futures_util::Stream;
+use futures_util::TryStreamExt;
+use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
+use http_body_util::Full;
+use http_body_util::StreamBody;
+use hyper::body::Bytes;
+use hyper::body::Frame;
+use hyper::header::HeaderName;
+use hyper::header::HeaderValue;
+use hyper::HeaderMap;
+
+use crate::http::Headers;
+use crate::http::Response;
+
+/// The error type a streamed body may yield.
+pub type BoxError = Box<dyn Error + Send + Sync + 'static>;
+
+/// The body of a response. It can either be fully
+/// buffered in memory or streamed to the client
+/// frame by frame.
+pub enum Body {
+    /// A body that is already fully available in memory.
+    Full(Bytes),
+
+    /// A body that is produced lazily while it is being
+    /// written to the client.
+    Stream(BoxBody<Bytes, BoxError>),
+}
+
+impl Body {
+    /// Creates an empty body.
+    pub fn empty() -> Self {
+        Self::Full(Bytes::new())
+    }
+
+    /// Creates a streamed body from the given stream of
+    /// bytes.
+    pub fn stream<S, B, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<B, E>> + Send + Sync + 'static,
+        B: Into<Bytes> + 'static,
+        E: Into<BoxError> + 'static,
+    {
+        let stream = stream
+            .map_ok(|chunk| Frame::data(chunk.into()))
+            .map_err(|error| error.into());
+
+        Self::Stream(StreamBody::new(stream).boxed())
+    }
+
+    /// Creates a streamed body from the given stream of
+    /// bytes that is followed by the given trailers once
+    /// the stream ends. The trailers are only awaited
+    /// after the last chunk is written, so they can
+    /// contain values computed while streaming, like
+    /// checksums.
+    pub fn stream_with_trailers<S, B, E, T>(stream: S, trailers: T) -> Self
+    where
+        S: Stream<Item = Result<B, E>> + Send + Sync + 'static,
+        B: Into<Bytes> + 'static,
+        E: Into<BoxError> + 'static,
+        T: Future<Output = Headers<Response>> + Send + Sync + 'static,
+    {
+        let stream = stream
+            .map_ok(|chunk| Frame::data(chunk.into()))
+            .map_err(|error| error.into());
+
+        let trailers = async move {
+            let trailers = trailers.await;
+
+            Some(header_map(trailers))
+        };
+
+        Self::Stream(StreamBody::new(stream).with_trailers(trailers).boxed())
+    }
+
+    /// Determines if the body is streamed.
+    pub fn is_stream(&self) -> bool {
+        matches!(self, Self::Stream(_))
+    }
+
+    /// Returns the bytes of the body if it's fully
+    /// available in memory. Streamed bodies have no
+    /// bytes until they are written.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Full(bytes) => bytes,
+            Self::Stream(_) => &[],
+        }
+    }
+
+    /// Returns the body as a string slice. Bodies that are
+    /// streamed or that are not valid UTF-8 will return an
+    /// empty string.
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(self.as_bytes()).unwrap_or_default()
+    }
+
+    /// Turns the body into the boxed body that is written
+    /// to the client.
+    pub(crate) fn boxed(self) -> BoxBody<Bytes, BoxError> {
+        match self {
+            Self::Full(bytes) => Full::new(bytes).map_err(|never| match never {}).boxed(),
+            Self::Stream(body) => body,
+        }
+    }
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl Debug for Body {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Full(bytes) => f.debug_tuple("Full").field(bytes).finish(),
+            Self::Stream(_) => f.debug_tuple("Stream").finish(),
+        }
+    }
+}
+
+impl From<String> for Body {
+    fn from(value: String) -> Self {
+        Self::Full(Bytes::from(value))
+    }
+}
+
+impl From<&str> for Body {
+    fn from(value: &str) -> Self {
+        Self::Full(Bytes::copy_from_slice(value.as_bytes()))
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Full(Bytes::from(value))
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(value: Bytes) -> Self {
+        Self::Full(value)
+    }
+}
+
+/// Converts the given headers into the header map
+/// expected by the trailers frame.
+fn header_map(headers: Headers<Response>) -> Result<HeaderMap, BoxError> {
+    let mut map = HeaderMap::new();
+
+    for (header, value) in headers {
+        let header = HeaderName::from_bytes(header.as_bytes())?;
+        let value = HeaderValue::from_str(&value)?;
+
+        map.append(header, value);
+    }
+
+    Ok(map)
+}
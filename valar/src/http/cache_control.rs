@@ -0,0 +1,257 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+use crate::http::headers::split_list;
+use crate::http::headers::typed::Error as TypedError;
+use crate::http::headers::typed::TypedHeader;
+
+/// The directives of a `Cache-Control` response header.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::cache_control::CacheControl;
+/// use valar::http::Response;
+///
+/// let response = Response::ok()
+///     .cache_control(CacheControl::new().public().max_age(3600))
+///     .build();
+///
+/// assert!(response
+///     .headers()
+///     .is("Cache-Control", "public, max-age=3600"));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CacheControl {
+    public: bool,
+    private: bool,
+    no_cache: bool,
+    no_store: bool,
+    no_transform: bool,
+    must_revalidate: bool,
+    proxy_revalidate: bool,
+    immutable: bool,
+    max_age: Option<u64>,
+    s_maxage: Option<u64>,
+    stale_while_revalidate: Option<u64>,
+    stale_if_error: Option<u64>,
+}
+
+impl CacheControl {
+    /// Creates a new `Cache-Control` value without any
+    /// directive.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The response may be stored by any cache, including
+    /// shared caches like proxies or CDNs.
+    pub fn public(mut self) -> Self {
+        self.public = true;
+        self.private = false;
+
+        self
+    }
+
+    /// The response may only be stored by the client's
+    /// private cache.
+    pub fn private(mut self) -> Self {
+        self.private = true;
+        self.public = false;
+
+        self
+    }
+
+    /// The response may be stored but must be validated
+    /// with the server before each reuse.
+    pub fn no_cache(mut self) -> Self {
+        self.no_cache = true;
+
+        self
+    }
+
+    /// The response must not be stored by any cache.
+    pub fn no_store(mut self) -> Self {
+        self.no_store = true;
+
+        self
+    }
+
+    /// Intermediaries must not transform the response.
+    pub fn no_transform(mut self) -> Self {
+        self.no_transform = true;
+
+        self
+    }
+
+    /// Stale responses must be validated before reuse.
+    pub fn must_revalidate(mut self) -> Self {
+        self.must_revalidate = true;
+
+        self
+    }
+
+    /// Same as `must_revalidate` but only for shared
+    /// caches.
+    pub fn proxy_revalidate(mut self) -> Self {
+        self.proxy_revalidate = true;
+
+        self
+    }
+
+    /// The response will not change while it's fresh.
+    pub fn immutable(mut self) -> Self {
+        self.immutable = true;
+
+        self
+    }
+
+    /// The number of seconds the response remains fresh.
+    pub fn max_age(mut self, seconds: u64) -> Self {
+        self.max_age = Some(seconds);
+
+        self
+    }
+
+    /// The number of seconds the response remains fresh
+    /// in shared caches.
+    pub fn s_maxage(mut self, seconds: u64) -> Self {
+        self.s_maxage = Some(seconds);
+
+        self
+    }
+
+    /// The number of seconds a stale response may be
+    /// reused while it's revalidated in the background.
+    pub fn stale_while_revalidate(mut self, seconds: u64) -> Self {
+        self.stale_while_revalidate = Some(seconds);
+
+        self
+    }
+
+    /// The number of seconds a stale response may be
+    /// reused when the server answers with an error.
+    pub fn stale_if_error(mut self, seconds: u64) -> Self {
+        self.stale_if_error = Some(seconds);
+
+        self
+    }
+}
+
+impl Display for CacheControl {
+    /// Formats the directives as a `Cache-Control` header
+    /// value.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let flags = [
+            (self.public, "public"),
+            (self.private, "private"),
+            (self.no_cache, "no-cache"),
+            (self.no_store, "no-store"),
+            (self.no_transform, "no-transform"),
+            (self.must_revalidate, "must-revalidate"),
+            (self.proxy_revalidate, "proxy-revalidate"),
+            (self.immutable, "immutable"),
+        ];
+
+        let durations = [
+            (self.max_age, "max-age"),
+            (self.s_maxage, "s-maxage"),
+            (self.stale_while_revalidate, "stale-while-revalidate"),
+            (self.stale_if_error, "stale-if-error"),
+        ];
+
+        let directives: Vec<String> = flags
+            .into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, directive)| directive.to_string())
+            .chain(
+                durations
+                    .into_iter()
+                    .filter_map(|(seconds, directive)| Some(format!("{directive}={}", seconds?))),
+            )
+            .collect();
+
+        write!(f, "{}", directives.join(", "))
+    }
+}
+
+impl TypedHeader for CacheControl {
+    const NAME: &'static str = "Cache-Control";
+
+    /// Decodes the known directives. Unknown directives are
+    /// ignored.
+    fn decode(value: &str) -> Result<Self, TypedError> {
+        let mut cache_control = Self::new();
+
+        for directive in split_list(value) {
+            let (name, seconds) = match directive.split_once('=') {
+                Some((name, seconds)) => {
+                    let seconds = seconds
+                        .trim()
+                        .trim_matches('"')
+                        .parse::<u64>()
+                        .map_err(|_| TypedError::InvalidValue(Self::NAME))?;
+
+                    (name.trim(), Some(seconds))
+                }
+                None => (directive.trim(), None),
+            };
+
+            cache_control = match (name.to_ascii_lowercase().as_str(), seconds) {
+                ("public", None) => cache_control.public(),
+                ("private", None) => cache_control.private(),
+                ("no-cache", None) => cache_control.no_cache(),
+                ("no-store", None) => cache_control.no_store(),
+                ("no-transform", None) => cache_control.no_transform(),
+                ("must-revalidate", None) => cache_control.must_revalidate(),
+                ("proxy-revalidate", None) => cache_control.proxy_revalidate(),
+                ("immutable", None) => cache_control.immutable(),
+                ("max-age", Some(seconds)) => cache_control.max_age(seconds),
+                ("s-maxage", Some(seconds)) => cache_control.s_maxage(seconds),
+                ("stale-while-revalidate", Some(seconds)) => {
+                    cache_control.stale_while_revalidate(seconds)
+                }
+                ("stale-if-error", Some(seconds)) => cache_control.stale_if_error(seconds),
+                _ => cache_control,
+            };
+        }
+
+        Ok(cache_control)
+    }
+
+    fn encode(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl From<CacheControl> for String {
+    fn from(value: CacheControl) -> Self {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::cache_control::CacheControl;
+
+    #[test]
+    fn it_can_format_cache_control() {
+        let value = CacheControl::new()
+            .public()
+            .max_age(3600)
+            .stale_while_revalidate(60);
+
+        assert_eq!(
+            value.to_string(),
+            "public, max-age=3600, stale-while-revalidate=60"
+        );
+    }
+
+    #[test]
+    fn it_keeps_public_and_private_exclusive() {
+        let value = CacheControl::new().public().private().no_cache();
+
+        assert_eq!(value.to_string(), "private, no-cache");
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use crate::http::Cookie;
+use crate::http::Request;
+use crate::http::Response;
+
+#[derive(Debug, Default)]
+struct Jar {
+    incoming: HashMap<String, String>,
+    changes: Vec<Cookie<Response>>,
+}
+
+/// A cookie jar holds the cookies sent by the client and
+/// keeps track of the cookies added or removed while
+/// handling the request. Only the changes are sent back
+/// to the client as `Set-Cookie` headers.
+///
+/// The jar is cheap to clone and all the clones share
+/// the same cookies. It's attached to the request by the
+/// [`Cookies`](crate::http::middleware::Cookies)
+/// middleware.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::Cookie;
+/// use valar::http::CookieJar;
+///
+/// let jar = CookieJar::new([("theme", "dark")]);
+///
+/// jar.add(Cookie::builder("locale", "en").build());
+/// jar.remove("theme");
+///
+/// assert_eq!(jar.get("locale"), Some("en".to_string()));
+/// assert!(!jar.has("theme"));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct CookieJar(Arc<Mutex<Jar>>);
+
+impl CookieJar {
+    /// Creates a new cookie jar with the given incoming
+    /// cookies.
+    pub fn new<I, N, V>(cookies: I) -> Self
+    where
+        I: IntoIterator<Item = (N, V)>,
+        N: Into<String>,
+        V: Into<String>,
+    {
+        let incoming = cookies
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect();
+
+        Self(Arc::new(Mutex::new(Jar {
+            incoming,
+            changes: Vec::new(),
+        })))
+    }
+
+    /// Creates a new cookie jar with the cookies sent in
+    /// the `Cookie` headers of the request.
+    pub fn from_request<App>(request: &Request<App>) -> Self
+    where
+        App: Send + Sync + 'static,
+    {
+        let cookies = request
+            .headers()
+            .get("Cookie")
+            .into_iter()
+            .flatten()
+            .flat_map(|value| value.split(';'))
+            .flat_map(Cookie::<Request<App>>::from_str)
+            .map(|cookie| (cookie.name().to_string(), cookie.value().to_string()));
+
+        Self::new(cookies.collect::<Vec<_>>())
+    }
+
+    /// Returns the value of the given cookie, taking into
+    /// account the changes made to the jar.
+    pub fn get(&self, name: &str) -> Option<String> {
+        let jar = self.0.lock().unwrap();
+
+        match jar.changes.iter().find(|cookie| cookie.name() == name) {
+            Some(cookie) if is_removal(cookie) => None,
+            Some(cookie) => Some(cookie.value().to_string()),
+            None => jar.incoming.get(name).cloned(),
+        }
+    }
+
+    /// Determines if the given cookie is in the jar.
+    pub fn has(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Adds the given cookie to the jar. It will be sent
+    /// to the client replacing any previous change made to
+    /// a cookie with the same name.
+    pub fn add<C>(&self, cookie: C)
+    where
+        C: Into<Cookie<Response>>,
+    {
+        let cookie: Cookie<Response> = cookie.into();
+        let mut jar = self.0.lock().unwrap();
+
+        jar.changes.retain(|change| change.name() != cookie.name());
+        jar.changes.push(cookie);
+    }
+
+    /// Removes the given cookie from the jar. The client
+    /// is told to expire the cookie right away.
+    pub fn remove(&self, name: &str) {
+        let cookie = Cookie::builder(name, "")
+            .max_age(Some(0))
+            .expires(Some(UNIX_EPOCH))
+            .build();
+
+        self.add(cookie);
+    }
+
+    /// Returns the cookies that changed and must be sent
+    /// back to the client.
+    pub fn changes(&self) -> Vec<Cookie<Response>> {
+        self.0.lock().unwrap().changes.clone()
+    }
+
+    /// Writes the changes to the given response as
+    /// `Set-Cookie` headers and clears them from the jar.
+    pub fn flush(&self, response: &mut Response) {
+        let changes = std::mem::take(&mut self.0.lock().unwrap().changes);
+
+        for cookie in changes {
+            response.headers_mut().set_cookie(cookie);
+        }
+    }
+}
+
+/// Determines if the given cookie expires it on the
+/// client.
+fn is_removal(cookie: &Cookie<Response>) -> bool {
+    cookie.max_age() == Some(&0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::http::Cookie;
+    use crate::http::CookieJar;
+    use crate::http::Request;
+    use crate::http::Response;
+
+    #[test]
+    fn it_reads_incoming_cookies() {
+        struct App;
+        let request = Request::builder()
+            .header("Cookie", "theme=dark; locale=en")
+            .build(Arc::new(App));
+
+        let jar = CookieJar::from_request(&request);
+
+        assert_eq!(jar.get("theme"), Some("dark".to_string()));
+        assert_eq!(jar.get("locale"), Some("en".to_string()));
+        assert!(jar.changes().is_empty());
+    }
+
+    #[test]
+    fn it_tracks_changes() {
+        let jar = CookieJar::new([("theme", "dark"), ("locale", "en")]);
+
+        jar.add(Cookie::builder("theme", "light").build());
+        jar.add(Cookie::builder("theme", "solarized").build());
+        jar.remove("locale");
+
+        assert_eq!(jar.get("theme"), Some("solarized".to_string()));
+        assert!(!jar.has("locale"));
+        assert_eq!(jar.changes().len(), 2);
+    }
+
+    #[test]
+    fn it_flushes_changes_to_responses() {
+        let jar = CookieJar::new([("locale", "en")]);
+        jar.clone().add(Cookie::builder("theme", "dark").build());
+        jar.remove("locale");
+
+        let mut response = Response::ok().build();
+        jar.flush(&mut response);
+
+        let cookies = response.headers().get("Set-Cookie").unwrap();
+
+        assert_eq!(
+            cookies,
+            &vec![
+                "theme=dark".to_string(),
+                "locale=; Expires=Thu, 01 Jan 1970 00:00:00 GMT; Max-Age=0".to_string(),
+            ]
+        );
+        assert!(jar.changes().is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+use std::backtrace::Backtrace;
+use std::error::Error;
+
+use serde_json::json;
+
+use crate::http::Request;
+use crate::http::Response;
+
+/// Escapes the given text to be placed in HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The details of the error behind a response: its
+/// message, its sources and where it happened. Reports
+/// are only collected in debug mode, to render the
+/// development error pages.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorReport {
+    message: String,
+    chain: Vec<String>,
+    backtrace: String,
+}
+
+impl ErrorReport {
+    /// Creates a new report with the given message and
+    /// backtrace.
+    pub fn new<M, B>(message: M, backtrace: B) -> Self
+    where
+        M: Into<String>,
+        B: Into<String>,
+    {
+        Self {
+            message: message.into(),
+            chain: Vec::new(),
+            backtrace: backtrace.into(),
+        }
+    }
+
+    /// Creates a new report of the given error, following
+    /// its sources. The backtrace is captured here.
+    pub fn from_error(error: &(dyn Error + 'static)) -> Self {
+        let mut chain = Vec::new();
+        let mut source = error.source();
+
+        while let Some(error) = source {
+            chain.push(error.to_string());
+            source = error.source();
+        }
+
+        Self {
+            message: error.to_string(),
+            chain,
+            backtrace: Backtrace::force_capture().to_string(),
+        }
+    }
+
+    /// Returns the message of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the messages of the sources of the error,
+    /// from the closest to the root cause.
+    pub fn chain(&self) -> &[String] {
+        &self.chain
+    }
+
+    /// Returns the backtrace of the error.
+    pub fn backtrace(&self) -> &str {
+        &self.backtrace
+    }
+}
+
+/// The details of the request that failed, captured
+/// before the handler consumes it.
+#[derive(Debug, Clone)]
+pub(crate) struct RequestDetails {
+    method: String,
+    uri: String,
+    route: Option<String>,
+    headers: Vec<(String, String)>,
+    wants_json: bool,
+}
+
+impl RequestDetails {
+    pub(crate) fn new<App>(request: &Request<App>) -> Self
+    where
+        App: Send + Sync + 'static,
+    {
+        let mut headers: Vec<(String, String)> = request
+            .headers()
+            .iter()
+            .map(|(header, value)| (header.clone(), value.clone()))
+            .collect();
+
+        headers.sort();
+
+        Self {
+            method: request.method().to_string(),
+            uri: request.uri().to_string(),
+            route: request
+                .route()
+                .map(|route| route.name().unwrap_or(route.path()).to_string()),
+            headers,
+            wants_json: request.wants_json(),
+        }
+    }
+}
+
+/// Renders the development error page of the given
+/// response: JSON when the request wants JSON and HTML
+/// otherwise.
+pub(crate) fn render(response: Response, request: &RequestDetails) -> Response {
+    let status = *response.status();
+    let report = response.error_report().cloned().unwrap_or_else(|| {
+        ErrorReport::new(String::from_utf8_lossy(response.bytes()), String::new())
+    });
+
+    if request.wants_json {
+        let body = json!({
+            "message": report.message(),
+            "chain": report.chain(),
+            "backtrace": report.backtrace().lines().collect::<Vec<_>>(),
+            "request": {
+                "method": request.method,
+                "uri": request.uri,
+                "route": request.route,
+            },
+        });
+
+        return Response::builder()
+            .status(status)
+            .json(&body)
+            .map(|builder| builder.build())
+            .unwrap_or(response);
+    }
+
+    let chain: String = report
+        .chain()
+        .iter()
+        .map(|cause| format!("<li>{}</li>", escape(cause)))
+        .collect();
+
+    let headers: String = request
+        .headers
+        .iter()
+        .map(|(header, value)| {
+            format!(
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape(header),
+                escape(value)
+            )
+        })
+        .collect();
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{status}</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 2rem; color: #1f2937; }}
+h1 {{ color: #b91c1c; }}
+pre {{ background: #f3f4f6; padding: 1rem; overflow-x: auto; font-size: 0.8rem; }}
+th {{ text-align: left; padding-right: 1rem; vertical-align: top; }}
+</style>
+</head>
+<body>
+<p>{status} &middot; {method} {uri} &middot; {route}</p>
+<h1>{message}</h1>
+<h2>Caused by</h2>
+<ul>{chain}</ul>
+<h2>Backtrace</h2>
+<pre>{backtrace}</pre>
+<h2>Request headers</h2>
+<table>{headers}</table>
+</body>
+</html>"#,
+        method = escape(&request.method),
+        uri = escape(&request.uri),
+        route = escape(request.route.as_deref().unwrap_or("no route")),
+        message = escape(report.message()),
+        backtrace = escape(report.backtrace()),
+    );
+
+    Response::builder().status(status).html(html).build()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::Value;
+    use thiserror::Error;
+
+    use crate::http::error_page::render;
+    use crate::http::error_page::ErrorReport;
+    use crate::http::error_page::RequestDetails;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::StatusCode;
+    use crate::http::Uri;
+
+    #[derive(Debug, Error)]
+    #[error("Failed to load <user>")]
+    struct LoadError(#[source] std::io::Error);
+
+    fn failure() -> Response {
+        let error = LoadError(std::io::Error::other("Connection refused"));
+
+        Response::internal_server_error()
+            .body(error.to_string())
+            .build()
+            .with_error_report(ErrorReport::from_error(&error))
+    }
+
+    #[test]
+    fn it_can_render_error_pages() {
+        let request = Request::get(Uri::from_static("/users/5"))
+            .header("X-Request-Id", "abc")
+            .build(Arc::new(()));
+
+        let response = render(failure(), &RequestDetails::new(&request));
+
+        assert_eq!(*response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        response
+            .assert_body_contains("Failed to load &lt;user&gt;")
+            .assert_body_contains("<li>Connection refused</li>")
+            .assert_body_contains("GET /users/5")
+            .assert_body_contains("<tr><th>X-Request-Id</th><td>abc</td></tr>");
+    }
+
+    #[test]
+    fn it_can_render_json_error_pages() {
+        let request = Request::get(Uri::from_static("/users/5"))
+            .header("Accept", "application/json")
+            .build(Arc::new(()));
+
+        let response = render(failure(), &RequestDetails::new(&request));
+        let body: Value = serde_json::from_slice(response.bytes()).unwrap();
+
+        assert_eq!(body["message"], "Failed to load <user>");
+        assert_eq!(body["chain"][0], "Connection refused");
+        assert_eq!(body["request"]["uri"], "/users/5");
+    }
+}
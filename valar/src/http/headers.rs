@@ -1,3 +1,5 @@
+pub mod typed;
+
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::str::FromStr;
@@ -10,6 +12,8 @@ use http::HeaderMap;
 use http::HeaderValue;
 use thiserror::Error;
 
+use crate::http::headers::typed::Error as TypedError;
+use crate::http::headers::typed::TypedHeader;
 use crate::http::Cookie;
 use crate::http::Request;
 use crate::http::Response;
@@ -23,16 +27,20 @@ pub enum ConversionError {
     ValueFailure(#[from] InvalidHeaderValue),
 }
 
+/// The headers of a request or a response. Header names
+/// are matched case insensitively but the original casing
+/// of each name and the order in which the headers were
+/// first added are preserved.
 #[derive(Debug)]
 pub struct Headers<T> {
-    headers: HashMap<String, Vec<String>>,
+    headers: Vec<(String, Vec<String>)>,
     _marker: PhantomData<T>,
 }
 
 impl<T> Default for Headers<T> {
     fn default() -> Self {
         Self {
-            headers: HashMap::new(),
+            headers: Vec::new(),
             _marker: PhantomData::<T>,
         }
     }
@@ -49,15 +57,18 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let headers: Headers<Request> = Headers::new(HashMap::new());
+    /// let headers: Headers<Request<()>> = Headers::new(HashMap::new());
     ///
     /// assert_eq!(headers.len(), 0);
     /// ```
     pub fn new(headers: HashMap<String, Vec<String>>) -> Self {
-        Self {
-            headers,
-            ..Self::default()
+        let mut result = Self::default();
+
+        for (header, values) in headers {
+            result.append_many(header, values);
         }
+
+        result
     }
 
     /// Returns the number of headers
@@ -68,7 +79,7 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> = Headers::default();
+    /// let mut headers: Headers<Request<()>> = Headers::default();
     ///
     /// assert_eq!(headers.len(), 0);
     ///
@@ -94,7 +105,7 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> = Headers::default();
+    /// let mut headers: Headers<Request<()>> = Headers::default();
     ///
     /// assert_eq!(headers.len_of("Content-Type"), 0);
     ///
@@ -121,13 +132,13 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> =
+    /// let mut headers: Headers<Request<()>> =
     ///     Headers::from([("Content-Type", "application/json")]);
     ///
     /// assert!(headers.has("Content-Type"));
     /// ```
     pub fn has(&self, header: &str) -> bool {
-        self.headers.contains_key(&header.to_lowercase())
+        self.position(header).is_some()
     }
 
     /// Only checks the first value of the header.
@@ -138,7 +149,7 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> =
+    /// let mut headers: Headers<Request<()>> =
     ///     Headers::from([("Content-Type", "application/json")]);
     ///
     /// assert!(headers.is("Content-Type", "application/json"));
@@ -158,7 +169,7 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> =
+    /// let mut headers: Headers<Request<()>> =
     ///     Headers::from([("Content-Type", "application/json; charset=utf-8")]);
     ///
     /// assert!(headers.contains("Content-Type", "application/json"));
@@ -178,7 +189,7 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> =
+    /// let mut headers: Headers<Request<()>> =
     ///     Headers::from([("Content-Type", "application/json")]);
     ///
     /// assert_eq!(
@@ -187,11 +198,40 @@ impl<T> Headers<T> {
     /// );
     /// ```
     pub fn get(&self, header: &str) -> Option<&Vec<String>> {
-        self.headers.get(&header.to_lowercase())
+        let position = self.position(header)?;
+
+        Some(&self.headers[position].1)
     }
 
     pub fn get_mut(&mut self, header: &str) -> Option<&mut Vec<String>> {
-        self.headers.get_mut(&header.to_lowercase())
+        let position = self.position(header)?;
+
+        Some(&mut self.headers[position].1)
+    }
+
+    /// Returns the name of the given header as it was
+    /// first added, keeping its original casing.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use valar::http::Headers;
+    /// use valar::http::Request;
+    ///
+    /// let headers: Headers<Request<()>> = Headers::from([("X-Request-ID", "1")]);
+    ///
+    /// assert_eq!(headers.name("x-request-id"), Some("X-Request-ID"));
+    /// ```
+    pub fn name(&self, header: &str) -> Option<&str> {
+        let position = self.position(header)?;
+
+        Some(&self.headers[position].0)
+    }
+
+    /// Returns the position of the given header.
+    fn position(&self, header: &str) -> Option<usize> {
+        self.headers
+            .iter()
+            .position(|(name, _)| name.eq_ignore_ascii_case(header))
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
@@ -208,7 +248,7 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> =
+    /// let mut headers: Headers<Request<()>> =
     ///     Headers::from([("Content-Type", "application/json")]);
     ///
     /// assert_eq!(headers.first("Content-Type"), Some("application/json"));
@@ -217,6 +257,108 @@ impl<T> Headers<T> {
         self.get(header)?.first().map(|value| value.as_str())
     }
 
+    /// Splits the values of a list header, like `Accept`,
+    /// `Cache-Control` or `Vary`, on commas. Commas inside
+    /// quoted strings are kept and empty elements are
+    /// skipped.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use valar::http::Headers;
+    /// use valar::http::Request;
+    ///
+    /// let headers: Headers<Request<()>> =
+    ///     Headers::from([("Cache-Control", r#"no-cache="Set-Cookie, Vary", max-age=60"#)]);
+    ///
+    /// assert_eq!(
+    ///     headers.values_split("Cache-Control"),
+    ///     vec![r#"no-cache="Set-Cookie, Vary""#, "max-age=60"]
+    /// );
+    /// ```
+    pub fn values_split(&self, header: &str) -> Vec<String> {
+        self.get(header)
+            .into_iter()
+            .flatten()
+            .flat_map(|value| split_list(value))
+            .collect()
+    }
+
+    /// Parses a list header with quality weights, like
+    /// `Accept` or `Accept-Language`, into its values
+    /// sorted from the most to the least preferred. Values
+    /// without a weight have a quality of 1, values with a
+    /// quality of 0 are not acceptable and are left out.
+    /// Values with the same quality keep their order.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use valar::http::Headers;
+    /// use valar::http::Request;
+    ///
+    /// let headers: Headers<Request<()>> =
+    ///     Headers::from([("Accept-Language", "en;q=0.8, es, *;q=0")]);
+    ///
+    /// assert_eq!(
+    ///     headers.qualified("Accept-Language"),
+    ///     vec![("es".to_string(), 1.0), ("en".to_string(), 0.8)]
+    /// );
+    /// ```
+    pub fn qualified(&self, header: &str) -> Vec<(String, f32)> {
+        let mut values: Vec<(String, f32)> = self
+            .values_split(header)
+            .iter()
+            .filter_map(|value| qualify(value))
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect();
+
+        values.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        values
+    }
+
+    /// Decodes the given typed header. Returns `None` when
+    /// the header is not present.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use valar::http::headers::typed::ContentLength;
+    /// use valar::http::Headers;
+    /// use valar::http::Request;
+    ///
+    /// let headers: Headers<Request<()>> = Headers::from([("Content-Length", "42")]);
+    ///
+    /// assert_eq!(headers.typed::<ContentLength>(), Some(Ok(ContentLength(42))));
+    /// ```
+    pub fn typed<H>(&self) -> Option<Result<H, TypedError>>
+    where
+        H: TypedHeader,
+    {
+        let values = self.get(H::NAME)?;
+
+        Some(H::decode(&values.join(", ")))
+    }
+
+    /// Inserts (replaces) the given typed header.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use valar::http::headers::typed::ContentType;
+    /// use valar::http::Headers;
+    /// use valar::http::Response;
+    ///
+    /// let mut headers: Headers<Response> = Headers::default();
+    ///
+    /// headers.insert_typed(ContentType::json());
+    ///
+    /// assert!(headers.is("Content-Type", "application/json"));
+    /// ```
+    pub fn insert_typed<H>(&mut self, header: H)
+    where
+        H: TypedHeader,
+    {
+        self.insert(H::NAME, header.encode());
+    }
+
     /// Inserts (replaces) a given header with the given
     /// value. Removes any previous values for that header.
     ///
@@ -225,7 +367,7 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> =
+    /// let mut headers: Headers<Request<()>> =
     ///     Headers::from([("Content-Type", "application/json")]);
     ///
     /// headers.insert("Content-Type", "text/plain");
@@ -240,9 +382,7 @@ impl<T> Headers<T> {
         H: Into<String>,
         V: Into<String>,
     {
-        let header: String = header.into();
-        self.headers
-            .insert(header.to_lowercase(), vec![value.into()]);
+        self.insert_many(header, vec![value]);
     }
 
     /// Replaces a given header with the given
@@ -254,7 +394,7 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> =
+    /// let mut headers: Headers<Request<()>> =
     ///     Headers::from([("Content-Type", "application/json")]);
     ///
     /// headers.insert_many("Content-Type", vec!["text/plain", "text/html"]);
@@ -270,10 +410,12 @@ impl<T> Headers<T> {
         V: Into<String>,
     {
         let header: String = header.into();
-        self.headers.insert(
-            header.to_lowercase(),
-            values.into_iter().map(|value| value.into()).collect(),
-        );
+        let values = values.into_iter().map(|value| value.into()).collect();
+
+        match self.get_mut(&header) {
+            Some(current) => *current = values,
+            None => self.headers.push((header, values)),
+        };
     }
 
     /// Appends a value to the given header.
@@ -286,7 +428,7 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> =
+    /// let mut headers: Headers<Request<()>> =
     ///     Headers::from([("Content-Type", "application/json")]);
     ///
     /// headers.append("Content-Type", "text/plain");
@@ -322,7 +464,7 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> =
+    /// let mut headers: Headers<Request<()>> =
     ///     Headers::from([("Content-Type", "application/json")]);
     ///
     /// headers.append_many("Content-Type", vec!["text/plain", "text/html"]);
@@ -362,8 +504,8 @@ impl<T> Headers<T> {
     /// use valar::http::Request;
     /// use valar::http::Response;
     ///
-    /// let mut headers: Headers<Request> =
-    ///     Headers::<Request>::from([("Content-Type", "application/json")]);
+    /// let mut headers: Headers<Request<()>> =
+    ///     Headers::<Request<()>>::from([("Content-Type", "application/json")]);
     ///
     /// headers.remove("Content-Type");
     ///
@@ -374,7 +516,8 @@ impl<T> Headers<T> {
         H: Into<String>,
     {
         let header: String = header.into();
-        self.headers.remove(&header.to_lowercase());
+        self.headers
+            .retain(|(name, _)| !name.eq_ignore_ascii_case(&header));
     }
 
     /// Clears all the headers.
@@ -385,7 +528,7 @@ impl<T> Headers<T> {
     /// use valar::http::Headers;
     /// use valar::http::Request;
     ///
-    /// let mut headers: Headers<Request> =
+    /// let mut headers: Headers<Request<()>> =
     ///     Headers::from([("Content-Type", "application/json")]);
     ///
     /// headers.clear();
@@ -432,6 +575,42 @@ impl<App: Send + Sync + 'static> Headers<Request<App>> {
 }
 
 impl Headers<Response> {
+    /// Adds the given header to the `Vary` header, merging
+    /// it with the headers already listed. Each header is
+    /// only listed once and a `*` value is kept as is,
+    /// since it already covers every header.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use valar::http::Headers;
+    /// use valar::http::Response;
+    ///
+    /// let mut headers: Headers<Response> = Headers::from([("Vary", "Accept")]);
+    ///
+    /// headers.append_vary("Accept-Encoding");
+    /// headers.append_vary("accept");
+    ///
+    /// assert!(headers.is("Vary", "Accept, Accept-Encoding"));
+    /// ```
+    pub fn append_vary(&mut self, header: &str) {
+        let mut values = self.values_split("Vary");
+
+        if values.iter().any(|value| value == "*") {
+            return;
+        }
+
+        if header == "*" {
+            values = vec![header.to_string()];
+        } else if !values
+            .iter()
+            .any(|value| value.eq_ignore_ascii_case(header))
+        {
+            values.push(header.to_string());
+        }
+
+        self.insert("Vary", values.join(", "));
+    }
+
     /// Computes and creates the cookies based on the
     /// `Set-Cookie` header.
     pub fn cookies(&self) -> Vec<Cookie<Response>> {
@@ -536,3 +715,153 @@ impl<T> TryFrom<Headers<T>> for HeaderMap {
         Ok(headers)
     }
 }
+
+/// Splits a list header value on the commas that are not
+/// part of a quoted string. Empty elements are skipped.
+pub(crate) fn split_list(value: &str) -> Vec<String> {
+    let mut elements = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for character in value.chars() {
+        match character {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                elements.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+
+        current.push(character);
+    }
+
+    elements.push(current);
+
+    elements
+        .into_iter()
+        .map(|element| element.trim().to_string())
+        .filter(|element| !element.is_empty())
+        .collect()
+}
+
+/// Splits the quality weight from the given list value.
+/// Returns `None` for empty values or invalid weights.
+fn qualify(value: &str) -> Option<(String, f32)> {
+    let mut quality = 1.0;
+    let mut parts = Vec::new();
+
+    for part in value.split(';').map(str::trim) {
+        match part.split_once('=') {
+            Some((name, weight)) if name.trim().eq_ignore_ascii_case("q") => {
+                quality = weight.trim().parse::<f32>().ok()?;
+
+                if !(0.0..=1.0).contains(&quality) {
+                    return None;
+                }
+            }
+            _ => parts.push(part),
+        }
+    }
+
+    match parts.first() {
+        Some(first) if !first.is_empty() => Some((parts.join(";"), quality)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::Headers;
+    use crate::http::Response;
+
+    #[test]
+    fn it_preserves_header_order_and_casing() {
+        let mut headers: Headers<Response> = Headers::default();
+        headers.insert("X-Request-ID", "1");
+        headers.insert("content-type", "text/plain");
+        headers.append("ETag", r#""abc""#);
+        headers.insert("Content-Type", "application/json");
+
+        let names: Vec<&String> = headers.iter().map(|(name, _)| name).collect();
+
+        assert_eq!(names, ["X-Request-ID", "content-type", "ETag"]);
+        assert!(headers.is("CONTENT-TYPE", "application/json"));
+        assert_eq!(headers.name("etag"), Some("ETag"));
+    }
+
+    #[test]
+    fn it_matches_header_names_case_insensitively() {
+        let mut headers: Headers<Response> = Headers::from([("Set-Cookie", "a=1")]);
+        headers.append("set-cookie", "b=2");
+
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers.len_of("SET-COOKIE"), 2);
+
+        headers.remove("SET-cookie");
+
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn it_can_split_list_values() {
+        let headers: Headers<Response> = Headers::from([
+            ("Vary", "Accept, , Accept-Encoding"),
+            ("Vary", "Origin"),
+            ("Link", r#"</a>; title="a, \"b\", c", </d>"#),
+        ]);
+
+        assert_eq!(
+            headers.values_split("Vary"),
+            vec!["Accept", "Accept-Encoding", "Origin"]
+        );
+        assert_eq!(
+            headers.values_split("Link"),
+            vec![r#"</a>; title="a, \"b\", c""#, "</d>"]
+        );
+        assert!(headers.values_split("Accept").is_empty());
+    }
+
+    #[test]
+    fn it_can_merge_vary_headers() {
+        let mut headers: Headers<Response> =
+            Headers::from([("Vary", "Accept"), ("Vary", "Origin")]);
+        headers.append_vary("accept");
+        headers.append_vary("Accept-Encoding");
+
+        assert_eq!(
+            headers.get("Vary"),
+            Some(&vec!["Accept, Origin, Accept-Encoding".to_string()])
+        );
+
+        headers.append_vary("*");
+        headers.append_vary("Cookie");
+
+        assert!(headers.is("Vary", "*"));
+    }
+
+    #[test]
+    fn it_can_parse_quality_values() {
+        let headers: Headers<Response> = Headers::from([
+            ("Accept", "text/html;level=1, application/json;q=0.9"),
+            (
+                "Accept",
+                "text/plain; q=0.5, image/png;q=0, */*;q=0.9, bad;q=x",
+            ),
+        ]);
+
+        assert_eq!(
+            headers.qualified("accept"),
+            vec![
+                ("text/html;level=1".to_string(), 1.0),
+                ("application/json".to_string(), 0.9),
+                ("*/*".to_string(), 0.9),
+                ("text/plain".to_string(), 0.5),
+            ]
+        );
+        assert!(headers.qualified("Accept-Language").is_empty());
+    }
+}
@@ -0,0 +1,453 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use thiserror::Error;
+
+use crate::http::headers::split_list;
+use crate::http::Uri;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[error("Invalid value for the {0} header")]
+    InvalidValue(&'static str),
+}
+
+/// A header with a well known format that can be decoded
+/// from and encoded into its raw value.
+pub trait TypedHeader: Sized {
+    /// The name of the header.
+    const NAME: &'static str;
+
+    /// Decodes the header from its raw value. Headers sent
+    /// multiple times are joined with commas.
+    fn decode(value: &str) -> Result<Self, Error>;
+
+    /// Encodes the header into its raw value.
+    fn encode(&self) -> String;
+}
+
+/// The `Content-Type` header.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::headers::typed::ContentType;
+///
+/// let content_type = ContentType::new("text/html; charset=utf-8").unwrap();
+///
+/// assert_eq!(content_type.essence(), "text/html");
+/// assert!(ContentType::new("text").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentType(String);
+
+impl ContentType {
+    /// Creates a new content type validating that it's a
+    /// `type/subtype` media type with optional parameters.
+    pub fn new<V>(value: V) -> Result<Self, Error>
+    where
+        V: Into<String>,
+    {
+        let value: String = value.into();
+
+        match is_media_type(&value, false) {
+            true => Ok(Self(value.trim().to_string())),
+            false => Err(Error::InvalidValue(Self::NAME)),
+        }
+    }
+
+    /// The `application/json` content type.
+    pub fn json() -> Self {
+        Self("application/json".to_string())
+    }
+
+    /// The `text/html; charset=utf-8` content type.
+    pub fn html() -> Self {
+        Self("text/html; charset=utf-8".to_string())
+    }
+
+    /// The `text/plain` content type.
+    pub fn text() -> Self {
+        Self("text/plain".to_string())
+    }
+
+    /// Returns the media type without its parameters, in
+    /// lowercase.
+    pub fn essence(&self) -> String {
+        essence(&self.0)
+    }
+
+    /// Returns the raw value of the content type.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TypedHeader for ContentType {
+    const NAME: &'static str = "Content-Type";
+
+    fn decode(value: &str) -> Result<Self, Error> {
+        Self::new(value)
+    }
+
+    fn encode(&self) -> String {
+        self.0.clone()
+    }
+}
+
+/// The `Content-Length` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentLength(pub u64);
+
+impl TypedHeader for ContentLength {
+    const NAME: &'static str = "Content-Length";
+
+    fn decode(value: &str) -> Result<Self, Error> {
+        let value = value.trim();
+
+        match value.bytes().all(|byte| byte.is_ascii_digit()) {
+            true => value
+                .parse()
+                .map(Self)
+                .map_err(|_| Error::InvalidValue(Self::NAME)),
+            false => Err(Error::InvalidValue(Self::NAME)),
+        }
+    }
+
+    fn encode(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// The `Authorization` header.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::headers::typed::Authorization;
+///
+/// let authorization = Authorization::basic("erik", "secret");
+///
+/// assert_eq!(
+///     authorization.basic_credentials(),
+///     Some(("erik".to_string(), "secret".to_string()))
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Authorization {
+    scheme: String,
+    credentials: String,
+}
+
+impl Authorization {
+    /// Creates a new authorization with the given scheme
+    /// and credentials. The scheme must be a token and the
+    /// credentials must not contain control characters.
+    pub fn new<S, C>(scheme: S, credentials: C) -> Result<Self, Error>
+    where
+        S: Into<String>,
+        C: Into<String>,
+    {
+        let scheme: String = scheme.into();
+        let credentials: String = credentials.into();
+
+        if !is_token(&scheme) || !is_visible(&credentials) {
+            return Err(Error::InvalidValue(Self::NAME));
+        }
+
+        Ok(Self {
+            scheme,
+            credentials: credentials.trim().to_string(),
+        })
+    }
+
+    /// Creates a new `Bearer` authorization.
+    pub fn bearer<T>(token: T) -> Result<Self, Error>
+    where
+        T: Into<String>,
+    {
+        Self::new("Bearer", token)
+    }
+
+    /// Creates a new `Basic` authorization encoding the
+    /// given username and password.
+    pub fn basic(username: &str, password: &str) -> Self {
+        Self {
+            scheme: "Basic".to_string(),
+            credentials: STANDARD.encode(format!("{username}:{password}")),
+        }
+    }
+
+    /// Returns the authorization scheme.
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Returns the raw credentials.
+    pub fn credentials(&self) -> &str {
+        &self.credentials
+    }
+
+    /// Returns the token of a `Bearer` authorization.
+    pub fn bearer_token(&self) -> Option<&str> {
+        match self.scheme.eq_ignore_ascii_case("Bearer") {
+            true => Some(&self.credentials),
+            false => None,
+        }
+    }
+
+    /// Returns the decoded username and password of a
+    /// `Basic` authorization.
+    pub fn basic_credentials(&self) -> Option<(String, String)> {
+        if !self.scheme.eq_ignore_ascii_case("Basic") {
+            return None;
+        }
+
+        let decoded = STANDARD.decode(&self.credentials).ok()?;
+        let decoded = String::from_utf8(decoded).ok()?;
+        let (username, password) = decoded.split_once(':')?;
+
+        Some((username.to_string(), password.to_string()))
+    }
+}
+
+impl TypedHeader for Authorization {
+    const NAME: &'static str = "Authorization";
+
+    fn decode(value: &str) -> Result<Self, Error> {
+        let (scheme, credentials) = value
+            .trim()
+            .split_once(' ')
+            .ok_or(Error::InvalidValue(Self::NAME))?;
+
+        Self::new(scheme, credentials)
+    }
+
+    fn encode(&self) -> String {
+        format!("{} {}", self.scheme, self.credentials)
+    }
+}
+
+/// The `Location` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location(Uri);
+
+impl Location {
+    /// Creates a new location validating it's a valid
+    /// absolute or relative URI.
+    pub fn new(location: &str) -> Result<Self, Error> {
+        location
+            .trim()
+            .parse()
+            .map(Self)
+            .map_err(|_| Error::InvalidValue(Self::NAME))
+    }
+
+    /// Returns the URI of the location.
+    pub fn uri(&self) -> &Uri {
+        &self.0
+    }
+}
+
+impl TypedHeader for Location {
+    const NAME: &'static str = "Location";
+
+    fn decode(value: &str) -> Result<Self, Error> {
+        Self::new(value)
+    }
+
+    fn encode(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// The `Accept` header.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::headers::typed::Accept;
+///
+/// let accept = Accept::new(["text/html", "application/*"]).unwrap();
+///
+/// assert!(accept.accepts("application/json"));
+/// assert!(!accept.accepts("image/png"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Accept(Vec<String>);
+
+impl Accept {
+    /// Creates a new accept header with the given media
+    /// ranges, validating each one.
+    pub fn new<I, V>(media_ranges: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<String>,
+    {
+        let media_ranges = media_ranges
+            .into_iter()
+            .map(|range| range.into().trim().to_string())
+            .collect::<Vec<_>>();
+
+        match media_ranges.iter().all(|range| is_media_type(range, true)) {
+            true => Ok(Self(media_ranges)),
+            false => Err(Error::InvalidValue(Self::NAME)),
+        }
+    }
+
+    /// Returns the media ranges, including their
+    /// parameters.
+    pub fn media_ranges(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Determines if the given media type matches any of
+    /// the media ranges, taking wildcards into account.
+    pub fn accepts(&self, media_type: &str) -> bool {
+        let media_type = essence(media_type);
+        let (kind, _) = media_type.split_once('/').unwrap_or((&media_type, ""));
+
+        self.0
+            .iter()
+            .map(|range| essence(range))
+            .any(|range| range == "*/*" || range == media_type || range == format!("{kind}/*"))
+    }
+}
+
+impl TypedHeader for Accept {
+    const NAME: &'static str = "Accept";
+
+    fn decode(value: &str) -> Result<Self, Error> {
+        Self::new(split_list(value))
+    }
+
+    fn encode(&self) -> String {
+        self.0.join(", ")
+    }
+}
+
+/// Returns the media type without its parameters, in
+/// lowercase.
+fn essence(media_type: &str) -> String {
+    media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Determines if the value is a `type/subtype` media type
+/// with optional parameters. Wildcards are only allowed
+/// for media ranges.
+fn is_media_type(value: &str, range: bool) -> bool {
+    let mut parts = value.split(';');
+    let media_type = parts.next().unwrap_or_default().trim();
+
+    let valid_type = match media_type.split_once('/') {
+        Some(("*", "*")) => range,
+        Some((kind, "*")) => range && is_token(kind),
+        Some((kind, subtype)) => is_token(kind) && is_token(subtype),
+        None => false,
+    };
+
+    valid_type
+        && parts.all(|parameter| match parameter.split_once('=') {
+            Some((name, value)) => is_token(name.trim()) && is_visible(value),
+            None => false,
+        })
+}
+
+/// Determines if the value is a token as defined by
+/// RFC 9110.
+pub(crate) fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte))
+}
+
+/// Determines if the value only contains visible
+/// characters and spaces.
+fn is_visible(value: &str) -> bool {
+    !value.trim().is_empty()
+        && value
+            .bytes()
+            .all(|byte| byte == b' ' || byte == b'\t' || byte.is_ascii_graphic() || byte >= 0x80)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::cache_control::CacheControl;
+    use crate::http::headers::typed::Accept;
+    use crate::http::headers::typed::Authorization;
+    use crate::http::headers::typed::ContentLength;
+    use crate::http::headers::typed::ContentType;
+    use crate::http::headers::typed::Location;
+    use crate::http::Headers;
+    use crate::http::Response;
+
+    #[test]
+    fn it_validates_typed_headers() {
+        assert!(ContentType::new("application/json; charset=utf-8").is_ok());
+        assert!(ContentType::new("application/").is_err());
+        assert!(ContentType::new("text/*").is_err());
+        assert!(Authorization::bearer("abc.def").is_ok());
+        assert!(Authorization::bearer("abc\ndef").is_err());
+        assert!(Location::new("/users/1").is_ok());
+        assert!(Location::new("/users /1").is_err());
+        assert!(Accept::new(["text/*", "*/*"]).is_ok());
+        assert!(Accept::new(["text"]).is_err());
+    }
+
+    #[test]
+    fn it_can_read_typed_headers() {
+        let headers: Headers<Response> = Headers::from([
+            ("Content-Type", "application/json"),
+            ("Content-Length", "42"),
+            ("Authorization", "Bearer abc"),
+            ("Accept", "text/html, application/json"),
+            ("Cache-Control", "public, max-age=60"),
+        ]);
+
+        assert_eq!(
+            headers.typed::<ContentType>(),
+            Some(Ok(ContentType::json()))
+        );
+        assert_eq!(
+            headers.typed::<ContentLength>(),
+            Some(Ok(ContentLength(42)))
+        );
+        assert_eq!(
+            headers
+                .typed::<Authorization>()
+                .unwrap()
+                .unwrap()
+                .bearer_token(),
+            Some("abc")
+        );
+        assert!(headers
+            .typed::<Accept>()
+            .unwrap()
+            .unwrap()
+            .accepts("application/json"));
+        assert_eq!(
+            headers.typed::<CacheControl>(),
+            Some(Ok(CacheControl::new().public().max_age(60)))
+        );
+        assert_eq!(headers.typed::<Location>(), None);
+    }
+
+    #[test]
+    fn it_can_write_typed_headers() {
+        let mut headers: Headers<Response> = Headers::default();
+        headers.insert_typed(Authorization::basic("erik", "secret"));
+        headers.insert_typed(ContentLength(10));
+
+        assert!(headers.is("Authorization", "Basic ZXJpazpzZWNyZXQ="));
+        assert!(headers.is("Content-Length", "10"));
+        assert_eq!(
+            headers.typed::<ContentLength>(),
+            Some(Ok(ContentLength(10)))
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::future::Future;
+use std::pin::Pin;
+
+use futures_util::future::join_all;
+use serde_json::json;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::http::Method;
+use crate::http::Response;
+
+/// A readiness check reports whether a dependency of the
+/// application, like the database, is available.
+pub type Check =
+    Box<dyn Fn() -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> + Send + Sync>;
+
+/// The health endpoints of the server, answered before the
+/// request reaches the router. The liveness endpoint
+/// reports that the server is running, while the readiness
+/// endpoint runs the registered checks and fails with a
+/// `503 Service Unavailable` when any of them fails.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::Health;
+/// use valar::http::Server;
+///
+/// let health = Health::new()
+///     .readiness("/ready")
+///     .check("database", || async { Ok::<_, String>(()) });
+///
+/// let server = Server::builder().health(health).build();
+/// ```
+pub struct Health {
+    liveness: String,
+    readiness: String,
+    checks: Vec<(String, Check)>,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            liveness: "/healthz".to_string(),
+            readiness: "/readyz".to_string(),
+            checks: Vec::new(),
+        }
+    }
+}
+
+impl Health {
+    /// Creates the health endpoints at `/healthz` and
+    /// `/readyz`, without any readiness checks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the path of the liveness endpoint.
+    pub fn liveness<P>(mut self, path: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.liveness = path.into();
+
+        self
+    }
+
+    /// Sets the path of the readiness endpoint.
+    pub fn readiness<P>(mut self, path: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.readiness = path.into();
+
+        self
+    }
+
+    /// Registers a readiness check with the given name.
+    /// The checks run concurrently on every readiness
+    /// request.
+    pub fn check<N, F, Fut, E>(mut self, name: N, check: F) -> Self
+    where
+        N: Into<String>,
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+    {
+        let check: Check = Box::new(move || {
+            let result = check();
+
+            Box::pin(async move { result.await.map_err(|error| error.to_string()) })
+        });
+
+        self.checks.push((name.into(), check));
+
+        self
+    }
+
+    /// Answers the request when it targets one of the
+    /// health endpoints.
+    pub(crate) async fn respond(&self, method: &Method, path: &str) -> Option<Response> {
+        if method != Method::GET && method != Method::HEAD {
+            return None;
+        }
+
+        if path == self.liveness {
+            return Some(Self::response(true, json!({ "status": "ok" })));
+        }
+
+        if path == self.readiness {
+            return Some(self.ready().await);
+        }
+
+        None
+    }
+
+    /// Runs the readiness checks.
+    async fn ready(&self) -> Response {
+        let results = join_all(self.checks.iter().map(|(_, check)| check())).await;
+        let ready = results.iter().all(Result::is_ok);
+
+        let checks: Map<String, Value> = self
+            .checks
+            .iter()
+            .zip(results)
+            .map(|((name, _), result)| {
+                let status = match result {
+                    Ok(()) => json!({ "status": "ok" }),
+                    Err(error) => json!({ "status": "failed", "error": error }),
+                };
+
+                (name.clone(), status)
+            })
+            .collect();
+
+        let status = if ready { "ok" } else { "failed" };
+
+        Self::response(ready, json!({ "status": status, "checks": checks }))
+    }
+
+    fn response(healthy: bool, body: Value) -> Response {
+        let builder = match healthy {
+            true => Response::ok(),
+            false => Response::service_unavailable(),
+        };
+
+        builder
+            .header("Cache-Control", "no-store")
+            .json_or(&body, String::from("{}"))
+            .build()
+    }
+}
+
+impl Debug for Health {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        let checks: Vec<&String> = self.checks.iter().map(|(name, _)| name).collect();
+
+        formatter
+            .debug_struct("Health")
+            .field("liveness", &self.liveness)
+            .field("readiness", &self.readiness)
+            .field("checks", &checks)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::http::Health;
+    use crate::http::Method;
+    use crate::http::StatusCode;
+
+    #[tokio::test]
+    async fn it_can_report_health() {
+        let health = Health::new()
+            .readiness("/ready")
+            .check("database", || async { Ok::<_, String>(()) })
+            .check("cache", || async { Err("Connection refused") });
+
+        let live = health.respond(&Method::GET, "/healthz").await.unwrap();
+        let ready = health.respond(&Method::GET, "/ready").await.unwrap();
+        let body: Value = serde_json::from_slice(ready.bytes()).unwrap();
+
+        assert_eq!(*live.status(), StatusCode::OK);
+        assert_eq!(*ready.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["checks"]["database"]["status"], "ok");
+        assert_eq!(body["checks"]["cache"]["error"], "Connection refused");
+        assert!(health.respond(&Method::GET, "/readyz").await.is_none());
+        assert!(health.respond(&Method::POST, "/healthz").await.is_none());
+    }
+}
@@ -1,7 +1,36 @@
-mod cookies;
+pub(crate) mod auth;
+mod basic_auth;
+mod body_limit;
+mod compression;
+pub(crate) mod cookies;
+mod etag;
+mod forwarded;
+mod https;
+pub(crate) mod locale;
 mod logger;
+mod logging;
+mod metrics;
+mod recover;
 mod session;
 
+pub use auth::Authenticate;
+pub use auth::Guard;
+pub use basic_auth::BasicAuth;
+pub use body_limit::BodyLimit;
+pub use body_limit::KB;
+pub use body_limit::MB;
+pub use compression::Compression;
+pub use cookies::Cookies;
 pub use cookies::QueueableCookies;
+pub use etag::ETag;
+pub use forwarded::Error as ForwardedError;
+pub use forwarded::ForwardedHeaders;
+pub use https::HttpsRedirect;
+pub use locale::Locale;
 pub use logger::Logger;
+pub use logging::Format as LoggingFormat;
+pub use logging::Logging;
+pub use metrics::Metrics;
+pub use metrics::Registry as MetricsRegistry;
+pub use recover::Recover;
 pub use session::Session;
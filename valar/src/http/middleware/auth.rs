@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::http::Request;
+use crate::http::Response;
+use crate::http::Result;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+
+/// The context key of the authenticated user.
+pub(crate) const AUTHENTICATED_USER: &str = "request:authenticated_user";
+
+/// Resolves the user of a request, for example from its
+/// bearer token or its session.
+///
+/// # Example
+///
+/// ```no_run
+/// use async_trait::async_trait;
+/// use valar::http::headers::typed::Authorization;
+/// use valar::http::middleware::Guard;
+/// use valar::http::Request;
+///
+/// struct App;
+/// struct User(String);
+/// struct TokenGuard;
+///
+/// #[async_trait]
+/// impl Guard<App> for TokenGuard {
+///     type User = User;
+///
+///     async fn user(&self, request: &Request<App>) -> Option<User> {
+///         let authorization = request.headers().typed::<Authorization>()?.ok()?;
+///
+///         match authorization.bearer_token() {
+///             Some("secret") => Some(User("erik".to_string())),
+///             _ => None,
+///         }
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait Guard<App: Send + Sync + 'static>: Send + Sync + 'static {
+    /// The type of the authenticated user.
+    type User: Send + Sync + 'static;
+
+    /// Returns the user of the given request, if any.
+    async fn user(&self, request: &Request<App>) -> Option<Self::User>;
+}
+
+/// Rejects the requests that the given guard can't resolve
+/// a user for with a `401 Unauthorized` response. The user
+/// of the accepted requests is available to the handlers
+/// with `request.user::<User>()`.
+///
+/// # Example
+///
+/// ```ignore
+/// use valar::http::middleware::Authenticate;
+///
+/// let authenticate = Authenticate::new(TokenGuard).challenge("Bearer");
+/// ```
+pub struct Authenticate<G> {
+    guard: G,
+    challenge: String,
+}
+
+impl<G> Authenticate<G> {
+    /// Creates a new authentication middleware with the
+    /// given guard.
+    pub fn new(guard: G) -> Self {
+        Self {
+            guard,
+            challenge: "Bearer".to_string(),
+        }
+    }
+
+    /// Sets the `WWW-Authenticate` challenge of the
+    /// rejected requests. Defaults to `Bearer`.
+    pub fn challenge<C>(mut self, challenge: C) -> Self
+    where
+        C: Into<String>,
+    {
+        self.challenge = challenge.into();
+
+        self
+    }
+}
+
+#[async_trait]
+impl<App, G> Middleware<App> for Authenticate<G>
+where
+    App: Send + Sync + 'static,
+    G: Guard<App>,
+{
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> Result {
+        let Some(user) = self.guard.user(&request).await else {
+            return Response::unauthorized(&self.challenge).into_err();
+        };
+
+        request
+            .context()
+            .insert(AUTHENTICATED_USER, Arc::new(user))
+            .await;
+
+        next(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+
+    use crate::http::headers::typed::Authorization;
+    use crate::http::middleware::Authenticate;
+    use crate::http::middleware::Guard;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::StatusCode;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::router::Compiled;
+    use crate::routing::Router;
+
+    struct App;
+
+    struct User(String);
+
+    struct TokenGuard;
+
+    #[async_trait]
+    impl Guard<App> for TokenGuard {
+        type User = User;
+
+        async fn user(&self, request: &Request<App>) -> Option<User> {
+            let authorization = request.headers().typed::<Authorization>()?.ok()?;
+
+            match authorization.bearer_token() {
+                Some("secret") => Some(User("erik".to_string())),
+                _ => None,
+            }
+        }
+    }
+
+    async fn profile(request: Request<App>) -> Result {
+        let user = request.user::<User>().await.unwrap();
+
+        Response::ok().body(user.0.clone()).into_ok()
+    }
+
+    fn router() -> Router<App, Compiled> {
+        Router::from_iter([Route::get("/profile", profile)
+            .middleware(Authenticate::new(TokenGuard).challenge("Bearer realm=\"valar\""))])
+        .compile()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_can_authenticate_requests() {
+        let request = Request::get(Uri::from_static("/profile"))
+            .header("Authorization", "Bearer secret")
+            .build(Arc::new(App));
+
+        let response = router().handle(request).await;
+
+        assert_eq!(*response.status(), StatusCode::OK);
+        assert_eq!(response.body(), "erik");
+    }
+
+    #[tokio::test]
+    async fn it_rejects_unauthenticated_requests() {
+        let missing = Request::get(Uri::from_static("/profile")).build(Arc::new(App));
+        let invalid = Request::get(Uri::from_static("/profile"))
+            .header("Authorization", "Bearer nope")
+            .build(Arc::new(App));
+
+        let missing = router().handle(missing).await;
+        let invalid = router().handle(invalid).await;
+
+        assert_eq!(*missing.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(*invalid.status(), StatusCode::UNAUTHORIZED);
+        missing.assert_header_is("WWW-Authenticate", "Bearer realm=\"valar\"");
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::http::headers::typed::Authorization;
+use crate::http::middleware::auth::AUTHENTICATED_USER;
+use crate::http::Request;
+use crate::http::Response;
+use crate::http::Result;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+use crate::utils::constant_time_eq;
+
+/// Verifies a username and password.
+type Verifier = Arc<dyn Fn(&str, &str) -> bool + Send + Sync + 'static>;
+
+/// Protects the routes with HTTP Basic authentication.
+/// Requests without valid credentials are rejected with a
+/// `401 Unauthorized` response and a `WWW-Authenticate`
+/// challenge, so browsers prompt for them. The username of
+/// the accepted requests is available to the handlers with
+/// `request.user::<String>()`.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::middleware::BasicAuth;
+///
+/// let staging = BasicAuth::users([("erik", "secret")]).realm("Staging");
+///
+/// let admin = BasicAuth::new(|username, password| {
+///     username == "admin" && password == std::env::var("ADMIN_PASSWORD").unwrap_or_default()
+/// });
+/// ```
+#[derive(Clone)]
+pub struct BasicAuth {
+    verifier: Verifier,
+    realm: String,
+}
+
+impl BasicAuth {
+    /// Creates a new middleware that accepts the
+    /// credentials the given callback verifies.
+    pub fn new<F>(verifier: F) -> Self
+    where
+        F: Fn(&str, &str) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            verifier: Arc::new(verifier),
+            realm: "Restricted".to_string(),
+        }
+    }
+
+    /// Creates a new middleware that accepts the given
+    /// usernames and passwords.
+    pub fn users<I, U, P>(users: I) -> Self
+    where
+        I: IntoIterator<Item = (U, P)>,
+        U: Into<String>,
+        P: Into<String>,
+    {
+        let users: HashMap<String, String> = users
+            .into_iter()
+            .map(|(username, password)| (username.into(), password.into()))
+            .collect();
+
+        Self::new(move |username, password| match users.get(username) {
+            Some(expected) => constant_time_eq(expected, password),
+            None => false,
+        })
+    }
+
+    /// Sets the realm of the challenge. Defaults to
+    /// `Restricted`.
+    pub fn realm<R>(mut self, realm: R) -> Self
+    where
+        R: Into<String>,
+    {
+        self.realm = realm.into();
+
+        self
+    }
+
+    /// Returns the `WWW-Authenticate` challenge.
+    fn challenge(&self) -> String {
+        let realm = self.realm.replace('\\', "\\\\").replace('"', "\\\"");
+
+        format!(r#"Basic realm="{realm}", charset="UTF-8""#)
+    }
+}
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for BasicAuth {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> Result {
+        let credentials = request
+            .headers()
+            .typed::<Authorization>()
+            .and_then(|authorization| authorization.ok()?.basic_credentials());
+
+        let username = match credentials {
+            Some((username, password)) if (self.verifier)(&username, &password) => username,
+            _ => return Response::unauthorized(&self.challenge()).into_err(),
+        };
+
+        request
+            .context()
+            .insert(AUTHENTICATED_USER, Arc::new(username))
+            .await;
+
+        next(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::http::headers::typed::Authorization;
+    use crate::http::headers::typed::TypedHeader;
+    use crate::http::middleware::BasicAuth;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::StatusCode;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn dashboard(request: Request<App>) -> Result {
+        let user = request.user::<String>().await.unwrap();
+
+        Response::ok().body(user.as_str()).into_ok()
+    }
+
+    #[tokio::test]
+    async fn it_can_authenticate_with_basic_credentials() {
+        let router = Router::from_iter([Route::get("/", dashboard)])
+            .middleware(BasicAuth::users([("erik", "secret")]).realm("Staging"))
+            .compile()
+            .unwrap();
+
+        let request = |credentials: Option<(&str, &str)>| {
+            let mut request = Request::get(Uri::from_static("/"));
+
+            if let Some((username, password)) = credentials {
+                let authorization = Authorization::basic(username, password);
+                request = request.header("Authorization", authorization.encode());
+            }
+
+            request.build(Arc::new(App))
+        };
+
+        let accepted = router.handle(request(Some(("erik", "secret")))).await;
+        let rejected = router.handle(request(Some(("erik", "wrong")))).await;
+        let missing = router.handle(request(None)).await;
+
+        assert_eq!(accepted.body(), "erik");
+        assert_eq!(*rejected.status(), StatusCode::UNAUTHORIZED);
+        missing.assert_header_is(
+            "WWW-Authenticate",
+            r#"Basic realm="Staging", charset="UTF-8""#,
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::http::Request;
+use crate::http::Response;
+use crate::http::Result as HttpResult;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+
+/// One kilobyte, in bytes.
+pub const KB: u64 = 1024;
+
+/// One megabyte, in bytes.
+pub const MB: u64 = 1024 * KB;
+
+/// Rejects the requests whose body is larger than the
+/// given size with a `413 Payload Too Large` response.
+/// The response is JSON when the request wants JSON and
+/// HTML otherwise.
+///
+/// The router still enforces its own limit before the body
+/// is read, so this middleware can only lower it.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::middleware::BodyLimit;
+/// use valar::http::middleware::MB;
+///
+/// let limit = BodyLimit::max(10 * MB);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimit {
+    max: u64,
+}
+
+impl BodyLimit {
+    /// Creates a new body limit middleware with the given
+    /// maximum size, in bytes.
+    pub fn max(bytes: u64) -> Self {
+        Self { max: bytes }
+    }
+
+    /// Returns the size of the body of the given request,
+    /// taking the `Content-Length` header into account.
+    fn size<App>(request: &Request<App>) -> u64
+    where
+        App: Send + Sync + 'static,
+    {
+        let length = request
+            .headers()
+            .first("Content-Length")
+            .and_then(|length| length.trim().parse().ok())
+            .unwrap_or(0);
+
+        u64::max(length, request.body().len() as u64)
+    }
+
+    /// Returns the response sent when the limit is
+    /// exceeded.
+    fn rejection<App>(&self, request: &Request<App>) -> HttpResult
+    where
+        App: Send + Sync + 'static,
+    {
+        let message = "Request body too large";
+        let response = Response::payload_too_large();
+
+        if request.wants_json() {
+            return response
+                .json(&json!({ "message": message, "limit": self.max }))?
+                .into_err();
+        }
+
+        response
+            .html(format!(
+                "<!DOCTYPE html><html><head><title>413 {message}</title></head>\
+                 <body><h1>413 {message}</h1></body></html>"
+            ))
+            .into_err()
+    }
+}
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for BodyLimit {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        if Self::size(&request) > self.max {
+            return self.rejection(&request);
+        }
+
+        next(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::http::middleware::BodyLimit;
+    use crate::http::middleware::KB;
+    use crate::http::Method;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::StatusCode;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn upload(request: Request<App>) -> Result {
+        Response::ok()
+            .body(request.body().len().to_string())
+            .into_ok()
+    }
+
+    async fn send(body: String, accept: &str) -> Response {
+        let router =
+            Router::from_iter([Route::post("/upload", upload).middleware(BodyLimit::max(KB))])
+                .compile()
+                .unwrap();
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(Uri::from_static("/upload"))
+            .header("Accept", accept)
+            .body(body)
+            .build(Arc::new(App));
+
+        router.handle(request).await
+    }
+
+    #[tokio::test]
+    async fn it_can_limit_the_body_size() {
+        let accepted = send("a".repeat(1024), "text/html").await;
+        let html = send("a".repeat(1025), "text/html").await;
+        let json = send("a".repeat(1025), "application/json").await;
+
+        assert_eq!(*accepted.status(), StatusCode::OK);
+        assert_eq!(*html.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(*json.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(html.headers().contains("Content-Type", "text/html"));
+        assert_eq!(
+            json.body(),
+            r#"{"limit":1024,"message":"Request body too large"}"#
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use std::io::Result as IoResult;
+use std::io::Write;
+
+use async_trait::async_trait;
+use brotli::CompressorWriter;
+use flate2::write::GzEncoder;
+use flate2::Compression as GzipLevel;
+use hyper::body::Bytes;
+
+use crate::http::Body;
+use crate::http::Request;
+use crate::http::Response;
+use crate::http::Result as HttpResult;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+
+/// Content types that are already compressed and would
+/// not benefit from being compressed again.
+const COMPRESSED_TYPES: [&str; 9] = [
+    "application/zip",
+    "application/gzip",
+    "application/x-gzip",
+    "application/x-bzip2",
+    "application/x-7z-compressed",
+    "application/x-rar-compressed",
+    "application/pdf",
+    "font/woff",
+    "font/woff2",
+];
+
+/// The encodings supported by the `Compression` middleware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    /// Picks the preferred encoding of the given request,
+    /// based on its `Accept-Encoding` header.
+    fn negotiate<App>(request: &Request<App>) -> Option<Self>
+    where
+        App: Send + Sync + 'static,
+    {
+        request
+            .headers()
+            .qualified("Accept-Encoding")
+            .iter()
+            .find_map(
+                |(encoding, _)| match encoding.to_ascii_lowercase().as_str() {
+                    "br" => Some(Self::Brotli),
+                    "gzip" | "x-gzip" | "*" => Some(Self::Gzip),
+                    _ => None,
+                },
+            )
+    }
+
+    /// Returns the `Content-Encoding` value.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    /// Compresses the given bytes.
+    fn compress(&self, bytes: &[u8]) -> IoResult<Vec<u8>> {
+        match self {
+            Self::Brotli => {
+                let mut writer = CompressorWriter::new(Vec::new(), 4096, 5, 22);
+                writer.write_all(bytes)?;
+
+                Ok(writer.into_inner())
+            }
+            Self::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), GzipLevel::default());
+                encoder.write_all(bytes)?;
+
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// Compresses the response bodies with brotli or gzip,
+/// depending on what the client accepts. Streamed bodies,
+/// small bodies and already compressed content types are
+/// sent as they are.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::middleware::Compression;
+///
+/// let compression = Compression::new().threshold(2048);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Compression {
+    threshold: usize,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self { threshold: 1024 }
+    }
+}
+
+impl Compression {
+    /// Creates a new compression middleware that compresses
+    /// bodies of 1KB or more.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the minimum body size, in bytes, to compress.
+    pub fn threshold(mut self, bytes: usize) -> Self {
+        self.threshold = bytes;
+
+        self
+    }
+
+    /// Determines if the given response may be compressed.
+    fn is_compressible(&self, response: &Response) -> bool {
+        if response.is_streamed()
+            || response.bytes().len() < self.threshold
+            || response.headers().has("Content-Encoding")
+        {
+            return false;
+        }
+
+        let content_type = response
+            .headers()
+            .first("Content-Type")
+            .unwrap_or_default()
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        let is_media = ["image/", "audio/", "video/"]
+            .iter()
+            .any(|prefix| content_type.starts_with(prefix));
+
+        (!is_media || content_type == "image/svg+xml")
+            && !COMPRESSED_TYPES.contains(&content_type.as_str())
+    }
+
+    /// Compresses the body of the given response in place.
+    fn compress(&self, response: &mut Response, encoding: Option<Encoding>) {
+        if !self.is_compressible(response) {
+            return;
+        }
+
+        response.headers_mut().append_vary("Accept-Encoding");
+
+        let Some(encoding) = encoding else {
+            return;
+        };
+
+        let Ok(compressed) = encoding.compress(response.bytes()) else {
+            return;
+        };
+
+        let headers = response.headers_mut();
+        headers.insert("Content-Encoding", encoding.name());
+        headers.insert("Content-Length", compressed.len().to_string());
+
+        *response.body_mut() = Body::Full(Bytes::from(compressed));
+    }
+}
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for Compression {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        let encoding = Encoding::negotiate(&request);
+        let mut result = next(request).await;
+
+        let response = match &mut result {
+            Ok(response) => response,
+            Err(response) => response,
+        };
+
+        self.compress(response, encoding);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::sync::Arc;
+
+    use flate2::read::GzDecoder;
+
+    use crate::http::middleware::Compression;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result as ResponseResult;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn large(_request: Request<App>) -> ResponseResult {
+        Response::ok().json(&vec!["valar"; 500])?.into_ok()
+    }
+
+    async fn small(_request: Request<App>) -> ResponseResult {
+        Response::ok().json(&"valar")?.into_ok()
+    }
+
+    async fn image(_request: Request<App>) -> ResponseResult {
+        Response::ok()
+            .header("Content-Type", "image/png")
+            .body(vec![0u8; 4096])
+            .into_ok()
+    }
+
+    async fn request(path: &'static str, accept: &str) -> Response {
+        let router = Router::from_iter([
+            Route::get("/large", large),
+            Route::get("/small", small),
+            Route::get("/image", image),
+        ])
+        .middleware(Compression::new())
+        .compile()
+        .unwrap();
+
+        let request = Request::get(Uri::from_static(path))
+            .header("Accept-Encoding", accept)
+            .build(Arc::new(App));
+
+        router.handle(request).await
+    }
+
+    #[tokio::test]
+    async fn it_can_compress_with_gzip() {
+        let response = request("/large", "gzip, deflate").await;
+
+        response
+            .assert_header_is("Content-Encoding", "gzip")
+            .assert_header_is("Vary", "Accept-Encoding");
+
+        let mut body = String::new();
+        GzDecoder::new(response.bytes())
+            .read_to_string(&mut body)
+            .unwrap();
+
+        assert_eq!(body, serde_json::to_string(&vec!["valar"; 500]).unwrap());
+        assert_eq!(
+            response.headers().first("Content-Length"),
+            Some(response.bytes().len().to_string().as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn it_can_compress_with_brotli() {
+        let response = request("/large", "gzip;q=0.5, br").await;
+
+        response.assert_header_is("Content-Encoding", "br");
+
+        let mut body = String::new();
+        brotli::Decompressor::new(response.bytes(), 4096)
+            .read_to_string(&mut body)
+            .unwrap();
+
+        assert_eq!(body, serde_json::to_string(&vec!["valar"; 500]).unwrap());
+    }
+
+    #[tokio::test]
+    async fn it_skips_incompressible_responses() {
+        let small = request("/small", "gzip").await;
+        let image = request("/image", "gzip").await;
+        let identity = request("/large", "identity").await;
+
+        assert!(!small.headers().has("Content-Encoding"));
+        assert!(!image.headers().has("Content-Encoding"));
+        assert!(!identity.headers().has("Content-Encoding"));
+        identity.assert_header_is("Vary", "Accept-Encoding");
+    }
+}
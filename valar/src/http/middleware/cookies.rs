@@ -1,12 +1,22 @@
 use async_trait::async_trait;
 
 use crate::http::Cookie;
+use crate::http::CookieJar;
 use crate::http::Request;
 use crate::http::Response;
 use crate::http::Result as HttpResult;
 use crate::routing::middleware::Handler;
 use crate::routing::middleware::Middleware;
 
+/// The context key where the queued cookies are kept.
+pub(crate) const QUEUED_COOKIES: &str = "response:queued_cookies";
+
+/// The context key where the cookie jar is kept.
+pub(crate) const COOKIE_JAR: &str = "request:cookie_jar";
+
+/// Sends the cookies queued on the request with
+/// `Request::queue_cookie` along with the response.
+/// Cookies already set by the response take precedence.
 pub struct QueueableCookies;
 
 #[async_trait]
@@ -14,12 +24,12 @@ impl<App: Send + Sync + 'static> Middleware<App> for QueueableCookies {
     async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
         let context = request.context().clone();
         let cookies: Vec<Cookie<Response>> = Vec::new();
-        context.insert("response:queued_cookies", cookies).await;
+        context.insert(QUEUED_COOKIES, cookies).await;
 
         let mut response = next(request).await;
 
         let cookies: Vec<Cookie<Response>> = context
-            .remove("response:queued_cookies")
+            .remove(QUEUED_COOKIES)
             .await
             .expect("The queued cookies key should exist in the context.");
 
@@ -29,9 +39,88 @@ impl<App: Send + Sync + 'static> Middleware<App> for QueueableCookies {
         };
 
         for cookie in cookies {
-            raw_response.headers_mut().set_cookie(cookie);
+            if !raw_response.headers().has_cookie(cookie.name()) {
+                raw_response.headers_mut().set_cookie(cookie);
+            }
         }
 
         response
     }
 }
+
+/// Attaches a [`CookieJar`] to the request and sends the
+/// changes made to it back to the client once the
+/// request has been handled.
+pub struct Cookies;
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for Cookies {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        let jar = CookieJar::from_request(&request);
+        let context = request.context().clone();
+        context.insert(COOKIE_JAR, jar.clone()).await;
+
+        let mut response = next(request).await;
+
+        let raw_response = match &mut response {
+            Ok(response) => response,
+            Err(response) => response,
+        };
+
+        jar.flush(raw_response);
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::http::middleware::QueueableCookies;
+    use crate::http::Cookie;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::routing::middleware::Handler;
+    use crate::routing::middleware::Middleware;
+
+    #[tokio::test]
+    async fn it_sends_queued_cookies() {
+        struct App;
+        let request = Request::builder().build(Arc::new(App));
+
+        let handler: Handler<App> = Arc::new(|request: Request<App>| {
+            Box::pin(async move {
+                request
+                    .queue_cookie(Cookie::builder("theme", "dark").build())
+                    .await?;
+
+                request
+                    .queue_cookie(Cookie::builder("locale", "en").build())
+                    .await?;
+
+                Response::ok()
+                    .queue_cookie(Cookie::builder("locale", "es").build())
+                    .into_ok()
+            })
+        });
+
+        let response = QueueableCookies.handle(handler, request).await.unwrap();
+
+        assert_eq!(
+            response.headers().get("Set-Cookie"),
+            Some(&vec!["locale=es".to_string(), "theme=dark".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn it_fails_to_queue_cookies_without_the_middleware() {
+        struct App;
+        let request = Request::builder().build(Arc::new(App));
+
+        assert!(request
+            .queue_cookie(Cookie::builder("theme", "dark").build())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+
+use crate::http::response::etag_for;
+use crate::http::Method;
+use crate::http::Request;
+use crate::http::Response;
+use crate::http::Result as HttpResult;
+use crate::http::StatusCode;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+
+/// The headers kept when a response is turned into a
+/// `304 Not Modified` one.
+const KEPT_HEADERS: [&str; 6] = [
+    "Cache-Control",
+    "Content-Location",
+    "Date",
+    "ETag",
+    "Expires",
+    "Vary",
+];
+
+/// Removes the weak prefix and the quotes of the given
+/// entity tag, for a weak comparison.
+fn opaque(tag: &str) -> &str {
+    tag.trim().trim_start_matches("W/").trim_matches('"')
+}
+
+/// Sets an `ETag` on the successful responses to `GET`
+/// and `HEAD` requests by hashing their body, and answers
+/// `304 Not Modified` when the `If-None-Match` header of
+/// the request matches it. Responses with an `ETag`
+/// already keep it and streamed responses are left as
+/// they are.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::middleware::ETag;
+///
+/// let etag = ETag;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ETag;
+
+impl ETag {
+    /// Determines if the given `If-None-Match` values match
+    /// the given entity tag.
+    fn matches(candidates: &[String], etag: &str) -> bool {
+        candidates
+            .iter()
+            .any(|candidate| candidate.trim() == "*" || opaque(candidate) == opaque(etag))
+    }
+
+    /// Returns the `304 Not Modified` version of the given
+    /// response.
+    fn not_modified(response: &Response) -> Response {
+        KEPT_HEADERS
+            .iter()
+            .filter_map(|header| {
+                response
+                    .headers()
+                    .get(header)
+                    .map(|values| (header, values.clone()))
+            })
+            .fold(
+                Response::builder().status(StatusCode::NOT_MODIFIED),
+                |builder, (header, values)| {
+                    values
+                        .into_iter()
+                        .fold(builder, |builder, value| builder.header(*header, value))
+                },
+            )
+            .build()
+    }
+}
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for ETag {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        if !matches!(*request.method(), Method::GET | Method::HEAD) {
+            return next(request).await;
+        }
+
+        let candidates = request.headers().values_split("If-None-Match");
+        let mut response = next(request).await?;
+
+        if !response.status().is_success() || response.is_streamed() {
+            return Ok(response);
+        }
+
+        let etag = match response.headers().first("ETag") {
+            Some(etag) => etag.to_string(),
+            None => {
+                let etag = format!(r#""{}""#, etag_for(response.bytes()));
+                response.headers_mut().insert("ETag", etag.as_str());
+
+                etag
+            }
+        };
+
+        match Self::matches(&candidates, &etag) {
+            true => Ok(Self::not_modified(&response)),
+            false => Ok(response),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::http::middleware::ETag;
+    use crate::http::Method;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::StatusCode;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn users(_request: Request<App>) -> Result {
+        Response::ok()
+            .header("Cache-Control", "no-cache")
+            .json(&["erik", "nil"])?
+            .into_ok()
+    }
+
+    async fn send(method: Method, if_none_match: Option<&str>) -> Response {
+        let router = Router::from_iter([Route::any("/users", users)])
+            .middleware(ETag)
+            .compile()
+            .unwrap();
+
+        let mut request = Request::builder()
+            .method(method)
+            .uri(Uri::from_static("/users"));
+
+        if let Some(etag) = if_none_match {
+            request = request.header("If-None-Match", etag);
+        }
+
+        router.handle(request.build(Arc::new(App))).await
+    }
+
+    #[tokio::test]
+    async fn it_can_set_etags() {
+        let response = send(Method::GET, None).await;
+        let etag = response.headers().first("ETag").unwrap();
+
+        assert_eq!(*response.status(), StatusCode::OK);
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert!(!send(Method::POST, None).await.headers().has("ETag"));
+    }
+
+    #[tokio::test]
+    async fn it_can_answer_not_modified() {
+        let etag = send(Method::GET, None)
+            .await
+            .headers()
+            .first("ETag")
+            .unwrap()
+            .to_string();
+
+        let matching = send(Method::GET, Some(&format!(r#""other", W/{etag}"#))).await;
+        let stale = send(Method::GET, Some(r#""other""#)).await;
+
+        assert_eq!(*matching.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(matching.body(), "");
+        matching
+            .assert_header_is("ETag", &etag)
+            .assert_header_is("Cache-Control", "no-cache");
+        assert_eq!(*stale.status(), StatusCode::OK);
+    }
+}
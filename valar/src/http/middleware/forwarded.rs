@@ -0,0 +1,437 @@
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use thiserror::Error as ThisError;
+
+use crate::http::Request;
+use crate::http::Result as HttpResult;
+use crate::http::Uri;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+
+#[derive(ThisError, Debug)]
+pub enum Error {
+    #[error("Invalid CIDR range: {0}")]
+    InvalidCidr(String),
+}
+
+/// A range of IP addresses, like `10.0.0.0/8`. A single
+/// address is a range with the full prefix length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Determines if the given address is in the range.
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = Error;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let error = || Error::InvalidCidr(range.to_string());
+        let (network, prefix) = match range.trim().split_once('/') {
+            Some((network, prefix)) => (network, Some(prefix)),
+            None => (range.trim(), None),
+        };
+
+        let network: IpAddr = network.parse().map_err(|_| error())?;
+        let max = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse().map_err(|_| error())?,
+            None => max,
+        };
+
+        match prefix <= max {
+            true => Ok(Self { network, prefix }),
+            false => Err(error()),
+        }
+    }
+}
+
+/// The forwarding information of a single proxy hop.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Hop {
+    client: Option<IpAddr>,
+    proto: Option<String>,
+    host: Option<String>,
+}
+
+/// Parses a node of the `Forwarded` header or of the
+/// `X-Forwarded-For` header into its IP address. Ports,
+/// quotes and IPv6 brackets are removed.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split_once(']')?.0.parse().ok();
+    }
+
+    node.parse()
+        .ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|address| address.ip()))
+}
+
+/// Rewrites the scheme, the host and the client IP of the
+/// requests coming from trusted proxies, using the
+/// `Forwarded` header or, when missing, the
+/// `X-Forwarded-For`, `X-Forwarded-Proto` and
+/// `X-Forwarded-Host` headers. Requests from any other
+/// peer are left as they are.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::middleware::ForwardedHeaders;
+///
+/// let forwarded = ForwardedHeaders::trusting(["10.0.0.0/8", "127.0.0.1"]).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ForwardedHeaders {
+    trusted: Vec<Cidr>,
+}
+
+impl ForwardedHeaders {
+    /// Creates a new middleware that trusts the proxies in
+    /// the given CIDR ranges.
+    pub fn trusting<I, S>(ranges: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let trusted = ranges
+            .into_iter()
+            .map(|range| range.as_ref().parse())
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { trusted })
+    }
+
+    /// Determines if the given address is a trusted proxy.
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted.iter().any(|range| range.contains(ip))
+    }
+
+    /// Returns the hops of the given request, from the
+    /// client to the closest proxy.
+    fn hops<App>(request: &Request<App>) -> Vec<Hop>
+    where
+        App: Send + Sync + 'static,
+    {
+        let headers = request.headers();
+
+        if headers.has("Forwarded") {
+            return headers
+                .values_split("Forwarded")
+                .iter()
+                .map(|element| {
+                    let mut hop = Hop::default();
+
+                    for pair in element.split(';') {
+                        let Some((key, value)) = pair.split_once('=') else {
+                            continue;
+                        };
+
+                        let value = value.trim().trim_matches('"');
+
+                        match key.trim().to_ascii_lowercase().as_str() {
+                            "for" => hop.client = parse_node(value),
+                            "proto" => hop.proto = Some(value.to_ascii_lowercase()),
+                            "host" => hop.host = Some(value.to_string()),
+                            _ => {}
+                        }
+                    }
+
+                    hop
+                })
+                .collect();
+        }
+
+        let mut hops: Vec<Hop> = headers
+            .values_split("X-Forwarded-For")
+            .iter()
+            .map(|node| Hop {
+                client: parse_node(node),
+                ..Default::default()
+            })
+            .collect();
+
+        if hops.is_empty() {
+            hops.push(Hop::default());
+        }
+
+        // Every proxy appends its own values, so the lists
+        // are aligned from the closest proxy backwards.
+        let protos = headers.values_split("X-Forwarded-Proto");
+        let hosts = headers.values_split("X-Forwarded-Host");
+
+        for (hop, proto) in hops.iter_mut().rev().zip(protos.iter().rev()) {
+            hop.proto = Some(proto.to_ascii_lowercase());
+        }
+
+        for (hop, host) in hops.iter_mut().rev().zip(hosts.iter().rev()) {
+            hop.host = Some(host.clone());
+        }
+
+        hops
+    }
+
+    /// Returns the hop of the client from the given hops:
+    /// the closest one whose address is not a trusted
+    /// proxy. The hops before it are set by the client and
+    /// can't be trusted.
+    fn client_hop<'a>(&self, hops: &'a [Hop]) -> Option<&'a Hop> {
+        hops.iter()
+            .rev()
+            .find(|hop| !hop.client.is_some_and(|ip| self.is_trusted(ip)))
+            .or(hops.first())
+    }
+
+    /// Returns the scheme the client used, as forwarded by
+    /// the closest untrusted hop. It is `None` unless the
+    /// request comes from a trusted proxy.
+    pub(crate) fn forwarded_proto<App>(&self, request: &Request<App>) -> Option<String>
+    where
+        App: Send + Sync + 'static,
+    {
+        let is_trusted = request
+            .remote_addr()
+            .is_some_and(|address| self.is_trusted(address.ip()));
+
+        if !is_trusted {
+            return None;
+        }
+
+        let hops = Self::hops(request);
+
+        self.client_hop(&hops)?.proto.clone()
+    }
+
+    /// Rewrites the URI of the given request with the
+    /// forwarded scheme and host.
+    fn rewrite_uri<App>(request: &mut Request<App>, proto: Option<&str>, host: Option<&str>)
+    where
+        App: Send + Sync + 'static,
+    {
+        let proto = proto.filter(|proto| matches!(*proto, "http" | "https"));
+
+        if proto.is_none() && host.is_none() {
+            return;
+        }
+
+        let uri = request.uri();
+        let scheme = proto.or(uri.scheme_str()).unwrap_or("http").to_string();
+        let authority = host
+            .map(str::to_string)
+            .or_else(|| uri.authority().map(|authority| authority.to_string()))
+            .or_else(|| request.headers().first("Host").map(str::to_string));
+        let path = uri
+            .path_and_query()
+            .map(|path| path.to_string())
+            .unwrap_or_else(|| "/".to_string());
+
+        let Some(authority) = authority else {
+            return;
+        };
+
+        let Ok(uri) = Uri::builder()
+            .scheme(scheme.as_str())
+            .authority(authority.as_str())
+            .path_and_query(path)
+            .build()
+        else {
+            return;
+        };
+
+        request.headers_mut().insert("Host", authority);
+        request.set_uri(uri);
+    }
+}
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for ForwardedHeaders {
+    async fn handle(&self, next: Handler<App>, mut request: Request<App>) -> HttpResult {
+        let is_trusted = request
+            .remote_addr()
+            .map(|address| self.is_trusted(address.ip()))
+            .unwrap_or(false);
+
+        if !is_trusted {
+            return next(request).await;
+        }
+
+        let hops = Self::hops(&request);
+
+        let Some(hop) = self.client_hop(&hops) else {
+            return next(request).await;
+        };
+
+        if let Some(ip) = hop.client {
+            request.set_client_ip(ip);
+        }
+
+        let (proto, host) = (hop.proto.clone(), hop.host.clone());
+
+        Self::rewrite_uri(&mut request, proto.as_deref(), host.as_deref());
+
+        next(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    use crate::http::middleware::forwarded::Cidr;
+    use crate::http::middleware::ForwardedHeaders;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn echo(request: Request<App>) -> Result {
+        let ip = request
+            .client_ip()
+            .map(|ip| ip.to_string())
+            .unwrap_or_default();
+
+        Response::ok()
+            .body(format!("{} {ip}", request.uri()))
+            .into_ok()
+    }
+
+    async fn request(peer: &str, headers: &[(&str, &str)]) -> String {
+        let router = Router::from_iter([Route::get("/users", echo)])
+            .middleware(ForwardedHeaders::trusting(["10.0.0.0/8", "::1"]).unwrap())
+            .compile()
+            .unwrap();
+
+        let request = Request::get(Uri::from_static("/users?page=2"))
+            .headers_iter(headers.iter().copied())
+            .remote_addr(peer.parse::<SocketAddr>().unwrap())
+            .build(Arc::new(App));
+
+        router.handle(request).await.body().to_string()
+    }
+
+    #[test]
+    fn it_can_match_cidr_ranges() {
+        let range: Cidr = "10.1.0.0/16".parse().unwrap();
+        let single: Cidr = "::1".parse().unwrap();
+
+        assert!(range.contains("10.1.200.3".parse::<IpAddr>().unwrap()));
+        assert!(!range.contains("10.2.0.1".parse::<IpAddr>().unwrap()));
+        assert!(single.contains("::1".parse::<IpAddr>().unwrap()));
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("nope".parse::<Cidr>().is_err());
+    }
+
+    #[tokio::test]
+    async fn it_can_use_the_x_forwarded_headers() {
+        let body = request(
+            "10.0.0.2:5000",
+            &[
+                ("X-Forwarded-For", "203.0.113.7, 10.0.0.9"),
+                ("X-Forwarded-Proto", "https, http"),
+                ("X-Forwarded-Host", "valar.rs, internal"),
+            ],
+        )
+        .await;
+
+        assert_eq!(body, "https://valar.rs/users?page=2 203.0.113.7");
+    }
+
+    #[tokio::test]
+    async fn it_ignores_the_values_set_by_the_client() {
+        let body = request(
+            "10.0.0.2:5000",
+            &[
+                ("X-Forwarded-For", "6.6.6.6, 203.0.113.7"),
+                ("X-Forwarded-Proto", "https, http"),
+                ("X-Forwarded-Host", "evil.test, valar.rs"),
+            ],
+        )
+        .await;
+
+        assert_eq!(body, "http://valar.rs/users?page=2 203.0.113.7");
+
+        let body = request(
+            "[::1]:5000",
+            &[(
+                "Forwarded",
+                "for=6.6.6.6;proto=https;host=evil.test, for=203.0.113.7;host=valar.rs",
+            )],
+        )
+        .await;
+
+        assert_eq!(body, "http://valar.rs/users?page=2 203.0.113.7");
+    }
+
+    #[tokio::test]
+    async fn it_can_use_the_forwarded_header() {
+        let body = request(
+            "[::1]:5000",
+            &[(
+                "Forwarded",
+                r#"for="[2001:db8::1]:4711";proto=https;host=valar.rs"#,
+            )],
+        )
+        .await;
+
+        assert_eq!(body, "https://valar.rs/users?page=2 2001:db8::1");
+    }
+
+    #[tokio::test]
+    async fn it_ignores_untrusted_peers() {
+        let body = request(
+            "198.51.100.1:5000",
+            &[
+                ("X-Forwarded-For", "203.0.113.7"),
+                ("X-Forwarded-Host", "evil.test"),
+            ],
+        )
+        .await;
+
+        assert_eq!(body, "/users?page=2 198.51.100.1");
+    }
+
+    #[tokio::test]
+    async fn it_skips_trusted_proxies_in_the_chain() {
+        let body = request(
+            "10.0.0.2:5000",
+            &[("X-Forwarded-For", "6.6.6.6, 203.0.113.7, 10.0.0.5")],
+        )
+        .await;
+
+        assert_eq!(body, "/users?page=2 203.0.113.7");
+    }
+}
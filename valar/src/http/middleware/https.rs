@@ -0,0 +1,282 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::http::middleware::ForwardedHeaders;
+use crate::http::Request;
+use crate::http::Response;
+use crate::http::Result as HttpResult;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+
+/// The `Strict-Transport-Security` policy sent with the
+/// secure responses.
+#[derive(Debug, Clone, Copy)]
+struct Hsts {
+    max_age: Duration,
+    include_subdomains: bool,
+    preload: bool,
+}
+
+impl Hsts {
+    /// Returns the value of the header.
+    fn header(&self) -> String {
+        let mut header = format!("max-age={}", self.max_age.as_secs());
+
+        if self.include_subdomains {
+            header.push_str("; includeSubDomains");
+        }
+
+        if self.preload {
+            header.push_str("; preload");
+        }
+
+        header
+    }
+}
+
+/// Redirects the plain HTTP requests to their HTTPS
+/// equivalent with a `301 Moved Permanently` response and,
+/// optionally, sends a `Strict-Transport-Security` header
+/// with the secure responses.
+///
+/// Behind a proxy that terminates TLS, either use the
+/// `ForwardedHeaders` middleware before this one or call
+/// `behind_proxy` so the forwarded scheme is honored.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use valar::http::middleware::ForwardedHeaders;
+/// use valar::http::middleware::HttpsRedirect;
+///
+/// let proxies = ForwardedHeaders::trusting(["10.0.0.0/8"]).unwrap();
+/// let https = HttpsRedirect::new()
+///     .behind_proxy(proxies)
+///     .hsts(Duration::from_secs(60 * 60 * 24 * 365))
+///     .include_subdomains();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HttpsRedirect {
+    proxies: Option<ForwardedHeaders>,
+    hsts: Option<Hsts>,
+}
+
+impl HttpsRedirect {
+    /// Creates a new middleware that only redirects.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Honors the scheme in the `Forwarded` and
+    /// `X-Forwarded-Proto` headers of the requests coming
+    /// from the given trusted proxies. Like with
+    /// [`ForwardedHeaders`], only the value appended by the
+    /// closest trusted proxy counts, not the one sent by
+    /// the client.
+    pub fn behind_proxy(mut self, proxies: ForwardedHeaders) -> Self {
+        self.proxies = Some(proxies);
+
+        self
+    }
+
+    /// Sends a `Strict-Transport-Security` header with the
+    /// given max age on the secure responses.
+    pub fn hsts(mut self, max_age: Duration) -> Self {
+        self.hsts = Some(Hsts {
+            max_age,
+            include_subdomains: false,
+            preload: false,
+        });
+
+        self
+    }
+
+    /// Applies the HSTS policy to the subdomains too. Does
+    /// nothing unless `hsts` is used.
+    pub fn include_subdomains(mut self) -> Self {
+        if let Some(hsts) = &mut self.hsts {
+            hsts.include_subdomains = true;
+        }
+
+        self
+    }
+
+    /// Allows the domain in the browsers HSTS preload
+    /// lists. Does nothing unless `hsts` is used.
+    pub fn preload(mut self) -> Self {
+        if let Some(hsts) = &mut self.hsts {
+            hsts.preload = true;
+        }
+
+        self
+    }
+
+    /// Determines if the given request was made over
+    /// HTTPS.
+    fn is_secure<App>(&self, request: &Request<App>) -> bool
+    where
+        App: Send + Sync + 'static,
+    {
+        if request.uri().scheme_str() == Some("https") {
+            return true;
+        }
+
+        self.proxies
+            .as_ref()
+            .and_then(|proxies| proxies.forwarded_proto(request))
+            .is_some_and(|scheme| scheme == "https")
+    }
+
+    /// Returns the HTTPS URL of the given request. The
+    /// default HTTP port is dropped from the host.
+    fn secure_url<App>(request: &Request<App>) -> Option<String>
+    where
+        App: Send + Sync + 'static,
+    {
+        let uri = request.uri();
+
+        let host = uri
+            .authority()
+            .map(|authority| authority.to_string())
+            .or_else(|| request.headers().first("Host").map(str::to_string))?;
+
+        let host = host.strip_suffix(":80").unwrap_or(&host);
+
+        let path = uri
+            .path_and_query()
+            .map(|path| path.to_string())
+            .unwrap_or_else(|| "/".to_string());
+
+        Some(format!("https://{host}{path}"))
+    }
+}
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for HttpsRedirect {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        if !self.is_secure(&request) {
+            return match Self::secure_url(&request) {
+                Some(url) => Response::moved_permanently(url).into_ok(),
+                None => Response::bad_request().with_canonical_message().into_err(),
+            };
+        }
+
+        let mut response = next(request).await;
+
+        if let Some(hsts) = &self.hsts {
+            let raw_response = match &mut response {
+                Ok(response) => response,
+                Err(response) => response,
+            };
+
+            raw_response
+                .headers_mut()
+                .insert("Strict-Transport-Security", hsts.header());
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::http::middleware::ForwardedHeaders;
+    use crate::http::middleware::HttpsRedirect;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::StatusCode;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn home(_request: Request<App>) -> Result {
+        Response::ok().into_ok()
+    }
+
+    #[tokio::test]
+    async fn it_can_redirect_to_https() {
+        let router = Router::from_iter([Route::get("/users", home)])
+            .middleware(HttpsRedirect::new())
+            .compile()
+            .unwrap();
+
+        let response = router
+            .handle(
+                Request::get(Uri::from_static("/users?page=2"))
+                    .header("Host", "example.com:80")
+                    .header("X-Forwarded-Proto", "https")
+                    .build(Arc::new(App)),
+            )
+            .await;
+
+        assert_eq!(*response.status(), StatusCode::MOVED_PERMANENTLY);
+        response.assert_header_is("Location", "https://example.com/users?page=2");
+    }
+
+    #[tokio::test]
+    async fn it_can_honor_proxies_and_set_hsts() {
+        let router = Router::from_iter([Route::get("/users", home)])
+            .middleware(
+                HttpsRedirect::new()
+                    .behind_proxy(ForwardedHeaders::trusting(["10.0.0.0/8"]).unwrap())
+                    .hsts(Duration::from_secs(3600))
+                    .include_subdomains(),
+            )
+            .compile()
+            .unwrap();
+
+        let request = |peer: &str, proto: &str| {
+            Request::get(Uri::from_static("/users"))
+                .header("Host", "example.com")
+                .header("X-Forwarded-Proto", proto)
+                .remote_addr(peer.parse::<SocketAddr>().unwrap())
+                .build(Arc::new(App))
+        };
+
+        let secure = router.handle(request("10.0.0.2:5000", "https")).await;
+        let plain = router.handle(request("10.0.0.2:5000", "http")).await;
+        let untrusted = router.handle(request("198.51.100.1:5000", "https")).await;
+
+        secure.assert_ok().assert_header_is(
+            "Strict-Transport-Security",
+            "max-age=3600; includeSubDomains",
+        );
+        plain.assert_header_is("Location", "https://example.com/users");
+        untrusted.assert_header_is("Location", "https://example.com/users");
+    }
+
+    #[tokio::test]
+    async fn it_ignores_the_scheme_sent_by_the_client() {
+        let router = Router::from_iter([Route::get("/users", home)])
+            .middleware(
+                HttpsRedirect::new()
+                    .behind_proxy(ForwardedHeaders::trusting(["10.0.0.0/8"]).unwrap()),
+            )
+            .compile()
+            .unwrap();
+
+        let response = router
+            .handle(
+                Request::get(Uri::from_static("/users"))
+                    .header("Host", "example.com")
+                    .header("X-Forwarded-For", "203.0.113.7")
+                    .header("X-Forwarded-Proto", "https, http")
+                    .remote_addr("10.0.0.2:5000".parse::<SocketAddr>().unwrap())
+                    .build(Arc::new(App)),
+            )
+            .await;
+
+        assert_eq!(*response.status(), StatusCode::MOVED_PERMANENTLY);
+        response.assert_header_is("Location", "https://example.com/users");
+    }
+}
@@ -0,0 +1,199 @@
+use async_trait::async_trait;
+
+use crate::http::Request;
+use crate::http::Result as HttpResult;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+
+/// The context key where the locale is kept.
+pub(crate) const LOCALE: &str = "request:locale";
+
+/// Returns the primary language subtag of the given
+/// locale, like `pt` for `pt-BR`.
+fn primary(locale: &str) -> &str {
+    locale.split(['-', '_']).next().unwrap_or(locale)
+}
+
+/// Resolves the locale of every request from, in order, a
+/// query parameter, a cookie and the `Accept-Language`
+/// header, and makes it available with
+/// `request.locale()`. Only the supported locales are
+/// picked, falling back to the first one.
+///
+/// A requested locale matches a supported one with the
+/// same primary language when there is no exact match, so
+/// `es-MX` resolves to `es` and `pt` to `pt-BR`.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::middleware::Locale;
+///
+/// let locale = Locale::supporting(["en", "es", "pt-BR"])
+///     .query("lang")
+///     .cookie("lang");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Locale {
+    supported: Vec<String>,
+    query: String,
+    cookie: String,
+}
+
+impl Locale {
+    /// Creates a new locale middleware with the given
+    /// supported locales. The first one is the default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no locales are given.
+    pub fn supporting<I, L>(locales: I) -> Self
+    where
+        I: IntoIterator<Item = L>,
+        L: Into<String>,
+    {
+        let supported: Vec<String> = locales.into_iter().map(Into::into).collect();
+
+        assert!(
+            !supported.is_empty(),
+            "At least one locale must be supported"
+        );
+
+        Self {
+            supported,
+            query: "locale".to_string(),
+            cookie: "locale".to_string(),
+        }
+    }
+
+    /// Sets the query parameter that picks the locale.
+    /// Defaults to `locale`.
+    pub fn query<Q>(mut self, name: Q) -> Self
+    where
+        Q: Into<String>,
+    {
+        self.query = name.into();
+
+        self
+    }
+
+    /// Sets the cookie that picks the locale. Defaults to
+    /// `locale`.
+    pub fn cookie<C>(mut self, name: C) -> Self
+    where
+        C: Into<String>,
+    {
+        self.cookie = name.into();
+
+        self
+    }
+
+    /// Returns the supported locale matching the given
+    /// one, if any.
+    fn negotiate(&self, locale: &str) -> Option<&String> {
+        let locale = locale.trim();
+
+        self.supported
+            .iter()
+            .find(|supported| supported.eq_ignore_ascii_case(locale))
+            .or_else(|| {
+                self.supported
+                    .iter()
+                    .find(|supported| primary(supported).eq_ignore_ascii_case(primary(locale)))
+            })
+    }
+
+    /// Returns the locale of the given request.
+    fn resolve<App>(&self, request: &Request<App>) -> String
+    where
+        App: Send + Sync + 'static,
+    {
+        let query = request.query_parameters().get(&self.query).cloned();
+        let cookie = request
+            .headers()
+            .cookie(&self.cookie)
+            .map(|cookie| cookie.value().to_string());
+
+        let explicit = query
+            .iter()
+            .chain(cookie.iter())
+            .find_map(|locale| self.negotiate(locale));
+
+        let accepted = || {
+            request
+                .headers()
+                .qualified("Accept-Language")
+                .iter()
+                .find_map(|(locale, _)| self.negotiate(locale))
+        };
+
+        explicit
+            .or_else(accepted)
+            .unwrap_or(&self.supported[0])
+            .clone()
+    }
+}
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for Locale {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        let locale = self.resolve(&request);
+
+        request.context().insert(LOCALE, locale).await;
+
+        next(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::http::middleware::Locale;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn greet(request: Request<App>) -> Result {
+        let locale = request.locale().await.unwrap_or_default();
+
+        Response::ok().body(locale).into_ok()
+    }
+
+    #[tokio::test]
+    async fn it_can_resolve_locales() {
+        let router = Router::from_iter([Route::get("/", greet)])
+            .middleware(Locale::supporting(["en", "es", "pt-BR"]))
+            .compile()
+            .unwrap();
+
+        let locale = |uri: &'static str, cookie: Option<&str>, language: Option<&str>| {
+            let mut request = Request::get(Uri::from_static(uri));
+
+            if let Some(cookie) = cookie {
+                request = request.header("Cookie", format!("locale={cookie}"));
+            }
+
+            if let Some(language) = language {
+                request = request.header("Accept-Language", language);
+            }
+
+            let request = request.build(Arc::new(App));
+
+            async { router.handle(request).await.body().to_string() }
+        };
+
+        assert_eq!(locale("/?locale=es", Some("en"), None).await, "es");
+        assert_eq!(locale("/?locale=fr", Some("pt"), None).await, "pt-BR");
+        assert_eq!(
+            locale("/", None, Some("fr, es-MX;q=0.9, en;q=0.8")).await,
+            "es"
+        );
+        assert_eq!(locale("/", None, Some("fr")).await, "en");
+    }
+}
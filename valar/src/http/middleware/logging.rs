@@ -0,0 +1,209 @@
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::http::Request;
+use crate::http::Response;
+use crate::http::Result as HttpResult;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+
+/// The output format of the `Logging` middleware.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Colored, fixed width lines meant to be read in a
+    /// terminal.
+    #[default]
+    Human,
+
+    /// One JSON object per line, meant to be ingested by
+    /// log aggregators.
+    Json,
+}
+
+/// A single logged request.
+#[derive(Debug, Clone, Serialize)]
+struct Record {
+    method: String,
+    path: String,
+    status: u16,
+    latency_ms: f64,
+    size: Option<u64>,
+}
+
+/// Logs the method, path, status, latency and body size
+/// of every response to the standard output.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::middleware::Logging;
+///
+/// let logging = Logging::json().except("/health").except("/assets/*");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Logging {
+    format: Format,
+    except: Vec<String>,
+}
+
+impl Logging {
+    /// Creates a new logging middleware with the human
+    /// format.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new logging middleware with the human
+    /// format.
+    pub fn human() -> Self {
+        Self::new().format(Format::Human)
+    }
+
+    /// Creates a new logging middleware with the JSON
+    /// format.
+    pub fn json() -> Self {
+        Self::new().format(Format::Json)
+    }
+
+    /// Sets the output format.
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+
+        self
+    }
+
+    /// Skips logging the requests to the given path. A
+    /// trailing `*` skips all the paths with that prefix.
+    pub fn except<P>(mut self, path: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.except.push(path.into());
+
+        self
+    }
+
+    /// Determines if the given path must not be logged.
+    fn is_excluded(&self, path: &str) -> bool {
+        self.except
+            .iter()
+            .any(|except| match except.strip_suffix('*') {
+                Some(prefix) => path.starts_with(prefix),
+                None => path == except,
+            })
+    }
+
+    /// Formats the given record in the JSON format.
+    fn json_line(record: &Record) -> String {
+        serde_json::to_string(record).unwrap_or_default()
+    }
+
+    /// Formats the given record in the human format.
+    fn human_line(prefix: &str, response: &Response, record: &Record) -> String {
+        let size = match record.size {
+            Some(size) => format!("{size}B"),
+            None => "stream".to_string(),
+        };
+
+        format!(
+            "{} {} {} {}",
+            prefix,
+            "•".dimmed(),
+            response.to_fixed_string(),
+            format!("{:.2}ms {size}", record.latency_ms).dimmed()
+        )
+    }
+}
+
+/// Returns the size of the response body, if known.
+fn body_size(response: &Response) -> Option<u64> {
+    let length = response
+        .headers()
+        .first("Content-Length")
+        .and_then(|length| length.parse().ok());
+
+    match response.is_streamed() {
+        true => length,
+        false => length.or(Some(response.bytes().len() as u64)),
+    }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for Logging {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        if self.is_excluded(request.uri().path()) {
+            return next(request).await;
+        }
+
+        let prefix = request.to_fixed_string();
+        let method = request.method().to_string();
+        let path = request.uri().path().to_string();
+        let start = Instant::now();
+
+        let result = next(request).await;
+
+        let response = match &result {
+            Ok(response) => response,
+            Err(response) => response,
+        };
+
+        let record = Record {
+            method,
+            path,
+            status: response.status().as_u16(),
+            latency_ms: milliseconds(start.elapsed()),
+            size: body_size(response),
+        };
+
+        match self.format {
+            Format::Human => println!("{}", Self::human_line(&prefix, response, &record)),
+            Format::Json => println!("{}", Self::json_line(&record)),
+        };
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::middleware::logging::body_size;
+    use crate::http::middleware::logging::Logging;
+    use crate::http::middleware::logging::Record;
+    use crate::http::Response;
+
+    #[test]
+    fn it_can_exclude_paths() {
+        let logging = Logging::json().except("/health").except("/assets/*");
+
+        assert!(logging.is_excluded("/health"));
+        assert!(logging.is_excluded("/assets/app.css"));
+        assert!(!logging.is_excluded("/healthz"));
+        assert!(!logging.is_excluded("/users"));
+    }
+
+    #[test]
+    fn it_can_format_json_records() {
+        let response = Response::ok().body("hello").build();
+
+        let record = Record {
+            method: "GET".to_string(),
+            path: "/users".to_string(),
+            status: 200,
+            latency_ms: 1.5,
+            size: body_size(&response),
+        };
+
+        assert_eq!(
+            Logging::json_line(&record),
+            r#"{"method":"GET","path":"/users","status":200,"latency_ms":1.5,"size":5}"#
+        );
+    }
+}
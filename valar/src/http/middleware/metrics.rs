@@ -0,0 +1,372 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::http::Request;
+use crate::http::Result as HttpResult;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+
+/// The default upper bounds of the duration histogram
+/// buckets, in seconds.
+const DEFAULT_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// The labels of a request: its method and its route.
+type RouteLabels = (String, String);
+
+/// The labels of a response: its method, its route and
+/// its status code.
+type ResponseLabels = (String, String, u16);
+
+/// The duration histogram of a set of labels.
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// Escapes the given label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Stores the request metrics recorded by the `Metrics`
+/// middleware and renders them in the Prometheus text
+/// format.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use valar::http::middleware::Metrics;
+/// use valar::http::middleware::MetricsRegistry;
+/// use valar::http::Request;
+/// use valar::http::Response;
+/// use valar::http::Result;
+///
+/// struct App {
+///     metrics: Arc<MetricsRegistry>,
+/// }
+///
+/// async fn metrics(request: Request<App>) -> Result {
+///     Response::ok()
+///         .header("Content-Type", "text/plain; version=0.0.4")
+///         .body(request.app().metrics.render())
+///         .into_ok()
+/// }
+///
+/// let registry = Arc::new(MetricsRegistry::new());
+/// let middleware = Metrics::with_registry(registry.clone());
+/// ```
+#[derive(Debug)]
+pub struct Registry {
+    buckets: Vec<f64>,
+    durations: Mutex<BTreeMap<ResponseLabels, Histogram>>,
+    in_flight: Mutex<BTreeMap<RouteLabels, i64>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::with_buckets(DEFAULT_BUCKETS)
+    }
+}
+
+impl Registry {
+    /// Creates a new registry with the default histogram
+    /// buckets, from 5ms to 10s.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new registry with the given histogram
+    /// buckets, in seconds.
+    pub fn with_buckets<B>(buckets: B) -> Self
+    where
+        B: Into<Vec<f64>>,
+    {
+        let mut buckets: Vec<f64> = buckets.into();
+        buckets.sort_by(f64::total_cmp);
+
+        Self {
+            buckets,
+            durations: Mutex::new(BTreeMap::new()),
+            in_flight: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the number of requests handled with the
+    /// given method, route and status code.
+    pub fn requests(&self, method: &str, route: &str, status: u16) -> u64 {
+        let key = (method.to_string(), route.to_string(), status);
+
+        self.durations
+            .lock()
+            .unwrap()
+            .get(&key)
+            .map(|histogram| histogram.count)
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of requests with the given method
+    /// and route being handled.
+    pub fn in_flight(&self, method: &str, route: &str) -> i64 {
+        let key = (method.to_string(), route.to_string());
+
+        self.in_flight
+            .lock()
+            .unwrap()
+            .get(&key)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Adds the given amount to the in-flight gauge of the
+    /// given labels.
+    fn track(&self, labels: &RouteLabels, amount: i64) {
+        *self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(labels.clone())
+            .or_default() += amount;
+    }
+
+    /// Records a handled request.
+    fn observe(&self, labels: ResponseLabels, seconds: f64) {
+        let mut durations = self.durations.lock().unwrap();
+        let histogram = durations.entry(labels).or_insert_with(|| Histogram {
+            buckets: vec![0; self.buckets.len()],
+            ..Default::default()
+        });
+
+        for (bucket, bound) in histogram.buckets.iter_mut().zip(&self.buckets) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+
+    /// Renders the metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let durations = self.durations.lock().unwrap().clone();
+        let in_flight = self.in_flight.lock().unwrap().clone();
+        let mut output = String::new();
+
+        output.push_str("# HELP http_requests_total The number of HTTP requests handled.\n");
+        output.push_str("# TYPE http_requests_total counter\n");
+
+        for ((method, route, status), histogram) in &durations {
+            let _ = writeln!(
+                output,
+                r#"http_requests_total{{method="{}",route="{}",status="{status}"}} {}"#,
+                escape(method),
+                escape(route),
+                histogram.count
+            );
+        }
+
+        output
+            .push_str("# HELP http_request_duration_seconds The duration of the HTTP requests.\n");
+        output.push_str("# TYPE http_request_duration_seconds histogram\n");
+
+        for ((method, route, status), histogram) in &durations {
+            let labels = format!(
+                r#"method="{}",route="{}",status="{status}""#,
+                escape(method),
+                escape(route)
+            );
+
+            for (count, bound) in histogram.buckets.iter().zip(&self.buckets) {
+                let _ = writeln!(
+                    output,
+                    r#"http_request_duration_seconds_bucket{{{labels},le="{bound}"}} {count}"#
+                );
+            }
+
+            let _ = writeln!(
+                output,
+                r#"http_request_duration_seconds_bucket{{{labels},le="+Inf"}} {}"#,
+                histogram.count
+            );
+            let _ = writeln!(
+                output,
+                "http_request_duration_seconds_sum{{{labels}}} {}",
+                histogram.sum
+            );
+            let _ = writeln!(
+                output,
+                "http_request_duration_seconds_count{{{labels}}} {}",
+                histogram.count
+            );
+        }
+
+        output.push_str(
+            "# HELP http_requests_in_flight The number of HTTP requests being handled.\n",
+        );
+        output.push_str("# TYPE http_requests_in_flight gauge\n");
+
+        for ((method, route), count) in &in_flight {
+            let _ = writeln!(
+                output,
+                r#"http_requests_in_flight{{method="{}",route="{}"}} {count}"#,
+                escape(method),
+                escape(route)
+            );
+        }
+
+        output
+    }
+}
+
+/// Decrements the in-flight gauge when dropped, so
+/// cancelled or panicking requests are not counted forever.
+struct InFlight<'a> {
+    registry: &'a Registry,
+    labels: RouteLabels,
+}
+
+impl<'a> InFlight<'a> {
+    fn new(registry: &'a Registry, labels: RouteLabels) -> Self {
+        registry.track(&labels, 1);
+
+        Self { registry, labels }
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.registry.track(&self.labels, -1);
+    }
+}
+
+/// Records the number of requests, their duration and the
+/// number of requests being handled, labeled by method,
+/// route and status code. The route label is the name of
+/// the matched route or, when it has none, its pattern,
+/// so paths with parameters share their metrics.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::middleware::Metrics;
+///
+/// let metrics = Metrics::new();
+/// let registry = metrics.registry();
+///
+/// println!("{}", registry.render());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    registry: Arc<Registry>,
+}
+
+impl Metrics {
+    /// Creates a new metrics middleware with its own
+    /// registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new metrics middleware that records into
+    /// the given registry.
+    pub fn with_registry(registry: Arc<Registry>) -> Self {
+        Self { registry }
+    }
+
+    /// Returns the registry the metrics are recorded into.
+    pub fn registry(&self) -> Arc<Registry> {
+        self.registry.clone()
+    }
+}
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for Metrics {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        let method = request.method().to_string();
+        let route = request
+            .route()
+            .map(|route| route.name().unwrap_or(route.path()).to_string())
+            .unwrap_or_default();
+
+        let start = Instant::now();
+        let in_flight = InFlight::new(&self.registry, (method.clone(), route.clone()));
+
+        let response = next(request).await;
+
+        drop(in_flight);
+
+        let status = match &response {
+            Ok(response) => response.status().as_u16(),
+            Err(response) => response.status().as_u16(),
+        };
+
+        self.registry
+            .observe((method, route, status), start.elapsed().as_secs_f64());
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::http::middleware::Metrics;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn handler(_request: Request<App>) -> Result {
+        Response::ok().into_ok()
+    }
+
+    #[tokio::test]
+    async fn it_can_record_metrics() {
+        let metrics = Metrics::new();
+        let registry = metrics.registry();
+
+        let router = Router::from_iter([
+            Route::get("/users/:id", handler).name("users.show"),
+            Route::get("/posts/:id", handler),
+        ])
+        .middleware(metrics)
+        .compile()
+        .unwrap();
+
+        for uri in ["/users/1", "/users/2", "/posts/1"] {
+            router
+                .handle(Request::get(Uri::from_static(uri)).build(Arc::new(App)))
+                .await;
+        }
+
+        let output = registry.render();
+
+        assert_eq!(registry.requests("GET", "users.show", 200), 2);
+        assert_eq!(registry.requests("GET", "/posts/:id", 200), 1);
+        assert_eq!(registry.in_flight("GET", "users.show"), 0);
+        assert!(output
+            .contains(r#"http_requests_total{method="GET",route="users.show",status="200"} 2"#));
+        assert!(output.contains(
+            r#"http_request_duration_seconds_bucket{method="GET",route="users.show",status="200",le="+Inf"} 2"#
+        ));
+    }
+}
@@ -0,0 +1,149 @@
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::Once;
+
+use async_trait::async_trait;
+use futures_util::FutureExt;
+use log::error;
+
+use crate::debug;
+use crate::http::error_page::ErrorReport;
+use crate::http::Request;
+use crate::http::Response;
+use crate::http::Result as HttpResult;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+
+thread_local! {
+    /// The backtrace of the last panic of the thread.
+    static BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+static HOOK: Once = Once::new();
+
+/// Installs a panic hook that keeps the backtrace of the
+/// panic around, so it can be logged once the panic is
+/// caught. The previous hook still runs.
+fn install_hook() {
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            BACKTRACE.with(|backtrace| {
+                *backtrace.borrow_mut() = Some(Backtrace::force_capture());
+            });
+
+            previous(info);
+        }));
+    });
+}
+
+/// Returns the message of the given panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message,
+            None => "Box<dyn Any>",
+        },
+    }
+}
+
+/// Turns the panics of the handlers into `500 Internal
+/// Server Error` responses and logs them with their
+/// backtrace, instead of dropping the connection. The
+/// panic message is only sent to the client in debug
+/// mode.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::middleware::Recover;
+///
+/// let recover = Recover;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Recover;
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for Recover {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        install_hook();
+
+        let method = request.method().clone();
+        let path = request.uri().path().to_string();
+
+        let payload = match AssertUnwindSafe(next(request)).catch_unwind().await {
+            Ok(result) => return result,
+            Err(payload) => payload,
+        };
+
+        let message = panic_message(payload.as_ref());
+        let backtrace = BACKTRACE
+            .with(|backtrace| backtrace.borrow_mut().take())
+            .map(|backtrace| backtrace.to_string())
+            .unwrap_or_default();
+
+        error!("Handler of {method} {path} panicked: {message}\n{backtrace}");
+
+        let response = Response::internal_server_error();
+
+        match debug::enabled() {
+            true => Err(response
+                .message(message)
+                .build()
+                .with_error_report(ErrorReport::new(message, backtrace))),
+            false => response.with_canonical_message().into_err(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::http::middleware::Recover;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::StatusCode;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn broken(_request: Request<App>) -> Result {
+        panic!("Something went wrong");
+    }
+
+    async fn working(_request: Request<App>) -> Result {
+        Response::ok().body("ok").into_ok()
+    }
+
+    #[tokio::test]
+    async fn it_can_recover_from_panics() {
+        let router = Router::from_iter([
+            Route::get("/broken", broken),
+            Route::get("/working", working),
+        ])
+        .middleware(Recover)
+        .compile()
+        .unwrap();
+
+        let app = Arc::new(App);
+        let broken = router
+            .handle(Request::get(Uri::from_static("/broken")).build(app.clone()))
+            .await;
+        let working = router
+            .handle(Request::get(Uri::from_static("/working")).build(app))
+            .await;
+
+        assert_eq!(*broken.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(broken.body(), "Internal Server Error");
+        assert_eq!(working.body(), "ok");
+    }
+}
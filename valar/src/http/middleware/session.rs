@@ -1,41 +1,221 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use async_trait::async_trait;
 use uuid::Uuid;
 
+use crate::http::cookie::SameSite;
+use crate::http::session::Record;
+use crate::http::session::Session as SessionData;
+use crate::http::session::SessionStore;
+use crate::http::session::SESSION;
 use crate::http::Cookie;
 use crate::http::Request;
-use crate::http::Result;
+use crate::http::Response;
+use crate::http::Result as HttpResult;
 use crate::routing::middleware::Handler;
 use crate::routing::middleware::Middleware;
 
-pub struct Session;
+/// Starts a session for every request. The session is
+/// loaded from the given store before the handler runs,
+/// made available with `request.session()` and persisted
+/// after the handler if it is not empty.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use valar::http::cookie::SameSite;
+/// use valar::http::middleware::Session;
+/// use valar::http::session::CacheStore;
+/// use valar::services::cache::MemoryCache;
+///
+/// # async fn example() {
+/// let cache = Arc::new(MemoryCache::new(Duration::from_secs(60)));
+///
+/// let session = Session::new(CacheStore::new(cache))
+///     .cookie("app_session")
+///     .lifetime(Duration::from_secs(60 * 60))
+///     .same_site(SameSite::Strict);
+/// # }
+/// ```
+pub struct Session {
+    store: Arc<dyn SessionStore>,
+    cookie: String,
+    lifetime: Duration,
+    same_site: SameSite,
+}
 
-#[async_trait]
-impl<App: Send + Sync + 'static> Middleware<App> for Session {
-    async fn handle(&self, next: Handler<App>, mut request: Request<App>) -> Result {
-        if request.headers().has_cookie("session_uuid") {
-            return next(request).await;
+impl Session {
+    /// Creates a new session middleware with the given
+    /// store. The cookie is named `session`, lasts two
+    /// hours and uses `SameSite=Lax`.
+    pub fn new<S>(store: S) -> Self
+    where
+        S: SessionStore,
+    {
+        Self {
+            store: Arc::new(store),
+            cookie: "session".to_string(),
+            lifetime: Duration::from_secs(2 * 60 * 60),
+            same_site: SameSite::Lax,
         }
+    }
+
+    /// Sets the name of the session cookie.
+    pub fn cookie<N>(mut self, name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        self.cookie = name.into();
 
-        let uuid = Uuid::now_v7();
-        let cookie =
-            Cookie::<Request<App>>::builder("session_uuid", uuid.as_hyphenated().to_string())
-                .build();
+        self
+    }
 
-        request.headers_mut().set_cookie(cookie);
+    /// Sets how long the session lasts since the last
+    /// request that used it.
+    pub fn lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
 
-        let mut response = next(request).await;
+        self
+    }
+
+    /// Sets the `SameSite` attribute of the session cookie.
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+
+        self
+    }
 
-        let cookie = Cookie::builder("session_uuid", uuid.as_hyphenated().to_string())
+    /// Loads the session of the given request or starts a
+    /// new one.
+    async fn load<App>(&self, request: &Request<App>) -> Result<Record, Response>
+    where
+        App: Send + Sync + 'static,
+    {
+        let token = request
+            .headers()
+            .cookie(&self.cookie)
+            .map(|cookie| cookie.value().to_string());
+
+        let record = match token {
+            Some(token) => self.store.load(&token).await?,
+            None => None,
+        };
+
+        Ok(record.unwrap_or_else(|| Record {
+            id: Uuid::now_v7().as_hyphenated().to_string(),
+            values: Default::default(),
+        }))
+    }
+
+    /// Persists the given session and sends its cookie with
+    /// the given response.
+    async fn save(&self, session: &SessionData, response: &mut Response) -> Result<(), Response> {
+        let record = session.record();
+
+        if record.values.is_empty() && !session.is_changed() {
+            return Ok(());
+        }
+
+        let token = self.store.save(&record, self.lifetime).await?;
+
+        let cookie = Cookie::builder(self.cookie.as_str(), token)
+            .path(Some("/"))
             .http_only(true)
+            .same_site(Some(self.same_site))
+            .max_age(Some(self.lifetime.as_secs()))
             .build();
 
+        response.headers_mut().set_cookie(cookie);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for Session {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        let record = self.load(&request).await?;
+
+        let session = SessionData::new(record);
+        let context = request.context().clone();
+        context.insert(SESSION, session.clone()).await;
+
+        let mut response = next(request).await;
+
         let raw_response = match &mut response {
             Ok(response) => response,
             Err(response) => response,
         };
 
-        raw_response.headers_mut().set_cookie(cookie);
+        self.save(&session, raw_response).await?;
 
         response
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::http::middleware::Session;
+    use crate::http::session::CacheStore;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+    use crate::services::cache::MemoryCache;
+
+    struct App;
+
+    async fn visit(request: Request<App>) -> Result {
+        let session = request.session().await.unwrap();
+        let visits: u64 = session.get("visits").unwrap_or(0) + 1;
+
+        session.insert("visits", visits)?;
+
+        Response::ok().body(visits.to_string()).into_ok()
+    }
+
+    async fn peek(_request: Request<App>) -> Result {
+        Response::ok().into_ok()
+    }
+
+    #[tokio::test]
+    async fn it_can_persist_sessions() {
+        let cache = Arc::new(MemoryCache::new(Duration::from_secs(60)));
+        let router = Router::from_iter([Route::get("/visit", visit), Route::get("/peek", peek)])
+            .middleware(Session::new(CacheStore::new(cache)).cookie("app_session"))
+            .compile()
+            .unwrap();
+
+        let app = Arc::new(App);
+        let first = router
+            .handle(Request::get(Uri::from_static("/visit")).build(app.clone()))
+            .await;
+        let cookie = first.headers().cookie("app_session").unwrap();
+
+        assert_eq!(first.body(), "1");
+        assert!(cookie.http_only());
+
+        let second = router
+            .handle(
+                Request::get(Uri::from_static("/visit"))
+                    .header("Cookie", format!("app_session={}", cookie.value()))
+                    .build(app.clone()),
+            )
+            .await;
+        let empty = router
+            .handle(Request::get(Uri::from_static("/peek")).build(app))
+            .await;
+
+        assert_eq!(second.body(), "2");
+        assert!(!empty.headers().has_cookie("app_session"));
+    }
+}
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Display;
+use std::future::Future;
+use std::net::IpAddr;
+use std::net::SocketAddr;
 use std::ops::Deref;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -10,13 +13,27 @@ use serde::Deserialize;
 use serde_json::Result as JsonResult;
 
 use crate::http::context::Context;
+use crate::http::context::Error as ContextError;
+use crate::http::middleware::auth::AUTHENTICATED_USER;
+use crate::http::middleware::cookies::COOKIE_JAR;
+use crate::http::middleware::cookies::QUEUED_COOKIES;
+use crate::http::middleware::locale::LOCALE;
+use crate::http::session::Session;
+use crate::http::session::SESSION;
+use crate::http::terminate::Terminators;
+use crate::http::terminate::TERMINATORS;
 use crate::http::Cookie;
+use crate::http::CookieJar;
 use crate::http::Headers;
 use crate::http::Method;
 use crate::http::Response;
 use crate::http::Uri;
 use crate::http::Version;
+use crate::routing::metadata::MatchedRoute;
+use crate::routing::url::Error as UrlError;
+use crate::routing::url::URL_GENERATOR;
 use crate::routing::Route;
+use crate::routing::UrlGenerator;
 use crate::utils::TruncatableToFit;
 // use crate::FakeApplication;
 
@@ -39,6 +56,9 @@ pub struct Request<App: Send + Sync> {
     route_parameters: HashMap<String, String>,
     query_parameters: HashMap<String, String>,
     metadata: HashMap<String, String>,
+    route: Option<MatchedRoute>,
+    remote_addr: Option<SocketAddr>,
+    client_ip: Option<IpAddr>,
 }
 
 impl<App: Send + Sync + 'static> Request<App> {
@@ -63,13 +83,15 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use valar::http::Method;
     /// use valar::http::Request;
     /// use valar::http::Uri;
     ///
     /// let uri = Uri::from_static("http://localhost:3000/?id=1&name=John");
     ///
-    /// let request = Request::builder().method(Method::GET).uri(uri).build();
+    /// let request = Request::builder().method(Method::GET).uri(uri).build(Arc::new(()));
     ///
     /// assert_eq!(request.method(), &Method::GET);
     /// assert_eq!(request.uri().path(), "/");
@@ -90,10 +112,12 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use valar::http::Method;
     /// use valar::http::Request;
     ///
-    /// let request = Request::builder().method(Method::GET).build();
+    /// let request = Request::builder().method(Method::GET).build(Arc::new(()));
     ///
     /// assert_eq!(request.method(), &Method::GET);
     /// ```
@@ -107,13 +131,15 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use valar::http::Method;
     /// use valar::http::Request;
     /// use valar::http::Uri;
     ///
     /// let uri = Uri::from_static("http://localhost:3000/foo");
     ///
-    /// let request = Request::builder().method(Method::GET).uri(uri).build();
+    /// let request = Request::builder().method(Method::GET).uri(uri).build(Arc::new(()));
     ///
     /// assert_eq!(request.uri().path(), "/foo");
     /// ```
@@ -126,10 +152,12 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use valar::http::Request;
     /// use valar::http::Version;
     ///
-    /// let request = Request::builder().version(Version::HTTP_11).build();
+    /// let request = Request::builder().version(Version::HTTP_11).build(Arc::new(()));
     ///
     /// assert_eq!(request.version(), &Version::HTTP_11);
     /// ```
@@ -142,9 +170,11 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use valar::http::Request;
     ///
-    /// let request = Request::builder().body("Hello World!").build();
+    /// let request = Request::builder().body("Hello World!").build(Arc::new(()));
     ///
     /// assert_eq!(request.body(), "Hello World!");
     /// ```
@@ -164,6 +194,35 @@ impl<App: Send + Sync + 'static> Request<App> {
         &mut self.metadata
     }
 
+    /// Returns the route that matched the request, with its
+    /// path, name and metadata. It's only available once
+    /// the router matched the request.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    ///
+    /// enum RequiresRole {
+    ///     Admin,
+    /// }
+    ///
+    /// async fn handler(request: Request<()>) -> Result {
+    ///     let route = request.route().expect("matched route");
+    ///
+    ///     if route.meta::<RequiresRole>().is_some() {
+    ///         return Response::forbidden().into_err();
+    ///     }
+    ///
+    ///     Response::ok().into_ok()
+    /// }
+    /// ```
+    pub fn route(&self) -> Option<&MatchedRoute> {
+        self.route.as_ref()
+    }
+
     /// Returns the headers of the request.
     pub fn headers(&self) -> &Headers<Self> {
         &self.headers
@@ -175,6 +234,227 @@ impl<App: Send + Sync + 'static> Request<App> {
         &mut self.headers
     }
 
+    /// Returns the address of the peer that opened the
+    /// connection, if known. Behind a proxy, this is the
+    /// address of the proxy.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// Returns the IP address of the client. It defaults to
+    /// the peer address and is replaced by the forwarded
+    /// one when the `ForwardedHeaders` middleware trusts
+    /// the peer.
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.client_ip
+            .or_else(|| self.remote_addr.map(|address| address.ip()))
+    }
+
+    /// Sets the IP address of the client.
+    pub fn set_client_ip(&mut self, ip: IpAddr) {
+        self.client_ip = Some(ip);
+    }
+
+    /// Sets the URI of the request. The query parameters
+    /// are parsed again from the new URI.
+    pub fn set_uri(&mut self, uri: Uri) {
+        self.query_parameters = Self::query_parameters_from(&uri);
+        self.uri = uri;
+    }
+
+    /// Sets the method of the request.
+    pub fn set_method(&mut self, method: Method) {
+        self.method = method;
+    }
+
+    /// Sets the body of the request.
+    pub fn set_body<B>(&mut self, body: B)
+    where
+        B: Into<String>,
+    {
+        self.body = body.into();
+    }
+
+    /// Returns the cookie jar attached to the request by
+    /// the `Cookies` middleware. Returns `None` when the
+    /// middleware is not used.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Cookie;
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    ///
+    /// async fn handler(request: Request<()>) -> Result {
+    ///     if let Some(jar) = request.cookie_jar().await {
+    ///         jar.add(Cookie::builder("theme", "dark").build());
+    ///     }
+    ///
+    ///     Response::ok().into_ok()
+    /// }
+    /// ```
+    pub async fn cookie_jar(&self) -> Option<CookieJar> {
+        self.context.cloned(COOKIE_JAR).await.ok()
+    }
+
+    /// Queues a cookie to be sent with the response by the
+    /// `QueueableCookies` middleware. Queuing a cookie
+    /// replaces any queued cookie with the same name.
+    ///
+    /// Fails when the middleware is not used.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Cookie;
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    ///
+    /// async fn handler(request: Request<()>) -> Result {
+    ///     request
+    ///         .queue_cookie(Cookie::builder("theme", "dark").build())
+    ///         .await?;
+    ///
+    ///     Response::ok().into_ok()
+    /// }
+    /// ```
+    pub async fn queue_cookie<C>(&self, cookie: C) -> Result<(), ContextError>
+    where
+        C: Into<Cookie<Response>>,
+    {
+        let cookie: Cookie<Response> = cookie.into();
+
+        self.context
+            .update(QUEUED_COOKIES, |cookies: &mut Vec<Cookie<Response>>| {
+                cookies.retain(|queued| queued.name() != cookie.name());
+                cookies.push(cookie);
+            })
+            .await
+    }
+
+    /// Returns the session started by the `Session`
+    /// middleware, if it is used.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    ///
+    /// async fn handler(request: Request<()>) -> Result {
+    ///     if let Some(session) = request.session().await {
+    ///         session.insert("theme", "dark")?;
+    ///     }
+    ///
+    ///     Response::ok().into_ok()
+    /// }
+    /// ```
+    pub async fn session(&self) -> Option<Session> {
+        self.context.cloned(SESSION).await.ok()
+    }
+
+    /// Registers work to run once the response has been
+    /// sent to the client, like audit logging or slow
+    /// webhook dispatches, so it doesn't delay the response.
+    /// The callbacks run in the order they were registered.
+    /// Does nothing if the request is not being handled by
+    /// a router.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    ///
+    /// async fn handler(request: Request<()>) -> Result {
+    ///     let path = request.uri().path().to_string();
+    ///
+    ///     request
+    ///         .after_response(move || async move {
+    ///             println!("Audited {path}");
+    ///         })
+    ///         .await;
+    ///
+    ///     Response::ok().into_ok()
+    /// }
+    /// ```
+    pub async fn after_response<F, Fut>(&self, callback: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        if let Ok(terminators) = self.context.cloned::<Terminators>(TERMINATORS).await {
+            terminators.push(callback);
+        }
+    }
+
+    /// Returns the locale resolved by the `Locale`
+    /// middleware, or `None` when it is not used.
+    pub async fn locale(&self) -> Option<String> {
+        self.context.cloned(LOCALE).await.ok()
+    }
+
+    /// Returns the user authenticated by the `Authenticate`
+    /// middleware. Returns `None` when the middleware is
+    /// not used or when its guard resolves another type.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    ///
+    /// struct User(String);
+    ///
+    /// async fn handler(request: Request<()>) -> Result {
+    ///     let Some(user) = request.user::<User>().await else {
+    ///         return Response::unauthorized("Bearer").into_err();
+    ///     };
+    ///
+    ///     Response::ok().body(user.0.clone()).into_ok()
+    /// }
+    /// ```
+    pub async fn user<U>(&self) -> Option<Arc<U>>
+    where
+        U: Send + Sync + 'static,
+    {
+        self.context.cloned(AUTHENTICATED_USER).await.ok()
+    }
+
+    /// Generates the URL of the named route with the given
+    /// parameters, using the router that handles the
+    /// request.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    ///
+    /// async fn handler(request: Request<()>) -> Result {
+    ///     let url = request.url_for("users.show", [("id", "5")]).await?;
+    ///
+    ///     Response::ok().body(url).into_ok()
+    /// }
+    /// ```
+    pub async fn url_for<P, K, V>(&self, name: &str, parameters: P) -> Result<String, UrlError>
+    where
+        P: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let urls: UrlGenerator = self.context.cloned(URL_GENERATOR).await.unwrap_or_default();
+
+        urls.url(name, parameters)
+    }
+
     /// Returns true if the request is considered to have a
     /// JSON body. This is determined by the
     /// "Content-Type" header.
@@ -185,6 +465,8 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use std::collections::HashMap;
     ///
     /// use valar::http::Request;
@@ -192,7 +474,7 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// let request = Request::builder()
     ///     .headers([("Content-Type", "application/json")])
     ///     .body(r#"{"name": "John"}"#.to_string())
-    ///     .build();
+    ///     .build(Arc::new(()));
     ///
     /// assert_eq!(request.is_json(), true);
     /// ```
@@ -210,13 +492,15 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use std::collections::HashMap;
     ///
     /// use valar::http::Request;
     ///
     /// let request = Request::builder()
     ///     .headers([("Content-Type", "application/json")])
-    ///     .build();
+    ///     .build(Arc::new(()));
     ///
     /// assert_eq!(request.wants_json(), true);
     /// ```
@@ -230,11 +514,13 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use std::collections::HashMap;
     ///
     /// use valar::http::Request;
     ///
-    /// let request = Request::builder().route_parameters([("id", "1")]).build();
+    /// let request = Request::builder().route_parameters([("id", "1")]).build(Arc::new(()));
     ///
     /// assert_eq!(request.has_parameter("id"), true);
     /// assert_eq!(request.has_parameter("name"), false);
@@ -249,11 +535,13 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use std::collections::HashMap;
     ///
     /// use valar::http::Request;
     ///
-    /// let request = Request::builder().route_parameters([("id", "1")]).build();
+    /// let request = Request::builder().route_parameters([("id", "1")]).build(Arc::new(()));
     ///
     /// assert_eq!(request.maybe_parameter("id").unwrap(), "1");
     /// assert_eq!(request.maybe_parameter("name"), None);
@@ -268,11 +556,13 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use std::collections::HashMap;
     ///
     /// use valar::http::Request;
     ///
-    /// let request = Request::builder().route_parameters([("id", "1")]).build();
+    /// let request = Request::builder().route_parameters([("id", "1")]).build(Arc::new(()));
     ///
     /// assert_eq!(request.route_parameter("id").unwrap(), "1");
     /// assert!(request.route_parameter("name").is_err());
@@ -293,11 +583,13 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use std::collections::HashMap;
     ///
     /// use valar::http::Request;
     ///
-    /// let request = Request::builder().route_parameters([("id", "1")]).build();
+    /// let request = Request::builder().route_parameters([("id", "1")]).build(Arc::new(()));
     ///
     /// let id: u32 = request.parameter("id").unwrap();
     ///
@@ -319,12 +611,14 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use valar::http::Request;
     /// use valar::http::Uri;
     ///
     /// let uri = Uri::from_static("http://localhost:3000/?id=1&name=John");
     ///
-    /// let request = Request::builder().uri(uri).build();
+    /// let request = Request::builder().uri(uri).build(Arc::new(()));
     ///
     /// assert_eq!(request.query_parameters().get("id").unwrap(), "1");
     /// assert_eq!(request.query_parameters().get("name").unwrap(), "John");
@@ -361,12 +655,14 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use valar::http::Request;
     /// use valar::http::Uri;
     ///
     /// let uri = Uri::from_static("http://localhost:3000/?id=1&name=John");
     ///
-    /// let request = Request::builder().uri(uri).build();
+    /// let request = Request::builder().uri(uri).build(Arc::new(()));
     ///
     /// assert!(request.has_query("id"));
     /// assert!(request.has_query("name"));
@@ -383,12 +679,14 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use valar::http::Request;
     /// use valar::http::Uri;
     ///
     /// let uri = Uri::from_static("http://localhost:3000/?id=1&name=John");
     ///
-    /// let request = Request::builder().uri(uri).build();
+    /// let request = Request::builder().uri(uri).build(Arc::new(()));
     ///
     /// assert_eq!(request.maybe_query("id").unwrap(), "1");
     /// assert_eq!(request.maybe_query("name").unwrap(), "John");
@@ -405,12 +703,14 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use valar::http::Request;
     /// use valar::http::Uri;
     ///
     /// let uri = Uri::from_static("http://localhost:3000/?id=1&name=John");
     ///
-    /// let request = Request::builder().uri(uri).build();
+    /// let request = Request::builder().uri(uri).build(Arc::new(()));
     ///
     /// assert_eq!(request.query_parameter("id").unwrap(), "1");
     /// assert_eq!(request.query_parameter("name").unwrap(), "John");
@@ -435,12 +735,14 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use valar::http::Request;
     /// use valar::http::Uri;
     ///
     /// let uri = Uri::from_static("http://localhost:3000/?id=1&name=John");
     ///
-    /// let request = Request::builder().uri(uri).build();
+    /// let request = Request::builder().uri(uri).build(Arc::new(()));
     ///
     /// let id: u32 = request.query("id").unwrap();
     /// let name: String = request.query("name").unwrap();
@@ -464,6 +766,8 @@ impl<App: Send + Sync + 'static> Request<App> {
     /// # Example
     ///
     /// ```no_run
+    /// use std::sync::Arc;
+    ///
     /// use serde::Deserialize;
     /// use valar::http::Request;
     ///
@@ -472,7 +776,7 @@ impl<App: Send + Sync + 'static> Request<App> {
     ///     name: String,
     /// }
     ///
-    /// let request = Request::builder().body(r#"{"name": "John"}"#).build();
+    /// let request = Request::builder().body(r#"{"name": "John"}"#).build(Arc::new(()));
     ///
     /// let user: User = request.json().unwrap();
     ///
@@ -487,6 +791,7 @@ impl<App: Send + Sync + 'static> Request<App> {
 
     pub fn parematrized(mut self, route: &Route<App>) -> Self {
         self.route_parameters = route.parameters(self.uri());
+        self.route = Some(route.matched());
 
         self
     }
@@ -546,6 +851,7 @@ pub struct RequestBuilder<App: Send + Sync + 'static> {
     body: String,
     route_parameters: HashMap<String, String>,
     metadata: HashMap<String, String>,
+    remote_addr: Option<SocketAddr>,
 }
 
 impl<App: Send + Sync + 'static> Default for RequestBuilder<App> {
@@ -559,6 +865,7 @@ impl<App: Send + Sync + 'static> Default for RequestBuilder<App> {
             body: Default::default(),
             route_parameters: Default::default(),
             metadata: Default::default(),
+            remote_addr: Default::default(),
         }
     }
 }
@@ -679,6 +986,15 @@ impl<App: Send + Sync + 'static> RequestBuilder<App> {
         self
     }
 
+    pub fn remote_addr<A>(mut self, address: A) -> Self
+    where
+        A: Into<SocketAddr>,
+    {
+        self.remote_addr = Some(address.into());
+
+        self
+    }
+
     pub fn build(self, app: Arc<App>) -> Request<App> {
         Request {
             app,
@@ -691,6 +1007,9 @@ impl<App: Send + Sync + 'static> RequestBuilder<App> {
             headers: self.headers,
             body: self.body,
             metadata: self.metadata,
+            route: None,
+            remote_addr: self.remote_addr,
+            client_ip: None,
         }
     }
 }
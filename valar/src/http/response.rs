@@ -1,19 +1,47 @@
 use std::error::Error;
+use std::fmt::Debug;
 use std::fmt::Display;
+use std::future::Future;
+use std::io::Result as IoResult;
+use std::path::Path;
 
 use colored::Colorize;
+use futures_util::Stream;
+use futures_util::StreamExt;
 use http::Response as BaseResponse;
 use http::Result as BaseHttpResult;
+use hyper::body::Bytes;
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::Error as JsonError;
 use serde_json::Result as JsonResult;
-
+use serde_json::Value;
+use sha2::Digest;
+use sha2::Sha256;
+use tokio::fs::File;
+use tokio_util::io::ReaderStream;
+
+use crate::debug;
+use crate::http::body::BoxError;
+use crate::http::cache_control::CacheControl;
+use crate::http::error_page::ErrorReport;
+use crate::http::headers::typed::TypedHeader;
+use crate::http::terminate::Terminating;
+use crate::http::terminate::Terminators;
+use crate::http::Body;
 use crate::http::Cookie;
 use crate::http::Headers;
+use crate::http::Method;
 use crate::http::Request;
 use crate::http::Result as HttpResult;
 use crate::http::StatusCode;
+use crate::http::Uri;
 use crate::http::Version;
+use crate::routing::url::Error as UrlError;
+#[cfg(feature = "templates")]
+use crate::services::views::Error as ViewError;
+#[cfg(feature = "templates")]
+use crate::services::Views;
 use crate::utils::TruncatableToFit;
 
 /// A response is used to send a response back
@@ -23,7 +51,9 @@ pub struct Response {
     status: StatusCode,
     version: Version,
     headers: Headers<Self>,
-    body: String,
+    body: Body,
+    terminators: Terminators,
+    error_report: Option<Box<ErrorReport>>,
 }
 
 impl Response {
@@ -66,6 +96,13 @@ impl Response {
         Self::builder().redirect(location)
     }
 
+    pub fn moved_permanently<P>(location: P) -> ResponseBuilder
+    where
+        P: Into<String>,
+    {
+        Self::builder().moved_permanently(location)
+    }
+
     pub fn temporary_redirect<P>(location: P) -> ResponseBuilder
     where
         P: Into<String>,
@@ -80,6 +117,55 @@ impl Response {
         Self::builder().permanent_redirect(location)
     }
 
+    /// Returns a response builder that redirects to the
+    /// named route with the given parameters.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    ///
+    /// async fn store(request: Request<()>) -> Result {
+    ///     Response::redirect_to_route(&request, "users.show", [("id", "5")])
+    ///         .await?
+    ///         .into_ok()
+    /// }
+    /// ```
+    pub async fn redirect_to_route<App, P, K, V>(
+        request: &Request<App>,
+        name: &str,
+        parameters: P,
+    ) -> Result<ResponseBuilder, UrlError>
+    where
+        App: Send + Sync + 'static,
+        P: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let location = request.url_for(name, parameters).await?;
+
+        Ok(Self::redirect(location))
+    }
+
+    /// Returns a response builder with an ok status code
+    /// and the rendered view as the HTML body. See
+    /// [`ResponseBuilder::view`].
+    #[cfg(feature = "templates")]
+    pub fn view<C>(views: &Views, name: &str, context: C) -> Result<ResponseBuilder, ViewError>
+    where
+        C: Serialize,
+    {
+        Self::ok().view(views, name, context)
+    }
+
+    /// Returns a response builder that redirects back to
+    /// the previous page. See [`ResponseBuilder::back`].
+    pub fn back<App: Send + Sync + 'static>(request: &Request<App>) -> ResponseBuilder {
+        Self::builder().back(request)
+    }
+
     /// Returns a response builder with a created status
     /// code.
     pub fn created() -> ResponseBuilder {
@@ -98,16 +184,86 @@ impl Response {
         Self::builder().not_found()
     }
 
-    /// Returns a response builder with a not found status
+    /// Returns a response builder with a bad request
+    /// status code.
+    pub fn bad_request() -> ResponseBuilder {
+        Self::builder().bad_request()
+    }
+
+    /// Returns a response builder with an unauthorized
+    /// status code and the given authentication challenges.
+    pub fn unauthorized(challenges: &str) -> ResponseBuilder {
+        Self::builder().unauthorized(challenges)
+    }
+
+    /// Returns a response builder with a forbidden status
     /// code.
-    pub fn internal_server_error() -> ResponseBuilder {
-        Self::builder().internal_server_error()
+    pub fn forbidden() -> ResponseBuilder {
+        Self::builder().forbidden()
+    }
+
+    /// Returns a response builder with a method not allowed
+    /// status code.
+    pub fn method_not_allowed() -> ResponseBuilder {
+        Self::builder().method_not_allowed()
+    }
+
+    /// Returns a response builder with a conflict status
+    /// code.
+    pub fn conflict() -> ResponseBuilder {
+        Self::builder().conflict()
     }
 
+    /// Returns a response builder with a gone status code.
+    pub fn gone() -> ResponseBuilder {
+        Self::builder().gone()
+    }
+
+    /// Returns a response builder with a payload too large
+    /// status code.
     pub fn payload_too_large() -> ResponseBuilder {
         Self::builder().payload_too_large()
     }
 
+    /// Returns a response builder with an unprocessable
+    /// entity status code.
+    pub fn unprocessable_entity() -> ResponseBuilder {
+        Self::builder().unprocessable_entity()
+    }
+
+    /// Returns a response builder with a too many requests
+    /// status code.
+    pub fn too_many_requests() -> ResponseBuilder {
+        Self::builder().too_many_requests()
+    }
+
+    /// Returns a response builder with an internal server
+    /// error status code.
+    pub fn internal_server_error() -> ResponseBuilder {
+        Self::builder().internal_server_error()
+    }
+
+    /// Returns a response builder with a not implemented
+    /// status code.
+    pub fn not_implemented() -> ResponseBuilder {
+        Self::builder().not_implemented()
+    }
+
+    /// Returns a response builder with a service
+    /// unavailable status code.
+    pub fn service_unavailable() -> ResponseBuilder {
+        Self::builder().service_unavailable()
+    }
+
+    /// Returns a response builder that streams the file
+    /// at the given path. See [`ResponseBuilder::file`].
+    pub async fn file<P>(path: P) -> IoResult<ResponseBuilder>
+    where
+        P: AsRef<Path>,
+    {
+        Self::builder().file(path).await
+    }
+
     /// Returns the response status code.
     pub fn status(&self) -> &StatusCode {
         &self.status
@@ -118,9 +274,51 @@ impl Response {
         &self.version
     }
 
-    /// Returns the response's body.
+    /// Returns the response's body. Streamed bodies or
+    /// bodies that are not valid UTF-8 are returned as an
+    /// empty string, use `bytes` for binary content.
     pub fn body(&self) -> &str {
-        &self.body
+        self.body.as_str()
+    }
+
+    /// Returns the response's body as raw bytes.
+    pub fn bytes(&self) -> &[u8] {
+        self.body.as_bytes()
+    }
+
+    /// Returns a mutable reference to the response's body.
+    pub fn body_mut(&mut self) -> &mut Body {
+        &mut self.body
+    }
+
+    /// Returns the work to run once the response has been
+    /// sent to the client.
+    pub fn terminators(&self) -> &Terminators {
+        &self.terminators
+    }
+
+    /// Returns the details of the error behind the
+    /// response. They are only collected in debug mode.
+    pub fn error_report(&self) -> Option<&ErrorReport> {
+        self.error_report.as_deref()
+    }
+
+    /// Sets the details of the error behind the response.
+    pub fn with_error_report(mut self, report: ErrorReport) -> Self {
+        self.error_report = Some(Box::new(report));
+
+        self
+    }
+
+    /// Sets the work to run once the response has been
+    /// sent to the client.
+    pub(crate) fn set_terminators(&mut self, terminators: Terminators) {
+        self.terminators = terminators;
+    }
+
+    /// Determines if the response's body is streamed.
+    pub fn is_streamed(&self) -> bool {
+        self.body.is_stream()
     }
 
     /// Returns the headers of the request.
@@ -200,8 +398,117 @@ impl Response {
         self
     }
 
-    /// Transforms the response to a hyper Response.
-    pub(crate) fn into_base_response(self) -> BaseHttpResult<BaseResponse<Body>> {
+    /// Asserts the body contains the given string.
+    pub fn assert_body_contains(&self, value: &str) -> &Self {
+        assert!(
+            self.body().contains(value),
+            "expected the body to contain `{value}`, got `{}`",
+            self.body()
+        );
+
+        self
+    }
+
+    /// Asserts the body deserializes into the expected
+    /// value.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Response;
+    ///
+    /// Response::ok()
+    ///     .json(&vec![1, 2, 3])
+    ///     .unwrap()
+    ///     .build()
+    ///     .assert_json(vec![1, 2, 3]);
+    /// ```
+    pub fn assert_json<T>(&self, expected: T) -> &Self
+    where
+        T: DeserializeOwned + PartialEq + Debug,
+    {
+        let actual: T = serde_json::from_slice(self.bytes())
+            .unwrap_or_else(|error| panic!("the body is not the expected JSON: {error}"));
+
+        assert_eq!(actual, expected);
+
+        self
+    }
+
+    /// Asserts the JSON body contains the given fragment.
+    /// Objects only need to contain the keys present in the
+    /// fragment and arrays only need to contain an element
+    /// matching each of the fragment's elements.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use serde_json::json;
+    /// use valar::http::Response;
+    ///
+    /// Response::ok()
+    ///     .json(&json!({ "id": 1, "name": "Erik" }))
+    ///     .unwrap()
+    ///     .build()
+    ///     .assert_json_contains(json!({ "name": "Erik" }));
+    /// ```
+    pub fn assert_json_contains<F>(&self, fragment: F) -> &Self
+    where
+        F: Serialize,
+    {
+        let actual: Value = serde_json::from_slice(self.bytes())
+            .unwrap_or_else(|error| panic!("the body is not valid JSON: {error}"));
+
+        let fragment = serde_json::to_value(fragment).expect("the fragment is not valid JSON");
+
+        assert!(
+            json_contains(&actual, &fragment),
+            "expected the JSON body to contain `{fragment}`, got `{actual}`"
+        );
+
+        self
+    }
+
+    /// Determines if the response status code allows a
+    /// body to be sent. Informational (1xx), 204 and 304
+    /// responses never carry content (RFC 9110).
+    pub fn permits_body(&self) -> bool {
+        permits_body(&self.status)
+    }
+
+    /// Removes the body of the response, leaving the rest
+    /// of the response untouched.
+    fn strip_body(&mut self) {
+        self.body = Body::empty();
+    }
+
+    /// Transforms the response to a hyper Response. The
+    /// method is the one of the request being answered,
+    /// since responses to HEAD requests must not contain
+    /// a body either.
+    pub(crate) fn into_base_response(
+        mut self,
+        method: &Method,
+    ) -> BaseHttpResult<BaseResponse<Body>> {
+        if !self.permits_body() {
+            self.strip_body();
+            self.headers.remove("Content-Type");
+
+            if self.status != StatusCode::NOT_MODIFIED {
+                self.headers.remove("Content-Length");
+            }
+        } else if method == Method::HEAD {
+            // The headers of a HEAD response must be the ones
+            // of the equivalent GET request, including the
+            // length of the content that is not sent.
+            if !self.body.is_stream() && !self.headers.has("Content-Length") {
+                let length = self.body.as_bytes().len().to_string();
+                self.headers.insert("Content-Length", length);
+            }
+
+            self.strip_body();
+        }
+
         let mut builder = BaseResponse::builder();
 
         for (header, value) in self.headers {
@@ -211,7 +518,27 @@ impl Response {
         builder
             .status(self.status)
             .version(self.version)
-            .body(Body::from(self.body))
+            .body(self.body)
+    }
+}
+
+impl Response {
+    /// Transforms the response to the hyper Response that
+    /// is written to the client. The terminators of the
+    /// response run once its body has been written.
+    pub(crate) fn into_http_response(self, method: &Method) -> BaseResponse<Terminating> {
+        let terminators = self.terminators.clone();
+
+        let response = self.into_base_response(method).unwrap_or_else(|err| {
+            eprintln!("Failed to build the response: {err}");
+
+            let mut response = BaseResponse::new(Body::from("Internal Server Error"));
+            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+
+            response
+        });
+
+        response.map(|body| Terminating::new(body.boxed(), terminators))
     }
 }
 
@@ -222,7 +549,12 @@ where
     E: Error + Send + Sync + 'static,
 {
     fn from(err: E) -> Self {
-        Self::internal_server_error().body(err.to_string()).build()
+        let response = Self::internal_server_error().body(err.to_string()).build();
+
+        match debug::enabled() {
+            true => response.with_error_report(ErrorReport::from_error(&err)),
+            false => response,
+        }
     }
 }
 
@@ -258,7 +590,7 @@ pub struct ResponseBuilder {
     status: StatusCode,
     version: Version,
     headers: Headers<Response>,
-    body: Option<String>,
+    body: Option<Body>,
     message: Option<ResponseMessage>,
 }
 
@@ -297,6 +629,28 @@ impl ResponseBuilder {
         self
     }
 
+    /// Sets a typed header to the response.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::headers::typed::ContentType;
+    /// use valar::http::Response;
+    ///
+    /// let response = Response::ok()
+    ///     .typed_header(ContentType::html())
+    ///     .body("<h1>Hello</h1>")
+    ///     .build();
+    /// ```
+    pub fn typed_header<H>(mut self, header: H) -> Self
+    where
+        H: TypedHeader,
+    {
+        self.headers.insert_typed(header);
+
+        self
+    }
+
     /// Add a cookie to the response.
     pub fn cookie<C>(mut self, cookie: C) -> Self
     where
@@ -308,6 +662,28 @@ impl ResponseBuilder {
         self
     }
 
+    /// Queues a cookie to be sent with the response. Unlike
+    /// `cookie`, it replaces any cookie with the same name
+    /// that was already added to the response. Queued
+    /// cookies take precedence over the ones queued on the
+    /// request with the `QueueableCookies` middleware.
+    pub fn queue_cookie<C>(mut self, cookie: C) -> Self
+    where
+        C: Into<Cookie<Response>>,
+    {
+        let cookie: Cookie<Response> = cookie.into();
+
+        if let Some(values) = self.headers.get_mut("Set-Cookie") {
+            values.retain(|value| {
+                value
+                    .parse::<Cookie<Response>>()
+                    .map_or(true, |queued| queued.name() != cookie.name())
+            });
+        }
+
+        self.cookie(cookie)
+    }
+
     /// Set the headers of the request.
     pub fn headers<H>(mut self, headers: H) -> Self
     where
@@ -332,13 +708,214 @@ impl ResponseBuilder {
     /// Set the body of the response.
     pub fn body<B>(mut self, body: B) -> Self
     where
-        B: Into<String>,
+        B: Into<Body>,
     {
         self.body = Some(body.into());
 
         self
     }
 
+    /// Streams the given chunks as the body of the
+    /// response. Since the length is not known up front,
+    /// the response is sent using chunked transfer
+    /// encoding.
+    pub fn chunked<S, B, E>(mut self, stream: S) -> Self
+    where
+        S: Stream<Item = Result<B, E>> + Send + Sync + 'static,
+        B: Into<Bytes> + 'static,
+        E: Into<BoxError> + 'static,
+    {
+        self.headers.remove("Content-Length");
+        self.body = Some(Body::stream(stream));
+
+        self
+    }
+
+    /// Streams the given chunks as the body of the
+    /// response followed by the trailers once the stream
+    /// ends. The trailer names must be declared up front
+    /// since they are announced in the `Trailer` header,
+    /// any other trailer is discarded. Clients only get
+    /// the trailers when they send `TE: trailers`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::convert::Infallible;
+    ///
+    /// use futures_util::stream;
+    /// use valar::http::Headers;
+    /// use valar::http::Response;
+    ///
+    /// let chunks = stream::iter([Ok::<_, Infallible>("Hello")]);
+    /// let trailers = async { Headers::from([("Checksum", "abc")]) };
+    ///
+    /// let response = Response::ok()
+    ///     .chunked_with_trailers(chunks, &["Checksum"], trailers)
+    ///     .build();
+    /// ```
+    pub fn chunked_with_trailers<S, B, E, T>(
+        mut self,
+        stream: S,
+        names: &[&str],
+        trailers: T,
+    ) -> Self
+    where
+        S: Stream<Item = Result<B, E>> + Send + Sync + 'static,
+        B: Into<Bytes> + 'static,
+        E: Into<BoxError> + 'static,
+        T: Future<Output = Headers<Response>> + Send + Sync + 'static,
+    {
+        let declared: Vec<String> = names.iter().map(|name| name.to_lowercase()).collect();
+        let trailers = async move {
+            trailers
+                .await
+                .into_iter()
+                .filter(|(name, _)| declared.contains(&name.to_lowercase()))
+                .collect()
+        };
+
+        self.headers.remove("Content-Length");
+        self.headers.insert("Trailer", names.join(", "));
+        self.body = Some(Body::stream_with_trailers(stream, trailers));
+
+        self
+    }
+
+    /// Streams the file at the given path as the body of
+    /// the response. The `Content-Type` is inferred from
+    /// the file extension and the `Content-Length` is taken
+    /// from the file metadata, so HEAD requests can be
+    /// answered without reading the file.
+    ///
+    /// The file is displayed inline by default, use
+    /// `attachment` to make the client download it.
+    pub async fn file<P>(mut self, path: P) -> IoResult<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::open(path).await?;
+        let metadata = file.metadata().await?;
+        let mime = mime_guess::from_path(path).first_or_octet_stream();
+
+        if let Some(filename) = path.file_name() {
+            let filename = filename.to_string_lossy();
+            self.headers.insert(
+                "Content-Disposition",
+                content_disposition("inline", &filename),
+            );
+        }
+
+        self.headers.insert("Content-Type", mime.to_string());
+        self.headers
+            .insert("Content-Length", metadata.len().to_string());
+
+        Ok(self.body(Body::stream(ReaderStream::new(file))))
+    }
+
+    /// Marks the response as an attachment so that clients
+    /// download it using the given file name instead of
+    /// displaying it.
+    pub fn attachment<F>(mut self, filename: F) -> Self
+    where
+        F: Into<String>,
+    {
+        let filename: String = filename.into();
+
+        self.headers.insert(
+            "Content-Disposition",
+            content_disposition("attachment", &filename),
+        );
+
+        self
+    }
+
+    /// Removes any body, message and `Content-Type` from
+    /// the response so that nothing is sent to the client.
+    pub fn empty(mut self) -> Self {
+        self.headers.remove("Content-Type");
+        self.body = Some(Body::empty());
+        self.message = None;
+
+        self
+    }
+
+    /// Sets a strong `ETag` validator. The value is quoted
+    /// automatically unless it already is.
+    pub fn etag<V>(self, value: V) -> Self
+    where
+        V: Into<String>,
+    {
+        let value: String = value.into();
+
+        match value.starts_with('"') || value.starts_with("W/\"") {
+            true => self.header("ETag", value),
+            false => self.header("ETag", format!(r#""{value}""#)),
+        }
+    }
+
+    /// Sets a weak `ETag` validator. Weak validators
+    /// indicate the content is semantically equivalent but
+    /// not necessarily byte by byte identical.
+    pub fn weak_etag<V>(self, value: V) -> Self
+    where
+        V: Into<String>,
+    {
+        let value: String = value.into();
+        let value = value.trim_start_matches("W/").trim_matches('"');
+
+        self.header("ETag", format!(r#"W/"{value}""#))
+    }
+
+    /// Sets a strong `ETag` validator computed by hashing
+    /// the current body of the response. Must be called
+    /// after setting the body. Streamed bodies can't be
+    /// hashed up front so no `ETag` is set for them.
+    pub fn etag_from_body(self) -> Self {
+        match &self.body {
+            Some(body) if !body.is_stream() => {
+                let etag = etag_for(body.as_bytes());
+
+                self.etag(etag)
+            }
+            _ => self,
+        }
+    }
+
+    /// Sets the `Cache-Control` header using the given
+    /// directives.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::cache_control::CacheControl;
+    /// use valar::http::Response;
+    ///
+    /// let response = Response::ok()
+    ///     .cache_control(
+    ///         CacheControl::new()
+    ///             .public()
+    ///             .max_age(3600)
+    ///             .stale_while_revalidate(60),
+    ///     )
+    ///     .build();
+    /// ```
+    pub fn cache_control(self, cache_control: CacheControl) -> Self {
+        self.header("Cache-Control", cache_control)
+    }
+
+    /// Prevents any cache from storing the response.
+    pub fn no_store(self) -> Self {
+        self.cache_control(CacheControl::new().no_store())
+    }
+
+    /// Forces caches to validate the response with the
+    /// server before reusing it.
+    pub fn no_cache(self) -> Self {
+        self.cache_control(CacheControl::new().no_cache())
+    }
+
     /// Sets the status code to OK.
     pub fn ok(self) -> Self {
         self.status(StatusCode::OK)
@@ -354,6 +931,11 @@ impl ResponseBuilder {
         self.status(StatusCode::NO_CONTENT)
     }
 
+    /// Sets the status code to BAD REQUEST.
+    pub fn bad_request(self) -> Self {
+        self.status(StatusCode::BAD_REQUEST)
+    }
+
     /// Sets the status code to UNAUTHORIZED.
     pub fn unauthorized(mut self, challenges: &str) -> Self {
         self.headers.insert("WWW-Authenticate", challenges);
@@ -362,6 +944,11 @@ impl ResponseBuilder {
         self
     }
 
+    /// Sets the status code to FORBIDDEN.
+    pub fn forbidden(self) -> Self {
+        self.status(StatusCode::FORBIDDEN)
+    }
+
     /// Sets the status code to NOT FOUND.
     pub fn not_found(mut self) -> Self {
         self.status = StatusCode::NOT_FOUND;
@@ -376,6 +963,33 @@ impl ResponseBuilder {
         self
     }
 
+    /// Sets the status code to CONFLICT.
+    pub fn conflict(self) -> Self {
+        self.status(StatusCode::CONFLICT)
+    }
+
+    /// Sets the status code to GONE.
+    pub fn gone(self) -> Self {
+        self.status(StatusCode::GONE)
+    }
+
+    /// Sets the status code to PAYLOAD TOO LARGE.
+    pub fn payload_too_large(mut self) -> Self {
+        self.status = StatusCode::PAYLOAD_TOO_LARGE;
+
+        self
+    }
+
+    /// Sets the status code to UNPROCESSABLE ENTITY.
+    pub fn unprocessable_entity(self) -> Self {
+        self.status(StatusCode::UNPROCESSABLE_ENTITY)
+    }
+
+    /// Sets the status code to TOO MANY REQUESTS.
+    pub fn too_many_requests(self) -> Self {
+        self.status(StatusCode::TOO_MANY_REQUESTS)
+    }
+
     /// Sets the status code to INTERNAL SERVER ERROR.
     pub fn internal_server_error(mut self) -> Self {
         self.status = StatusCode::INTERNAL_SERVER_ERROR;
@@ -383,11 +997,14 @@ impl ResponseBuilder {
         self
     }
 
-    /// Sets the status code to INTERNAL SERVER ERROR.
-    pub fn payload_too_large(mut self) -> Self {
-        self.status = StatusCode::PAYLOAD_TOO_LARGE;
+    /// Sets the status code to NOT IMPLEMENTED.
+    pub fn not_implemented(self) -> Self {
+        self.status(StatusCode::NOT_IMPLEMENTED)
+    }
 
-        self
+    /// Sets the status code to SERVICE UNAVAILABLE.
+    pub fn service_unavailable(self) -> Self {
+        self.status(StatusCode::SERVICE_UNAVAILABLE)
     }
 
     pub fn see_other<L>(mut self, location: L) -> Self
@@ -410,6 +1027,19 @@ impl ResponseBuilder {
         self.see_other(location)
     }
 
+    /// Moved permanently. Use when the resource is now
+    /// located at a different URI permanently and clients
+    /// may follow the redirect with a GET request.
+    pub fn moved_permanently<L>(mut self, location: L) -> Self
+    where
+        L: Into<String>,
+    {
+        self.headers.insert("Location", location);
+        self.status = StatusCode::MOVED_PERMANENTLY;
+
+        self
+    }
+
     /// Temporary redirect. Use when the resource is now
     /// located at a different URI temporarily.
     pub fn temporary_redirect<L>(mut self, location: L) -> Self
@@ -434,6 +1064,41 @@ impl ResponseBuilder {
         self
     }
 
+    /// Redirects back to the page that originated the
+    /// request using the `Referer` header. When the header
+    /// is missing or points to another host, it redirects
+    /// to `/` instead.
+    pub fn back<App: Send + Sync + 'static>(self, request: &Request<App>) -> Self {
+        self.back_or(request, "/")
+    }
+
+    /// Redirects back to the page that originated the
+    /// request using the `Referer` header. Only a path or
+    /// an absolute URL of the same host is followed. When
+    /// the header is missing or points anywhere else, it
+    /// redirects to the given fallback location instead.
+    pub fn back_or<App, L>(self, request: &Request<App>, fallback: L) -> Self
+    where
+        App: Send + Sync + 'static,
+        L: Into<String>,
+    {
+        let host = request.headers().first("Host").or_else(|| {
+            request
+                .uri()
+                .authority()
+                .map(|authority| authority.as_str())
+        });
+        let referer = request
+            .headers()
+            .first("Referer")
+            .filter(|referer| is_same_origin(referer, host));
+
+        match referer {
+            Some(referer) => self.see_other(referer.to_string()),
+            None => self.see_other(fallback),
+        }
+    }
+
     pub fn message<M>(mut self, message: M) -> Self
     where
         M: Into<String>,
@@ -460,6 +1125,37 @@ impl ResponseBuilder {
         self.body(html.into())
     }
 
+    /// Renders the given view with the given context and
+    /// sets it as the HTML body of the response.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use serde::Serialize;
+    /// use valar::http::Response;
+    /// use valar::services::Views;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Context {
+    ///     count: i32,
+    /// }
+    ///
+    /// let views = Views::new("resources/views");
+    /// let response = Response::ok()
+    ///     .view(&views, "counter/show", Context { count: 1 })
+    ///     .unwrap()
+    ///     .build();
+    /// ```
+    #[cfg(feature = "templates")]
+    pub fn view<C>(self, views: &Views, name: &str, context: C) -> Result<Self, ViewError>
+    where
+        C: Serialize,
+    {
+        let html = views.render(name, context)?;
+
+        Ok(self.html(html))
+    }
+
     /// Sets the apropiate headers for a text response.
     pub fn text<T>(mut self, text: T) -> Self
     where
@@ -471,24 +1167,70 @@ impl ResponseBuilder {
     }
 
     /// Sets the apropiate body and headers for a JSON
-    /// response.
+    /// response. The JSON is pretty printed when the debug
+    /// mode is enabled.
     pub fn json<J>(mut self, json: &J) -> JsonResult<Self>
     where
         J: Serialize,
     {
         self.headers.insert("Content-Type", "application/json");
-        self = self.body(serde_json::to_string(json)?);
+        self = self.body(to_json(json)?);
+
+        Ok(self)
+    }
+
+    /// Sets the apropiate body and headers for a pretty
+    /// printed JSON response.
+    pub fn json_pretty<J>(mut self, json: &J) -> JsonResult<Self>
+    where
+        J: Serialize,
+    {
+        self.headers.insert("Content-Type", "application/json");
+        self = self.body(serde_json::to_string_pretty(json)?);
 
         Ok(self)
     }
 
+    /// Streams the items of the given stream as newline
+    /// delimited JSON, one compact JSON value per line, so
+    /// clients can process them as they arrive. The stream
+    /// is aborted if an item fails to serialize.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::stream;
+    /// use serde_json::json;
+    /// use valar::http::Response;
+    ///
+    /// let users = stream::iter([json!({ "id": 1 }), json!({ "id": 2 })]);
+    ///
+    /// let response = Response::ok().ndjson(users).build();
+    /// ```
+    pub fn ndjson<S, J>(mut self, stream: S) -> Self
+    where
+        S: Stream<Item = J> + Send + Sync + 'static,
+        J: Serialize,
+    {
+        let lines = stream.map(|item| {
+            let mut line = serde_json::to_vec(&item)?;
+            line.push(b'\n');
+
+            Ok::<_, JsonError>(line)
+        });
+
+        self.headers.insert("Content-Type", "application/x-ndjson");
+
+        self.chunked(lines)
+    }
+
     pub fn json_or<J>(mut self, json: &J, default: String) -> Self
     where
         J: Serialize,
     {
         self.headers.insert("Content-Type", "application/json");
 
-        self.body(serde_json::to_string(json).unwrap_or(default))
+        self.body(to_json(json).unwrap_or(default))
     }
 
     pub fn json_or_else<J, D>(mut self, json: &J, default: D) -> Self
@@ -498,7 +1240,7 @@ impl ResponseBuilder {
     {
         self.headers.insert("Content-Type", "application/json");
 
-        self.body(serde_json::to_string(json).unwrap_or_else(default))
+        self.body(to_json(json).unwrap_or_else(default))
     }
 
     pub fn content_type<V>(self, value: V) -> Self
@@ -512,18 +1254,56 @@ impl ResponseBuilder {
         self.content_type("application/json")
     }
 
+    /// Uses the content type the request accepts. The
+    /// response varies on `Accept` so caches keep one copy
+    /// per content type.
     pub fn match_content_type<App: Send + Sync + 'static>(self, request: &Request<App>) -> Self {
+        let builder = self.vary("Accept");
+
         match request.headers().first("Accept") {
-            Some(header) => self.content_type(header),
-            None => self,
+            Some(header) => builder.content_type(header),
+            None => builder,
         }
     }
 
+    /// Adds the given request header to the `Vary` header,
+    /// keeping the headers already listed. See
+    /// [`Headers::append_vary`].
+    pub fn vary(mut self, header: &str) -> Self {
+        self.headers.append_vary(header);
+
+        self
+    }
+
+    /// Adds the given request headers to the `Vary` header,
+    /// keeping the headers already listed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Response;
+    ///
+    /// let response = Response::ok()
+    ///     .vary("Accept")
+    ///     .vary_by(&["Accept-Language", "Accept"])
+    ///     .build();
+    ///
+    /// assert!(response
+    ///     .headers()
+    ///     .is("Vary", "Accept, Accept-Language"));
+    /// ```
+    pub fn vary_by(self, headers: &[&str]) -> Self {
+        headers
+            .iter()
+            .fold(self, |builder, header| builder.vary(header))
+    }
+
     /// Builds the HTTP response.
     pub fn build(self) -> Response {
         let body = match (self.body, self.message) {
             (Some(body), _) => body,
-            (None, None) => String::new(),
+            (None, None) => Body::empty(),
+            (None, Some(_)) if !permits_body(&self.status) => Body::empty(),
             (None, Some(message)) => {
                 let message = match message {
                     ResponseMessage::Text(message) => message,
@@ -535,7 +1315,7 @@ impl ResponseBuilder {
                         .to_string(),
                 };
 
-                message
+                Body::from(message)
 
                 // TODO: Make this based on content type?
 
@@ -553,6 +1333,8 @@ impl ResponseBuilder {
             version: self.version,
             headers: self.headers,
             body,
+            terminators: Terminators::new(),
+            error_report: None,
         }
     }
 
@@ -585,3 +1367,509 @@ impl Default for ResponseBuilder {
         }
     }
 }
+
+/// Serializes the given value to JSON, pretty printing it
+/// when the debug mode is enabled.
+fn to_json<J>(json: &J) -> JsonResult<String>
+where
+    J: Serialize,
+{
+    match debug::enabled() {
+        true => serde_json::to_string_pretty(json),
+        false => serde_json::to_string(json),
+    }
+}
+
+/// Computes an opaque validator for the given content.
+pub(crate) fn etag_for(content: &[u8]) -> String {
+    let hash = Sha256::digest(content);
+
+    hash[..16]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Determines if the given JSON value contains the
+/// given fragment.
+fn json_contains(actual: &Value, fragment: &Value) -> bool {
+    match (actual, fragment) {
+        (Value::Object(actual), Value::Object(fragment)) => {
+            fragment.iter().all(|(key, fragment)| {
+                actual
+                    .get(key)
+                    .is_some_and(|actual| json_contains(actual, fragment))
+            })
+        }
+        (Value::Array(actual), Value::Array(fragment)) => fragment
+            .iter()
+            .all(|fragment| actual.iter().any(|actual| json_contains(actual, fragment))),
+        (actual, fragment) => actual == fragment,
+    }
+}
+
+/// Determines if a response with the given status code
+/// may contain a body.
+fn permits_body(status: &StatusCode) -> bool {
+    !(status.is_informational()
+        || *status == StatusCode::NO_CONTENT
+        || *status == StatusCode::NOT_MODIFIED)
+}
+
+/// Formats a `Content-Disposition` header value. Names
+/// that are not plain ASCII are also included using the
+/// RFC 6266 extended notation.
+fn content_disposition(disposition: &str, filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|char| match char {
+            '"' | '\\' => '_',
+            char if char.is_ascii() && !char.is_ascii_control() => char,
+            _ => '_',
+        })
+        .collect();
+
+    if fallback == filename {
+        return format!(r#"{disposition}; filename="{filename}""#);
+    }
+
+    let encoded: String = filename
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect();
+
+    format!(r#"{disposition}; filename="{fallback}"; filename*=UTF-8''{encoded}"#)
+}
+
+/// Determines if the given location stays on the given
+/// host: a path starting with a single `/`, or an HTTP
+/// URL of that host. Protocol relative locations like
+/// `//evil.com` or `/\evil.com` are rejected, as browsers
+/// follow them to other hosts.
+fn is_same_origin(location: &str, host: Option<&str>) -> bool {
+    if location.starts_with('/') {
+        return !location.starts_with("//") && !location.starts_with("/\\");
+    }
+
+    let Ok(uri) = location.parse::<Uri>() else {
+        return false;
+    };
+
+    matches!(uri.scheme_str(), Some("http" | "https"))
+        && uri
+            .authority()
+            .is_some_and(|authority| Some(authority.as_str()) == host)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::env::temp_dir;
+    use std::sync::Arc;
+
+    use futures_util::stream;
+    use http_body_util::BodyExt;
+    use serde_json::json;
+    use tokio::fs::remove_file;
+    use tokio::fs::write;
+
+    use crate::http::cache_control::CacheControl;
+    use crate::http::Body;
+    use crate::http::Cookie;
+    use crate::http::Headers;
+    use crate::http::Method;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::StatusCode;
+
+    #[tokio::test]
+    async fn it_can_stream_files() {
+        let path = temp_dir().join("valar-it-can-stream-files.pdf");
+        write(&path, [0x25, 0x50, 0x44, 0x46, 0xff, 0x00])
+            .await
+            .unwrap();
+
+        let response = Response::file(&path).await.unwrap().build();
+
+        remove_file(&path).await.unwrap();
+
+        assert!(response.is_streamed());
+        response
+            .assert_ok()
+            .assert_header_is("Content-Type", "application/pdf")
+            .assert_header_is("Content-Length", "6")
+            .assert_header_is(
+                "Content-Disposition",
+                r#"inline; filename="valar-it-can-stream-files.pdf""#,
+            );
+    }
+
+    #[tokio::test]
+    async fn it_fails_to_stream_missing_files() {
+        let path = temp_dir().join("valar-it-fails-to-stream-missing-files.txt");
+
+        assert!(Response::file(&path).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn it_can_stream_chunks_with_trailers() {
+        let chunks = stream::iter([Ok::<_, Infallible>("Hello, "), Ok("World")]);
+        let trailers = async { Headers::from([("Checksum", "abc"), ("Signature", "xyz")]) };
+
+        let response = Response::ok()
+            .header("Content-Length", "12")
+            .chunked_with_trailers(chunks, &["Checksum"], trailers)
+            .build();
+
+        assert!(response.is_streamed());
+        assert!(!response.headers().has("Content-Length"));
+        response.assert_header_is("Trailer", "Checksum");
+
+        let body = match response
+            .into_base_response(&Method::GET)
+            .unwrap()
+            .into_body()
+        {
+            Body::Stream(body) => body.collect().await.unwrap(),
+            Body::Full(_) => panic!("expected a streamed body"),
+        };
+
+        let trailers = body.trailers().unwrap();
+
+        assert_eq!(trailers["checksum"], "abc");
+        assert!(!trailers.contains_key("signature"));
+        assert_eq!(body.to_bytes(), "Hello, World");
+    }
+
+    #[tokio::test]
+    async fn it_can_stream_ndjson() {
+        let users = stream::iter([json!({ "id": 1 }), json!({ "id": 2, "name": "Erik" })]);
+
+        let response = Response::ok().ndjson(users).build();
+
+        assert!(response.is_streamed());
+        response.assert_header_is("Content-Type", "application/x-ndjson");
+
+        let body = match response
+            .into_base_response(&Method::GET)
+            .unwrap()
+            .into_body()
+        {
+            Body::Stream(body) => body.collect().await.unwrap().to_bytes(),
+            Body::Full(_) => panic!("expected a streamed body"),
+        };
+
+        assert_eq!(body, "{\"id\":1}\n{\"id\":2,\"name\":\"Erik\"}\n");
+    }
+
+    #[test]
+    fn it_can_create_attachments() {
+        Response::ok()
+            .attachment("report.pdf")
+            .build()
+            .assert_header_is(
+                "Content-Disposition",
+                r#"attachment; filename="report.pdf""#,
+            );
+
+        Response::ok()
+            .attachment("résumé.pdf")
+            .build()
+            .assert_header_is(
+                "Content-Disposition",
+                r#"attachment; filename="r_sum_.pdf"; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf"#,
+            );
+    }
+
+    #[test]
+    fn it_does_not_send_bodies_for_no_content() {
+        let response = Response::no_content().with_canonical_message().build();
+
+        assert_eq!(response.body(), "");
+
+        let response = Response::no_content()
+            .text("Hello")
+            .build()
+            .into_base_response(&Method::GET)
+            .unwrap();
+
+        assert!(response.body().as_bytes().is_empty());
+        assert!(!response.headers().contains_key("Content-Type"));
+    }
+
+    #[test]
+    fn it_does_not_send_bodies_for_not_modified() {
+        let response = Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header("Content-Length", "5")
+            .text("Hello")
+            .build()
+            .into_base_response(&Method::GET)
+            .unwrap();
+
+        assert!(response.body().as_bytes().is_empty());
+        assert!(!response.headers().contains_key("Content-Type"));
+        assert_eq!(response.headers()["Content-Length"], "5");
+    }
+
+    #[test]
+    fn it_does_not_send_bodies_for_head_requests() {
+        let response = Response::ok()
+            .text("Hello")
+            .build()
+            .into_base_response(&Method::HEAD)
+            .unwrap();
+
+        assert!(response.body().as_bytes().is_empty());
+        assert_eq!(response.headers()["Content-Type"], "text/plain");
+        assert_eq!(response.headers()["Content-Length"], "5");
+    }
+
+    #[test]
+    fn it_can_build_empty_responses() {
+        let response = Response::ok()
+            .json_content_type()
+            .with_canonical_message()
+            .empty()
+            .build();
+
+        assert_eq!(response.body(), "");
+        assert!(!response.headers().has("Content-Type"));
+    }
+
+    #[test]
+    fn it_can_set_etags() {
+        Response::ok()
+            .etag("abc")
+            .build()
+            .assert_header_is("ETag", r#""abc""#);
+
+        Response::ok()
+            .etag(r#"W/"abc""#)
+            .build()
+            .assert_header_is("ETag", r#"W/"abc""#);
+
+        Response::ok()
+            .weak_etag("abc")
+            .build()
+            .assert_header_is("ETag", r#"W/"abc""#);
+    }
+
+    #[test]
+    fn it_can_set_etags_from_the_body() {
+        let first = Response::ok().text("Hello").etag_from_body().build();
+        let second = Response::ok().text("Hello").etag_from_body().build();
+        let third = Response::ok().text("World").etag_from_body().build();
+
+        let etag = first.headers().first("ETag").unwrap();
+
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(etag.len(), 34);
+        assert_eq!(second.headers().first("ETag"), Some(etag));
+        assert_ne!(third.headers().first("ETag"), Some(etag));
+        assert!(!Response::ok()
+            .etag_from_body()
+            .build()
+            .headers()
+            .has("ETag"));
+    }
+
+    #[test]
+    fn it_can_set_cache_control() {
+        let response = Response::ok()
+            .cache_control(
+                CacheControl::new()
+                    .public()
+                    .max_age(3600)
+                    .stale_while_revalidate(60),
+            )
+            .build();
+
+        assert!(response.headers().is(
+            "Cache-Control",
+            "public, max-age=3600, stale-while-revalidate=60"
+        ));
+
+        let response = Response::ok().no_cache().no_store().build();
+
+        assert!(response.headers().is("Cache-Control", "no-store"));
+        assert!(Response::ok()
+            .no_cache()
+            .build()
+            .headers()
+            .is("Cache-Control", "no-cache"));
+    }
+
+    #[test]
+    fn it_can_redirect_back() {
+        struct App;
+        let app = Arc::new(App);
+
+        let request = Request::builder()
+            .headers([
+                ("Host", "example.com"),
+                ("Referer", "http://example.com/foo?bar=baz"),
+            ])
+            .build(app.clone());
+
+        Response::back(&request)
+            .build()
+            .assert_status(&StatusCode::SEE_OTHER)
+            .assert_header_is("Location", "http://example.com/foo?bar=baz");
+
+        let request = Request::builder().build(app.clone());
+
+        Response::back(&request)
+            .build()
+            .assert_header_is("Location", "/");
+
+        let request = Request::builder()
+            .headers([("Host", "example.com"), ("Referer", "http://evil.com/")])
+            .build(app.clone());
+
+        Response::ok()
+            .back_or(&request, "/home")
+            .build()
+            .assert_header_is("Location", "/home");
+
+        let request = Request::builder()
+            .headers([("Host", "example.com"), ("Referer", "/foo")])
+            .build(app.clone());
+
+        Response::back(&request)
+            .build()
+            .assert_header_is("Location", "/foo");
+
+        for referer in [
+            "//evil.com/",
+            "/\\evil.com/",
+            "https://evil.com/",
+            "javascript:alert(1)",
+            "https://example.com.evil.com/",
+            "evil.com",
+        ] {
+            let request = Request::builder()
+                .headers([("Host", "example.com"), ("Referer", referer)])
+                .build(app.clone());
+
+            Response::back(&request)
+                .build()
+                .assert_header_is("Location", "/");
+        }
+    }
+
+    #[test]
+    fn it_can_build_common_status_codes() {
+        let responses = [
+            (Response::bad_request(), StatusCode::BAD_REQUEST),
+            (Response::forbidden(), StatusCode::FORBIDDEN),
+            (Response::conflict(), StatusCode::CONFLICT),
+            (Response::gone(), StatusCode::GONE),
+            (
+                Response::unprocessable_entity(),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (Response::too_many_requests(), StatusCode::TOO_MANY_REQUESTS),
+            (Response::not_implemented(), StatusCode::NOT_IMPLEMENTED),
+            (
+                Response::service_unavailable(),
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+        ];
+
+        for (builder, status) in responses {
+            builder.build().assert_status(&status);
+        }
+
+        Response::unauthorized("Basic")
+            .build()
+            .assert_status(&StatusCode::UNAUTHORIZED)
+            .assert_header_is("WWW-Authenticate", "Basic");
+    }
+
+    #[test]
+    fn it_can_pretty_print_json() {
+        let response = Response::ok()
+            .json_pretty(&json!({ "name": "Erik" }))
+            .unwrap()
+            .build();
+
+        response.assert_is_json();
+        assert_eq!(response.body(), "{\n  \"name\": \"Erik\"\n}");
+    }
+
+    #[test]
+    fn it_can_assert_json_bodies() {
+        let response = Response::ok()
+            .json(&json!({
+                "id": 1,
+                "name": "Erik",
+                "roles": [{ "name": "admin" }, { "name": "user" }],
+            }))
+            .unwrap()
+            .build();
+
+        response
+            .assert_body_contains("Erik")
+            .assert_json(json!({
+                "id": 1,
+                "name": "Erik",
+                "roles": [{ "name": "admin" }, { "name": "user" }],
+            }))
+            .assert_json_contains(json!({ "name": "Erik" }))
+            .assert_json_contains(json!({ "roles": [{ "name": "user" }] }));
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_fails_json_assertions_on_missing_fragments() {
+        Response::ok()
+            .json(&json!({ "id": 1, "roles": ["admin"] }))
+            .unwrap()
+            .build()
+            .assert_json_contains(json!({ "roles": ["user"] }));
+    }
+
+    #[test]
+    fn it_can_queue_cookies() {
+        let response = Response::ok()
+            .cookie(Cookie::builder("theme", "dark").build())
+            .cookie(Cookie::builder("locale", "en").build())
+            .queue_cookie(Cookie::builder("theme", "light").build())
+            .build();
+
+        assert_eq!(
+            response.headers().get("Set-Cookie"),
+            Some(&vec!["locale=en".to_string(), "theme=light".to_string()])
+        );
+    }
+
+    #[test]
+    fn it_varies_on_negotiated_content_types() {
+        struct App;
+        let request = Request::builder()
+            .header("Accept", "application/json")
+            .build(Arc::new(App));
+
+        Response::ok()
+            .vary("Origin")
+            .match_content_type(&request)
+            .vary_by(&["accept", "Accept-Language"])
+            .build()
+            .assert_header_is("Content-Type", "application/json")
+            .assert_header_is("Vary", "Origin, Accept, Accept-Language");
+    }
+
+    #[test]
+    fn it_keeps_binary_bodies_intact() {
+        let response = Response::ok().body(vec![0xff, 0x00, 0x10]).build();
+
+        assert_eq!(response.bytes(), &[0xff, 0x00, 0x10]);
+        assert_eq!(response.body(), "");
+    }
+}
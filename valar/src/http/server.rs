@@ -1,19 +1,251 @@
+pub mod access_log;
+#[cfg(feature = "http3")]
+mod http3;
+mod listener;
+
 use std::convert::Infallible;
+use std::future::Future;
+use std::io::Result as IoResult;
 use std::net::SocketAddr;
+#[cfg(any(unix, feature = "http3"))]
+use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
 
 use colored::Colorize;
+use http_body_util::Full;
+use hyper::body::Body;
+use hyper::body::Bytes;
+use hyper::body::Incoming;
+use hyper::header::HeaderValue;
+use hyper::header::ALT_SVC;
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
+use hyper::Request as BaseRequest;
+use hyper::Response as BaseResponse;
+use hyper::StatusCode;
+use hyper::Version;
+use hyper_util::rt::TokioIo;
 use log::debug;
-use log::info;
-use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 
+use crate::debug as debug_mode;
+use crate::http::body::BoxError;
+use crate::http::server::access_log::Entry;
+#[cfg(feature = "http3")]
+use crate::http::server::http3::Http3;
+use crate::http::server::listener::accept;
+use crate::http::server::listener::Bind;
+use crate::http::server::listener::Listener;
+use crate::http::server::listener::Stream;
+use crate::http::terminate::Terminating;
+use crate::http::Health;
 use crate::routing::router::Compiled;
+use crate::routing::router::DEFAULT_MAX_BODY_SIZE;
 use crate::routing::Router;
 
+pub use access_log::AccessLog;
+
+/// A hook is work that runs at a point of the lifecycle
+/// of the server, like warming caches when it boots or
+/// closing database pools once it stops.
+pub type Hook = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// Stops a running server. The server stops accepting
+/// connections, waits for the in-flight ones to finish and
+/// runs its shutdown hooks.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::Server;
+///
+/// let server = Server::builder().build();
+/// let handle = server.shutdown_handle();
+///
+/// // Later, from anywhere else...
+/// handle.shutdown();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle(Arc<watch::Sender<bool>>);
+
+impl ShutdownHandle {
+    fn new() -> Self {
+        Self(Arc::new(watch::Sender::new(false)))
+    }
+
+    /// Starts shutting down the server.
+    pub fn shutdown(&self) {
+        self.0.send_replace(true);
+    }
+
+    /// Determines if the server is shutting down.
+    pub fn is_shutting_down(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    fn subscribe(&self) -> watch::Receiver<bool> {
+        self.0.subscribe()
+    }
+}
+
+/// Waits until the server starts shutting down.
+async fn shutting_down(receiver: &mut watch::Receiver<bool>) {
+    let _ = receiver.wait_for(|shutting_down| *shutting_down).await;
+}
+
+/// Waits for a `SIGINT` or, on unix, a `SIGTERM`.
+async fn signal() {
+    let interrupt = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::signal;
+        use tokio::signal::unix::SignalKind;
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => {},
+        _ = terminate => {},
+    }
+}
+
+/// Everything the connections need to answer their
+/// requests.
+struct Dispatcher<App: Send + Sync + 'static> {
+    app: Arc<App>,
+    router: Arc<Router<App, Compiled>>,
+    health: Option<Arc<Health>>,
+    access_log: Option<Arc<AccessLog>>,
+    alt_svc: Option<HeaderValue>,
+    max_body_size: u64,
+}
+
+impl<App: Send + Sync + 'static> Dispatcher<App> {
+    /// Handles a request with the router, unless it
+    /// targets a health endpoint. The terminators of the
+    /// response run once its body has been written.
+    async fn serve<B>(
+        self: Arc<Self>,
+        request: BaseRequest<B>,
+        remote_addr: Option<SocketAddr>,
+    ) -> Result<BaseResponse<Terminating>, Infallible>
+    where
+        B: Body<Data = Bytes>,
+        B::Error: Into<BoxError>,
+    {
+        let started = Instant::now();
+        let method = request.method().clone();
+        let version = request.version();
+        let entry = self.access_log.as_ref().map(|_| Entry {
+            remote_addr,
+            method: method.clone(),
+            uri: request.uri().clone(),
+            version,
+            status: StatusCode::OK,
+            size: None,
+            referer: header(&request, "Referer"),
+            user_agent: header(&request, "User-Agent"),
+            duration: Duration::ZERO,
+            time: SystemTime::now(),
+        });
+
+        let checked = match &self.health {
+            Some(health) => health.respond(&method, request.uri().path()).await,
+            None => None,
+        };
+
+        let response = match checked {
+            Some(response) => response,
+            None => {
+                self.router
+                    .handle_base(self.app.clone(), request, remote_addr, self.max_body_size)
+                    .await
+            }
+        };
+        let mut response = response.into_http_response(&method);
+
+        if let Some(alt_svc) = &self.alt_svc {
+            if version != Version::HTTP_3 {
+                response.headers_mut().insert(ALT_SVC, alt_svc.clone());
+            }
+        }
+
+        if let (Some(access_log), Some(mut entry)) = (&self.access_log, entry) {
+            entry.status = response.status();
+            entry.size = response.body().size_hint().exact();
+            entry.duration = started.elapsed();
+
+            access_log.log(&entry);
+        }
+
+        Ok(response)
+    }
+}
+
+/// Returns the value of the given header of the request.
+fn header<B>(request: &BaseRequest<B>, name: &str) -> Option<String> {
+    request
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string)
+}
+
+/// Returns the `Alt-Svc` header value that advertises an
+/// HTTP/3 listener on the given UDP port.
+fn alt_svc(port: u16) -> String {
+    format!(r#"h3=":{port}"; ma=86400"#)
+}
+
+/// Whether an accepted connection may be served.
+enum Admission {
+    /// There is no connection limit.
+    Unlimited,
+
+    /// The connection fits in the limit while the permit
+    /// is held.
+    Admitted(OwnedSemaphorePermit),
+
+    /// The limit is reached and the connection must be
+    /// turned away.
+    Saturated,
+}
+
 pub struct Server {
-    address: SocketAddr,
+    binds: Vec<Bind>,
+    permits: Option<Arc<Semaphore>>,
+    reject_when_saturated: bool,
+    max_body_size: u64,
+    health: Option<Arc<Health>>,
+    access_log: Option<Arc<AccessLog>>,
+    #[cfg(feature = "http3")]
+    http3: Option<Http3>,
+    grace_period: Duration,
+    shutdown: ShutdownHandle,
+    addresses: watch::Sender<Option<Vec<SocketAddr>>>,
+    boot_hooks: Mutex<Vec<Hook>>,
+    ready_hooks: Mutex<Vec<Hook>>,
+    shutdown_hooks: Mutex<Vec<Hook>>,
 }
 
 impl Server {
@@ -21,12 +253,113 @@ impl Server {
         ServerBuilder::new()
     }
 
-    async fn handler(
-        _: Request<hyper::body::Incoming>,
-    ) -> Result<Response<Full<Bytes>>, Infallible> {
-        Ok(BaseResponse::new(Full::new(Bytes::from("Hello, World!"))))
+    /// Returns a handle that stops the server. The server
+    /// also stops on `SIGINT` and `SIGTERM`.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// Returns the first TCP address the server listens
+    /// on, once it has started. When listening on port
+    /// `0`, it contains the port picked by the system.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addrs().first().copied()
+    }
+
+    /// Returns all the TCP addresses the server listens
+    /// on, once it has started.
+    pub fn local_addrs(&self) -> Vec<SocketAddr> {
+        self.addresses.borrow().clone().unwrap_or_default()
+    }
+
+    /// Waits until the server listens on all its addresses
+    /// and returns the first TCP one, if any. It is meant
+    /// for tests that run a real server on a free port.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use valar::http::Server;
+    /// use valar::routing::router::Compiled;
+    /// use valar::routing::Router;
+    ///
+    /// async fn run(router: Arc<Router<(), Compiled>>) {
+    ///     let server = Arc::new(Server::builder().address(([127, 0, 0, 1], 0)).build());
+    ///
+    ///     tokio::spawn({
+    ///         let server = server.clone();
+    ///
+    ///         async move { server.start(Arc::new(()), router).await }
+    ///     });
+    ///
+    ///     let address = server.listening().await.unwrap();
+    ///
+    ///     println!("Testing against http://{address}");
+    /// }
+    /// ```
+    pub async fn listening(&self) -> Option<SocketAddr> {
+        let mut addresses = self.addresses.subscribe();
+        let _ = addresses.wait_for(Option::is_some).await;
+
+        self.local_addr()
+    }
+
+    /// Answers the connections turned away because the
+    /// server is saturated.
+    async fn unavailable(
+        _: BaseRequest<Incoming>,
+    ) -> Result<BaseResponse<Full<Bytes>>, Infallible> {
+        let mut response = BaseResponse::new(Full::new(Bytes::from("Service Unavailable")));
+
+        *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+        response
+            .headers_mut()
+            .insert("Retry-After", "1".parse().unwrap());
+
+        Ok(response)
+    }
+
+    /// Accepts a new connection from the given listeners.
+    /// With a connection limit, it waits for a free slot
+    /// before accepting or, when saturated connections are
+    /// rejected, accepts right away and reports whether
+    /// there is room for it.
+    async fn accept(
+        &self,
+        listeners: &[Listener],
+    ) -> IoResult<(Stream, Option<SocketAddr>, Admission)> {
+        let Some(permits) = &self.permits else {
+            let (stream, address) = accept(listeners).await?;
+
+            return Ok((stream, address, Admission::Unlimited));
+        };
+
+        if self.reject_when_saturated {
+            let (stream, address) = accept(listeners).await?;
+            let admission = match permits.clone().try_acquire_owned() {
+                Ok(permit) => Admission::Admitted(permit),
+                Err(_) => Admission::Saturated,
+            };
+
+            return Ok((stream, address, admission));
+        }
+
+        let permit = permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("The connection semaphore is never closed");
+        let (stream, address) = accept(listeners).await?;
+
+        Ok((stream, address, Admission::Admitted(permit)))
     }
 
+    /// Starts the server and runs it until it is shut down,
+    /// either with a signal or with its shutdown handle.
+    /// Every connection is served on its own task, and the
+    /// tasks are tracked so shutting down waits for them.
     pub async fn start<App: Send + Sync + 'static>(
         &self,
         app: Arc<App>,
@@ -36,55 +369,203 @@ impl Server {
         println!("{}", "Lambda Studio • https://λ.studio".italic().dimmed());
         println!();
 
-        let Ok(listener) = TcpListener::bind(&self.address).await else {
-            eprintln!("Failed to bind to address: {}", self.address);
-            return;
+        Self::run_hooks(&self.boot_hooks).await;
+
+        let mut listeners = Vec::new();
+
+        for bind in &self.binds {
+            match Listener::bind(bind).await {
+                Ok(listener) => listeners.push(listener),
+                Err(err) => {
+                    eprintln!("Failed to bind to {}: {err}", bind.describe());
+                    self.addresses.send_replace(Some(Vec::new()));
+                    return;
+                }
+            }
+        }
+
+        #[cfg(feature = "http3")]
+        let endpoint = match &self.http3 {
+            Some(http3) => match http3.bind() {
+                Ok(endpoint) => Some(endpoint),
+                Err(err) => {
+                    eprintln!("Failed to bind to {}: {err}", http3.describe());
+                    self.addresses.send_replace(Some(Vec::new()));
+                    return;
+                }
+            },
+            None => None,
         };
 
-        tokio::task::spawn(async move {
-            loop {
-                let Ok((stream, _)) = listener.accept().await else {
-                    eprintln!("Failed to accept connection");
-                    continue;
-                };
+        let addresses = listeners.iter().filter_map(Listener::local_addr).collect();
 
-                let io = TokioIo::new(stream);
+        self.addresses.send_replace(Some(addresses));
 
-                if let Err(err) = http1::Builder::new()
-                    // `service_fn` converts our function in a `Service`
-                    .serve_connection(io, service_fn(handler))
-                    .await
-                {
-                    println!("Error serving connection: {:?}", err);
+        let handle = self.shutdown_handle();
+
+        tokio::spawn(async move {
+            signal().await;
+            handle.shutdown();
+        });
+
+        debug!("Maximum request body size: {} bytes", self.max_body_size);
+
+        #[cfg(feature = "http3")]
+        let http3 = endpoint
+            .as_ref()
+            .and_then(|endpoint| endpoint.local_addr().ok());
+
+        #[cfg(not(feature = "http3"))]
+        let http3 = None;
+
+        self.banner(&listeners, http3);
+
+        let alt_svc =
+            http3.and_then(|address| HeaderValue::from_str(&alt_svc(address.port())).ok());
+
+        Self::run_hooks(&self.ready_hooks).await;
+
+        let dispatcher = Arc::new(Dispatcher {
+            app,
+            router,
+            health: self.health.clone(),
+            access_log: self.access_log.clone(),
+            alt_svc,
+            max_body_size: self.max_body_size,
+        });
+
+        let mut connections = JoinSet::new();
+        let mut shutdown = self.shutdown.subscribe();
+
+        #[cfg(feature = "http3")]
+        if let Some(endpoint) = endpoint {
+            connections.spawn(http3::serve(
+                endpoint,
+                dispatcher.clone(),
+                self.shutdown.subscribe(),
+            ));
+        }
+
+        loop {
+            tokio::select! {
+                accepted = self.accept(&listeners) => {
+                    let Ok((stream, remote_addr, admission)) = accepted else {
+                        eprintln!("Failed to accept connection");
+                        continue;
+                    };
+
+                    let permit = match admission {
+                        Admission::Unlimited => None,
+                        Admission::Admitted(permit) => Some(permit),
+                        Admission::Saturated => {
+                            connections.spawn(async move {
+                                let io = TokioIo::new(stream);
+                                let _ = http1::Builder::new()
+                                    .keep_alive(false)
+                                    .serve_connection(io, service_fn(Self::unavailable))
+                                    .await;
+                            });
+
+                            continue;
+                        }
+                    };
+
+                    let mut shutdown = self.shutdown.subscribe();
+                    let dispatcher = dispatcher.clone();
+
+                    connections.spawn(async move {
+                        let io = TokioIo::new(stream);
+                        let service = service_fn(move |request| {
+                            dispatcher.clone().serve(request, remote_addr)
+                        });
+                        let connection = http1::Builder::new().serve_connection(io, service);
+
+                        tokio::pin!(connection);
+
+                        let result = tokio::select! {
+                            result = connection.as_mut() => result,
+                            _ = shutting_down(&mut shutdown) => {
+                                connection.as_mut().graceful_shutdown();
+                                connection.await
+                            }
+                        };
+
+                        if let Err(err) = result {
+                            println!("Error serving connection: {:?}", err);
+                        }
+
+                        drop(permit);
+                    });
                 }
+                Some(_) = connections.join_next() => {}
+                _ = shutting_down(&mut shutdown) => break,
             }
-        });
+        }
 
-        // let service = make_service_fn(move |conn| {
-        //     debug!("Incoming connection: {:?}", conn);
-        //     let app = app.clone();
-        //     let router = router.clone();
+        drop(listeners);
+
+        self.drain(connections).await;
+
+        Self::run_hooks(&self.shutdown_hooks).await;
+
+        println!("{}", "Server stopped.".dimmed());
+    }
 
-        //     async move {
-        //         Ok::<_, Infallible>(service_fn(move |request| {
-        //             info!("{} {}", request.method(),
-        // request.uri());             let app =
-        // app.clone();             let router =
-        // router.clone();
+    /// Runs the given hooks in the order they were
+    /// registered. Each hook runs once.
+    async fn run_hooks(hooks: &Mutex<Vec<Hook>>) {
+        let hooks = std::mem::take(&mut *hooks.lock().unwrap());
 
-        //             async move { router.handle_base(app,
-        // request).await.into_base_response() }         }))
-        //     }
-        // });
+        for hook in hooks {
+            hook().await;
+        }
+    }
 
-        // let server =
-        // BaseServer::bind(&self.address).serve(service);
+    /// Waits for the given connections to finish, up to
+    /// the grace period. The ones still open after it are
+    /// dropped.
+    async fn drain(&self, mut connections: JoinSet<()>) {
+        if connections.is_empty() {
+            return;
+        }
 
         println!(
-            "Server running at: {}{}",
-            "http://".bold(),
-            self.address.to_string().bold()
+            "{}",
+            format!(
+                "Shutting down, waiting for {} connection(s)...",
+                connections.len()
+            )
+            .dimmed()
         );
+
+        let drained = tokio::time::timeout(self.grace_period, async {
+            while connections.join_next().await.is_some() {}
+        })
+        .await;
+
+        if drained.is_err() {
+            eprintln!(
+                "Dropping {} connection(s) after the grace period",
+                connections.len()
+            );
+            connections.shutdown().await;
+        }
+    }
+
+    fn banner(&self, listeners: &[Listener], http3: Option<SocketAddr>) {
+        for (bind, listener) in self.binds.iter().zip(listeners) {
+            let target = match listener.local_addr() {
+                Some(address) => Bind::Tcp(address).describe(),
+                None => bind.describe(),
+            };
+
+            println!("Server running at: {}", target.bold());
+        }
+        if let Some(address) = http3 {
+            let target = format!("https://{address} (HTTP/3)");
+
+            println!("Server running at: {}", target.bold());
+        }
         println!();
 
         println!(
@@ -106,16 +587,43 @@ impl Server {
                 .italic()
         );
         println!();
-
-        // if let Err(e) = server.await {
-        //     eprintln!("server error: {}", e);
-        // }
     }
 }
 
-#[derive(Default)]
 pub struct ServerBuilder {
-    address: Option<SocketAddr>,
+    binds: Vec<Bind>,
+    max_connections: Option<usize>,
+    reject_when_saturated: bool,
+    max_body_size: u64,
+    health: Option<Health>,
+    access_log: Option<AccessLog>,
+    #[cfg(feature = "http3")]
+    http3: Option<Http3>,
+    debug: Option<bool>,
+    grace_period: Duration,
+    boot_hooks: Vec<Hook>,
+    ready_hooks: Vec<Hook>,
+    shutdown_hooks: Vec<Hook>,
+}
+
+impl Default for ServerBuilder {
+    fn default() -> Self {
+        Self {
+            binds: Vec::new(),
+            max_connections: None,
+            reject_when_saturated: false,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            health: None,
+            access_log: None,
+            #[cfg(feature = "http3")]
+            http3: None,
+            debug: None,
+            grace_period: Duration::from_secs(30),
+            boot_hooks: Vec::new(),
+            ready_hooks: Vec::new(),
+            shutdown_hooks: Vec::new(),
+        }
+    }
 }
 
 impl ServerBuilder {
@@ -123,20 +631,609 @@ impl ServerBuilder {
         Self::default()
     }
 
+    /// Listens on the given TCP address. Calling it again,
+    /// or together with `unix_socket`, listens on every
+    /// given target with the same router and application.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::net::Ipv6Addr;
+    ///
+    /// use valar::http::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .address(([127, 0, 0, 1], 3000))
+    ///     .address((Ipv6Addr::LOCALHOST, 3000))
+    ///     .build();
+    /// ```
     pub fn address<A>(mut self, address: A) -> Self
     where
         A: Into<SocketAddr>,
     {
-        self.address = Some(address.into());
+        self.binds.push(Bind::Tcp(address.into()));
+
+        self
+    }
+
+    /// Listens on all the given TCP addresses.
+    pub fn addresses<I, A>(self, addresses: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<SocketAddr>,
+    {
+        addresses
+            .into_iter()
+            .fold(self, |builder, address| builder.address(address))
+    }
+
+    /// Listens on a unix domain socket at the given path.
+    /// A stale socket file left by a previous run is
+    /// replaced and the file is removed once the server
+    /// stops.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .unix_socket("/run/app/app.sock")
+    ///     .socket_permissions(0o660)
+    ///     .build();
+    /// ```
+    #[cfg(unix)]
+    pub fn unix_socket<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.binds.push(Bind::Unix {
+            path: path.into(),
+            permissions: None,
+        });
+
+        self
+    }
+
+    /// Sets the permissions of the last unix domain socket
+    /// file, like `0o660` to let a proxy in the same group
+    /// connect. Does nothing unless `unix_socket` is used.
+    #[cfg(unix)]
+    pub fn socket_permissions(mut self, mode: u32) -> Self {
+        let last = self
+            .binds
+            .iter_mut()
+            .rev()
+            .find(|bind| matches!(bind, Bind::Unix { .. }));
+
+        if let Some(Bind::Unix { permissions, .. }) = last {
+            *permissions = Some(mode);
+        }
+
+        self
+    }
+
+    /// Limits the number of connections served at once.
+    /// Once reached, new connections wait in the listen
+    /// backlog until another one closes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .max_connections(10_000)
+    ///     .reject_when_saturated()
+    ///     .build();
+    /// ```
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = Some(max);
+
+        self
+    }
+
+    /// Answers the connections over the limit with a `503
+    /// Service Unavailable` response instead of leaving
+    /// them waiting. Does nothing unless `max_connections`
+    /// is used.
+    pub fn reject_when_saturated(mut self) -> Self {
+        self.reject_when_saturated = true;
+
+        self
+    }
+
+    /// Sets the maximum size, in bytes, of request bodies
+    /// for the routers that don't set their own. Defaults
+    /// to 2 MB.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::middleware::MB;
+    /// use valar::http::Server;
+    ///
+    /// let server = Server::builder().max_body_size(64 * MB).build();
+    /// ```
+    pub fn max_body_size(mut self, bytes: u64) -> Self {
+        self.max_body_size = bytes;
+
+        self
+    }
+
+    /// Answers the liveness and readiness endpoints of the
+    /// given health checks before any request reaches the
+    /// router.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Health;
+    /// use valar::http::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .health(Health::new().liveness("/live").readiness("/ready"))
+    ///     .build();
+    /// ```
+    pub fn health(mut self, health: Health) -> Self {
+        self.health = Some(health);
+
+        self
+    }
+
+    /// Logs every request answered by the server to the
+    /// given access log.
+    pub fn access_log(mut self, access_log: AccessLog) -> Self {
+        self.access_log = Some(access_log);
+
+        self
+    }
+
+    /// Serves the router over HTTP/3 on the given UDP
+    /// address, using the PEM certificate chain and private
+    /// key for TLS. The TCP listeners advertise it to the
+    /// clients with the `Alt-Svc` header. HTTP/3 support
+    /// is experimental.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .address(([0, 0, 0, 0], 8443))
+    ///     .http3(([0, 0, 0, 0], 8443), "certs/cert.pem", "certs/key.pem")
+    ///     .build();
+    /// ```
+    #[cfg(feature = "http3")]
+    pub fn http3<A, C, K>(mut self, address: A, certificate: C, key: K) -> Self
+    where
+        A: Into<SocketAddr>,
+        C: Into<PathBuf>,
+        K: Into<PathBuf>,
+    {
+        self.http3 = Some(Http3 {
+            address: address.into(),
+            certificate: certificate.into(),
+            key: key.into(),
+        });
+
+        self
+    }
+
+    /// Enables or disables the debug mode, which renders
+    /// detailed error pages with the error chain, the
+    /// backtrace and the request details. When not set,
+    /// the `VALAR_DEBUG` environment variable is used. It
+    /// should only be enabled while developing.
+    pub fn debug(mut self, enabled: bool) -> Self {
+        self.debug = Some(enabled);
+
+        self
+    }
+
+    /// Sets how long the in-flight connections may take to
+    /// finish when shutting down. Defaults to 30 seconds.
+    pub fn grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+
+        self
+    }
+
+    /// Registers work to run when the server starts, before
+    /// it listens for connections, like warming caches or
+    /// running migrations. The hooks run in the order they
+    /// were registered.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .on_boot(|| async {
+    ///         println!("Warming the cache...");
+    ///     })
+    ///     .on_ready(|| async {
+    ///         println!("Accepting connections");
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn on_boot<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.boot_hooks.push(Box::new(move || Box::pin(hook())));
+
+        self
+    }
+
+    /// Registers work to run once the server listens on
+    /// all its addresses, right before it accepts the
+    /// first connection. The hooks run in the order they
+    /// were registered.
+    pub fn on_ready<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.ready_hooks.push(Box::new(move || Box::pin(hook())));
+
+        self
+    }
+
+    /// Registers work to run once the server has stopped.
+    /// The hooks run in the order they were registered.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use valar::http::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .grace_period(Duration::from_secs(10))
+    ///     .on_shutdown(|| async {
+    ///         println!("Closing the database pool...");
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn on_shutdown<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.shutdown_hooks.push(Box::new(move || Box::pin(hook())));
 
         self
     }
 
     pub fn build(self) -> Server {
+        if let Some(enabled) = self.debug.or_else(debug_mode::from_env) {
+            debug_mode::set(enabled);
+        }
+
         Server {
-            address: self
-                .address
-                .unwrap_or_else(|| SocketAddr::from(([127, 0, 0, 1], 3000))),
+            binds: match self.binds.is_empty() {
+                true => vec![Bind::Tcp(SocketAddr::from(([127, 0, 0, 1], 3000)))],
+                false => self.binds,
+            },
+            permits: self
+                .max_connections
+                .map(|max| Arc::new(Semaphore::new(max))),
+            reject_when_saturated: self.reject_when_saturated,
+            max_body_size: self.max_body_size,
+            health: self.health.map(Arc::new),
+            access_log: self.access_log.map(Arc::new),
+            #[cfg(feature = "http3")]
+            http3: self.http3,
+            grace_period: self.grace_period,
+            shutdown: ShutdownHandle::new(),
+            addresses: watch::Sender::new(None),
+            boot_hooks: Mutex::new(self.boot_hooks),
+            ready_hooks: Mutex::new(self.ready_hooks),
+            shutdown_hooks: Mutex::new(self.shutdown_hooks),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use tokio::net::TcpStream;
+    use tokio::sync::Barrier;
+
+    use crate::http::server::alt_svc;
+    use crate::http::server::listener::Bind;
+    use crate::http::server::listener::Listener;
+    use crate::http::server::Admission;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::Server;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App {
+        terminated: Arc<AtomicBool>,
+        rendezvous: Barrier,
+    }
+
+    impl Default for App {
+        fn default() -> Self {
+            Self {
+                terminated: Arc::default(),
+                rendezvous: Barrier::new(2),
+            }
+        }
+    }
+
+    async fn handler(_request: Request<App>) -> Result {
+        Response::ok().into_ok()
+    }
+
+    async fn wait(request: Request<App>) -> Result {
+        request.app().rendezvous.wait().await;
+
+        Response::ok().into_ok()
+    }
+
+    async fn greet(request: Request<App>) -> Result {
+        let ran = request.app().terminated.clone();
+
+        request
+            .after_response(move || async move { ran.store(true, Ordering::SeqCst) })
+            .await;
+
+        Response::ok()
+            .body(format!("Hello, {}!", request.body()))
+            .into_ok()
+    }
+
+    #[tokio::test]
+    async fn it_can_shut_down_gracefully() {
+        let closed = Arc::new(AtomicBool::new(false));
+        let flag = closed.clone();
+
+        let server = Server::builder()
+            .address(([127, 0, 0, 1], 0))
+            .on_shutdown(move || async move { flag.store(true, Ordering::SeqCst) })
+            .build();
+
+        let router = Arc::new(
+            Router::from_iter([Route::get("/", handler)])
+                .compile()
+                .unwrap(),
+        );
+        let handle = server.shutdown_handle();
+
+        handle.shutdown();
+        server.start(Arc::new(App::default()), router).await;
+
+        assert!(handle.is_shutting_down());
+        assert!(closed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn it_can_run_lifecycle_hooks() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let event = |name: &'static str| {
+            let events = events.clone();
+
+            move || async move { events.lock().unwrap().push(name) }
+        };
+
+        let server = Server::builder()
+            .address(([127, 0, 0, 1], 0))
+            .on_shutdown(event("shutdown"))
+            .on_ready(event("ready"))
+            .on_boot(event("boot"))
+            .build();
+
+        let router = Arc::new(
+            Router::from_iter([Route::get("/", handler)])
+                .compile()
+                .unwrap(),
+        );
+
+        server.shutdown_handle().shutdown();
+        server.start(Arc::new(App::default()), router).await;
+
+        assert_eq!(*events.lock().unwrap(), ["boot", "ready", "shutdown"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_can_serve_requests() {
+        use tokio::io::AsyncReadExt;
+        use tokio::io::AsyncWriteExt;
+        use tokio::net::UnixStream;
+        use uuid::Uuid;
+
+        let path = std::env::temp_dir().join(format!("valar-{}.sock", Uuid::now_v7()));
+        let server = Arc::new(Server::builder().unix_socket(&path).build());
+        let app = Arc::new(App::default());
+        let router = Arc::new(
+            Router::from_iter([Route::post("/", greet)])
+                .compile()
+                .unwrap(),
+        );
+
+        let running = tokio::spawn({
+            let server = server.clone();
+            let app = app.clone();
+
+            async move { server.start(app, router).await }
+        });
+
+        let mut stream = loop {
+            match UnixStream::connect(&path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::task::yield_now().await,
+            }
+        };
+
+        stream
+            .write_all(b"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\nConnection: close\r\n\r\nValar")
+            .await
+            .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        server.shutdown_handle().shutdown();
+        running.await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("Hello, Valar!"));
+        assert!(tokio::time::timeout(Duration::from_secs(1), async {
+            while !app.terminated.load(Ordering::SeqCst) {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_can_serve_connections_concurrently() {
+        use tokio::io::AsyncReadExt;
+        use tokio::io::AsyncWriteExt;
+        use tokio::net::UnixStream;
+        use uuid::Uuid;
+
+        let path = std::env::temp_dir().join(format!("valar-{}.sock", Uuid::now_v7()));
+        let server = Arc::new(Server::builder().unix_socket(&path).build());
+        let router = Arc::new(
+            Router::from_iter([Route::get("/wait", wait)])
+                .compile()
+                .unwrap(),
+        );
+
+        let running = tokio::spawn({
+            let server = server.clone();
+
+            async move { server.start(Arc::new(App::default()), router).await }
+        });
+
+        let request = |path: std::path::PathBuf| async move {
+            let mut stream = loop {
+                match UnixStream::connect(&path).await {
+                    Ok(stream) => break stream,
+                    Err(_) => tokio::task::yield_now().await,
+                }
+            };
+
+            stream
+                .write_all(b"GET /wait HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+
+            response
+        };
+
+        // Each handler waits for the other one, so both
+        // only finish when they are served at once.
+        let responses = tokio::time::timeout(
+            Duration::from_secs(5),
+            futures_util::future::join(request(path.clone()), request(path.clone())),
+        )
+        .await;
+
+        server.shutdown_handle().shutdown();
+        running.await.unwrap();
+
+        let (first, second) = responses.expect("connections were served one at a time");
+
+        assert!(first.starts_with("HTTP/1.1 200 OK"));
+        assert!(second.starts_with("HTTP/1.1 200 OK"));
+    }
+
+    #[test]
+    fn it_can_advertise_http3() {
+        assert_eq!(alt_svc(8443), r#"h3=":8443"; ma=86400"#);
+    }
+
+    #[tokio::test]
+    async fn it_can_listen_on_ephemeral_ports() {
+        use tokio::io::AsyncReadExt;
+        use tokio::io::AsyncWriteExt;
+
+        let server = Arc::new(Server::builder().address(([127, 0, 0, 1], 0)).build());
+        let router = Arc::new(
+            Router::from_iter([Route::get("/", handler)])
+                .compile()
+                .unwrap(),
+        );
+
+        assert_eq!(server.local_addr(), None);
+
+        let running = tokio::spawn({
+            let server = server.clone();
+
+            async move { server.start(Arc::new(App::default()), router).await }
+        });
+
+        let address = server.listening().await.unwrap();
+        let mut stream = TcpStream::connect(address).await.unwrap();
+
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        server.shutdown_handle().shutdown();
+        running.await.unwrap();
+
+        assert_ne!(address.port(), 0);
+        assert_eq!(server.local_addrs(), [address]);
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+    }
+
+    #[tokio::test]
+    async fn it_can_limit_connections() {
+        let server = Server::builder()
+            .max_connections(1)
+            .reject_when_saturated()
+            .build();
+
+        let listener = Listener::bind(&Bind::Tcp(([127, 0, 0, 1], 0).into()))
+            .await
+            .unwrap();
+        let Listener::Tcp(tcp) = &listener else {
+            unreachable!()
+        };
+        let address = tcp.local_addr().unwrap();
+        let listeners = [listener];
+
+        let _first = TcpStream::connect(address).await.unwrap();
+        let _second = TcpStream::connect(address).await.unwrap();
+
+        let (_, _, first) = server.accept(&listeners).await.unwrap();
+        let (_, _, second) = server.accept(&listeners).await.unwrap();
+
+        assert!(matches!(first, Admission::Admitted(_)));
+        assert!(matches!(second, Admission::Saturated));
+
+        drop(first);
+
+        let _third = TcpStream::connect(address).await.unwrap();
+        let (_, _, third) = server.accept(&listeners).await.unwrap();
+
+        assert!(matches!(third, Admission::Admitted(_)));
+    }
+}
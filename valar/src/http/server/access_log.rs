@@ -0,0 +1,557 @@
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Result as IoResult;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::SyncSender;
+use std::sync::mpsc::TrySendError;
+use std::sync::OnceLock;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+
+use crate::http::Method;
+use crate::http::StatusCode;
+use crate::http::Uri;
+use crate::http::Version;
+
+/// The format of the access log lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// The Common Log Format used by most web servers.
+    #[default]
+    Common,
+
+    /// The Common Log Format followed by the referer and
+    /// the user agent of the request.
+    Combined,
+
+    /// One JSON object per line, meant to be ingested by
+    /// log aggregators.
+    Json,
+}
+
+/// Where the access log lines are written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Sink {
+    Stdout,
+    File {
+        path: PathBuf,
+        max_size: Option<u64>,
+        max_files: usize,
+    },
+}
+
+/// A request answered by the server.
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+    pub(crate) remote_addr: Option<SocketAddr>,
+    pub(crate) method: Method,
+    pub(crate) uri: Uri,
+    pub(crate) version: Version,
+    pub(crate) status: StatusCode,
+    pub(crate) size: Option<u64>,
+    pub(crate) referer: Option<String>,
+    pub(crate) user_agent: Option<String>,
+    pub(crate) duration: Duration,
+    pub(crate) time: SystemTime,
+}
+
+/// A JSON access log line.
+#[derive(Serialize)]
+struct Record<'a> {
+    time: String,
+    remote_addr: Option<String>,
+    method: &'a str,
+    uri: String,
+    version: String,
+    status: u16,
+    size: Option<u64>,
+    referer: Option<&'a str>,
+    user_agent: Option<&'a str>,
+    duration_ms: f64,
+}
+
+/// The file the access log is being written to, along
+/// with its size.
+struct Output {
+    file: File,
+    size: u64,
+}
+
+/// The number of lines waiting to be written before new
+/// ones are dropped.
+const BACKLOG: usize = 8192;
+
+/// The thread writing the lines, so requests never wait
+/// on the disk.
+struct Writer {
+    lines: SyncSender<String>,
+    thread: JoinHandle<()>,
+}
+
+/// Logs every request answered by the server, independent
+/// from the application logs. Lines can be written in the
+/// Common Log Format, the Combined Log Format or as JSON,
+/// either to the standard output or to a file that is
+/// rotated once it grows too large.
+///
+/// High traffic deployments can log a sample of the
+/// requests, optionally keeping all the server errors.
+///
+/// Lines are written by a background thread. When it falls
+/// too far behind, new lines are dropped instead of
+/// slowing down the requests.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::server::AccessLog;
+/// use valar::http::Server;
+///
+/// let access_log = AccessLog::file("/var/log/app/access.log")
+///     .json()
+///     .rotate(64 * 1024 * 1024, 5)
+///     .sample(0.1)
+///     .always_log_errors();
+///
+/// let server = Server::builder().access_log(access_log).build();
+/// ```
+pub struct AccessLog {
+    format: Format,
+    sink: Sink,
+    sample_rate: f64,
+    always_log_errors: bool,
+    requests: AtomicU64,
+    writer: OnceLock<Writer>,
+}
+
+impl AccessLog {
+    fn new(sink: Sink) -> Self {
+        Self {
+            format: Format::default(),
+            sink,
+            sample_rate: 1.0,
+            always_log_errors: false,
+            requests: AtomicU64::new(0),
+            writer: OnceLock::new(),
+        }
+    }
+
+    /// Creates an access log that writes to the standard
+    /// output.
+    pub fn stdout() -> Self {
+        Self::new(Sink::Stdout)
+    }
+
+    /// Creates an access log that appends to the file at
+    /// the given path. The file is created if it does not
+    /// exist.
+    pub fn file<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self::new(Sink::File {
+            path: path.into(),
+            max_size: None,
+            max_files: 0,
+        })
+    }
+
+    /// Sets the format of the lines.
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+
+        self
+    }
+
+    /// Uses the Common Log Format.
+    pub fn common(self) -> Self {
+        self.format(Format::Common)
+    }
+
+    /// Uses the Combined Log Format.
+    pub fn combined(self) -> Self {
+        self.format(Format::Combined)
+    }
+
+    /// Uses one JSON object per line.
+    pub fn json(self) -> Self {
+        self.format(Format::Json)
+    }
+
+    /// Rotates the log file once it grows past the given
+    /// size, in bytes, keeping the given number of rotated
+    /// files as `access.log.1`, `access.log.2` and so on.
+    /// Does nothing when logging to the standard output.
+    pub fn rotate(mut self, max_size: u64, max_files: usize) -> Self {
+        if let Sink::File {
+            max_size: size,
+            max_files: files,
+            ..
+        } = &mut self.sink
+        {
+            *size = Some(max_size);
+            *files = max_files;
+        }
+
+        self
+    }
+
+    /// Logs only the given fraction of the requests, from
+    /// `0.0` to `1.0`. The requests are sampled evenly, so
+    /// a rate of `0.25` logs one every four requests.
+    pub fn sample(mut self, rate: f64) -> Self {
+        self.sample_rate = rate.clamp(0.0, 1.0);
+
+        self
+    }
+
+    /// Logs all the server errors, even the ones left out
+    /// by the sample rate.
+    pub fn always_log_errors(mut self) -> Self {
+        self.always_log_errors = true;
+
+        self
+    }
+
+    /// Determines if the next request must be logged.
+    fn sampled(&self, status: StatusCode) -> bool {
+        let count = self.requests.fetch_add(1, Ordering::Relaxed) as f64;
+
+        if self.always_log_errors && status.is_server_error() {
+            return true;
+        }
+
+        ((count + 1.0) * self.sample_rate).floor() > (count * self.sample_rate).floor()
+    }
+
+    /// Logs the given request.
+    pub(crate) fn log(&self, entry: &Entry) {
+        if !self.sampled(entry.status) {
+            return;
+        }
+
+        let line = self.line(entry);
+
+        if let Err(TrySendError::Full(_)) = self.writer().lines.try_send(line) {
+            eprintln!("The access log is falling behind, dropping a line");
+        }
+    }
+
+    /// Returns the writer, starting it on the first line.
+    fn writer(&self) -> &Writer {
+        self.writer.get_or_init(|| {
+            let (lines, receiver) = mpsc::sync_channel(BACKLOG);
+            let sink = self.sink.clone();
+            let thread = thread::Builder::new()
+                .name("valar-access-log".to_string())
+                .spawn(move || write_all(sink, receiver))
+                .expect("Failed to start the access log writer");
+
+            Writer { lines, thread }
+        })
+    }
+
+    /// Formats the given request.
+    fn line(&self, entry: &Entry) -> String {
+        let host = entry
+            .remote_addr
+            .map(|address| address.ip().to_string())
+            .unwrap_or_else(|| "-".to_string());
+
+        if self.format == Format::Json {
+            let record = Record {
+                time: rfc3339(entry.time),
+                remote_addr: entry.remote_addr.map(|address| address.to_string()),
+                method: entry.method.as_str(),
+                uri: entry.uri.to_string(),
+                version: format!("{:?}", entry.version),
+                status: entry.status.as_u16(),
+                size: entry.size,
+                referer: entry.referer.as_deref(),
+                user_agent: entry.user_agent.as_deref(),
+                duration_ms: entry.duration.as_secs_f64() * 1000.0,
+            };
+
+            return serde_json::to_string(&record).unwrap_or_default();
+        }
+
+        let size = entry
+            .size
+            .map(|size| size.to_string())
+            .unwrap_or_else(|| "-".to_string());
+
+        let line = format!(
+            r#"{host} - - [{}] "{} {} {:?}" {} {size}"#,
+            common_time(entry.time),
+            entry.method,
+            entry.uri,
+            entry.version,
+            entry.status.as_u16()
+        );
+
+        match self.format {
+            Format::Combined => format!(
+                r#"{line} "{}" "{}""#,
+                entry.referer.as_deref().unwrap_or("-"),
+                entry.user_agent.as_deref().unwrap_or("-")
+            ),
+            _ => line,
+        }
+    }
+}
+
+impl Drop for AccessLog {
+    /// Waits for the pending lines to be written.
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            drop(writer.lines);
+
+            let _ = writer.thread.join();
+        }
+    }
+}
+
+impl Debug for AccessLog {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        formatter
+            .debug_struct("AccessLog")
+            .field("format", &self.format)
+            .field("sink", &self.sink)
+            .field("sample_rate", &self.sample_rate)
+            .field("always_log_errors", &self.always_log_errors)
+            .finish()
+    }
+}
+
+/// Writes the lines received until the access log is
+/// dropped.
+fn write_all(sink: Sink, lines: Receiver<String>) {
+    let mut output = None;
+
+    for line in lines {
+        if let Err(err) = write(&sink, &mut output, &line) {
+            eprintln!("Failed to write the access log: {err}");
+        }
+    }
+}
+
+/// Writes the given line to the sink.
+fn write(sink: &Sink, output: &mut Option<Output>, line: &str) -> IoResult<()> {
+    let Sink::File {
+        path,
+        max_size,
+        max_files,
+    } = sink
+    else {
+        println!("{line}");
+
+        return Ok(());
+    };
+
+    let length = line.len() as u64 + 1;
+
+    if let (Some(current), Some(max_size)) = (output.as_ref(), max_size) {
+        if current.size > 0 && current.size + length > *max_size {
+            *output = None;
+            rotate(path, *max_files)?;
+        }
+    }
+
+    if output.is_none() {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+
+        *output = Some(Output { file, size });
+    }
+
+    let current = output.as_mut().expect("The access log file is open");
+
+    writeln!(current.file, "{line}")?;
+    current.size += length;
+
+    Ok(())
+}
+
+/// Shifts the rotated files of the given log file and
+/// moves it to the first one. The oldest file is removed.
+fn rotate(path: &Path, max_files: usize) -> IoResult<()> {
+    let rotated = |index: usize| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{index}"));
+
+        PathBuf::from(name)
+    };
+
+    if max_files == 0 {
+        return std::fs::remove_file(path);
+    }
+
+    for index in (1..max_files).rev() {
+        let from = rotated(index);
+
+        if from.exists() {
+            std::fs::rename(from, rotated(index + 1))?;
+        }
+    }
+
+    std::fs::rename(path, rotated(1))
+}
+
+/// Returns the UTC date and time of the given instant, as
+/// the year, month, day, hour, minute and second.
+fn civil(time: SystemTime) -> (i64, u32, u32, u64, u64, u64) {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+    let month = if month < 10 { month + 3 } else { month - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    let time_of_day = seconds % 86_400;
+
+    (
+        year,
+        month,
+        day,
+        time_of_day / 3_600,
+        time_of_day % 3_600 / 60,
+        time_of_day % 60,
+    )
+}
+
+/// Formats the given instant like `10/Oct/2000:13:55:36 +0000`.
+fn common_time(time: SystemTime) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let (year, month, day, hour, minute, second) = civil(time);
+
+    format!(
+        "{day:02}/{}/{year}:{hour:02}:{minute:02}:{second:02} +0000",
+        MONTHS[month as usize - 1]
+    )
+}
+
+/// Formats the given instant like `2000-10-10T13:55:36Z`.
+fn rfc3339(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, second) = civil(time);
+
+    format!("{year}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use serde_json::Value;
+
+    use crate::http::server::access_log::Entry;
+    use crate::http::server::AccessLog;
+    use crate::http::Method;
+    use crate::http::StatusCode;
+    use crate::http::Uri;
+    use crate::http::Version;
+
+    fn entry(status: StatusCode) -> Entry {
+        Entry {
+            remote_addr: Some(([127, 0, 0, 1], 54321).into()),
+            method: Method::GET,
+            uri: Uri::from_static("/users?page=2"),
+            version: Version::HTTP_11,
+            status,
+            size: Some(512),
+            referer: None,
+            user_agent: Some("curl/8.0".to_string()),
+            duration: Duration::from_millis(3),
+            time: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        }
+    }
+
+    #[test]
+    fn it_can_format_access_logs() {
+        let entry = entry(StatusCode::OK);
+
+        assert_eq!(
+            AccessLog::stdout().line(&entry),
+            r#"127.0.0.1 - - [14/Nov/2023:22:13:20 +0000] "GET /users?page=2 HTTP/1.1" 200 512"#
+        );
+        assert_eq!(
+            AccessLog::stdout().combined().line(&entry),
+            r#"127.0.0.1 - - [14/Nov/2023:22:13:20 +0000] "GET /users?page=2 HTTP/1.1" 200 512 "-" "curl/8.0""#
+        );
+
+        let json: Value = serde_json::from_str(&AccessLog::stdout().json().line(&entry)).unwrap();
+
+        assert_eq!(json["time"], "2023-11-14T22:13:20Z");
+        assert_eq!(json["remote_addr"], "127.0.0.1:54321");
+        assert_eq!(json["status"], 200);
+    }
+
+    #[test]
+    fn it_can_sample_access_logs() {
+        let access_log = AccessLog::stdout().sample(0.25).always_log_errors();
+
+        let logged = (0..8)
+            .filter(|_| access_log.sampled(StatusCode::OK))
+            .count();
+
+        assert_eq!(logged, 2);
+        assert!(access_log.sampled(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[test]
+    fn it_can_rotate_access_logs() {
+        let directory = std::env::temp_dir().join(format!("valar-logs-{}", std::process::id()));
+        let path = directory.join("access.log");
+
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let access_log = AccessLog::file(&path).rotate(100, 2);
+        let entry = entry(StatusCode::OK);
+
+        for _ in 0..4 {
+            access_log.log(&entry);
+        }
+
+        drop(access_log);
+
+        let lines = |path: &std::path::Path| {
+            std::fs::read_to_string(path)
+                .map(|contents| contents.lines().count())
+                .unwrap_or(0)
+        };
+
+        assert_eq!(lines(&path), 1);
+        assert_eq!(lines(&directory.join("access.log.1")), 1);
+        assert_eq!(lines(&directory.join("access.log.2")), 1);
+        assert!(!directory.join("access.log.3").exists());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,255 @@
+use std::io::Error;
+use std::io::ErrorKind;
+use std::io::Result as IoResult;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use http_body_util::BodyExt;
+use http_body_util::Full;
+use hyper::body::Buf;
+use hyper::body::Bytes;
+use hyper::Request as BaseRequest;
+use hyper::Version;
+use quinn::crypto::rustls::QuicServerConfig;
+use quinn::Endpoint;
+use quinn::ServerConfig;
+use rustls::pki_types::CertificateDer;
+use rustls::pki_types::PrivateKeyDer;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+use crate::http::body::BoxError;
+use crate::http::server::shutting_down;
+use crate::http::server::Dispatcher;
+
+/// The headers that only make sense on HTTP/1.1
+/// connections and must not be sent over HTTP/3.
+const CONNECTION_HEADERS: [&str; 5] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Where the server listens for HTTP/3 connections, along
+/// with the TLS certificate it presents. QUIC always runs
+/// over TLS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Http3 {
+    pub(crate) address: SocketAddr,
+    pub(crate) certificate: PathBuf,
+    pub(crate) key: PathBuf,
+}
+
+impl Http3 {
+    /// Describes the address for humans.
+    pub(crate) fn describe(&self) -> String {
+        format!("https://{} (HTTP/3)", self.address)
+    }
+
+    /// Starts listening for QUIC connections.
+    pub(crate) fn bind(&self) -> IoResult<Endpoint> {
+        let certificates = self.certificates()?;
+        let key = self.private_key()?;
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let mut tls = rustls::ServerConfig::builder_with_provider(provider)
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(invalid)?
+            .with_no_client_auth()
+            .with_single_cert(certificates, key)
+            .map_err(invalid)?;
+
+        tls.alpn_protocols = vec![b"h3".to_vec()];
+
+        let crypto = QuicServerConfig::try_from(tls).map_err(invalid)?;
+
+        Endpoint::server(ServerConfig::with_crypto(Arc::new(crypto)), self.address)
+    }
+
+    /// Reads the PEM certificate chain.
+    fn certificates(&self) -> IoResult<Vec<CertificateDer<'static>>> {
+        let pem = std::fs::read(&self.certificate)?;
+
+        rustls_pemfile::certs(&mut pem.as_slice()).collect()
+    }
+
+    /// Reads the PEM private key.
+    fn private_key(&self) -> IoResult<PrivateKeyDer<'static>> {
+        let pem = std::fs::read(&self.key)?;
+
+        rustls_pemfile::private_key(&mut pem.as_slice())?.ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("{} has no private key", self.key.display()),
+            )
+        })
+    }
+}
+
+fn invalid<E>(error: E) -> Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    Error::new(ErrorKind::InvalidInput, error)
+}
+
+/// Accepts QUIC connections until the server shuts down,
+/// serving their requests with the given dispatcher.
+pub(crate) async fn serve<App: Send + Sync + 'static>(
+    endpoint: Endpoint,
+    dispatcher: Arc<Dispatcher<App>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut connections = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            incoming = endpoint.accept() => {
+                let Some(incoming) = incoming else {
+                    break;
+                };
+
+                let dispatcher = dispatcher.clone();
+                let shutdown = shutdown.clone();
+
+                connections.spawn(async move {
+                    if let Err(err) = connection(incoming, dispatcher, shutdown).await {
+                        println!("Error serving HTTP/3 connection: {err}");
+                    }
+                });
+            }
+            Some(_) = connections.join_next() => {}
+            _ = shutting_down(&mut shutdown) => break,
+        }
+    }
+
+    while connections.join_next().await.is_some() {}
+
+    endpoint.close(0u32.into(), b"shutting down");
+    endpoint.wait_idle().await;
+}
+
+/// Serves the requests of a QUIC connection. Once the
+/// server shuts down, the connection stops accepting
+/// requests and finishes the in-flight ones.
+async fn connection<App: Send + Sync + 'static>(
+    incoming: quinn::Incoming,
+    dispatcher: Arc<Dispatcher<App>>,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<(), BoxError> {
+    let connection = incoming.await?;
+    let remote_addr = connection.remote_address();
+    let mut connection = h3::server::builder()
+        .build(h3_quinn::Connection::new(connection))
+        .await?;
+    let mut requests = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            accepted = connection.accept() => {
+                let Ok(Some(resolver)) = accepted else {
+                    break;
+                };
+
+                let dispatcher = dispatcher.clone();
+
+                requests.spawn(async move {
+                    if let Err(err) = request(resolver, dispatcher, remote_addr).await {
+                        println!("Error serving HTTP/3 request: {err}");
+                    }
+                });
+            }
+            Some(_) = requests.join_next() => {}
+            _ = shutting_down(&mut shutdown) => {
+                connection.shutdown(requests.len()).await?;
+                break;
+            }
+        }
+    }
+
+    while requests.join_next().await.is_some() {}
+
+    Ok(())
+}
+
+/// Serves a single HTTP/3 request.
+async fn request<App: Send + Sync + 'static>(
+    resolver: h3::server::RequestResolver<h3_quinn::Connection, Bytes>,
+    dispatcher: Arc<Dispatcher<App>>,
+    remote_addr: SocketAddr,
+) -> Result<(), BoxError> {
+    let (request, mut stream) = resolver.resolve_request().await?;
+
+    // The body is read up to one byte past the limit, so
+    // the router still answers the oversized requests.
+    let limit = dispatcher.max_body_size.saturating_add(1);
+    let mut body = Vec::new();
+
+    while let Some(mut chunk) = stream.recv_data().await? {
+        if body.len() as u64 >= limit {
+            break;
+        }
+
+        let length = chunk.remaining();
+        body.extend_from_slice(&chunk.copy_to_bytes(length));
+    }
+
+    let (mut parts, _) = request.into_parts();
+    parts.version = Version::HTTP_3;
+
+    let request = BaseRequest::from_parts(parts, Full::new(Bytes::from(body)));
+    let response = dispatcher
+        .serve(request, Some(remote_addr))
+        .await
+        .unwrap_or_else(|never| match never {});
+
+    let (mut parts, mut body) = response.into_parts();
+
+    for header in CONNECTION_HEADERS {
+        parts.headers.remove(header);
+    }
+
+    parts.version = Version::HTTP_3;
+
+    stream
+        .send_response(hyper::Response::from_parts(parts, ()))
+        .await?;
+
+    while let Some(frame) = body.frame().await {
+        match frame?.into_data() {
+            Ok(data) => stream.send_data(data).await?,
+            Err(frame) => {
+                if let Ok(trailers) = frame.into_trailers() {
+                    stream.send_trailers(trailers).await?;
+                }
+            }
+        }
+    }
+
+    stream.finish().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use crate::http::server::http3::Http3;
+
+    #[test]
+    fn it_can_report_missing_certificates() {
+        let http3 = Http3 {
+            address: ([127, 0, 0, 1], 0).into(),
+            certificate: "missing/cert.pem".into(),
+            key: "missing/key.pem".into(),
+        };
+
+        let error = http3.bind().unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+    }
+}
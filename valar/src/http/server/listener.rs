@@ -0,0 +1,293 @@
+use std::io::Result as IoResult;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use futures_util::future::select_all;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+
+/// Where the server listens for connections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Bind {
+    Tcp(SocketAddr),
+
+    #[cfg(unix)]
+    Unix {
+        path: PathBuf,
+        permissions: Option<u32>,
+    },
+}
+
+impl Bind {
+    /// Returns the URL clients reach the server at.
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Self::Tcp(address) => format!("http://{address}"),
+            #[cfg(unix)]
+            Self::Unix { path, .. } => format!("unix:{}", path.display()),
+        }
+    }
+}
+
+/// A listening socket.
+pub(crate) enum Listener {
+    Tcp(TcpListener),
+
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+}
+
+impl Listener {
+    /// Starts listening at the given bind target.
+    pub(crate) async fn bind(bind: &Bind) -> IoResult<Self> {
+        match bind {
+            Bind::Tcp(address) => Ok(Self::Tcp(TcpListener::bind(address).await?)),
+            #[cfg(unix)]
+            Bind::Unix { path, permissions } => {
+                remove_stale_socket(path)?;
+
+                let listener = match permissions {
+                    Some(mode) => bind_with_permissions(path, *mode)?,
+                    None => UnixListener::bind(path)?,
+                };
+
+                Ok(Self::Unix(listener, path.clone()))
+            }
+        }
+    }
+
+    /// Returns the address the listener is bound to. Only
+    /// TCP listeners have one.
+    pub(crate) fn local_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp(listener) => listener.local_addr().ok(),
+            #[cfg(unix)]
+            Self::Unix(..) => None,
+        }
+    }
+
+    /// Accepts a new connection. The peer address is only
+    /// known for TCP connections.
+    pub(crate) async fn accept(&self) -> IoResult<(Stream, Option<SocketAddr>)> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, address) = listener.accept().await?;
+
+                Ok((Stream::Tcp(stream), Some(address)))
+            }
+            #[cfg(unix)]
+            Self::Unix(listener, _) => {
+                let (stream, _) = listener.accept().await?;
+
+                Ok((Stream::Unix(stream), None))
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Listener {
+    /// Removes the socket file of unix listeners, so the
+    /// next run doesn't find a stale one.
+    fn drop(&mut self) {
+        if let Self::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Accepts a new connection from any of the given
+/// listeners.
+pub(crate) async fn accept(listeners: &[Listener]) -> IoResult<(Stream, Option<SocketAddr>)> {
+    let accepts = listeners.iter().map(|listener| Box::pin(listener.accept()));
+    let (accepted, _, _) = select_all(accepts).await;
+
+    accepted
+}
+
+/// Binds a unix listener whose socket file has the given
+/// permissions from the start. The socket is created in a
+/// directory only the current user can access, where its
+/// permissions are set, and then moved to the given path,
+/// so no other user can connect in between.
+#[cfg(unix)]
+fn bind_with_permissions(path: &std::path::Path, mode: u32) -> IoResult<UnixListener> {
+    use std::fs::DirBuilder;
+    use std::fs::Permissions;
+    use std::os::unix::fs::DirBuilderExt;
+    use std::os::unix::fs::PermissionsExt;
+
+    use uuid::Uuid;
+
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let directory = path.with_file_name(format!(".{name}.{}", Uuid::now_v7()));
+
+    DirBuilder::new().mode(0o700).create(&directory)?;
+
+    let private = directory.join("socket");
+    let listener = UnixListener::bind(&private).and_then(|listener| {
+        std::fs::set_permissions(&private, Permissions::from_mode(mode))?;
+        std::fs::rename(&private, path)?;
+
+        Ok(listener)
+    });
+
+    let _ = std::fs::remove_file(&private);
+    let _ = std::fs::remove_dir(&directory);
+
+    listener
+}
+
+/// Removes the socket file at the given path if no server
+/// is listening on it anymore. Fails when another server
+/// is still using it or when the path is not a socket.
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> IoResult<()> {
+    use std::io::Error;
+    use std::io::ErrorKind;
+    use std::os::unix::fs::FileTypeExt;
+
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+
+    if !metadata.file_type().is_socket() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(Error::new(
+            ErrorKind::AddrInUse,
+            format!("{} is already in use", path.display()),
+        ));
+    }
+
+    std::fs::remove_file(path)
+}
+
+/// An accepted connection.
+pub(crate) enum Stream {
+    Tcp(TcpStream),
+
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buffer: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_read(context, buffer),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_read(context, buffer),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buffer: &[u8],
+    ) -> Poll<IoResult<usize>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_write(context, buffer),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_write(context, buffer),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<IoResult<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_flush(context),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_flush(context),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<IoResult<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_shutdown(context),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_shutdown(context),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpStream;
+
+    use crate::http::server::listener::accept;
+    use crate::http::server::listener::Bind;
+    use crate::http::server::listener::Listener;
+
+    #[tokio::test]
+    async fn it_can_accept_from_multiple_listeners() {
+        let bind = Bind::Tcp(([127, 0, 0, 1], 0).into());
+        let listeners = vec![
+            Listener::bind(&bind).await.unwrap(),
+            Listener::bind(&bind).await.unwrap(),
+        ];
+
+        for listener in &listeners {
+            let Listener::Tcp(tcp) = listener else {
+                unreachable!()
+            };
+            let client = TcpStream::connect(tcp.local_addr().unwrap()).await.unwrap();
+            let (_, peer) = accept(&listeners).await.unwrap();
+
+            assert_eq!(peer, Some(client.local_addr().unwrap()));
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_can_listen_on_unix_sockets() {
+        use std::os::unix::fs::PermissionsExt;
+
+        use uuid::Uuid;
+
+        let path = std::env::temp_dir().join(format!("valar-{}.sock", Uuid::now_v7()));
+        let bind = Bind::Unix {
+            path: path.clone(),
+            permissions: Some(0o600),
+        };
+
+        let listener = Listener::bind(&bind).await.unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(Listener::bind(&bind).await.is_err());
+
+        drop(listener);
+
+        std::os::unix::net::UnixListener::bind(&path).unwrap();
+
+        let listener = Listener::bind(&bind).await.unwrap();
+
+        tokio::net::UnixStream::connect(&path).await.unwrap();
+        assert!(listener.accept().await.unwrap().1.is_none());
+
+        drop(listener);
+
+        assert!(!path.exists());
+    }
+}
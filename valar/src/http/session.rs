@@ -0,0 +1,301 @@
+pub mod cookie;
+pub mod database;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+pub use cookie::CookieStore;
+pub use database::DatabaseStore;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use thiserror::Error as ThisError;
+
+use crate::database::Error as DatabaseError;
+use crate::services::cache::Error as CacheError;
+use crate::services::cache::Value;
+use crate::services::Cacheable;
+
+/// The context key where the session is kept.
+pub(crate) const SESSION: &str = "request:session";
+
+/// The values stored in a session.
+pub type Values = HashMap<String, JsonValue>;
+
+#[derive(ThisError, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// The session does not fit in a cookie.
+    #[error("The session cookie takes {0} bytes, over the limit of browsers")]
+    CookieTooLarge(usize),
+}
+
+/// A session as loaded from or persisted to a store.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Record {
+    pub id: String,
+    pub values: Values,
+}
+
+/// Loads and persists the sessions. The token is the value
+/// of the session cookie: stores that keep the data on the
+/// server use the session ID while stores that keep it on
+/// the client use the serialized session itself.
+#[async_trait]
+pub trait SessionStore: Send + Sync + 'static {
+    /// Loads the session of the given token. Unknown or
+    /// expired sessions are `None`.
+    async fn load(&self, token: &str) -> Result<Option<Record>, Error>;
+
+    /// Persists the given session for the given lifetime
+    /// and returns the token to send to the client.
+    async fn save(&self, record: &Record, lifetime: Duration) -> Result<String, Error>;
+}
+
+/// Stores the sessions in a cache, as JSON, under the
+/// `session:<id>` key.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use valar::http::session::CacheStore;
+/// use valar::services::cache::MemoryCache;
+///
+/// # async fn example() {
+/// let store = CacheStore::new(Arc::new(MemoryCache::new(Duration::from_secs(60))));
+/// # }
+/// ```
+pub struct CacheStore {
+    cache: Arc<Cacheable>,
+    prefix: String,
+}
+
+impl CacheStore {
+    /// Creates a new store using the given cache.
+    pub fn new(cache: Arc<Cacheable>) -> Self {
+        Self {
+            cache,
+            prefix: "session:".to_string(),
+        }
+    }
+
+    /// Sets the prefix of the cache keys.
+    pub fn prefix<P>(mut self, prefix: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.prefix = prefix.into();
+
+        self
+    }
+
+    fn key(&self, id: &str) -> String {
+        format!("{}{id}", self.prefix)
+    }
+}
+
+#[async_trait]
+impl SessionStore for CacheStore {
+    async fn load(&self, token: &str) -> Result<Option<Record>, Error> {
+        let value = match self.cache.get(&self.key(token)).await {
+            Ok(value) => value,
+            Err(CacheError::NotFound(_) | CacheError::Expired(_)) => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        let values = serde_json::from_str(value.value())?;
+
+        Ok(Some(Record {
+            id: token.to_string(),
+            values,
+        }))
+    }
+
+    async fn save(&self, record: &Record, lifetime: Duration) -> Result<String, Error> {
+        let value = Value::new(serde_json::to_string(&record.values)?).expires_in(lifetime);
+
+        self.cache.insert(self.key(&record.id), value).await?;
+
+        Ok(record.id.clone())
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    record: Record,
+    changed: bool,
+}
+
+/// The session of a request, attached by the
+/// [`Session`](crate::http::middleware::Session)
+/// middleware. The values are loaded before the handler
+/// runs and persisted after it, if they changed.
+///
+/// The session is cheap to clone and all the clones share
+/// the same values.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::Request;
+/// use valar::http::Response;
+/// use valar::http::Result;
+///
+/// async fn handler(request: Request<()>) -> Result {
+///     let session = request.session().await.unwrap();
+///     let visits: u64 = session.get("visits").unwrap_or(0);
+///
+///     session.insert("visits", visits + 1)?;
+///
+///     Response::ok().into_ok()
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Session(Arc<Mutex<State>>);
+
+impl Session {
+    /// Creates a new session from the given record.
+    pub fn new(record: Record) -> Self {
+        Self(Arc::new(Mutex::new(State {
+            record,
+            changed: false,
+        })))
+    }
+
+    /// Returns the ID of the session.
+    pub fn id(&self) -> String {
+        self.0.lock().unwrap().record.id.clone()
+    }
+
+    /// Returns the value of the given key, if it exists
+    /// and has the given type.
+    pub fn get<T>(&self, key: &str) -> Option<T>
+    where
+        T: DeserializeOwned,
+    {
+        let state = self.0.lock().unwrap();
+        let value = state.record.values.get(key)?.clone();
+
+        serde_json::from_value(value).ok()
+    }
+
+    /// Stores the given value under the given key.
+    pub fn insert<K, T>(&self, key: K, value: T) -> Result<(), serde_json::Error>
+    where
+        K: Into<String>,
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        let mut state = self.0.lock().unwrap();
+
+        state.record.values.insert(key.into(), value);
+        state.changed = true;
+
+        Ok(())
+    }
+
+    /// Determines if the given key exists.
+    pub fn has(&self, key: &str) -> bool {
+        self.0.lock().unwrap().record.values.contains_key(key)
+    }
+
+    /// Returns a copy of all the values of the session.
+    pub fn all(&self) -> Values {
+        self.0.lock().unwrap().record.values.clone()
+    }
+
+    /// Removes the given key, returning its previous
+    /// value, if any.
+    pub fn forget(&self, key: &str) -> Option<JsonValue> {
+        let mut state = self.0.lock().unwrap();
+        let value = state.record.values.remove(key);
+
+        state.changed |= value.is_some();
+
+        value
+    }
+
+    /// Removes all the values of the session. The session
+    /// keeps its ID.
+    pub fn clear(&self) {
+        let mut state = self.0.lock().unwrap();
+
+        state.changed |= !state.record.values.is_empty();
+        state.record.values.clear();
+    }
+
+    /// Determines if the session values changed since they
+    /// were loaded.
+    pub fn is_changed(&self) -> bool {
+        self.0.lock().unwrap().changed
+    }
+
+    /// Returns a copy of the session as a record.
+    pub fn record(&self) -> Record {
+        self.0.lock().unwrap().record.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::http::session::Record;
+    use crate::http::session::Session;
+
+    #[test]
+    fn it_can_store_values() {
+        let session = Session::new(Record {
+            id: "abc".to_string(),
+            values: Default::default(),
+        });
+
+        assert!(!session.is_changed());
+
+        session.insert("visits", 3).unwrap();
+
+        assert!(session.is_changed());
+        assert_eq!(session.get::<u64>("visits"), Some(3));
+        assert_eq!(session.get::<String>("visits"), None);
+        assert_eq!(session.get::<u64>("missing"), None);
+        assert_eq!(session.id(), "abc");
+    }
+
+    #[test]
+    fn it_can_remove_values() {
+        let session = Session::new(Record {
+            id: "abc".to_string(),
+            values: [("visits".to_string(), json!(3))].into(),
+        });
+
+        assert!(session.has("visits"));
+        assert!(!session.has("missing"));
+        assert_eq!(session.forget("missing"), None);
+        assert!(!session.is_changed());
+        assert_eq!(session.all(), [("visits".to_string(), json!(3))].into());
+        assert_eq!(session.forget("visits"), Some(json!(3)));
+        assert!(session.is_changed());
+        assert!(!session.has("visits"));
+
+        session.insert("user", 1).unwrap();
+        session.insert("theme", "dark").unwrap();
+        session.clear();
+
+        assert!(session.all().is_empty());
+        assert_eq!(session.id(), "abc");
+    }
+}
@@ -0,0 +1,205 @@
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::Hmac;
+use hmac::Mac;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::http::session::Error;
+use crate::http::session::Record;
+use crate::http::session::SessionStore;
+use crate::http::session::Values;
+
+/// The minimum length of the signing key, in bytes.
+const MIN_KEY_BYTES: usize = 32;
+
+/// The maximum length of the cookie value. Browsers limit
+/// a cookie to about 4KB, including its name and
+/// attributes.
+const MAX_COOKIE_BYTES: usize = 4000;
+
+/// Stores the sessions in the session cookie itself, as
+/// JSON signed with HMAC-SHA256, so no server side
+/// storage is needed. The values can be read by the
+/// client but not changed, and browsers limit cookies to
+/// about 4KB, so saving a larger session fails.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::middleware::Session;
+/// use valar::http::session::CookieStore;
+///
+/// let secret = std::env::var("APP_KEY").unwrap();
+/// let session = Session::new(CookieStore::new(secret));
+/// ```
+pub struct CookieStore {
+    key: Vec<u8>,
+}
+
+/// The contents of a session cookie.
+#[derive(Serialize, Deserialize)]
+struct Payload {
+    id: String,
+    values: Values,
+    expires_at: u64,
+}
+
+impl CookieStore {
+    /// Creates a new store signing the cookies with the
+    /// given key, which should be a random secret.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the key is shorter than 32 bytes.
+    pub fn new<K>(key: K) -> Self
+    where
+        K: Into<Vec<u8>>,
+    {
+        let key = key.into();
+
+        assert!(
+            key.len() >= MIN_KEY_BYTES,
+            "The session key must be at least {MIN_KEY_BYTES} bytes long"
+        );
+
+        Self { key }
+    }
+
+    fn mac(&self) -> Hmac<Sha256> {
+        Hmac::new_from_slice(&self.key).expect("HMAC accepts keys of any length")
+    }
+
+    fn signature(&self, payload: &str) -> String {
+        let mut mac = self.mac();
+
+        mac.update(payload.as_bytes());
+
+        URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())
+    }
+
+    fn verify(&self, payload: &str, signature: &str) -> bool {
+        let Ok(signature) = URL_SAFE_NO_PAD.decode(signature) else {
+            return false;
+        };
+        let mut mac = self.mac();
+
+        mac.update(payload.as_bytes());
+        mac.verify_slice(&signature).is_ok()
+    }
+}
+
+#[async_trait]
+impl SessionStore for CookieStore {
+    async fn load(&self, token: &str) -> Result<Option<Record>, Error> {
+        let Some((payload, signature)) = token.rsplit_once('.') else {
+            return Ok(None);
+        };
+
+        if !self.verify(payload, signature) {
+            return Ok(None);
+        }
+
+        let Ok(payload) = URL_SAFE_NO_PAD.decode(payload) else {
+            return Ok(None);
+        };
+        let payload: Payload = serde_json::from_slice(&payload)?;
+
+        if payload.expires_at <= now() {
+            return Ok(None);
+        }
+
+        Ok(Some(Record {
+            id: payload.id,
+            values: payload.values,
+        }))
+    }
+
+    async fn save(&self, record: &Record, lifetime: Duration) -> Result<String, Error> {
+        let payload = serde_json::to_vec(&Payload {
+            id: record.id.clone(),
+            values: record.values.clone(),
+            expires_at: now() + lifetime.as_secs(),
+        })?;
+        let payload = URL_SAFE_NO_PAD.encode(payload);
+        let signature = self.signature(&payload);
+        let token = format!("{payload}.{signature}");
+
+        if token.len() > MAX_COOKIE_BYTES {
+            return Err(Error::CookieTooLarge(token.len()));
+        }
+
+        Ok(token)
+    }
+}
+
+/// Returns the current time in seconds since the epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use crate::http::session::CookieStore;
+    use crate::http::session::Error;
+    use crate::http::session::Record;
+    use crate::http::session::SessionStore;
+
+    #[tokio::test]
+    async fn it_can_store_sessions_in_cookies() {
+        let store = CookieStore::new("a very secret key of 32 bytes!!!");
+        let record = Record {
+            id: "abc".to_string(),
+            values: [("visits".to_string(), json!(3))].into(),
+        };
+
+        let token = store.save(&record, Duration::from_secs(60)).await.unwrap();
+
+        assert_eq!(store.load(&token).await.unwrap(), Some(record.clone()));
+
+        let (payload, _) = token.rsplit_once('.').unwrap();
+        let forged = format!("{payload}.{}", "A".repeat(43));
+
+        assert_eq!(store.load(&forged).await.unwrap(), None);
+        assert_eq!(
+            CookieStore::new("another very secret key of 32 bytes")
+                .load(&token)
+                .await
+                .unwrap(),
+            None
+        );
+
+        let expired = store.save(&record, Duration::ZERO).await.unwrap();
+
+        assert_eq!(store.load(&expired).await.unwrap(), None);
+
+        let large = Record {
+            id: "abc".to_string(),
+            values: [("bio".to_string(), json!("a".repeat(4000)))].into(),
+        };
+
+        assert!(matches!(
+            store.save(&large, Duration::from_secs(60)).await,
+            Err(Error::CookieTooLarge(_))
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 32 bytes")]
+    fn it_rejects_short_keys() {
+        CookieStore::new("short");
+    }
+}
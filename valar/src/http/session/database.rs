@@ -0,0 +1,156 @@
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+
+use crate::database::builder::Whereable;
+use crate::database::model::upsert;
+use crate::database::Connection;
+use crate::database::Database;
+use crate::database::Executor;
+use crate::http::session::Error;
+use crate::http::session::Record;
+use crate::http::session::SessionStore;
+
+/// Stores the sessions in a database table, as JSON. The
+/// table is `sessions` by default and needs the columns:
+///
+/// ```sql
+/// CREATE TABLE sessions (
+///     id TEXT PRIMARY KEY,
+///     payload TEXT NOT NULL,
+///     expires_at BIGINT NOT NULL
+/// );
+/// ```
+///
+/// Expired sessions are ignored but not deleted, so they
+/// should be pruned now and then.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use valar::database::Database;
+/// use valar::http::middleware::Session;
+/// use valar::http::session::DatabaseStore;
+///
+/// # async fn example() -> Result<(), valar::database::Error> {
+/// let database = Arc::new(Database::connect("postgres://localhost/valar").await?);
+/// let session = Session::new(DatabaseStore::new(database).table("user_sessions"));
+/// # Ok(())
+/// # }
+/// ```
+pub struct DatabaseStore {
+    database: Arc<Database>,
+    table: String,
+}
+
+impl DatabaseStore {
+    /// Creates a new store using the given database.
+    pub fn new(database: Arc<Database>) -> Self {
+        Self {
+            database,
+            table: "sessions".to_string(),
+        }
+    }
+
+    /// Sets the table of the sessions.
+    pub fn table<T>(mut self, table: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.table = table.into();
+
+        self
+    }
+}
+
+#[async_trait]
+impl SessionStore for DatabaseStore {
+    async fn load(&self, token: &str) -> Result<Option<Record>, Error> {
+        let now = now();
+        let payloads: Vec<String> = Database::table(&self.table)
+            .select(["payload"])
+            .where_equal("id", &token)
+            .where_greater_than("expires_at", &now)
+            .pluck("payload", &*self.database)
+            .await?;
+
+        let Some(payload) = payloads.first() else {
+            return Ok(None);
+        };
+
+        Ok(Some(Record {
+            id: token.to_string(),
+            values: serde_json::from_str(payload)?,
+        }))
+    }
+
+    async fn save(&self, record: &Record, lifetime: Duration) -> Result<String, Error> {
+        let payload = serde_json::to_string(&record.values)?;
+        let expires_at = now() + lifetime.as_secs() as i64;
+
+        let statement = upsert(&self.table, "id", &["id", "payload", "expires_at"]);
+
+        self.database
+            .execute(&statement, &[&record.id, &payload, &expires_at])
+            .await?;
+
+        Ok(record.id.clone())
+    }
+}
+
+/// Returns the current time in seconds since the epoch.
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs() as i64
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use crate::database::Connection;
+    use crate::database::Database;
+    use crate::http::session::DatabaseStore;
+    use crate::http::session::Record;
+    use crate::http::session::SessionStore;
+
+    #[tokio::test]
+    async fn it_can_store_sessions_in_the_database() {
+        let database = Database::connect_sqlite(":memory:").unwrap();
+
+        database
+            .execute(
+                "CREATE TABLE sessions (id TEXT PRIMARY KEY, payload TEXT NOT NULL, expires_at BIGINT NOT NULL)",
+                &[],
+            )
+            .await
+            .unwrap();
+
+        let store = DatabaseStore::new(Arc::new(database));
+        let mut record = Record {
+            id: "abc".to_string(),
+            values: [("visits".to_string(), json!(3))].into(),
+        };
+
+        store.save(&record, Duration::from_secs(60)).await.unwrap();
+        record.values.insert("visits".to_string(), json!(4));
+        store.save(&record, Duration::from_secs(60)).await.unwrap();
+
+        assert_eq!(store.load("abc").await.unwrap(), Some(record.clone()));
+        assert_eq!(store.load("missing").await.unwrap(), None);
+
+        store.save(&record, Duration::ZERO).await.unwrap();
+
+        assert_eq!(store.load("abc").await.unwrap(), None);
+    }
+}
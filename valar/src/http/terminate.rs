@@ -0,0 +1,129 @@
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+
+use http_body_util::combinators::BoxBody;
+use hyper::body::Body as HttpBody;
+use hyper::body::Bytes;
+use hyper::body::Frame;
+use hyper::body::SizeHint;
+
+use crate::http::body::BoxError;
+
+/// The context key where the terminators of the request
+/// are kept.
+pub(crate) const TERMINATORS: &str = "request:terminators";
+
+/// A terminator is work that runs once the response has
+/// been sent to the client.
+pub type Terminator = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// The terminators registered while handling a request.
+/// They are cheap to clone and all the clones share the
+/// same terminators.
+#[derive(Clone, Default)]
+pub struct Terminators(Arc<Mutex<Vec<Terminator>>>);
+
+impl Terminators {
+    /// Creates an empty list of terminators.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the given callback.
+    pub fn push<F, Fut>(&self, callback: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let terminator: Terminator = Box::new(move || Box::pin(callback()));
+
+        self.0.lock().unwrap().push(terminator);
+    }
+
+    /// Returns the number of terminators registered.
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    /// Determines if there are no terminators registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Runs the terminators in the order they were
+    /// registered. Each terminator runs once, so running
+    /// them again does nothing.
+    pub async fn run(&self) {
+        let terminators = std::mem::take(&mut *self.0.lock().unwrap());
+
+        for terminator in terminators {
+            terminator().await;
+        }
+    }
+
+    /// Runs the terminators in a new task, so they don't
+    /// hold back the caller.
+    pub fn spawn(self) {
+        if self.is_empty() {
+            return;
+        }
+
+        tokio::spawn(async move { self.run().await });
+    }
+}
+
+impl Debug for Terminators {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        formatter
+            .debug_struct("Terminators")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// A response body that runs the terminators of its
+/// response once it has been written to the client, or
+/// once the connection drops it.
+pub(crate) struct Terminating {
+    body: BoxBody<Bytes, BoxError>,
+    terminators: Terminators,
+}
+
+impl Terminating {
+    pub(crate) fn new(body: BoxBody<Bytes, BoxError>, terminators: Terminators) -> Self {
+        Self { body, terminators }
+    }
+}
+
+impl HttpBody for Terminating {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.body).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+}
+
+impl Drop for Terminating {
+    fn drop(&mut self) {
+        std::mem::take(&mut self.terminators).spawn();
+    }
+}
@@ -0,0 +1,548 @@
+use std::convert::Infallible;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
+use http_body_util::Full;
+use hyper::body::Body as HttpBody;
+use hyper::body::Bytes;
+use hyper::header::HeaderName;
+use hyper::header::HeaderValue;
+use hyper::Request as BaseRequest;
+use hyper::Response as BaseResponse;
+use tower::Layer as TowerLayer;
+use tower::Service;
+use tower::ServiceExt;
+
+use crate::http::body::BoxError;
+use crate::http::error_page::ErrorReport;
+use crate::http::Body;
+use crate::http::Headers;
+use crate::http::Request;
+use crate::http::Response;
+use crate::http::Result as HttpResult;
+use crate::routing::middleware::Handler;
+use crate::routing::middleware::Middleware;
+use crate::routing::router::Compiled;
+use crate::routing::router::DEFAULT_MAX_BODY_SIZE;
+use crate::routing::Router;
+
+/// The future returned by the Tower services of Valar.
+pub type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// The response of the Tower services of Valar.
+pub type HttpResponse = BaseResponse<BoxBody<Bytes, BoxError>>;
+
+/// Turns the given request into the request handed to
+/// Tower services. Headers that are not valid HTTP
+/// headers are left out.
+fn base_request<App>(request: &Request<App>) -> BaseRequest<Full<Bytes>>
+where
+    App: Send + Sync + 'static,
+{
+    let mut base = BaseRequest::new(Full::new(Bytes::copy_from_slice(request.body().as_bytes())));
+
+    *base.method_mut() = request.method().clone();
+    *base.uri_mut() = request.uri().clone();
+    *base.version_mut() = *request.version();
+
+    for (header, value) in request.headers().iter() {
+        if let (Ok(header), Ok(value)) = (
+            HeaderName::from_bytes(header.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            base.headers_mut().append(header, value);
+        }
+    }
+
+    base
+}
+
+/// Turns the given response of a Tower service into a
+/// response. Its body is streamed to the client.
+fn from_base_response<B>(response: BaseResponse<B>) -> Response
+where
+    B: HttpBody<Data = Bytes> + Send + Sync + 'static,
+    B::Error: Into<BoxError>,
+{
+    let (parts, body) = response.into_parts();
+
+    let headers = parts.headers.iter().map(|(header, value)| {
+        let value = value.to_str().unwrap_or_default().to_string();
+
+        (header.to_string(), value)
+    });
+
+    Response::builder()
+        .status(parts.status)
+        .headers_iter(headers)
+        .body(Body::Stream(body.map_err(Into::into).boxed()))
+        .build()
+}
+
+/// Answers a request whose Tower service failed.
+fn service_error<E>(error: E) -> Response
+where
+    E: Into<BoxError>,
+{
+    let error: BoxError = error.into();
+
+    Response::internal_server_error()
+        .body(error.to_string())
+        .build()
+}
+
+/// A compiled router exposed as a Tower service, so it can
+/// be served by any Tower compatible server or wrapped by
+/// Tower layers.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use valar::http::tower::RouterService;
+/// use valar::routing::router::Compiled;
+/// use valar::routing::Router;
+///
+/// struct App;
+///
+/// fn service(router: Arc<Router<App, Compiled>>) -> RouterService<App> {
+///     RouterService::new(Arc::new(App), router).max_body_size(1024)
+/// }
+/// ```
+pub struct RouterService<App: Send + Sync + 'static> {
+    app: Arc<App>,
+    router: Arc<Router<App, Compiled>>,
+    max_body_size: u64,
+}
+
+impl<App: Send + Sync + 'static> RouterService<App> {
+    /// Creates a new service that handles the requests
+    /// with the given router and application.
+    pub fn new(app: Arc<App>, router: Arc<Router<App, Compiled>>) -> Self {
+        Self {
+            app,
+            router,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// Sets the maximum size, in bytes, of request bodies
+    /// when the router doesn't set its own. Defaults to 2
+    /// MB.
+    pub fn max_body_size(mut self, bytes: u64) -> Self {
+        self.max_body_size = bytes;
+
+        self
+    }
+}
+
+impl<App: Send + Sync + 'static> Clone for RouterService<App> {
+    fn clone(&self) -> Self {
+        Self {
+            app: self.app.clone(),
+            router: self.router.clone(),
+            max_body_size: self.max_body_size,
+        }
+    }
+}
+
+impl<App, B> Service<BaseRequest<B>> for RouterService<App>
+where
+    App: Send + Sync + 'static,
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Error = Infallible;
+    type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+    type Response = HttpResponse;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: BaseRequest<B>) -> Self::Future {
+        let service = self.clone();
+
+        Box::pin(async move {
+            let method = request.method().clone();
+            let response = service
+                .router
+                .handle_base(service.app, request, None, service.max_body_size)
+                .await;
+
+            Ok(response.into_http_response(&method).map(BoxBody::new))
+        })
+    }
+}
+
+/// Turns the given Tower service into a route handler.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::convert::Infallible;
+///
+/// use http_body_util::Full;
+/// use hyper::body::Bytes;
+/// use valar::http::tower::handler;
+/// use valar::routing::route::Builder as Route;
+///
+/// struct App;
+///
+/// let service = tower::service_fn(|_request: hyper::Request<Full<Bytes>>| async {
+///     Ok::<_, Infallible>(hyper::Response::new(Full::new(Bytes::from("pong"))))
+/// });
+///
+/// let route = Route::<App>::get("/ping", handler(service));
+/// ```
+pub fn handler<App, S, B>(
+    service: S,
+) -> impl Fn(Request<App>) -> BoxFuture<HttpResult> + Send + Sync + 'static
+where
+    App: Send + Sync + 'static,
+    S: Service<BaseRequest<Full<Bytes>>, Response = BaseResponse<B>>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+    B: HttpBody<Data = Bytes> + Send + Sync + 'static,
+    B::Error: Into<BoxError>,
+{
+    move |request| {
+        let service = service.clone();
+
+        Box::pin(async move {
+            match service.oneshot(base_request(&request)).await {
+                Ok(response) => Ok(from_base_response(response)),
+                Err(error) => Err(service_error(error)),
+            }
+        })
+    }
+}
+
+/// How the rest of the middleware chain answered, kept in
+/// the extensions of its response so it survives the
+/// Tower layers.
+#[derive(Clone)]
+struct Outcome {
+    failed: bool,
+    report: Option<ErrorReport>,
+}
+
+/// The rest of the middleware chain and the route handler,
+/// exposed as the Tower service that the layers wrap.
+pub struct Next<App: Send + Sync + 'static> {
+    next: Handler<App>,
+    request: Arc<Mutex<Option<Request<App>>>>,
+}
+
+impl<App: Send + Sync + 'static> Clone for Next<App> {
+    fn clone(&self) -> Self {
+        Self {
+            next: self.next.clone(),
+            request: self.request.clone(),
+        }
+    }
+}
+
+impl<App, B> Service<BaseRequest<B>> for Next<App>
+where
+    App: Send + Sync + 'static,
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Error = Infallible;
+    type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+    type Response = HttpResponse;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, base: BaseRequest<B>) -> Self::Future {
+        let next = self.next.clone();
+        let request = self.request.lock().unwrap().take();
+
+        Box::pin(async move {
+            let Some(mut request) = request else {
+                let response = Response::internal_server_error()
+                    .message("The request was already handled")
+                    .build();
+
+                return Ok(response.into_http_response(base.method()).map(BoxBody::new));
+            };
+
+            // The layers may have changed the request, like
+            // decompressing its body.
+            let (parts, body) = base.into_parts();
+
+            let body = match body.collect().await {
+                Ok(body) => body.to_bytes(),
+                Err(_) => {
+                    let response = Response::bad_request()
+                        .message("Failed to read the request body")
+                        .build();
+
+                    return Ok(response.into_http_response(&parts.method).map(BoxBody::new));
+                }
+            };
+
+            let headers: Headers<Request<App>> = parts
+                .headers
+                .iter()
+                .map(|(header, value)| {
+                    let value = value.to_str().unwrap_or_default().to_string();
+
+                    (header.to_string(), value)
+                })
+                .collect();
+
+            request.set_method(parts.method.clone());
+            request.set_uri(parts.uri);
+            request.set_body(String::from_utf8_lossy(&body));
+            *request.headers_mut() = headers;
+
+            let (failed, response) = match next(request).await {
+                Ok(response) => (false, response),
+                Err(response) => (true, response),
+            };
+
+            let outcome = Outcome {
+                failed,
+                report: response.error_report().cloned(),
+            };
+
+            let mut response = response.into_http_response(&parts.method);
+            response.extensions_mut().insert(outcome);
+
+            Ok(response.map(BoxBody::new))
+        })
+    }
+}
+
+/// Uses the given Tower layer as a middleware, which
+/// unlocks the middlewares of the Tower ecosystem, like
+/// the ones of `tower-http`.
+///
+/// The request is handed to the layer as a hyper request
+/// and the changes the layer makes to its method, URI,
+/// headers and body are kept. Responses from the layer
+/// are streamed to the client.
+///
+/// # Example
+///
+/// ```no_run
+/// use http_body_util::Full;
+/// use hyper::body::Bytes;
+/// use tower::util::MapRequestLayer;
+/// use valar::http::tower::Layer;
+/// use valar::routing::Router;
+///
+/// struct App;
+///
+/// let router = Router::<App>::from_iter([]).middleware(Layer::new(MapRequestLayer::new(
+///     |mut request: hyper::Request<Full<Bytes>>| {
+///         request.headers_mut().remove("X-Internal");
+///
+///         request
+///     },
+/// )));
+/// ```
+pub struct Layer<L, B> {
+    layer: L,
+    body: PhantomData<fn() -> B>,
+}
+
+impl<L, B> Layer<L, B> {
+    /// Creates a new middleware with the given layer.
+    pub fn new(layer: L) -> Self {
+        Self {
+            layer,
+            body: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<App, L, B> Middleware<App> for Layer<L, B>
+where
+    App: Send + Sync + 'static,
+    L: TowerLayer<Next<App>> + Send + Sync + 'static,
+    L::Service: Service<BaseRequest<Full<Bytes>>, Response = BaseResponse<B>> + Send + 'static,
+    <L::Service as Service<BaseRequest<Full<Bytes>>>>::Future: Send,
+    <L::Service as Service<BaseRequest<Full<Bytes>>>>::Error: Into<BoxError>,
+    B: HttpBody<Data = Bytes> + Send + Sync + 'static,
+    B::Error: Into<BoxError>,
+{
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        let base = base_request(&request);
+        let service = self.layer.layer(Next {
+            next,
+            request: Arc::new(Mutex::new(Some(request))),
+        });
+
+        let response = match service.oneshot(base).await {
+            Ok(response) => response,
+            Err(error) => return Err(service_error(error)),
+        };
+
+        let outcome = response.extensions().get::<Outcome>().cloned();
+        let mut response = from_base_response(response);
+
+        match outcome {
+            Some(Outcome { failed, report }) => {
+                if let Some(report) = report {
+                    response = response.with_error_report(report);
+                }
+
+                match failed {
+                    true => Err(response),
+                    false => Ok(response),
+                }
+            }
+            // The layer answered on its own.
+            None if response.status().is_client_error() || response.status().is_server_error() => {
+                Err(response)
+            }
+            None => Ok(response),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::Arc;
+
+    use http_body_util::BodyExt;
+    use http_body_util::Full;
+    use hyper::body::Bytes;
+    use tower::util::MapRequestLayer;
+    use tower::util::MapResponseLayer;
+    use tower::ServiceExt;
+
+    use crate::http::tower::handler;
+    use crate::http::tower::HttpResponse;
+    use crate::http::tower::Layer;
+    use crate::http::tower::RouterService;
+    use crate::http::Body;
+    use crate::http::Method;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result;
+    use crate::http::StatusCode;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn show(request: Request<App>) -> Result {
+        let id = request.route_parameter("id")?;
+        let layer = request
+            .headers()
+            .first("X-Layer")
+            .unwrap_or_default()
+            .to_string();
+
+        Response::ok().body(format!("{id}:{layer}")).into_ok()
+    }
+
+    async fn collect(response: Response) -> String {
+        let body = response
+            .into_base_response(&Method::GET)
+            .unwrap()
+            .into_body();
+
+        let bytes = match body {
+            Body::Full(bytes) => bytes,
+            Body::Stream(body) => body.collect().await.unwrap().to_bytes(),
+        };
+
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_can_serve_routers_as_tower_services() {
+        let router = Router::from_iter([Route::get("/users/:id", show)])
+            .compile()
+            .unwrap();
+
+        let service = RouterService::new(Arc::new(App), Arc::new(router));
+        let request = hyper::Request::get("/users/5")
+            .body(Full::new(Bytes::new()))
+            .unwrap();
+
+        let response: HttpResponse = service.oneshot(request).await.unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+
+        assert_eq!(body, "5:");
+    }
+
+    #[tokio::test]
+    async fn it_can_mount_tower_services() {
+        let service = tower::service_fn(|request: hyper::Request<Full<Bytes>>| async move {
+            let body = format!("{} {}", request.method(), request.uri().path());
+
+            Ok::<_, Infallible>(hyper::Response::new(Full::new(Bytes::from(body))))
+        });
+
+        let router = Router::from_iter([Route::get("/tower", handler(service))])
+            .compile()
+            .unwrap();
+
+        let response = router
+            .handle(Request::get(Uri::from_static("/tower")).build(Arc::new(App)))
+            .await;
+
+        assert_eq!(collect(response).await, "GET /tower");
+    }
+
+    #[tokio::test]
+    async fn it_can_use_tower_layers_as_middleware() {
+        let request_layer = MapRequestLayer::new(|mut request: hyper::Request<Full<Bytes>>| {
+            request
+                .headers_mut()
+                .insert("X-Layer", "request".parse().unwrap());
+
+            request
+        });
+
+        let response_layer = MapResponseLayer::new(|mut response: HttpResponse| {
+            response
+                .headers_mut()
+                .insert("X-Layer", "response".parse().unwrap());
+
+            response
+        });
+
+        let router = Router::from_iter([Route::get("/users/:id", show)])
+            .middleware(Layer::new(request_layer))
+            .middleware(Layer::new(response_layer))
+            .compile()
+            .unwrap();
+
+        let response = router
+            .handle(Request::get(Uri::from_static("/users/5")).build(Arc::new(App)))
+            .await;
+        let missing = router
+            .handle(Request::get(Uri::from_static("/missing")).build(Arc::new(App)))
+            .await;
+
+        assert!(response.headers().is("X-Layer", "response"));
+        assert_eq!(collect(response).await, "5:request");
+        assert_eq!(*missing.status(), StatusCode::NOT_FOUND);
+        assert!(missing.headers().is("X-Layer", "response"));
+    }
+}
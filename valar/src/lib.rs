@@ -1,5 +1,6 @@
 pub mod config;
 pub mod database;
+pub mod debug;
 pub mod http;
 pub mod routing;
 pub mod services;
@@ -7,3 +8,17 @@ pub mod state;
 mod utils;
 
 pub use state::State;
+#[cfg(feature = "macros")]
+pub use valar_macros::any;
+#[cfg(feature = "macros")]
+pub use valar_macros::delete;
+#[cfg(feature = "macros")]
+pub use valar_macros::get;
+#[cfg(feature = "macros")]
+pub use valar_macros::patch;
+#[cfg(feature = "macros")]
+pub use valar_macros::post;
+#[cfg(feature = "macros")]
+pub use valar_macros::put;
+#[cfg(feature = "macros")]
+pub use valar_macros::routes;
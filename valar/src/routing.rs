@@ -1,8 +1,14 @@
 // pub mod config;
 // pub mod matcher;
+pub mod metadata;
 pub mod middleware;
+pub mod resource;
 pub mod route;
 pub mod router;
+pub mod table;
+pub mod url;
 
+pub use resource::Resource;
 pub use route::Route;
 pub use router::Router;
+pub use url::UrlGenerator;
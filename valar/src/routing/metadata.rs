@@ -0,0 +1,136 @@
+use std::any::Any;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::Arc;
+
+/// Typed metadata attached to routes, keyed by type. It
+/// lets middlewares make per-route decisions, for example
+/// requiring a role or labelling metrics.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::routing::metadata::Metadata;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum RequiresRole {
+///     Admin,
+/// }
+///
+/// let mut metadata = Metadata::new();
+/// metadata.insert(RequiresRole::Admin);
+///
+/// assert_eq!(metadata.get::<RequiresRole>(), Some(&RequiresRole::Admin));
+/// ```
+#[derive(Clone, Default)]
+pub struct Metadata(HashMap<TypeId, Arc<dyn Any + Send + Sync>>);
+
+impl Metadata {
+    /// Creates an empty metadata map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts the given value, replacing any previous
+    /// value of the same type.
+    pub fn insert<T>(&mut self, value: T)
+    where
+        T: Any + Send + Sync,
+    {
+        self.0.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Returns the value of the given type.
+    pub fn get<T>(&self) -> Option<&T>
+    where
+        T: Any + Send + Sync,
+    {
+        self.0
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    /// Determines if there's a value of the given type.
+    pub fn has<T>(&self) -> bool
+    where
+        T: Any + Send + Sync,
+    {
+        self.0.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Determines if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Extend<Metadata> for Metadata {
+    /// Merges the given metadata. Values of the same type
+    /// are replaced.
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = Metadata>,
+    {
+        for metadata in iter {
+            self.0.extend(metadata.0);
+        }
+    }
+}
+
+impl Debug for Metadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Metadata")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+/// The route that matched a request, as seen by the
+/// middlewares and the handler.
+#[derive(Debug, Clone)]
+pub struct MatchedRoute {
+    path: String,
+    name: Option<String>,
+    metadata: Metadata,
+}
+
+impl MatchedRoute {
+    pub(crate) fn new(path: String, name: Option<String>, metadata: Metadata) -> Self {
+        Self {
+            path,
+            name,
+            metadata,
+        }
+    }
+
+    /// Returns the path of the route.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the name of the route.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the metadata of the route.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns the metadata value of the given type.
+    pub fn meta<T>(&self) -> Option<&T>
+    where
+        T: Any + Send + Sync,
+    {
+        self.metadata.get()
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::slice::Iter;
@@ -19,6 +20,36 @@ pub type Handler<App> = Arc<
 #[async_trait]
 pub trait Middleware<App: Send + Sync + 'static> {
     async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult;
+
+    /// Returns the name of the middleware, used when
+    /// inspecting the routes. Defaults to its type name.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Returns the name of the middleware group this
+    /// middleware stands for. Only group references return
+    /// a name, and they are replaced by the middlewares of
+    /// the group when the router is compiled.
+    #[doc(hidden)]
+    fn group(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// A reference to a middleware group registered on the
+/// router with `Router::middleware_group`.
+pub(crate) struct GroupReference(pub(crate) String);
+
+#[async_trait]
+impl<App: Send + Sync + 'static> Middleware<App> for GroupReference {
+    async fn handle(&self, next: Handler<App>, request: Request<App>) -> HttpResult {
+        next(request).await
+    }
+
+    fn group(&self) -> Option<&str> {
+        Some(&self.0)
+    }
 }
 
 type SharableMiddleware<App> = Arc<dyn Middleware<App> + Send + Sync + 'static>;
@@ -93,6 +124,55 @@ impl<App: Send + Sync + 'static> Middlewares<App> {
         self.0.push(middleware);
     }
 
+    /// Adds the given middleware and returns the
+    /// middlewares, to build middleware groups.
+    pub fn middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: Middleware<App> + Send + Sync + 'static,
+    {
+        self.push(Arc::new(middleware));
+
+        self
+    }
+
+    /// Replaces the group references with the middlewares
+    /// of the given groups. Fails with the name of the
+    /// first group that doesn't exist.
+    pub(crate) fn expand(&self, groups: &HashMap<String, Self>) -> Result<Self, String> {
+        let mut middlewares = Vec::new();
+
+        for middleware in &self.0 {
+            match middleware.group() {
+                Some(name) => match groups.get(name) {
+                    Some(group) => middlewares.extend(group.0.iter().cloned()),
+                    None => return Err(name.to_string()),
+                },
+                None => middlewares.push(middleware.clone()),
+            }
+        }
+
+        Ok(Self(middlewares))
+    }
+
+    /// Returns the names of the middlewares, in the order
+    /// they run.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.0.iter().map(|middleware| middleware.name()).collect()
+    }
+
+    /// Returns the middlewares whose name is not in the
+    /// given exclusions.
+    pub fn without(&self, excluded: &[String]) -> Self {
+        let middlewares = self
+            .0
+            .iter()
+            .filter(|middleware| !excluded.iter().any(|name| name == middleware.name()))
+            .cloned()
+            .collect();
+
+        Self(middlewares)
+    }
+
     pub fn wrap(self, handler: HttpHandler<App>) -> HttpHandler<App> {
         let iterator = self.0.into_iter();
         Arc::new(move |request| {
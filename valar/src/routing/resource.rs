@@ -0,0 +1,365 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::http::Handler;
+use crate::http::Method;
+use crate::http::Request;
+use crate::http::Result as HttpResult;
+use crate::routing::route::Builder;
+
+/// The actions of a resource, in the order their routes
+/// are registered. The `create` route is registered last
+/// so that `/users/create` takes precedence over
+/// `/users/:id` when matching requests.
+const ACTIONS: [Action; 7] = [
+    Action::Index,
+    Action::Store,
+    Action::Show,
+    Action::Edit,
+    Action::Update,
+    Action::Destroy,
+    Action::Create,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Index,
+    Create,
+    Store,
+    Show,
+    Edit,
+    Update,
+    Destroy,
+}
+
+impl Action {
+    /// Returns the name suffix of the action route.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Index => "index",
+            Self::Create => "create",
+            Self::Store => "store",
+            Self::Show => "show",
+            Self::Edit => "edit",
+            Self::Update => "update",
+            Self::Destroy => "destroy",
+        }
+    }
+
+    /// Returns the methods the action route answers to.
+    fn methods(&self) -> Vec<Method> {
+        match self {
+            Self::Index | Self::Create | Self::Show | Self::Edit => {
+                vec![Method::GET, Method::HEAD]
+            }
+            Self::Store => vec![Method::POST],
+            Self::Update => vec![Method::PUT, Method::PATCH],
+            Self::Destroy => vec![Method::DELETE],
+        }
+    }
+
+    /// Returns the path of the action route given the
+    /// resource path and its parameter.
+    fn path(&self, path: &str, parameter: &str) -> String {
+        match self {
+            Self::Index | Self::Store => path.to_string(),
+            Self::Create => format!("{path}/create"),
+            Self::Show | Self::Update | Self::Destroy => format!("{path}/:{parameter}"),
+            Self::Edit => format!("{path}/:{parameter}/edit"),
+        }
+    }
+}
+
+/// A resource expands to the conventional CRUD routes of
+/// a path. Only the actions given a handler are
+/// registered:
+///
+/// | Action    | Method      | Path               | Name            |
+/// |-----------|-------------|--------------------|-----------------|
+/// | `index`   | GET         | `/users`           | `users.index`   |
+/// | `create`  | GET         | `/users/create`    | `users.create`  |
+/// | `store`   | POST        | `/users`           | `users.store`   |
+/// | `show`    | GET         | `/users/:id`       | `users.show`    |
+/// | `edit`    | GET         | `/users/:id/edit`  | `users.edit`    |
+/// | `update`  | PUT / PATCH | `/users/:id`       | `users.update`  |
+/// | `destroy` | DELETE      | `/users/:id`       | `users.destroy` |
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::Request;
+/// use valar::http::Response;
+/// use valar::http::Result;
+/// use valar::routing::route::Builder as Route;
+/// use valar::routing::Router;
+///
+/// struct App;
+///
+/// async fn handler(_request: Request<App>) -> Result {
+///     Response::ok().into_ok()
+/// }
+///
+/// let router = Router::from_iter([Route::resource("/users")
+///     .index(handler)
+///     .show(handler)
+///     .store(handler)
+///     .update(handler)
+///     .destroy(handler)
+///     .into()]);
+/// ```
+pub struct Resource<App: Send + Sync + 'static> {
+    path: String,
+    name: String,
+    parameter: String,
+    handlers: Vec<(Action, Handler<App>)>,
+}
+
+impl<App: Send + Sync + 'static> Resource<App> {
+    /// Creates a new resource at the given path. Its
+    /// routes are named after the static segments of the
+    /// path, for example `admin.users.index`.
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<String>,
+    {
+        let path = format!("/{}", path.into().trim_matches('/'));
+
+        let name = path
+            .split('/')
+            .filter(|segment| !segment.is_empty() && !segment.starts_with(':'))
+            .collect::<Vec<_>>()
+            .join(".");
+
+        Self {
+            path,
+            name,
+            parameter: "id".to_string(),
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Changes the name prefix of the resource routes.
+    pub fn name<N>(mut self, name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        self.name = name.into();
+
+        self
+    }
+
+    /// Changes the name of the route parameter that
+    /// identifies a resource. Defaults to `id`.
+    pub fn parameter<N>(mut self, parameter: N) -> Self
+    where
+        N: Into<String>,
+    {
+        self.parameter = parameter.into();
+
+        self
+    }
+
+    /// Lists the resources.
+    pub fn index<H, R>(self, handler: H) -> Self
+    where
+        R: Future<Output = HttpResult> + Send + 'static,
+        H: Fn(Request<App>) -> R + Send + Sync + 'static,
+    {
+        self.action(Action::Index, handler)
+    }
+
+    /// Shows the form to create a resource.
+    pub fn create<H, R>(self, handler: H) -> Self
+    where
+        R: Future<Output = HttpResult> + Send + 'static,
+        H: Fn(Request<App>) -> R + Send + Sync + 'static,
+    {
+        self.action(Action::Create, handler)
+    }
+
+    /// Stores a new resource.
+    pub fn store<H, R>(self, handler: H) -> Self
+    where
+        R: Future<Output = HttpResult> + Send + 'static,
+        H: Fn(Request<App>) -> R + Send + Sync + 'static,
+    {
+        self.action(Action::Store, handler)
+    }
+
+    /// Shows a single resource.
+    pub fn show<H, R>(self, handler: H) -> Self
+    where
+        R: Future<Output = HttpResult> + Send + 'static,
+        H: Fn(Request<App>) -> R + Send + Sync + 'static,
+    {
+        self.action(Action::Show, handler)
+    }
+
+    /// Shows the form to edit a resource.
+    pub fn edit<H, R>(self, handler: H) -> Self
+    where
+        R: Future<Output = HttpResult> + Send + 'static,
+        H: Fn(Request<App>) -> R + Send + Sync + 'static,
+    {
+        self.action(Action::Edit, handler)
+    }
+
+    /// Updates a resource. Answers both PUT and PATCH
+    /// requests.
+    pub fn update<H, R>(self, handler: H) -> Self
+    where
+        R: Future<Output = HttpResult> + Send + 'static,
+        H: Fn(Request<App>) -> R + Send + Sync + 'static,
+    {
+        self.action(Action::Update, handler)
+    }
+
+    /// Deletes a resource.
+    pub fn destroy<H, R>(self, handler: H) -> Self
+    where
+        R: Future<Output = HttpResult> + Send + 'static,
+        H: Fn(Request<App>) -> R + Send + Sync + 'static,
+    {
+        self.action(Action::Destroy, handler)
+    }
+
+    /// Sets the handler of the given action, replacing any
+    /// previous one.
+    fn action<H, R>(mut self, action: Action, handler: H) -> Self
+    where
+        R: Future<Output = HttpResult> + Send + 'static,
+        H: Fn(Request<App>) -> R + Send + Sync + 'static,
+    {
+        let handler: Handler<App> = Arc::new(move |req| Box::pin(handler(req)));
+
+        self.handlers.retain(|(existing, _)| *existing != action);
+        self.handlers.push((action, handler));
+
+        self
+    }
+}
+
+impl<App: Send + Sync + 'static> From<Resource<App>> for Builder<App> {
+    /// Expands the resource into a group with a route for
+    /// each of its actions.
+    fn from(resource: Resource<App>) -> Self {
+        let routes: Vec<Builder<App>> = ACTIONS
+            .iter()
+            .filter_map(|action| {
+                let (_, handler) = resource
+                    .handlers
+                    .iter()
+                    .find(|(existing, _)| existing == action)?;
+
+                let path = action.path(&resource.path, &resource.parameter);
+                let route = Builder::with_methods(path, action.methods(), handler.clone())
+                    .name(format!("{}.{}", resource.name, action.name()));
+
+                Some(route)
+            })
+            .collect();
+
+        Builder::group(routes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tokio::join;
+
+    use crate::http::Method;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result as ResponseResult;
+    use crate::http::Uri;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn index(_request: Request<App>) -> ResponseResult {
+        Response::ok().body("index").into_ok()
+    }
+
+    async fn create(_request: Request<App>) -> ResponseResult {
+        Response::ok().body("create").into_ok()
+    }
+
+    async fn show(request: Request<App>) -> ResponseResult {
+        let id: String = request.parameter("id")?;
+
+        Response::ok().body(format!("show {id}")).into_ok()
+    }
+
+    async fn update(_request: Request<App>) -> ResponseResult {
+        Response::ok().body("update").into_ok()
+    }
+
+    fn request(method: Method, uri: &'static str) -> Request<App> {
+        Request::builder()
+            .method(method)
+            .uri(Uri::from_static(uri))
+            .build(Arc::new(App))
+    }
+
+    #[tokio::test]
+    async fn it_can_route_resources() {
+        let router = Router::from_iter([Route::resource("/users")
+            .index(index)
+            .create(create)
+            .show(show)
+            .update(update)
+            .into()])
+        .compile()
+        .unwrap();
+
+        let r1 = router.handle(request(Method::GET, "/users"));
+        let r2 = router.handle(request(Method::GET, "/users/create"));
+        let r3 = router.handle(request(Method::GET, "/users/5"));
+        let r4 = router.handle(request(Method::PATCH, "/users/5"));
+        let r5 = router.handle(request(Method::PUT, "/users/5"));
+        let r6 = router.handle(request(Method::DELETE, "/users/5"));
+
+        let (r1, r2, r3, r4, r5, r6) = join!(r1, r2, r3, r4, r5, r6);
+
+        assert_eq!(r1.body(), "index");
+        assert_eq!(r2.body(), "create");
+        assert_eq!(r3.body(), "show 5");
+        assert_eq!(r4.body(), "update");
+        assert_eq!(r5.body(), "update");
+        r6.assert_not_found();
+    }
+
+    #[tokio::test]
+    async fn it_names_resource_routes() {
+        let router = Router::from_iter([
+            Route::resource("/admin/users")
+                .parameter("user")
+                .show(show)
+                .update(update)
+                .into(),
+            Route::resource("/posts")
+                .name("articles")
+                .index(index)
+                .into(),
+        ])
+        .compile()
+        .unwrap();
+
+        assert_eq!(
+            router.url("admin.users.show", [("user", "5")]).unwrap(),
+            "/admin/users/5"
+        );
+        assert_eq!(
+            router.url("admin.users.update", [("user", "5")]).unwrap(),
+            "/admin/users/5"
+        );
+        assert_eq!(
+            router.url("articles.index", [("page", "2")]).unwrap(),
+            "/posts?page=2"
+        );
+        assert!(router.url("admin.users.index", [("user", "5")]).is_err());
+    }
+}
@@ -1,10 +1,13 @@
+use std::any::Any;
 use std::collections::HashMap;
 use std::future::Future;
-use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 
 use regex::Error as RegexError;
 use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::http::Handler;
 use crate::http::Method;
@@ -12,8 +15,13 @@ use crate::http::Request;
 use crate::http::Response;
 use crate::http::Result as HttpResult;
 use crate::http::Uri;
+use crate::routing::metadata::MatchedRoute;
+use crate::routing::metadata::Metadata;
+use crate::routing::middleware::GroupReference;
 use crate::routing::middleware::Middleware;
 use crate::routing::middleware::Middlewares;
+use crate::routing::resource::Resource;
+use crate::routing::router::Error;
 
 /// Routes are used to match requests to handlers. They
 /// store information about the path, the HTTP method and
@@ -24,12 +32,25 @@ pub struct Data<App: Send + Sync + 'static> {
     handler: Handler<App>,
     parameters: HashMap<String, String>,
     middlewares: Middlewares<App>,
+    name: Option<String>,
+    case: Option<Case>,
+    max_body_size: Option<u64>,
+    timeout: Option<Duration>,
+    excluded_middlewares: Vec<String>,
+    metadata: Metadata,
 }
 
 #[derive(Default)]
 pub struct Config<App: Send + Sync + 'static> {
     middlewares: Middlewares<App>,
     parameters: HashMap<String, String>,
+    name: String,
+    prefix: String,
+    case: Option<Case>,
+    max_body_size: Option<u64>,
+    timeout: Option<Duration>,
+    excluded_middlewares: Vec<String>,
+    metadata: Metadata,
 }
 
 pub struct Group<App: Send + Sync + 'static> {
@@ -47,6 +68,55 @@ pub struct Route<App: Send + Sync + 'static> {
     path: String,
     method: Method,
     handler: Handler<App>,
+    name: Option<String>,
+    middlewares: Vec<&'static str>,
+    case: Case,
+    max_body_size: Option<u64>,
+    metadata: Metadata,
+    segments: Vec<Segment>,
+}
+
+/// A segment of a route path, as its regex matches it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// A static segment, lowercased when the route is case
+    /// insensitive.
+    Static(String),
+
+    /// A parameter with its constraint, if any.
+    Parameter(Option<String>),
+
+    /// A trailing wildcard capturing the rest of the path.
+    Wildcard,
+}
+
+/// Determines how the letter case of request paths is
+/// taken into account when matching routes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Case {
+    /// Paths must match the route casing.
+    #[default]
+    Sensitive,
+
+    /// Paths match regardless of their casing.
+    Insensitive,
+
+    /// Paths match regardless of their casing, but the ones
+    /// with uppercase letters are permanently redirected
+    /// to their lowercase version.
+    Lowercase,
+}
+
+/// A structured description of a compiled route, useful
+/// to generate docs, dashboards or client SDKs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RouteInfo {
+    pub method: String,
+    pub path: String,
+    pub name: Option<String>,
+    pub middleware: Vec<String>,
+    pub parameters: Vec<String>,
 }
 
 impl<App: Send + Sync + 'static> Config<App> {
@@ -54,8 +124,22 @@ impl<App: Send + Sync + 'static> Config<App> {
         Self {
             middlewares,
             parameters: Default::default(),
+            name: Default::default(),
+            prefix: Default::default(),
+            case: None,
+            max_body_size: None,
+            timeout: None,
+            excluded_middlewares: Vec::new(),
+            metadata: Metadata::new(),
         }
     }
+
+    /// Sets the default case sensitivity of the routes.
+    pub fn with_case(mut self, case: Case) -> Self {
+        self.case = Some(case);
+
+        self
+    }
 }
 
 impl<App: Send + Sync + 'static> Clone for Config<App> {
@@ -63,6 +147,13 @@ impl<App: Send + Sync + 'static> Clone for Config<App> {
         Self {
             middlewares: self.middlewares.clone(),
             parameters: self.parameters.clone(),
+            name: self.name.clone(),
+            prefix: self.prefix.clone(),
+            case: self.case,
+            max_body_size: self.max_body_size,
+            timeout: self.timeout,
+            excluded_middlewares: self.excluded_middlewares.clone(),
+            metadata: self.metadata.clone(),
         }
     }
 }
@@ -71,19 +162,76 @@ impl<'a, App: Send + Sync + 'static> FromIterator<&'a Config<App>> for Config<Ap
     fn from_iter<T: IntoIterator<Item = &'a Self>>(iter: T) -> Self {
         let mut parameters = HashMap::new();
         let mut middlewares = Middlewares::new();
+        let mut name = String::new();
+        let mut prefix = String::new();
+        let mut case = None;
+        let mut max_body_size = None;
+        let mut timeout = None;
+        let mut excluded_middlewares = Vec::new();
+        let mut metadata = Metadata::new();
 
         for config in iter {
             parameters.extend(config.parameters.clone());
             middlewares.extend(config.middlewares.clone());
+            name.push_str(&config.name);
+            prefix = join_paths(&prefix, &config.prefix);
+            case = config.case.or(case);
+            max_body_size = config.max_body_size.or(max_body_size);
+            timeout = config.timeout.or(timeout);
+            excluded_middlewares.extend(config.excluded_middlewares.clone());
+            metadata.extend([config.metadata.clone()]);
         }
 
         Self {
             middlewares,
             parameters,
+            name,
+            prefix,
+            case,
+            max_body_size,
+            timeout,
+            excluded_middlewares,
+            metadata,
         }
     }
 }
 
+/// Joins a path prefix with a path. An empty prefix
+/// leaves the path untouched.
+pub(crate) fn join_paths(prefix: &str, path: &str) -> String {
+    if prefix.trim_matches('/').is_empty() {
+        return path.to_string();
+    }
+
+    let segments: Vec<&str> = [prefix, path]
+        .iter()
+        .map(|segment| segment.trim_matches('/'))
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    format!("/{}", segments.join("/"))
+}
+
+/// Wraps the handler so it fails with a 503 response when
+/// it takes longer than the given timeout.
+fn with_timeout<App: Send + Sync + 'static>(
+    handler: Handler<App>,
+    timeout: Duration,
+) -> Handler<App> {
+    Arc::new(move |request| {
+        let handler = handler.clone();
+
+        Box::pin(async move {
+            match tokio::time::timeout(timeout, handler(request)).await {
+                Ok(result) => result,
+                Err(_) => Response::service_unavailable()
+                    .message("The request took too long to complete")
+                    .into_err(),
+            }
+        })
+    })
+}
+
 async fn not_found_handler<App: Send + Sync + 'static>(request: Request<App>) -> HttpResult {
     Response::not_found()
         .message(format!(
@@ -91,12 +239,12 @@ async fn not_found_handler<App: Send + Sync + 'static>(request: Request<App>) ->
             request.method(),
             request.uri()
         ))
-        .into_ok()
+        .into_err()
 }
 
 impl<App: Send + Sync + 'static> Builder<App> {
     pub fn fallback() -> Self {
-        Builder::any(".*", not_found_handler)
+        Builder::any(".*", not_found_handler).case(Case::Sensitive)
     }
 
     pub fn group<I>(routes: I) -> Self
@@ -107,6 +255,13 @@ impl<App: Send + Sync + 'static> Builder<App> {
             config: Config {
                 middlewares: Default::default(),
                 parameters: Default::default(),
+                name: Default::default(),
+                prefix: Default::default(),
+                case: None,
+                max_body_size: None,
+                timeout: None,
+                excluded_middlewares: Vec::new(),
+                metadata: Metadata::new(),
             },
             routes: routes.into(),
         };
@@ -114,26 +269,53 @@ impl<App: Send + Sync + 'static> Builder<App> {
         Self::Group(group)
     }
 
-    /// Adds a GET route to the router.
-    pub fn get<P, H, R>(path: P, handler: H) -> Self
+    /// Creates a route with the given methods and an
+    /// already boxed handler.
+    pub(crate) fn with_methods<P>(path: P, methods: Vec<Method>, handler: Handler<App>) -> Self
     where
         P: Into<String>,
-        R: Future<Output = HttpResult> + Send + 'static,
-        H: Fn(Request<App>) -> R + Send + Sync + 'static,
     {
-        let handler: Handler<App> = Arc::new(move |req| Box::pin(handler(req)));
-
         let data = Data {
             path: path.into(),
-            methods: vec![Method::GET],
+            methods,
             handler,
             parameters: Default::default(),
             middlewares: Default::default(),
+            name: None,
+            case: None,
+            max_body_size: None,
+            timeout: None,
+            excluded_middlewares: Vec::new(),
+            metadata: Metadata::new(),
         };
 
         Self::Data(data)
     }
 
+    /// Starts a resource with the conventional CRUD routes
+    /// at the given path. See [`Resource`].
+    pub fn resource<P>(path: P) -> Resource<App>
+    where
+        P: Into<String>,
+    {
+        Resource::new(path)
+    }
+
+    /// Adds a GET route to the router. GET routes also
+    /// answer HEAD requests.
+    pub fn get<P, H, R>(path: P, handler: H) -> Self
+    where
+        P: Into<String>,
+        R: Future<Output = HttpResult> + Send + 'static,
+        H: Fn(Request<App>) -> R + Send + Sync + 'static,
+    {
+        Self::with_methods(
+            path,
+            vec![Method::GET, Method::HEAD],
+            Arc::new(move |req| Box::pin(handler(req))),
+        )
+    }
+
     /// Adds a POST route to the router.
     pub fn post<P, H, R>(path: P, handler: H) -> Self
     where
@@ -141,17 +323,11 @@ impl<App: Send + Sync + 'static> Builder<App> {
         R: Future<Output = HttpResult> + Send + 'static,
         H: Fn(Request<App>) -> R + Send + Sync + 'static,
     {
-        let handler: Handler<App> = Arc::new(move |req| Box::pin(handler(req)));
-
-        let data = Data {
-            path: path.into(),
-            methods: vec![Method::POST],
-            handler,
-            parameters: Default::default(),
-            middlewares: Default::default(),
-        };
-
-        Self::Data(data)
+        Self::with_methods(
+            path,
+            vec![Method::POST],
+            Arc::new(move |req| Box::pin(handler(req))),
+        )
     }
 
     /// Adds a PUT route to the router.
@@ -161,17 +337,11 @@ impl<App: Send + Sync + 'static> Builder<App> {
         R: Future<Output = HttpResult> + Send + 'static,
         H: Fn(Request<App>) -> R + Send + Sync + 'static,
     {
-        let handler: Handler<App> = Arc::new(move |req| Box::pin(handler(req)));
-
-        let data = Data {
-            path: path.into(),
-            methods: vec![Method::PUT],
-            handler,
-            parameters: Default::default(),
-            middlewares: Default::default(),
-        };
-
-        Self::Data(data)
+        Self::with_methods(
+            path,
+            vec![Method::PUT],
+            Arc::new(move |req| Box::pin(handler(req))),
+        )
     }
 
     /// Adds a PATCH route to the router.
@@ -181,17 +351,11 @@ impl<App: Send + Sync + 'static> Builder<App> {
         R: Future<Output = HttpResult> + Send + 'static,
         H: Fn(Request<App>) -> R + Send + Sync + 'static,
     {
-        let handler: Handler<App> = Arc::new(move |req| Box::pin(handler(req)));
-
-        let data = Data {
-            path: path.into(),
-            methods: vec![Method::PATCH],
-            handler,
-            parameters: Default::default(),
-            middlewares: Default::default(),
-        };
-
-        Self::Data(data)
+        Self::with_methods(
+            path,
+            vec![Method::PATCH],
+            Arc::new(move |req| Box::pin(handler(req))),
+        )
     }
 
     /// Adds a DELETE route to the router.
@@ -201,17 +365,11 @@ impl<App: Send + Sync + 'static> Builder<App> {
         R: Future<Output = HttpResult> + Send + 'static,
         H: Fn(Request<App>) -> R + Send + Sync + 'static,
     {
-        let handler: Handler<App> = Arc::new(move |req| Box::pin(handler(req)));
-
-        let data = Data {
-            path: path.into(),
-            methods: vec![Method::DELETE],
-            handler,
-            parameters: Default::default(),
-            middlewares: Default::default(),
-        };
-
-        Self::Data(data)
+        Self::with_methods(
+            path,
+            vec![Method::DELETE],
+            Arc::new(move |req| Box::pin(handler(req))),
+        )
     }
 
     /// Adds a route to the router that matches all http
@@ -222,8 +380,6 @@ impl<App: Send + Sync + 'static> Builder<App> {
         R: Future<Output = HttpResult> + Send + 'static,
         H: Fn(Request<App>) -> R + Send + Sync + 'static,
     {
-        let handler: Handler<App> = Arc::new(move |req| Box::pin(handler(req)));
-
         let methods = vec![
             Method::OPTIONS,
             Method::GET,
@@ -236,15 +392,41 @@ impl<App: Send + Sync + 'static> Builder<App> {
             Method::PATCH,
         ];
 
-        let data = Data {
-            path: path.into(),
-            methods,
-            handler,
-            parameters: Default::default(),
-            middlewares: Default::default(),
-        };
+        Self::with_methods(path, methods, Arc::new(move |req| Box::pin(handler(req))))
+    }
 
-        Self::Data(data)
+    /// Adds a route that temporarily redirects all the
+    /// requests to the given location with a 307 response.
+    /// The method and body of the request are preserved.
+    pub fn redirect<P, L>(path: P, location: L) -> Self
+    where
+        P: Into<String>,
+        L: Into<String>,
+    {
+        let location: String = location.into();
+
+        Self::any(path, move |_| {
+            let location = location.clone();
+
+            async move { Response::temporary_redirect(location).into_ok() }
+        })
+    }
+
+    /// Adds a route that permanently redirects all the
+    /// requests to the given location with a 308 response.
+    /// The method and body of the request are preserved.
+    pub fn permanent_redirect<P, L>(path: P, location: L) -> Self
+    where
+        P: Into<String>,
+        L: Into<String>,
+    {
+        let location: String = location.into();
+
+        Self::any(path, move |_| {
+            let location = location.clone();
+
+            async move { Response::permanent_redirect(location).into_ok() }
+        })
     }
 
     pub fn middleware<M>(mut self, middleware: M) -> Self
@@ -261,6 +443,90 @@ impl<App: Send + Sync + 'static> Builder<App> {
         self
     }
 
+    /// Attaches a typed value to the route, or the routes
+    /// of a group. Middlewares and handlers can read it from
+    /// [`Request::route`]. Values set on a route replace
+    /// the values of the same type set on its groups.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    /// use valar::routing::route::Builder as Route;
+    ///
+    /// struct App;
+    ///
+    /// enum RequiresRole {
+    ///     Admin,
+    /// }
+    ///
+    /// async fn dashboard(_request: Request<App>) -> Result {
+    ///     Response::ok().into_ok()
+    /// }
+    ///
+    /// let route = Route::get("/admin", dashboard).meta(RequiresRole::Admin);
+    /// ```
+    pub fn meta<T>(mut self, value: T) -> Self
+    where
+        T: Any + Send + Sync,
+    {
+        match &mut self {
+            Self::Data(data) => data.metadata.insert(value),
+            Self::Group(group) => group.config.metadata.insert(value),
+        };
+
+        self
+    }
+
+    /// Excludes the given middleware from the route, or
+    /// the routes of a group, even when it's added by a
+    /// parent group or the router.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::middleware::Cookies;
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    /// use valar::routing::route::Builder as Route;
+    ///
+    /// struct App;
+    ///
+    /// async fn webhook(_request: Request<App>) -> Result {
+    ///     Response::ok().into_ok()
+    /// }
+    ///
+    /// let routes = Route::group([
+    ///     Route::post("/webhooks/stripe", webhook).without_middleware::<Cookies>(),
+    /// ])
+    /// .middleware(Cookies);
+    /// ```
+    pub fn without_middleware<M>(self) -> Self
+    where
+        M: Middleware<App>,
+    {
+        self.without_middleware_named(std::any::type_name::<M>())
+    }
+
+    /// Excludes the middlewares with the given name, as
+    /// returned by [`Middleware::name`].
+    pub fn without_middleware_named<N>(mut self, name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        let excluded = match &mut self {
+            Self::Data(data) => &mut data.excluded_middlewares,
+            Self::Group(group) => &mut group.config.excluded_middlewares,
+        };
+
+        excluded.push(name.into());
+
+        self
+    }
+
     pub fn where_parameter<N, V>(mut self, name: N, value: V) -> Self
     where
         N: Into<String>,
@@ -276,7 +542,300 @@ impl<App: Send + Sync + 'static> Builder<App> {
         self
     }
 
-    pub fn compile(self, previous: Config<App>) -> Result<Vec<Route<App>>, RegexError> {
+    /// Constrains the given parameter to unsigned integers.
+    pub fn where_int<N>(self, name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        self.where_parameter(name, "[0-9]+")
+    }
+
+    /// Constrains the given parameter to UUIDs.
+    pub fn where_uuid<N>(self, name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        self.where_parameter(
+            name,
+            "[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}",
+        )
+    }
+
+    /// Constrains the given parameter to one of the given
+    /// values.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    /// use valar::routing::route::Builder as Route;
+    ///
+    /// struct App;
+    ///
+    /// async fn index(_request: Request<App>) -> Result {
+    ///     Response::ok().into_ok()
+    /// }
+    ///
+    /// let route = Route::get("/tickets/:status", index)
+    ///     .where_in("status", ["open", "closed"]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if no values are given.
+    pub fn where_in<N, I, V>(self, name: N, values: I) -> Self
+    where
+        N: Into<String>,
+        I: IntoIterator<Item = V>,
+        V: AsRef<str>,
+    {
+        let name = name.into();
+        let values: Vec<String> = values
+            .into_iter()
+            .map(|value| regex::escape(value.as_ref()))
+            .collect();
+
+        assert!(
+            !values.is_empty(),
+            "The parameter {name} must allow at least one value"
+        );
+
+        self.where_parameter(name, format!("(?:{})", values.join("|")))
+    }
+
+    /// Sets how the letter case of request paths is taken
+    /// into account when matching the route, or the routes
+    /// of a group. Overrides the router default.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    /// use valar::routing::route::Builder as Route;
+    /// use valar::routing::route::Case;
+    ///
+    /// struct App;
+    ///
+    /// async fn promo(_request: Request<App>) -> Result {
+    ///     Response::ok().into_ok()
+    /// }
+    ///
+    /// // `/Summer-Sale` redirects to `/summer-sale`.
+    /// let route = Route::get("/summer-sale", promo).case(Case::Lowercase);
+    /// ```
+    pub fn case(mut self, case: Case) -> Self {
+        match &mut self {
+            Self::Data(data) => data.case = Some(case),
+            Self::Group(group) => group.config.case = Some(case),
+        };
+
+        self
+    }
+
+    /// Sets the maximum size, in bytes, of the request
+    /// bodies accepted by the route, or the routes of a
+    /// group. Overrides the router default.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    /// use valar::routing::route::Builder as Route;
+    ///
+    /// struct App;
+    ///
+    /// async fn upload(_request: Request<App>) -> Result {
+    ///     Response::ok().into_ok()
+    /// }
+    ///
+    /// let route = Route::post("/uploads", upload).max_body_size(50 * 1024 * 1024);
+    /// ```
+    pub fn max_body_size(mut self, bytes: u64) -> Self {
+        match &mut self {
+            Self::Data(data) => data.max_body_size = Some(bytes),
+            Self::Group(group) => group.config.max_body_size = Some(bytes),
+        };
+
+        self
+    }
+
+    /// Sets how long the route, or the routes of a group,
+    /// may take to produce a response, including their
+    /// middlewares. Slower requests get a 503 response.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    /// use valar::routing::route::Builder as Route;
+    ///
+    /// struct App;
+    ///
+    /// async fn report(_request: Request<App>) -> Result {
+    ///     Response::ok().into_ok()
+    /// }
+    ///
+    /// let route = Route::get("/report", report).timeout(Duration::from_secs(120));
+    /// ```
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        match &mut self {
+            Self::Data(data) => data.timeout = Some(timeout),
+            Self::Group(group) => group.config.timeout = Some(timeout),
+        };
+
+        self
+    }
+
+    /// Prefixes the path of the route, or the paths of all
+    /// the routes of a group.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    /// use valar::routing::route::Builder as Route;
+    ///
+    /// struct App;
+    ///
+    /// async fn index(_request: Request<App>) -> Result {
+    ///     Response::ok().into_ok()
+    /// }
+    ///
+    /// // Matches `/admin/users`.
+    /// let routes = Route::group([Route::get("/users", index)]).prefix("/admin");
+    /// ```
+    pub fn prefix<P>(mut self, prefix: P) -> Self
+    where
+        P: Into<String>,
+    {
+        let prefix: String = prefix.into();
+
+        match &mut self {
+            Self::Data(data) => data.path = join_paths(&prefix, &data.path),
+            Self::Group(group) => group.config.prefix = join_paths(&prefix, &group.config.prefix),
+        };
+
+        self
+    }
+
+    /// Adds the middlewares of the group registered on the
+    /// router under the given name. They run in the
+    /// position the group is added at.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::middleware::Cookies;
+    /// use valar::http::middleware::QueueableCookies;
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    /// use valar::routing::middleware::Middlewares;
+    /// use valar::routing::route::Builder as Route;
+    /// use valar::routing::Router;
+    ///
+    /// struct App;
+    ///
+    /// async fn home(_request: Request<App>) -> Result {
+    ///     Response::ok().into_ok()
+    /// }
+    ///
+    /// let router = Router::from_iter([
+    ///     Route::group([Route::get("/", home)]).middleware_group("web"),
+    /// ])
+    /// .middleware_group(
+    ///     "web",
+    ///     Middlewares::new()
+    ///         .middleware(Cookies)
+    ///         .middleware(QueueableCookies),
+    /// );
+    /// ```
+    pub fn middleware_group<N>(self, name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        self.middleware(GroupReference(name.into()))
+    }
+
+    /// Replaces the middleware group references of the
+    /// route, or of the group and its routes, with the
+    /// middlewares of the given groups.
+    pub(crate) fn expand_middleware_groups(
+        &mut self,
+        groups: &HashMap<String, Middlewares<App>>,
+    ) -> Result<(), String> {
+        match self {
+            Self::Data(data) => data.middlewares = data.middlewares.expand(groups)?,
+            Self::Group(group) => {
+                group.config.middlewares = group.config.middlewares.expand(groups)?;
+
+                for route in &mut group.routes {
+                    route.expand_middleware_groups(groups)?;
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Adds the given middlewares to the route or group.
+    pub(crate) fn with_middlewares(mut self, middlewares: Middlewares<App>) -> Self {
+        match &mut self {
+            Self::Data(data) => data.middlewares.extend(middlewares),
+            Self::Group(group) => group.config.middlewares.extend(middlewares),
+        };
+
+        self
+    }
+
+    /// Names the route so its URL can be generated with
+    /// `Router::url`. Naming a group prefixes the names of
+    /// all of its routes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Result;
+    /// use valar::http::Response;
+    /// use valar::routing::route::Builder as Route;
+    ///
+    /// struct App;
+    ///
+    /// async fn show(_request: Request<App>) -> Result {
+    ///     Response::ok().into_ok()
+    /// }
+    ///
+    /// let routes = Route::group([
+    ///     Route::get("/users/:id", show).name("show"),
+    /// ])
+    /// .name("users.");
+    /// ```
+    pub fn name<N>(mut self, name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        match &mut self {
+            Self::Data(data) => data.name = Some(name.into()),
+            Self::Group(group) => group.config.name = name.into(),
+        };
+
+        self
+    }
+
+    pub fn compile(self, previous: Config<App>) -> Result<Vec<Route<App>>, Error> {
         match self {
             Builder::Data(data) => data.compile(previous),
             Builder::Group(group) => group.compile(previous),
@@ -285,7 +844,7 @@ impl<App: Send + Sync + 'static> Builder<App> {
 }
 
 impl<App: Send + Sync + 'static> Group<App> {
-    pub fn compile(self, config: Config<App>) -> Result<Vec<Route<App>>, RegexError> {
+    pub fn compile(self, config: Config<App>) -> Result<Vec<Route<App>>, Error> {
         let mut routes = Vec::new();
 
         for route in self.routes {
@@ -300,26 +859,53 @@ impl<App: Send + Sync + 'static> Group<App> {
 }
 
 impl<App: Send + Sync + 'static> Data<App> {
+    /// Returns the segments of the route path. The static
+    /// segments of case insensitive routes are lowercased,
+    /// so equivalent routes get the same segments.
+    fn segments(&self) -> Vec<Segment> {
+        let insensitive = matches!(self.case, Some(Case::Insensitive | Case::Lowercase));
+
+        self.path
+            .trim_matches('/')
+            .split('/')
+            .map(|segment| match segment.chars().next() {
+                Some(':') => {
+                    Segment::Parameter(self.parameters.get(segment.trim_matches(':')).cloned())
+                }
+                Some('*') => Segment::Wildcard,
+                _ if insensitive => Segment::Static(segment.to_lowercase()),
+                _ => Segment::Static(segment.to_string()),
+            })
+            .collect()
+    }
+
     /// Returns the regex string literal for the given
-    /// route.
+    /// route. A trailing `*name` segment captures the
+    /// rest of the path, including slashes.
     fn to_regex_string(&self) -> String {
-        let segments = self.path.trim_matches('/').split('/');
-
-        let regex_path = segments
-            .map(|segment| match segment.starts_with(':') {
-                true => self
-                    .parameters
-                    .get(segment.trim_matches(':'))
-                    .map(|segment| segment.deref())
-                    .unwrap_or("[a-zA-Z0-9-_]+"),
-                false => segment,
+        let insensitive = matches!(self.case, Some(Case::Insensitive | Case::Lowercase));
+
+        let regex_path = self
+            .segments()
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Static(segment) => segment,
+                Segment::Parameter(constraint) => {
+                    constraint.unwrap_or("[a-zA-Z0-9-_]+".to_string())
+                }
+                Segment::Wildcard => ".+".to_string(),
             })
             .collect::<Vec<_>>()
             .join("/");
 
+        let flags = match insensitive {
+            true => "(?i)",
+            false => "",
+        };
+
         match regex_path.is_empty() {
-            true => "^/$".to_string(),
-            false => format!("^/{regex_path}/?$"),
+            true => format!("{flags}^/$"),
+            false => format!("{flags}^/{regex_path}/?$"),
         }
     }
 
@@ -329,11 +915,42 @@ impl<App: Send + Sync + 'static> Data<App> {
         Regex::new(&self.to_regex_string())
     }
 
-    pub fn compile(self, config: Config<App>) -> Result<Vec<Route<App>>, RegexError> {
+    /// Compiles the route within the given configuration.
+    /// It fails when a wildcard segment is not the last
+    /// one, since it captures the rest of the path.
+    pub fn compile(mut self, config: Config<App>) -> Result<Vec<Route<App>>, Error> {
+        self.path = join_paths(&config.prefix, &self.path);
+
+        let mut segments = self.path.trim_matches('/').split('/').rev().skip(1);
+
+        if segments.any(|segment| segment.starts_with('*')) {
+            return Err(Error::MisplacedWildcard(self.path));
+        }
+
+        self.case = self.case.or(config.case);
+        self.max_body_size = self.max_body_size.or(config.max_body_size);
+        self.timeout = self.timeout.or(config.timeout);
+
+        let mut metadata = config.metadata.clone();
+        metadata.extend([std::mem::take(&mut self.metadata)]);
+
         let mut routes = Vec::new();
         let regex = self.to_regex()?;
+        let segments = self.segments();
         let middlewares = Middlewares::from_iter([&config.middlewares, &self.middlewares]);
+        let excluded: Vec<String> = config
+            .excluded_middlewares
+            .iter()
+            .chain(&self.excluded_middlewares)
+            .cloned()
+            .collect();
+        let middlewares = middlewares.without(&excluded);
+        let names = middlewares.names();
         let handler = middlewares.wrap(self.handler.clone());
+        let handler = match self.timeout {
+            Some(timeout) => with_timeout(handler, timeout),
+            None => handler,
+        };
 
         for method in self.methods {
             let route = Route {
@@ -341,6 +958,15 @@ impl<App: Send + Sync + 'static> Data<App> {
                 path: self.path.clone(),
                 method,
                 handler: handler.clone(),
+                name: self
+                    .name
+                    .as_ref()
+                    .map(|name| format!("{}{name}", config.name)),
+                middlewares: names.clone(),
+                case: self.case.unwrap_or_default(),
+                max_body_size: self.max_body_size,
+                metadata: metadata.clone(),
+                segments: segments.clone(),
             };
 
             routes.push(route);
@@ -367,6 +993,102 @@ impl<App: Send + Sync + 'static> Route<App> {
         &self.handler
     }
 
+    /// Returns the name of the route, including the
+    /// prefixes of the groups it belongs to.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns how the route matches the letter case of
+    /// request paths.
+    pub fn case(&self) -> Case {
+        self.case
+    }
+
+    /// Returns the maximum request body size of the route,
+    /// if it overrides the router default.
+    pub fn max_body_size(&self) -> Option<u64> {
+        self.max_body_size
+    }
+
+    /// Returns the metadata attached to the route.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Determines if the route matches every path the
+    /// other one matches, so the other one can't be reached
+    /// when this one is found first. It does when their
+    /// segments are the same and each of its parameters is
+    /// unconstrained or has the same constraint.
+    pub(crate) fn shadows(&self, other: &Route<App>) -> bool {
+        if self.method != other.method || self.segments.len() != other.segments.len() {
+            return false;
+        }
+
+        if self.case == Case::Sensitive && other.case != Case::Sensitive {
+            return false;
+        }
+
+        self.segments
+            .iter()
+            .zip(&other.segments)
+            .all(|(segment, other)| match (segment, other) {
+                (Segment::Static(segment), Segment::Static(other)) => match self.case {
+                    Case::Sensitive => segment == other,
+                    _ => *segment == other.to_lowercase(),
+                },
+                (Segment::Parameter(None), Segment::Parameter(_)) => true,
+                (Segment::Parameter(segment), Segment::Parameter(other)) => segment == other,
+                (Segment::Wildcard, Segment::Wildcard) => true,
+                _ => false,
+            })
+    }
+
+    /// Returns the description of the route given to the
+    /// requests it matches.
+    pub(crate) fn matched(&self) -> MatchedRoute {
+        MatchedRoute::new(self.path.clone(), self.name.clone(), self.metadata.clone())
+    }
+
+    /// Returns the names of the middlewares that run
+    /// before the handler, in order.
+    pub fn middlewares(&self) -> &[&'static str] {
+        &self.middlewares
+    }
+
+    /// Returns the names of the parameters in the route
+    /// path, including a trailing wildcard.
+    pub fn parameter_names(&self) -> Vec<&str> {
+        self.path
+            .split('/')
+            .filter_map(|segment| {
+                segment
+                    .strip_prefix(':')
+                    .or_else(|| segment.strip_prefix('*'))
+            })
+            .collect()
+    }
+
+    /// Returns a structured description of the route.
+    pub fn info(&self) -> RouteInfo {
+        RouteInfo {
+            method: self.method.to_string(),
+            path: self.path.clone(),
+            name: self.name.clone(),
+            middleware: self
+                .middlewares
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            parameters: self
+                .parameter_names()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        }
+    }
+
     /// Handles the route with the given app and request.
     pub async fn handle(&self, request: Request<App>) -> Response {
         match (self.handler)(request).await {
@@ -377,18 +1099,26 @@ impl<App: Send + Sync + 'static> Route<App> {
 
     /// Get the parameters of the route given a path.
     pub(crate) fn parameters(&self, uri: &Uri) -> HashMap<String, String> {
-        self.path
-            .trim_matches('/')
-            .split('/')
-            .zip(uri.path().trim_matches('/').split('/'))
-            .filter(|&(route_segment, _)| route_segment.starts_with(':'))
-            .map(|(route_segment, path_segment)| {
-                let parameter = route_segment.trim_start_matches(':').to_string();
-                let value = path_segment.to_string();
+        let path_segments: Vec<&str> = uri.path().trim_matches('/').split('/').collect();
+        let mut parameters = HashMap::new();
 
-                (parameter, value)
-            })
-            .collect()
+        for (index, route_segment) in self.path.trim_matches('/').split('/').enumerate() {
+            // A wildcard segment captures the rest of the path.
+            if let Some(parameter) = route_segment.strip_prefix('*') {
+                let value = path_segments.get(index..).unwrap_or_default().join("/");
+                parameters.insert(parameter.to_string(), value);
+
+                break;
+            }
+
+            if let (Some(parameter), Some(value)) =
+                (route_segment.strip_prefix(':'), path_segments.get(index))
+            {
+                parameters.insert(parameter.to_string(), value.to_string());
+            }
+        }
+
+        parameters
     }
 }
 
@@ -1,35 +1,73 @@
+use std::collections::HashMap;
+use std::future::Future;
 use std::marker::PhantomData;
+use std::net::SocketAddr;
+use std::pin::Pin;
 use std::sync::Arc;
 
-use http_body_util::{combinators::BoxBody, BodyExt, Empty, Full};
+use http_body_util::BodyExt;
+use http_body_util::LengthLimitError;
+use http_body_util::Limited;
 use hyper::body::Body;
-use hyper::body::Buf;
 use hyper::body::Bytes;
-use hyper::body::Frame;
-use hyper::body::Incoming;
-use hyper::server::conn::http1;
-use hyper::service::service_fn;
 use hyper::Request as BaseRequest;
-use hyper::Uri;
-use hyper::{Method, Response as BaseResponse, StatusCode};
 use regex::Error as RegexError;
 use thiserror::Error as ThisError;
-use tokio::net::TcpListener;
 
+use crate::debug;
+use crate::http::body::BoxError;
+use crate::http::error_page;
+use crate::http::error_page::RequestDetails;
+use crate::http::terminate::Terminators;
+use crate::http::terminate::TERMINATORS;
 use crate::http::Headers;
 use crate::http::Method;
 use crate::http::Request;
 use crate::http::Response;
+use crate::http::Uri;
 use crate::routing::middleware::Middleware;
 use crate::routing::middleware::Middlewares;
 use crate::routing::route::Builder;
+use crate::routing::route::Case;
 use crate::routing::route::Config;
 use crate::routing::route::Route;
+use crate::routing::route::RouteInfo;
+use crate::routing::table::RouteTable;
+use crate::routing::url::Error as UrlError;
+use crate::routing::url::UrlGenerator;
+use crate::routing::url::URL_GENERATOR;
 use crate::utils::TruncatableToFit;
 
+/// The default maximum size, in bytes, of request bodies.
+pub const DEFAULT_MAX_BODY_SIZE: u64 = 1024 * 1024 * 2;
+
 #[derive(Debug, ThisError)]
-#[error(transparent)]
-pub struct Error(#[from] RegexError);
+pub enum Error {
+    #[error(transparent)]
+    Regex(#[from] RegexError),
+
+    #[error("The route {method} {path} is shadowed by {method} {shadowing}")]
+    DuplicateRoute {
+        method: String,
+        path: String,
+        shadowing: String,
+    },
+
+    #[error("The middleware group {0} is not registered")]
+    UnknownMiddlewareGroup(String),
+
+    #[error("The wildcard of the route {0} is not its last segment")]
+    MisplacedWildcard(String),
+
+    #[error("Only routers that are not compiled can be mounted")]
+    CompiledMount,
+}
+
+/// An error handler renders the error response produced by
+/// a route (or by the fallback route) into the response
+/// that is sent to the client.
+pub type ErrorHandler =
+    Arc<dyn Fn(Response) -> Pin<Box<dyn Future<Output = Response> + Send + 'static>> + Send + Sync>;
 
 pub enum Pending {}
 
@@ -46,10 +84,29 @@ pub struct Router<App: Send + Sync + 'static, State = Pending> {
     /// Stores the current router configuration.
     middlewares: Middlewares<App>,
 
+    /// Stores the middleware groups by name.
+    middleware_groups: HashMap<String, Middlewares<App>>,
+
     /// Stores the routes that the router will use to
     /// match requests.
     routes: Routes<App>,
 
+    /// Stores the error handlers by status code.
+    error_handlers: HashMap<u16, ErrorHandler>,
+
+    /// Generates the URLs of the named routes.
+    urls: UrlGenerator,
+
+    /// Stores the default case sensitivity of the routes.
+    case: Option<Case>,
+
+    /// Stores the default maximum size of request bodies.
+    max_body_size: Option<u64>,
+
+    /// Stores the first error found while building the
+    /// router, which compiling it returns.
+    error: Option<Error>,
+
     state: PhantomData<State>,
 }
 
@@ -81,7 +138,132 @@ impl<App: Send + Sync + 'static> Router<App, Pending> {
         self
     }
 
+    /// Registers a middleware group under the given name.
+    /// Groups and routes add its middlewares with
+    /// `middleware_group`. Registering a group again
+    /// replaces it.
+    pub fn middleware_group<N>(mut self, name: N, middlewares: Middlewares<App>) -> Self
+    where
+        N: Into<String>,
+    {
+        self.middleware_groups.insert(name.into(), middlewares);
+
+        self
+    }
+
+    /// Mounts the routes of the given router at a path
+    /// prefix. The middlewares of the mounted router only
+    /// run for its own routes, and the names of its routes
+    /// are kept, and so are its case sensitivity and body
+    /// size limit. Its middleware groups are added to the
+    /// ones of this router, which take precedence. Its
+    /// fallback route and error handlers are discarded in
+    /// favour of the ones of this router.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    /// use valar::routing::route::Builder as Route;
+    /// use valar::routing::Router;
+    ///
+    /// struct App;
+    ///
+    /// async fn dashboard(_request: Request<App>) -> Result {
+    ///     Response::ok().into_ok()
+    /// }
+    ///
+    /// let admin = Router::from_iter([
+    ///     Route::get("/", dashboard).name("admin.dashboard"),
+    /// ]);
+    ///
+    /// let router = Router::from_iter([]).mount("/admin", admin);
+    /// ```
+    pub fn mount<P>(mut self, prefix: P, router: Router<App, Pending>) -> Self
+    where
+        P: Into<String>,
+    {
+        let routes = match router.routes {
+            // The first route is the fallback of the mounted
+            // router, which would shadow the routes of this one.
+            Routes::Pending(routes) => routes.into_iter().skip(1).collect::<Vec<_>>(),
+            Routes::Compiled(_) => {
+                self.error.get_or_insert(Error::CompiledMount);
+
+                return self;
+            }
+        };
+
+        if let Some(error) = router.error {
+            self.error.get_or_insert(error);
+        }
+
+        for (name, middlewares) in router.middleware_groups {
+            self.middleware_groups.entry(name).or_insert(middlewares);
+        }
+
+        let mut group = Builder::group(routes)
+            .with_middlewares(router.middlewares)
+            .prefix(prefix);
+
+        if let Some(case) = router.case {
+            group = group.case(case);
+        }
+
+        if let Some(bytes) = router.max_body_size {
+            group = group.max_body_size(bytes);
+        }
+
+        match &mut self.routes {
+            Routes::Pending(routes) => routes.push(group),
+            Routes::Compiled(_) => {
+                self.error.get_or_insert(Error::CompiledMount);
+            }
+        };
+
+        self
+    }
+
+    /// Sets how the letter case of request paths is taken
+    /// into account when matching routes. Groups and
+    /// routes may override it.
+    pub fn case(mut self, case: Case) -> Self {
+        self.case = Some(case);
+
+        self
+    }
+
+    /// Sets the default maximum size, in bytes, of request
+    /// bodies. Groups and routes may override it. When not
+    /// set, the server default is used.
+    pub fn max_body_size(mut self, bytes: u64) -> Self {
+        self.max_body_size = Some(bytes);
+
+        self
+    }
+
+    /// Registers a handler that renders the error responses
+    /// with the given status code, for example to brand
+    /// the 404 and 500 pages. Error handlers receive the
+    /// error response returned by the route.
+    pub fn on_error<H, R>(mut self, status: u16, handler: H) -> Self
+    where
+        H: Fn(Response) -> R + Send + Sync + 'static,
+        R: Future<Output = Response> + Send + 'static,
+    {
+        let handler: ErrorHandler = Arc::new(move |response| Box::pin(handler(response)));
+        self.error_handlers.insert(status, handler);
+
+        self
+    }
+
     pub fn compile(self) -> Result<Router<App, Compiled>, Error> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
         let mut compiled_routes = Vec::new();
 
         let routes = match self.routes {
@@ -89,21 +271,91 @@ impl<App: Send + Sync + 'static> Router<App, Pending> {
             _ => unreachable!(),
         };
 
-        for route in routes {
+        for mut route in routes {
+            route
+                .expand_middleware_groups(&self.middleware_groups)
+                .map_err(Error::UnknownMiddlewareGroup)?;
+
             let config = Config::from_middlewares(self.middlewares.clone());
+            let config = match self.case {
+                Some(case) => config.with_case(case),
+                None => config,
+            };
+
             compiled_routes.extend(route.compile(config)?);
         }
 
+        Self::ensure_unique(&compiled_routes)?;
+
+        let urls = UrlGenerator::new(
+            compiled_routes
+                .iter()
+                .filter_map(|route| Some((route.name()?.to_string(), route.path().to_string()))),
+        );
+
         let router = Router {
             state: PhantomData::<Compiled>,
             middlewares: self.middlewares,
+            middleware_groups: self.middleware_groups,
             routes: Routes::Compiled(compiled_routes),
+            error_handlers: self.error_handlers,
+            urls,
+            case: self.case,
+            max_body_size: self.max_body_size,
+            error: None,
         };
 
         Ok(router)
     }
 }
 
+impl<App: Send + Sync + 'static, State> Router<App, State> {
+    /// Fails when two routes share the same method and
+    /// pattern, since the last one would always shadow the
+    /// other. Parameter names and trailing slashes don't
+    /// change the pattern of a route, and neither does the
+    /// letter case of a case insensitive one, which also
+    /// shadows a case sensitive route with the same
+    /// pattern. It also fails when a later route matches
+    /// every path of an earlier one, like an unconstrained
+    /// parameter registered after a constrained one. The
+    /// fallback route, which is always the first one, may
+    /// be overridden.
+    fn ensure_unique(routes: &[Route<App>]) -> Result<(), Error> {
+        let fallback = routes.first().map(|route| route.path());
+        let mut seen: HashMap<(String, String), &str> = HashMap::new();
+        let mut previous_routes: HashMap<&Method, Vec<&Route<App>>> = HashMap::new();
+
+        for route in routes {
+            if Some(route.path()) == fallback {
+                continue;
+            }
+
+            let pattern = route.regex().as_str().trim_start_matches("(?i)");
+            let key = (route.method().to_string(), pattern.to_string());
+            let previous_routes = previous_routes.entry(route.method()).or_default();
+            let shadowed = seen.insert(key, route.path()).or_else(|| {
+                previous_routes
+                    .iter()
+                    .find(|previous| route.shadows(previous))
+                    .map(|previous| previous.path())
+            });
+
+            if let Some(previous) = shadowed {
+                return Err(Error::DuplicateRoute {
+                    method: route.method().to_string(),
+                    path: previous.to_string(),
+                    shadowing: route.path().to_string(),
+                });
+            }
+
+            previous_routes.push(route);
+        }
+
+        Ok(())
+    }
+}
+
 impl<App: Send + Sync + 'static> Router<App, Compiled> {
     /// Returns the routes of the router.
     pub fn routes(&self) -> &[Route<App>] {
@@ -113,6 +365,12 @@ impl<App: Send + Sync + 'static> Router<App, Compiled> {
         }
     }
 
+    /// Returns a structured description of every route,
+    /// in the same order as `summary`.
+    pub fn info(&self) -> Vec<RouteInfo> {
+        self.routes().iter().rev().map(Route::info).collect()
+    }
+
     /// Determines if the router has a route that matches
     /// the given criteria.
     pub fn has_route(&self, path: &str, method: &Method) -> bool {
@@ -134,6 +392,57 @@ impl<App: Send + Sync + 'static> Router<App, Compiled> {
             .expect("There should always be a fallback route in a router.")
     }
 
+    /// Generates the URL of the named route with the given
+    /// parameters. Parameters that are not part of the
+    /// route path are appended as the query string.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::http::Request;
+    /// use valar::http::Response;
+    /// use valar::http::Result;
+    /// use valar::routing::route::Builder as Route;
+    /// use valar::routing::Router;
+    ///
+    /// struct App;
+    ///
+    /// async fn show(_request: Request<App>) -> Result {
+    ///     Response::ok().into_ok()
+    /// }
+    ///
+    /// let router = Router::from_iter([
+    ///     Route::get("/users/:id", show).name("users.show"),
+    /// ])
+    /// .compile()
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     router.url("users.show", [("id", "5")]).unwrap(),
+    ///     "/users/5"
+    /// );
+    /// ```
+    pub fn url<P, K, V>(&self, name: &str, parameters: P) -> Result<String, UrlError>
+    where
+        P: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.urls.url(name, parameters)
+    }
+
+    /// Returns the matching table of the router, which can
+    /// be saved to disk and inspected by external tools.
+    /// It can't be loaded back into a router.
+    pub fn table(&self) -> RouteTable {
+        RouteTable::from_router(self)
+    }
+
+    /// Returns the URL generator of the router.
+    pub fn urls(&self) -> &UrlGenerator {
+        &self.urls
+    }
+
     pub fn summary(&self) -> Vec<String> {
         let summary: Vec<String> = self
             .routes()
@@ -152,52 +461,196 @@ impl<App: Send + Sync + 'static> Router<App, Compiled> {
         summary
     }
 
-    pub(crate) async fn handle_base(
+    pub(crate) async fn handle_base<B>(
         &self,
         app: Arc<App>,
-        request: BaseRequest<Incoming>,
-    ) -> Response {
-        let request = match Self::build_request(request, app.clone()).await {
+        request: BaseRequest<B>,
+        remote_addr: Option<SocketAddr>,
+        default_limit: u64,
+    ) -> Response
+    where
+        B: Body<Data = Bytes>,
+        B::Error: Into<BoxError>,
+    {
+        let route = self.find(request.method(), request.uri());
+        let limit = self.body_limit_or(route, default_limit);
+
+        let request = match Self::build_request(request, app, remote_addr, limit).await {
             Ok(request) => request,
-            Err(response) => return response,
+            Err(response) => return self.render_error(response).await,
         };
 
-        self.handle(request).await
+        self.dispatch(request, route, default_limit).await
     }
 
     pub async fn handle(&self, request: Request<App>) -> Response {
+        self.handle_with_limit(request, DEFAULT_MAX_BODY_SIZE).await
+    }
+
+    /// Handles the given request, using the given maximum
+    /// body size when neither the router nor the route set
+    /// one.
+    pub(crate) async fn handle_with_limit(
+        &self,
+        request: Request<App>,
+        default_limit: u64,
+    ) -> Response {
         let route = self.find(request.method(), request.uri());
+
+        self.dispatch(request, route, default_limit).await
+    }
+
+    /// Handles the given request with the route matching
+    /// it.
+    async fn dispatch(
+        &self,
+        request: Request<App>,
+        route: &Route<App>,
+        default_limit: u64,
+    ) -> Response {
+        if route.case() == Case::Lowercase {
+            let uri = request.uri();
+
+            if let Some(response) = Self::lowercase_redirect(uri.path(), uri.query()) {
+                return response;
+            }
+        }
+
+        if request.body().len() as u64 > self.body_limit_or(route, default_limit) {
+            return self.render_error(Self::payload_too_large()).await;
+        }
+
         let request = request.parematrized(route);
+        let details = debug::enabled().then(|| RequestDetails::new(&request));
+
+        let terminators = Terminators::new();
+
+        request
+            .context()
+            .insert(URL_GENERATOR, self.urls.clone())
+            .await;
+        request
+            .context()
+            .insert(TERMINATORS, terminators.clone())
+            .await;
+
+        let mut response = match (route.handler())(request).await {
+            Ok(response) => response,
+            Err(response) => self.render_failure(response, details.as_ref()).await,
+        };
+
+        response.set_terminators(terminators);
 
-        route.handle(request).await
+        response
     }
 
-    /// Turns a request into a base `Request` object.
-    pub(crate) async fn build_request(
-        mut base: BaseRequest<Incoming>,
-        app: Arc<App>,
-    ) -> Result<Request<App>, Response> {
-        // TODO: Allow this to be dynamic. Current hardcoded 2MB.
-        const MAX_ALLOWED_RESPONSE_SIZE: u64 = 1024 * 1024 * 2;
+    /// Returns the maximum request body size of the given
+    /// route.
+    pub fn body_limit(&self, route: &Route<App>) -> u64 {
+        self.body_limit_or(route, DEFAULT_MAX_BODY_SIZE)
+    }
 
-        let content_length = base
-            .body()
-            .size_hint()
-            .upper()
-            .unwrap_or(MAX_ALLOWED_RESPONSE_SIZE + 1);
+    /// Returns the maximum request body size of the given
+    /// route, or the given one when neither the router nor
+    /// the route set it.
+    pub(crate) fn body_limit_or(&self, route: &Route<App>, default_limit: u64) -> u64 {
+        route
+            .max_body_size()
+            .or(self.max_body_size)
+            .unwrap_or(default_limit)
+    }
 
-        if content_length > MAX_ALLOWED_RESPONSE_SIZE {
-            let error = Response::payload_too_large()
-                .message("Request body too large")
-                .build();
+    fn payload_too_large() -> Response {
+        Response::payload_too_large()
+            .message("Request body too large")
+            .build()
+    }
 
-            return Err(error);
+    /// Returns a permanent redirect to the lowercase version
+    /// of the given path, if it has uppercase letters. The
+    /// query string is kept as is.
+    fn lowercase_redirect(path: &str, query: Option<&str>) -> Option<Response> {
+        if !path.chars().any(|character| character.is_ascii_uppercase()) {
+            return None;
         }
 
-        let bytes = base.body_mut();
+        let location = match query {
+            Some(query) => format!("{}?{query}", path.to_ascii_lowercase()),
+            None => path.to_ascii_lowercase(),
+        };
+
+        Some(Response::permanent_redirect(location).build())
+    }
+
+    /// Renders the given error response using the error
+    /// handler registered for its status code, if any.
+    pub async fn render_error(&self, response: Response) -> Response {
+        match self.error_handlers.get(&response.status().as_u16()) {
+            Some(handler) => handler(response).await,
+            None => response,
+        }
+    }
 
-        let headers: Headers<Request<App>> = base
-            .headers()
+    /// Renders the error response of a route with its
+    /// error handler and then, in debug mode, decorates
+    /// server errors with the development error page. The
+    /// report of the error is kept when the handler drops
+    /// it.
+    async fn render_failure(
+        &self,
+        response: Response,
+        details: Option<&RequestDetails>,
+    ) -> Response {
+        let report = response.error_report().cloned();
+        let mut response = self.render_error(response).await;
+
+        let Some(details) = details else {
+            return response;
+        };
+
+        if !response.status().is_server_error() {
+            return response;
+        }
+
+        if let (None, Some(report)) = (response.error_report(), report) {
+            response = response.with_error_report(report);
+        }
+
+        error_page::render(response, details)
+    }
+
+    /// Turns a hyper request into a `Request`, reading
+    /// its body up to the given limit.
+    pub(crate) async fn build_request<B>(
+        base: BaseRequest<B>,
+        app: Arc<App>,
+        remote_addr: Option<SocketAddr>,
+        limit: u64,
+    ) -> Result<Request<App>, Response>
+    where
+        B: Body<Data = Bytes>,
+        B::Error: Into<BoxError>,
+    {
+        let content_length = base.body().size_hint().lower();
+
+        if content_length > limit {
+            return Err(Self::payload_too_large());
+        }
+
+        let (parts, body) = base.into_parts();
+
+        let body = match Limited::new(body, limit as usize).collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(err) if err.is::<LengthLimitError>() => return Err(Self::payload_too_large()),
+            Err(_) => {
+                return Err(Response::bad_request()
+                    .message("Failed to read the request body")
+                    .build())
+            }
+        };
+
+        let headers: Headers<Request<App>> = parts
+            .headers
             .iter()
             .map(|(key, value)| {
                 let key = key.to_string();
@@ -207,15 +660,18 @@ impl<App: Send + Sync + 'static> Router<App, Compiled> {
             })
             .collect();
 
-        let builder = Request::builder()
-            .method(base.method().clone())
-            .uri(base.uri().clone())
-            .version(base.version())
+        let mut builder = Request::builder()
+            .method(parts.method)
+            .uri(parts.uri)
+            .version(parts.version)
             .headers(headers)
-            .body(bytes.escape_ascii().to_string())
-            .build(app);
+            .body(String::from_utf8_lossy(&body).into_owned());
 
-        Ok(builder)
+        if let Some(address) = remote_addr {
+            builder = builder.remote_addr(address);
+        }
+
+        Ok(builder.build(app))
     }
 }
 
@@ -228,7 +684,13 @@ impl<App: Send + Sync + 'static> FromIterator<Builder<App>> for Router<App> {
         Self {
             state: PhantomData::<Pending>,
             middlewares: Middlewares::new(),
+            middleware_groups: HashMap::new(),
             routes: Routes::Pending(routes_with_fallbacks),
+            error_handlers: HashMap::new(),
+            urls: UrlGenerator::default(),
+            case: None,
+            max_body_size: None,
+            error: None,
         }
     }
 }
@@ -246,15 +708,31 @@ impl<App: Send + Sync + 'static> FromIterator<Builder<App>> for Router<App> {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
     use std::sync::Arc;
+    use std::time::Duration;
 
+    use async_trait::async_trait;
+    use http_body_util::Full;
+    use hyper::body::Bytes;
+    use hyper::Request as BaseRequest;
     use tokio::join;
 
+    use crate::http::error_page::RequestDetails;
+    use crate::http::Method;
     use crate::http::Request;
     use crate::http::Response;
     use crate::http::Result as ResponseResult;
+    use crate::http::StatusCode;
     use crate::http::Uri;
+    use crate::routing::middleware::Handler;
+    use crate::routing::middleware::Middleware;
+    use crate::routing::middleware::Middlewares;
     use crate::routing::route::Builder as Route;
+    use crate::routing::route::Case;
+    use crate::routing::router::Error;
+    use crate::routing::router::DEFAULT_MAX_BODY_SIZE;
     use crate::routing::Router;
 
     struct App;
@@ -300,4 +778,673 @@ mod tests {
         r8.assert_not_found();
         r9.assert_not_found();
     }
+
+    async fn failing_handler(_request: Request<App>) -> ResponseResult {
+        Response::internal_server_error().message("Boom").into_err()
+    }
+
+    #[tokio::test]
+    async fn it_can_render_errors() {
+        let app = Arc::new(App);
+
+        let router = Router::from_iter([
+            Route::get("/", handler),
+            Route::get("/fail", failing_handler),
+        ])
+        .on_error(404, |_| async {
+            Response::not_found().html("<h1>Lost?</h1>").build()
+        })
+        .on_error(500, |response| async move {
+            let message = format!("<h1>Oops: {}</h1>", response.body());
+
+            Response::internal_server_error().html(message).build()
+        })
+        .compile()
+        .unwrap();
+
+        let ok = router.handle(Request::get(Uri::from_static("/")).build(app.clone()));
+        let missing = router.handle(Request::get(Uri::from_static("/foo")).build(app.clone()));
+        let failure = router.handle(Request::get(Uri::from_static("/fail")).build(app.clone()));
+
+        let (ok, missing, failure) = join!(ok, missing, failure);
+
+        ok.assert_ok();
+        assert_eq!(ok.body(), "");
+
+        missing
+            .assert_not_found()
+            .assert_header_is("Content-Type", "text/html");
+        assert_eq!(missing.body(), "<h1>Lost?</h1>");
+
+        assert_eq!(failure.body(), "<h1>Oops: Boom</h1>");
+    }
+
+    #[tokio::test]
+    async fn it_renders_errors_before_the_debug_page() {
+        let app = Arc::new(App);
+        let rendered = Arc::new(AtomicBool::new(false));
+        let handled = rendered.clone();
+
+        let router = Router::from_iter([Route::get("/", handler)])
+            .on_error(500, move |response| {
+                handled.store(true, Ordering::SeqCst);
+
+                async move { response }
+            })
+            .compile()
+            .unwrap();
+
+        let request = Request::get(Uri::from_static("/")).build(app);
+        let details = RequestDetails::new(&request);
+        let failure = Response::internal_server_error().message("Boom").build();
+
+        let response = router.render_failure(failure, Some(&details)).await;
+
+        assert!(rendered.load(Ordering::SeqCst));
+        assert!(response.body().contains("<h1>Boom</h1>"));
+    }
+
+    async fn redirecting_handler(request: Request<App>) -> ResponseResult {
+        Response::redirect_to_route(&request, "users.show", [("id", "5")])
+            .await?
+            .into_ok()
+    }
+
+    #[tokio::test]
+    async fn it_can_generate_named_route_urls() {
+        let app = Arc::new(App);
+
+        let router = Router::from_iter([
+            Route::get("/", handler).name("home"),
+            Route::group([
+                Route::get("/users/:id", handler).name("show"),
+                Route::post("/users", redirecting_handler).name("store"),
+            ])
+            .name("users."),
+        ])
+        .compile()
+        .unwrap();
+
+        assert_eq!(router.url("home", [("page", "2")]).unwrap(), "/?page=2");
+        assert_eq!(router.url("users.show", [("id", "5")]).unwrap(), "/users/5");
+        assert!(router.url("show", [("id", "5")]).is_err());
+
+        let response = router
+            .handle(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri(Uri::from_static("/users"))
+                    .build(app),
+            )
+            .await;
+
+        response.assert_header_is("Location", "/users/5");
+    }
+
+    #[tokio::test]
+    async fn it_can_constrain_parameters() {
+        let app = Arc::new(App);
+
+        let router = Router::from_iter([
+            Route::get("/users/:id", handler).where_int("id"),
+            Route::get("/posts/:post", handler).where_uuid("post"),
+            Route::get("/tickets/:status", handler).where_in("status", ["open", "a.b"]),
+        ])
+        .compile()
+        .unwrap();
+
+        let uri = |path: &'static str| Request::get(Uri::from_static(path)).build(app.clone());
+
+        router.handle(uri("/users/15")).await.assert_ok();
+        router.handle(uri("/users/abc")).await.assert_not_found();
+        router
+            .handle(uri("/posts/67e55044-10b1-426f-9247-bb680e5fe0c8"))
+            .await
+            .assert_ok();
+        router
+            .handle(uri("/posts/67e55044"))
+            .await
+            .assert_not_found();
+        router.handle(uri("/tickets/open")).await.assert_ok();
+        router.handle(uri("/tickets/a.b")).await.assert_ok();
+        router.handle(uri("/tickets/axb")).await.assert_not_found();
+        router
+            .handle(uri("/tickets/opened"))
+            .await
+            .assert_not_found();
+    }
+
+    #[test]
+    #[should_panic(expected = "The parameter status must allow at least one value")]
+    fn it_rejects_empty_parameter_values() {
+        let _ = Route::get("/tickets/:status", handler).where_in("status", Vec::<&str>::new());
+    }
+
+    async fn file_handler(request: Request<App>) -> ResponseResult {
+        let path: String = request.parameter("path")?;
+
+        Response::ok().body(path).into_ok()
+    }
+
+    #[tokio::test]
+    async fn it_can_match_wildcard_segments() {
+        let app = Arc::new(App);
+
+        let router = Router::from_iter([Route::get("/files/*path", file_handler)])
+            .compile()
+            .unwrap();
+
+        let uri = |path: &'static str| Request::get(Uri::from_static(path)).build(app.clone());
+
+        let nested = router.handle(uri("/files/docs/2023/report.pdf")).await;
+        let single = router.handle(uri("/files/readme.md/")).await;
+        let missing = router.handle(uri("/files")).await;
+
+        assert_eq!(nested.body(), "docs/2023/report.pdf");
+        assert_eq!(single.body(), "readme.md");
+        missing.assert_not_found();
+
+        let misplaced = Router::from_iter([
+            Route::group([Route::get("/raw", file_handler)]).prefix("/files/*path")
+        ])
+        .compile();
+
+        assert!(matches!(
+            misplaced,
+            Err(Error::MisplacedWildcard(path)) if path == "/files/*path/raw"
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_can_redirect_routes() {
+        let app = Arc::new(App);
+
+        let router = Router::from_iter([
+            Route::redirect("/old", "/new"),
+            Route::permanent_redirect("/legacy", "/"),
+        ])
+        .compile()
+        .unwrap();
+
+        let temporary = router
+            .handle(Request::get(Uri::from_static("/old")).build(app.clone()))
+            .await;
+        let permanent = router
+            .handle(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri(Uri::from_static("/legacy"))
+                    .build(app),
+            )
+            .await;
+
+        assert_eq!(temporary.status().as_u16(), 307);
+        temporary.assert_header_is("Location", "/new");
+        assert_eq!(permanent.status().as_u16(), 308);
+        permanent.assert_header_is("Location", "/");
+    }
+
+    struct Tag(&'static str);
+
+    #[async_trait]
+    impl Middleware<App> for Tag {
+        async fn handle(&self, next: Handler<App>, request: Request<App>) -> ResponseResult {
+            let mut response = next(request).await?;
+            response.headers_mut().insert("X-Tag", self.0);
+
+            Ok(response)
+        }
+    }
+
+    #[tokio::test]
+    async fn it_can_mount_routers() {
+        let app = Arc::new(App);
+
+        let admin = Router::from_iter([
+            Route::get("/", handler).name("admin.dashboard"),
+            Route::get("/users/:id", handler).name("admin.users.show"),
+            Route::post("/uploads", handler).max_body_size(8),
+            Route::post("/notes", handler),
+        ])
+        .middleware(Tag("admin"))
+        .max_body_size(4);
+
+        let router = Router::from_iter([Route::get("/", handler)])
+            .mount("/admin/", admin)
+            .compile()
+            .unwrap();
+
+        let uri = |path: &'static str| Request::get(Uri::from_static(path)).build(app.clone());
+
+        let home = router.handle(uri("/")).await;
+        let dashboard = router.handle(uri("/admin")).await;
+        let user = router.handle(uri("/admin/users/5")).await;
+
+        home.assert_ok();
+        assert!(!home.headers().has("X-Tag"));
+        dashboard.assert_ok().assert_header_is("X-Tag", "admin");
+        user.assert_ok().assert_header_is("X-Tag", "admin");
+        router.handle(uri("/users/5")).await.assert_not_found();
+
+        let post = |path: &'static str, body: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri(Uri::from_static(path))
+                .body(body.to_string())
+                .build(app.clone())
+        };
+
+        router
+            .handle(post("/admin/uploads", "abcdefgh"))
+            .await
+            .assert_ok();
+        router
+            .handle(post("/admin/notes", "abcde"))
+            .await
+            .assert_status(&StatusCode::PAYLOAD_TOO_LARGE);
+
+        assert_eq!(
+            router.url("admin.dashboard", [("page", "1")]).unwrap(),
+            "/admin?page=1"
+        );
+        assert_eq!(
+            router.url("admin.users.show", [("id", "5")]).unwrap(),
+            "/admin/users/5"
+        );
+    }
+
+    #[test]
+    fn it_detects_duplicate_routes() {
+        let duplicated = Router::from_iter([
+            Route::get("/users/:id", handler),
+            Route::post("/users/:id", handler),
+            Route::get("/users/:user/", handler),
+        ])
+        .compile();
+
+        let constrained = Router::from_iter([
+            Route::get("/users/:id", handler).where_int("id"),
+            Route::get("/users/:name", handler),
+            Route::any(".*", handler),
+        ])
+        .compile();
+
+        let reachable = Router::from_iter([
+            Route::get("/users/:name", handler),
+            Route::get("/users/:id", handler).where_int("id"),
+            Route::get("/users/:id/posts", handler).where_int("id"),
+            Route::get("/users/:slug/posts", handler).where_parameter("slug", "[a-z]+"),
+        ])
+        .compile();
+
+        let insensitive = Router::from_iter([
+            Route::get("/Users", handler).case(Case::Insensitive),
+            Route::get("/users", handler).case(Case::Lowercase),
+        ])
+        .compile();
+
+        let sensitive = Router::from_iter([
+            Route::get("/users", handler),
+            Route::get("/users", handler).case(Case::Insensitive),
+        ])
+        .compile();
+
+        let distinct = Router::from_iter([
+            Route::get("/Users", handler).case(Case::Sensitive),
+            Route::get("/users", handler).case(Case::Sensitive),
+        ])
+        .compile();
+
+        assert_eq!(
+            duplicated.err().map(|error| error.to_string()),
+            Some("The route GET /users/:id is shadowed by GET /users/:user/".to_string())
+        );
+        assert_eq!(
+            constrained.err().map(|error| error.to_string()),
+            Some("The route GET /users/:id is shadowed by GET /users/:name".to_string())
+        );
+        assert!(reachable.is_ok());
+        assert!(matches!(insensitive, Err(Error::DuplicateRoute { .. })));
+        assert!(matches!(sensitive, Err(Error::DuplicateRoute { .. })));
+        assert!(distinct.is_ok());
+    }
+
+    #[test]
+    fn it_can_describe_routes() {
+        let router =
+            Router::from_iter([Route::group([
+                Route::get("/users/:id/files/*path", handler).name("files.show")
+            ])
+            .middleware(Tag("users"))])
+            .compile()
+            .unwrap();
+
+        let info = router.info();
+
+        assert_eq!(info.len(), 2 + 9);
+        assert_eq!(info[0].method, "HEAD");
+        assert_eq!(info[1].method, "GET");
+        assert_eq!(info[1].path, "/users/:id/files/*path");
+        assert_eq!(info[1].name.as_deref(), Some("files.show"));
+        assert_eq!(info[1].middleware, vec![std::any::type_name::<Tag>()]);
+        assert_eq!(info[1].parameters, vec!["id", "path"]);
+        assert_eq!(info[10].path, ".*");
+        assert!(info[10].middleware.is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_can_match_paths_case_insensitively() {
+        let app = Arc::new(App);
+
+        let router = Router::from_iter([
+            Route::get("/Strict", handler).case(Case::Sensitive),
+            Route::get("/about", handler),
+            Route::group([Route::get("/summer-sale", handler)]).case(Case::Lowercase),
+        ])
+        .case(Case::Insensitive)
+        .compile()
+        .unwrap();
+
+        let uri = |path: &'static str| Request::get(Uri::from_static(path)).build(app.clone());
+
+        router.handle(uri("/ABOUT")).await.assert_ok();
+        router.handle(uri("/Strict")).await.assert_ok();
+        router.handle(uri("/strict")).await.assert_not_found();
+        router.handle(uri("/summer-sale")).await.assert_ok();
+
+        let redirect = router.handle(uri("/Summer-Sale?ref=Mail")).await;
+
+        assert_eq!(redirect.status().as_u16(), 308);
+        redirect.assert_header_is("Location", "/summer-sale?ref=Mail");
+        router.handle(uri("/Missing")).await.assert_not_found();
+    }
+
+    #[tokio::test]
+    async fn it_can_limit_request_bodies() {
+        let app = Arc::new(App);
+
+        let router = Router::from_iter([
+            Route::post("/small", handler),
+            Route::group([Route::post("/uploads", handler)]).max_body_size(8),
+            Route::post("/tiny", handler).max_body_size(2),
+        ])
+        .max_body_size(4)
+        .compile()
+        .unwrap();
+
+        let request = |path: &'static str, body: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri(Uri::from_static(path))
+                .body(body.to_string())
+                .build(app.clone())
+        };
+
+        router.handle(request("/small", "abcd")).await.assert_ok();
+        router
+            .handle(request("/small", "abcde"))
+            .await
+            .assert_status(&StatusCode::PAYLOAD_TOO_LARGE);
+        router
+            .handle(request("/uploads", "abcdefgh"))
+            .await
+            .assert_ok();
+        router
+            .handle(request("/tiny", "abc"))
+            .await
+            .assert_status(&StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn it_can_fall_back_to_the_server_body_limit() {
+        let app = Arc::new(App);
+
+        let router = Router::from_iter([
+            Route::post("/", handler),
+            Route::post("/uploads", handler).max_body_size(16),
+        ])
+        .compile()
+        .unwrap();
+
+        let request = |path: &'static str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri(Uri::from_static(path))
+                .body("abcdefgh".to_string())
+                .build(app.clone())
+        };
+
+        router.handle(request("/")).await.assert_ok();
+        router
+            .handle_with_limit(request("/"), 4)
+            .await
+            .assert_status(&StatusCode::PAYLOAD_TOO_LARGE);
+        router
+            .handle_with_limit(request("/uploads"), 4)
+            .await
+            .assert_ok();
+    }
+
+    #[tokio::test]
+    async fn it_renders_oversized_bodies_as_errors() {
+        let router = Router::from_iter([Route::post("/", handler)])
+            .max_body_size(4)
+            .on_error(413, |_| async {
+                Response::payload_too_large()
+                    .html("<h1>Too big</h1>")
+                    .build()
+            })
+            .compile()
+            .unwrap();
+
+        let request = BaseRequest::builder()
+            .method(Method::POST)
+            .uri("/")
+            .body(Full::new(Bytes::from("abcdefgh")))
+            .unwrap();
+
+        let response = router
+            .handle_base(Arc::new(App), request, None, DEFAULT_MAX_BODY_SIZE)
+            .await;
+
+        response.assert_status(&StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(response.body(), "<h1>Too big</h1>");
+    }
+
+    async fn slow_handler(_request: Request<App>) -> ResponseResult {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        Response::ok().into_ok()
+    }
+
+    #[tokio::test]
+    async fn it_can_time_out_routes() {
+        let app = Arc::new(App);
+
+        let router = Router::from_iter([
+            Route::get("/fast", handler).timeout(Duration::from_millis(10)),
+            Route::get("/slow", slow_handler).timeout(Duration::from_millis(10)),
+            Route::group([Route::get("/report", slow_handler)]).timeout(Duration::from_secs(5)),
+        ])
+        .compile()
+        .unwrap();
+
+        let uri = |path: &'static str| Request::get(Uri::from_static(path)).build(app.clone());
+
+        router.handle(uri("/fast")).await.assert_ok();
+        router
+            .handle(uri("/slow"))
+            .await
+            .assert_status(&StatusCode::SERVICE_UNAVAILABLE);
+        router.handle(uri("/report")).await.assert_ok();
+    }
+
+    #[tokio::test]
+    async fn it_can_exclude_middlewares() {
+        let app = Arc::new(App);
+
+        let router = Router::from_iter([Route::group([
+            Route::get("/dashboard", handler),
+            Route::post("/webhook", handler).without_middleware::<Tag>(),
+            Route::group([Route::get("/health", handler)])
+                .without_middleware_named(std::any::type_name::<Tag>()),
+        ])
+        .middleware(Tag("auth"))])
+        .compile()
+        .unwrap();
+
+        let request = |method: Method, path: &'static str| {
+            Request::builder()
+                .method(method)
+                .uri(Uri::from_static(path))
+                .build(app.clone())
+        };
+
+        let dashboard = router.handle(request(Method::GET, "/dashboard")).await;
+        let webhook = router.handle(request(Method::POST, "/webhook")).await;
+        let health = router.handle(request(Method::GET, "/health")).await;
+
+        dashboard.assert_header_is("X-Tag", "auth");
+        assert!(!webhook.headers().has("X-Tag"));
+        assert!(!health.headers().has("X-Tag"));
+    }
+
+    #[tokio::test]
+    async fn it_can_apply_middleware_groups() {
+        let app = Arc::new(App);
+
+        let api = Router::from_iter([
+            Route::group([Route::get("/users", handler)]).middleware_group("api")
+        ])
+        .middleware_group("api", Middlewares::new().middleware(Tag("api")))
+        .middleware_group("web", Middlewares::new().middleware(Tag("ignored")));
+
+        let router = Router::from_iter([
+            Route::group([Route::get("/", handler)]).middleware_group("web"),
+            Route::get("/plain", handler),
+        ])
+        .middleware_group("web", Middlewares::new().middleware(Tag("web")))
+        .mount("/api", api)
+        .compile()
+        .unwrap();
+
+        let uri = |path: &'static str| Request::get(Uri::from_static(path)).build(app.clone());
+
+        router
+            .handle(uri("/"))
+            .await
+            .assert_header_is("X-Tag", "web");
+        router
+            .handle(uri("/api/users"))
+            .await
+            .assert_header_is("X-Tag", "api");
+        assert!(!router.handle(uri("/plain")).await.headers().has("X-Tag"));
+
+        let unknown = Router::from_iter([Route::get("/", handler).middleware_group("admin")])
+            .compile()
+            .err()
+            .map(|error| error.to_string());
+
+        assert_eq!(
+            unknown,
+            Some("The middleware group admin is not registered".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn it_can_run_work_after_responses() {
+        let audited = Arc::new(AtomicBool::new(false));
+        let flag = audited.clone();
+
+        let router = Router::from_iter([Route::get("/", move |request: Request<App>| {
+            let flag = flag.clone();
+
+            async move {
+                request
+                    .after_response(move || async move { flag.store(true, Ordering::SeqCst) })
+                    .await;
+
+                Response::ok().into_ok()
+            }
+        })])
+        .compile()
+        .unwrap();
+
+        let response = router
+            .handle(Request::get(Uri::from_static("/")).build(Arc::new(App)))
+            .await;
+
+        assert_eq!(response.terminators().len(), 1);
+        assert!(!audited.load(Ordering::SeqCst));
+
+        response.terminators().run().await;
+
+        assert!(audited.load(Ordering::SeqCst));
+        assert!(response.terminators().is_empty());
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum RequiresRole {
+        Admin,
+        Editor,
+    }
+
+    struct Area(&'static str);
+
+    struct Authorize;
+
+    #[async_trait]
+    impl Middleware<App> for Authorize {
+        async fn handle(&self, next: Handler<App>, request: Request<App>) -> ResponseResult {
+            let route = request.route().unwrap();
+
+            if route.meta::<RequiresRole>() == Some(&RequiresRole::Admin) {
+                return Response::forbidden().into_err();
+            }
+
+            let area = route.meta::<Area>().map(|area| area.0).unwrap_or("none");
+            let name = route.name().unwrap_or("unnamed").to_string();
+            let mut response = next(request).await?;
+
+            response.headers_mut().insert("X-Area", area);
+            response.headers_mut().insert("X-Route", name);
+
+            Ok(response)
+        }
+    }
+
+    #[tokio::test]
+    async fn it_exposes_route_metadata() {
+        let app = Arc::new(App);
+
+        let router = Router::from_iter([Route::group([
+            Route::get("/admin", handler).meta(RequiresRole::Admin),
+            Route::get("/posts", handler).name("posts.index"),
+        ])
+        .meta(RequiresRole::Editor)
+        .meta(Area("cms"))
+        .middleware(Authorize)])
+        .compile()
+        .unwrap();
+
+        let uri = |path: &'static str| Request::get(Uri::from_static(path)).build(app.clone());
+
+        router
+            .handle(uri("/admin"))
+            .await
+            .assert_status(&StatusCode::FORBIDDEN);
+
+        let posts = router.handle(uri("/posts")).await;
+
+        posts.assert_ok();
+        posts.assert_header_is("X-Area", "cms");
+        posts.assert_header_is("X-Route", "posts.index");
+        assert_eq!(
+            router
+                .find(&Method::GET, &Uri::from_static("/admin"))
+                .metadata()
+                .get::<RequiresRole>(),
+            Some(&RequiresRole::Admin)
+        );
+    }
 }
@@ -0,0 +1,248 @@
+use std::io::Error as IoError;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Error as RegexError;
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Error as JsonError;
+use thiserror::Error;
+
+use crate::http::Method;
+use crate::routing::route::Case;
+use crate::routing::router::Compiled;
+use crate::routing::Router;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Unable to read or write the route table: {0}")]
+    Io(#[from] IoError),
+
+    #[error("Unable to encode or decode the route table: {0}")]
+    Json(#[from] JsonError),
+
+    #[error("Invalid regex in the route table: {0}")]
+    Regex(#[from] RegexError),
+}
+
+/// A single entry of the route table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub method: String,
+    pub path: String,
+    pub regex: String,
+    pub name: Option<String>,
+    pub case: Case,
+    pub parameters: Vec<String>,
+
+    /// The regex, compiled on the first lookup.
+    #[serde(skip)]
+    compiled: OnceLock<Regex>,
+}
+
+impl Entry {
+    /// Determines if the entry matches the given method
+    /// and path.
+    fn matches(&self, method: &Method, path: &str) -> Result<bool, Error> {
+        if self.method != method.as_str() {
+            return Ok(false);
+        }
+
+        let regex = match self.compiled.get() {
+            Some(regex) => regex,
+            None => {
+                let regex = Regex::new(&self.regex)?;
+
+                self.compiled.get_or_init(|| regex)
+            }
+        };
+
+        Ok(regex.is_match(path))
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.method == other.method
+            && self.path == other.path
+            && self.regex == other.regex
+            && self.name == other.name
+            && self.case == other.case
+            && self.parameters == other.parameters
+    }
+}
+
+impl Eq for Entry {}
+
+/// The matching table of a compiled router. It holds the
+/// method, path, regex, name, case sensitivity and
+/// parameter names of every route but not their
+/// handlers, so it can be saved to disk, loaded back and
+/// inspected by external tools.
+///
+/// Entries keep the router order, so matching a loaded
+/// table yields the same route the router would use.
+///
+/// The table is meant for inspection only: a router
+/// always compiles its own routes, so loading a table
+/// does not make its startup faster.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::http::Method;
+/// use valar::http::Request;
+/// use valar::http::Response;
+/// use valar::http::Result;
+/// use valar::routing::route::Builder as Route;
+/// use valar::routing::table::RouteTable;
+/// use valar::routing::Router;
+///
+/// struct App;
+///
+/// async fn show(_request: Request<App>) -> Result {
+///     Response::ok().into_ok()
+/// }
+///
+/// let router = Router::from_iter([
+///     Route::get("/users/:id", show).name("users.show"),
+/// ])
+/// .compile()
+/// .unwrap();
+///
+/// router.table().save("routes.json").unwrap();
+///
+/// let table = RouteTable::load("routes.json").unwrap();
+/// let entry = table.find(&Method::GET, "/users/5").unwrap().unwrap();
+///
+/// assert_eq!(entry.name.as_deref(), Some("users.show"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteTable {
+    routes: Vec<Entry>,
+}
+
+impl RouteTable {
+    /// Builds the route table of the given router.
+    pub fn from_router<App>(router: &Router<App, Compiled>) -> Self
+    where
+        App: Send + Sync + 'static,
+    {
+        let routes = router
+            .routes()
+            .iter()
+            .map(|route| Entry {
+                method: route.method().to_string(),
+                path: route.path().to_string(),
+                regex: route.regex().to_string(),
+                name: route.name().map(String::from),
+                case: route.case(),
+                parameters: route
+                    .parameter_names()
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                compiled: OnceLock::new(),
+            })
+            .collect();
+
+        Self { routes }
+    }
+
+    /// Returns the entries of the table.
+    pub fn routes(&self) -> &[Entry] {
+        &self.routes
+    }
+
+    /// Returns the entry that matches the given method and
+    /// path, the same way the router does. Regexes are
+    /// compiled once, on their first lookup. Fails when an
+    /// entry holds an invalid regex.
+    pub fn find(&self, method: &Method, path: &str) -> Result<Option<&Entry>, Error> {
+        for entry in self.routes.iter().rev() {
+            if entry.matches(method, path)? {
+                return Ok(Some(entry));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Encodes the table as JSON.
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Decodes a table from JSON.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Saves the table as JSON to the given file.
+    pub fn save<P>(&self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        std::fs::write(path, self.to_json()?)?;
+
+        Ok(())
+    }
+
+    /// Loads a table from the given JSON file.
+    pub fn load<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::Method;
+    use crate::http::Request;
+    use crate::http::Response;
+    use crate::http::Result as ResponseResult;
+    use crate::routing::route::Builder as Route;
+    use crate::routing::route::Case;
+    use crate::routing::table::RouteTable;
+    use crate::routing::Router;
+
+    struct App;
+
+    async fn handler(_request: Request<App>) -> ResponseResult {
+        Response::ok().into_ok()
+    }
+
+    #[test]
+    fn it_can_save_and_load_route_tables() {
+        let router = Router::from_iter([
+            Route::get("/users/:id", handler).name("users.show"),
+            Route::post("/users", handler),
+        ])
+        .compile()
+        .unwrap();
+
+        let table = router.table();
+        let path = std::env::temp_dir().join("valar-route-table-test.json");
+
+        table.save(&path).unwrap();
+        let loaded = RouteTable::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, table);
+        assert_eq!(loaded.routes().len(), router.routes().len());
+
+        let show = loaded.find(&Method::HEAD, "/users/5").unwrap().unwrap();
+        let store = loaded.find(&Method::POST, "/users").unwrap().unwrap();
+        let fallback = loaded.find(&Method::GET, "/posts").unwrap().unwrap();
+
+        assert_eq!(show.name.as_deref(), Some("users.show"));
+        assert_eq!(show.case, Case::Sensitive);
+        assert_eq!(show.parameters, ["id"]);
+        assert_eq!(store.path, "/users");
+        assert_eq!(fallback.path, ".*");
+        assert!(show.compiled.get().is_some());
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+/// The context key where the router stores the URL
+/// generator of each request.
+pub(crate) const URL_GENERATOR: &str = "request:url_generator";
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[error("No route named {0} was found")]
+    RouteNotFound(String),
+
+    #[error("Missing the {1} parameter to generate the URL of the {0} route")]
+    MissingParameter(String, String),
+}
+
+/// Generates the URLs of named routes. It's cheap to
+/// clone and is available in handlers with
+/// `Request::url_for`.
+#[derive(Debug, Default, Clone)]
+pub struct UrlGenerator(Arc<HashMap<String, String>>);
+
+impl UrlGenerator {
+    /// Creates a new URL generator with the paths of the
+    /// given named routes.
+    pub fn new<I, N, P>(routes: I) -> Self
+    where
+        I: IntoIterator<Item = (N, P)>,
+        N: Into<String>,
+        P: Into<String>,
+    {
+        let routes = routes
+            .into_iter()
+            .map(|(name, path)| (name.into(), path.into()))
+            .collect();
+
+        Self(Arc::new(routes))
+    }
+
+    /// Determines if there's a route with the given name.
+    pub fn has(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    /// Generates the URL of the named route, replacing its
+    /// parameters with the given values. Parameters that
+    /// are not part of the route path are appended as the
+    /// query string.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::routing::url::UrlGenerator;
+    ///
+    /// let urls = UrlGenerator::new([("users.show", "/users/:id")]);
+    ///
+    /// assert_eq!(
+    ///     urls.url("users.show", [("id", "5"), ("tab", "posts")]),
+    ///     Ok("/users/5?tab=posts".to_string())
+    /// );
+    /// ```
+    pub fn url<P, K, V>(&self, name: &str, parameters: P) -> Result<String, Error>
+    where
+        P: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let path = self
+            .0
+            .get(name)
+            .ok_or_else(|| Error::RouteNotFound(name.to_string()))?;
+
+        let mut parameters: Vec<(String, String)> = parameters
+            .into_iter()
+            .map(|(key, value)| (key.as_ref().to_string(), value.as_ref().to_string()))
+            .collect();
+
+        let mut segments = Vec::new();
+
+        for segment in path.trim_matches('/').split('/') {
+            let (parameter, wildcard) = match segment.chars().next() {
+                Some(':') => (&segment[1..], false),
+                Some('*') => (&segment[1..], true),
+                _ => {
+                    segments.push(segment.to_string());
+                    continue;
+                }
+            };
+
+            let position = parameters
+                .iter()
+                .position(|(key, _)| key == parameter)
+                .ok_or_else(|| Error::MissingParameter(name.to_string(), parameter.to_string()))?;
+
+            let (_, value) = parameters.remove(position);
+
+            // Wildcards keep the slashes of their value.
+            let value = match wildcard {
+                true => value.split('/').map(encode).collect::<Vec<_>>().join("/"),
+                false => encode(&value),
+            };
+
+            segments.push(value);
+        }
+
+        let mut url = format!("/{}", segments.join("/"));
+
+        if !parameters.is_empty() {
+            let query: Vec<String> = parameters
+                .iter()
+                .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+                .collect();
+
+            url = format!("{url}?{}", query.join("&"));
+        }
+
+        Ok(url)
+    }
+}
+
+/// Percent encodes everything but the unreserved
+/// characters of the given URL component.
+pub(crate) fn encode(component: &str) -> String {
+    component
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::routing::url::Error;
+    use crate::routing::url::UrlGenerator;
+
+    #[test]
+    fn it_can_generate_urls() {
+        let urls = UrlGenerator::new([
+            ("home", "/"),
+            ("users.show", "/users/:id"),
+            ("posts.show", "/users/:user/posts/:post/"),
+            ("files.show", "/files/*path"),
+        ]);
+
+        assert_eq!(urls.url("home", [("page", "2")]), Ok("/?page=2".into()));
+        assert_eq!(urls.url("users.show", [("id", "5")]), Ok("/users/5".into()));
+        assert_eq!(
+            urls.url("posts.show", [("post", "a b"), ("user", "1"), ("q", "x&y")]),
+            Ok("/users/1/posts/a%20b?q=x%26y".into())
+        );
+        assert_eq!(
+            urls.url("files.show", [("path", "docs/read me.md")]),
+            Ok("/files/docs/read%20me.md".into())
+        );
+    }
+
+    #[test]
+    fn it_fails_to_generate_invalid_urls() {
+        let urls = UrlGenerator::new([("users.show", "/users/:id")]);
+        let none: [(&str, &str); 0] = [];
+
+        assert_eq!(
+            urls.url("users.index", none),
+            Err(Error::RouteNotFound("users.index".into()))
+        );
+        assert_eq!(
+            urls.url("users.show", none),
+            Err(Error::MissingParameter("users.show".into(), "id".into()))
+        );
+    }
+}
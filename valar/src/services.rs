@@ -1,4 +1,8 @@
 pub mod cache;
+#[cfg(feature = "templates")]
+pub mod views;
 
 pub use cache::Cache;
 pub use cache::Cacheable;
+#[cfg(feature = "templates")]
+pub use views::Views;
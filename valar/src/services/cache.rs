@@ -1,14 +1,41 @@
+pub mod file;
+pub mod locks;
+pub mod manager;
 pub mod memory;
+pub mod prefixed;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod tags;
 
+use std::collections::HashMap;
+use std::future::Future;
 use std::marker::PhantomData;
-// use std::sync::Arc;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex as SyncMutex;
+use std::sync::MutexGuard as SyncMutexGuard;
+use std::sync::PoisonError;
 use std::time::Duration;
 
 use async_trait::async_trait;
+pub use file::FileCache;
+pub use locks::Lease;
+pub use locks::Locks;
+pub use locks::Mutex;
+pub use locks::MutexGuard;
+pub use locks::Mutexes;
+pub use manager::CacheManager;
 pub use memory::MemoryCache;
+pub use prefixed::Namespaces;
+pub use prefixed::PrefixedCache;
+#[cfg(feature = "redis")]
+pub use redis::RedisCache;
+pub use tags::TaggedCache;
+pub use tags::Tags;
 // use serde::Deserialize;
 // use serde::Serialize;
 use thiserror::Error;
+use tokio::sync::Mutex as AsyncMutex;
 use tokio::time::Instant;
 
 // use crate::services::Service;
@@ -21,6 +48,23 @@ pub enum Error {
 
     #[error("Cache key expired: {0}")]
     Expired(String),
+
+    #[error("Cache value is not an integer: {0}")]
+    NotInteger(String),
+
+    #[error("Cache operation not supported: {0}")]
+    Unsupported(&'static str),
+
+    #[error("Invalid cache configuration: {0}")]
+    Config(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The store failed, like when the connection to its
+    /// server is lost.
+    #[error(transparent)]
+    Backend(Box<dyn std::error::Error + Send + Sync>),
 }
 
 /// The default store for the cache.
@@ -82,6 +126,287 @@ pub trait Cache<Store = Default> {
     async fn insert(&self, key: String, value: Value<Insertable>) -> Result<(), Error>;
     async fn delete(&self, key: &str) -> Result<(), Error>;
     async fn clear(&self) -> Result<(), Error>;
+
+    /// Stores the value unless the key holds one already,
+    /// returning whether it was stored. No other write can
+    /// interleave with it.
+    async fn add(&self, _key: String, _value: Value<Insertable>) -> Result<bool, Error> {
+        Err(Error::Unsupported("insert if missing"))
+    }
+
+    /// Returns the value of the key and deletes it. No
+    /// other caller can read the same value with it.
+    async fn pull(&self, _key: &str) -> Result<Value<Retreived>, Error> {
+        Err(Error::Unsupported("read and delete"))
+    }
+
+    /// Stores the value without an expiration.
+    async fn forever(&self, key: String, value: String) -> Result<(), Error> {
+        self.insert(key, Value::new(value)).await
+    }
+
+    /// Adds the given amount to the integer stored in the
+    /// key, which starts at zero when it is missing, and
+    /// returns the new value. The expiration of the key is
+    /// kept. No other write can interleave with it.
+    async fn increment(&self, _key: &str, _by: i64) -> Result<i64, Error> {
+        Err(Error::Unsupported("increment"))
+    }
+
+    /// Subtracts the given amount from the integer stored
+    /// in the key, like [`Cache::increment`].
+    async fn decrement(&self, key: &str, by: i64) -> Result<i64, Error> {
+        self.increment(key, -by).await
+    }
+
+    /// Deletes the key if it holds the given value,
+    /// returning whether it did. No other write can
+    /// interleave with it.
+    async fn delete_if(&self, _key: &str, _value: &str) -> Result<bool, Error> {
+        Err(Error::Unsupported("compare and delete"))
+    }
+
+    /// Expires the key after the given time if it holds
+    /// the given value, returning whether it did. No other
+    /// write can interleave with it.
+    async fn extend_if(&self, _key: &str, _value: &str, _ttl: Duration) -> Result<bool, Error> {
+        Err(Error::Unsupported("compare and extend"))
+    }
+
+    /// Deletes every key starting with the given prefix,
+    /// which [`PrefixedCache::clear`] relies on.
+    async fn clear_prefix(&self, _prefix: &str) -> Result<(), Error> {
+        Err(Error::Unsupported("clearing keys by prefix"))
+    }
+
+    /// Identifies the store behind the cache, so
+    /// [`Remember::remember_locked`] only coordinates the
+    /// calls made on the same one. Wrappers return the
+    /// identity of the cache they wrap.
+    fn identity(&self) -> usize {
+        std::ptr::from_ref(self).cast::<()>().addr()
+    }
+}
+
+#[async_trait]
+impl<Store, C> Cache<Store> for &C
+where
+    C: Cache<Store> + Sync + ?Sized,
+{
+    async fn get(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        (**self).get(key).await
+    }
+
+    async fn insert(&self, key: String, value: Value<Insertable>) -> Result<(), Error> {
+        (**self).insert(key, value).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Error> {
+        (**self).delete(key).await
+    }
+
+    async fn clear(&self) -> Result<(), Error> {
+        (**self).clear().await
+    }
+
+    async fn add(&self, key: String, value: Value<Insertable>) -> Result<bool, Error> {
+        (**self).add(key, value).await
+    }
+
+    async fn pull(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        (**self).pull(key).await
+    }
+
+    async fn increment(&self, key: &str, by: i64) -> Result<i64, Error> {
+        (**self).increment(key, by).await
+    }
+
+    async fn delete_if(&self, key: &str, value: &str) -> Result<bool, Error> {
+        (**self).delete_if(key, value).await
+    }
+
+    async fn extend_if(&self, key: &str, value: &str, ttl: Duration) -> Result<bool, Error> {
+        (**self).extend_if(key, value, ttl).await
+    }
+
+    async fn clear_prefix(&self, prefix: &str) -> Result<(), Error> {
+        (**self).clear_prefix(prefix).await
+    }
+
+    fn identity(&self) -> usize {
+        (**self).identity()
+    }
+}
+
+#[async_trait]
+impl<Store, C> Cache<Store> for Arc<C>
+where
+    C: Cache<Store> + Send + Sync + ?Sized,
+{
+    async fn get(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        (**self).get(key).await
+    }
+
+    async fn insert(&self, key: String, value: Value<Insertable>) -> Result<(), Error> {
+        (**self).insert(key, value).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Error> {
+        (**self).delete(key).await
+    }
+
+    async fn clear(&self) -> Result<(), Error> {
+        (**self).clear().await
+    }
+
+    async fn add(&self, key: String, value: Value<Insertable>) -> Result<bool, Error> {
+        (**self).add(key, value).await
+    }
+
+    async fn pull(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        (**self).pull(key).await
+    }
+
+    async fn increment(&self, key: &str, by: i64) -> Result<i64, Error> {
+        (**self).increment(key, by).await
+    }
+
+    async fn delete_if(&self, key: &str, value: &str) -> Result<bool, Error> {
+        (**self).delete_if(key, value).await
+    }
+
+    async fn extend_if(&self, key: &str, value: &str, ttl: Duration) -> Result<bool, Error> {
+        (**self).extend_if(key, value, ttl).await
+    }
+
+    async fn clear_prefix(&self, prefix: &str) -> Result<(), Error> {
+        (**self).clear_prefix(prefix).await
+    }
+
+    fn identity(&self) -> usize {
+        (**self).identity()
+    }
+}
+
+/// Adds the given amount to the stored value.
+pub(crate) fn add(key: &str, value: Option<&str>, by: i64) -> Result<i64, Error> {
+    let current = match value {
+        Some(value) => value
+            .parse::<i64>()
+            .map_err(|_| Error::NotInteger(key.to_string()))?,
+        None => 0,
+    };
+
+    Ok(current.saturating_add(by))
+}
+
+/// The locks of the keys being computed by
+/// [`Remember::remember_locked`] in this process, by the
+/// identity of their cache and the key.
+type Computing = HashMap<(usize, String), Arc<AsyncMutex<()>>>;
+
+static COMPUTING: LazyLock<SyncMutex<Computing>> = LazyLock::new(SyncMutex::default);
+
+/// Reads values from the cache, computing and storing
+/// them when they are missing. It is implemented for
+/// every cache.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use valar::services::cache::Error;
+/// use valar::services::cache::Remember;
+/// use valar::services::Cacheable;
+///
+/// # async fn example(cache: &Cacheable) -> Result<(), Error> {
+/// let stats = cache
+///     .remember("stats", Duration::from_secs(60), || async {
+///         Ok::<_, Error>("42 users".to_string())
+///     })
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait Remember<Store = Default>: Cache<Store> + Sync {
+    /// Returns the value of the key or, when it is
+    /// missing, computes it with the callback and stores
+    /// it for the given time.
+    async fn remember<F, R, E>(&self, key: &str, ttl: Duration, compute: F) -> Result<String, E>
+    where
+        F: FnOnce() -> R + Send,
+        R: Future<Output = Result<String, E>> + Send,
+        E: From<Error>,
+    {
+        if let Some(value) = self.find(key).await? {
+            return Ok(value);
+        }
+
+        let value = compute().await?;
+
+        self.insert(key.to_string(), Value::new(value.clone()).expires_in(ttl))
+            .await?;
+
+        Ok(value)
+    }
+
+    /// Like [`Remember::remember`], but concurrent calls
+    /// for a missing key wait for the first one to compute
+    /// it instead of computing it as well. Calls are only
+    /// coordinated within this process and the same
+    /// cache.
+    async fn remember_locked<F, R, E>(
+        &self,
+        key: &str,
+        ttl: Duration,
+        compute: F,
+    ) -> Result<String, E>
+    where
+        F: FnOnce() -> R + Send,
+        R: Future<Output = Result<String, E>> + Send,
+        E: From<Error>,
+    {
+        if let Some(value) = self.find(key).await? {
+            return Ok(value);
+        }
+
+        let entry = (self.identity(), key.to_string());
+        let lock = computing().entry(entry.clone()).or_default().clone();
+        let guard = lock.lock().await;
+        let value = match self.find(key).await {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => self.remember(key, ttl, compute).await,
+            Err(error) => Err(error.into()),
+        };
+
+        drop(guard);
+
+        let mut computing = computing();
+
+        if Arc::strong_count(&lock) == 2 {
+            computing.remove(&entry);
+        }
+
+        value
+    }
+
+    /// Returns the value of the key, or `None` when it is
+    /// missing or expired.
+    async fn find(&self, key: &str) -> Result<Option<String>, Error> {
+        match self.get(key).await {
+            Ok(value) => Ok(Some(value.into_value())),
+            Err(Error::NotFound(_) | Error::Expired(_)) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl<Store, C> Remember<Store> for C where C: Cache<Store> + Sync + ?Sized {}
+
+fn computing() -> SyncMutexGuard<'static, Computing> {
+    COMPUTING.lock().unwrap_or_else(PoisonError::into_inner)
 }
 
 // #[derive(Error, Debug)]
@@ -202,3 +527,162 @@ pub trait Cache<Store = Default> {
 // Facade<Store> {         Facade::new(self,
 // self.singleton())     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use tokio::sync::Notify;
+    use tokio::time::sleep;
+    use tokio::time::timeout;
+
+    use crate::services::cache::Error;
+    use crate::services::cache::Insertable;
+    use crate::services::cache::MemoryCache;
+    use crate::services::cache::Remember;
+    use crate::services::cache::Retreived;
+    use crate::services::cache::Value;
+    use crate::services::Cache;
+    use crate::services::Cacheable;
+
+    #[tokio::test]
+    async fn it_can_remember_values() {
+        let cache: &Cacheable = &MemoryCache::new(Duration::from_secs(60));
+        let computed = AtomicUsize::new(0);
+        let compute = || async {
+            computed.fetch_add(1, Ordering::SeqCst);
+            sleep(Duration::from_millis(10)).await;
+
+            Ok::<_, Error>("Erik".to_string())
+        };
+
+        let first = cache.remember("user", Duration::from_secs(60), compute);
+        let second = cache.remember("user", Duration::from_secs(60), compute);
+
+        assert_eq!(first.await.unwrap(), "Erik");
+        assert_eq!(second.await.unwrap(), "Erik");
+        assert_eq!(computed.load(Ordering::SeqCst), 1);
+
+        let (first, second) = tokio::join!(
+            cache.remember_locked("admin", Duration::from_secs(60), compute),
+            cache.remember_locked("admin", Duration::from_secs(60), compute),
+        );
+
+        assert_eq!(first.unwrap(), "Erik");
+        assert_eq!(second.unwrap(), "Erik");
+        assert_eq!(computed.load(Ordering::SeqCst), 2);
+
+        cache
+            .remember("expired", Duration::ZERO, compute)
+            .await
+            .unwrap();
+        cache
+            .remember("expired", Duration::ZERO, compute)
+            .await
+            .unwrap();
+
+        assert_eq!(computed.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn it_only_coordinates_the_same_cache() {
+        let first: &Cacheable = &MemoryCache::new(Duration::from_secs(60));
+        let second: &Cacheable = &MemoryCache::new(Duration::from_secs(60));
+        let computed = Notify::new();
+
+        let waiting = first.remember_locked("user", Duration::from_secs(60), || async {
+            computed.notified().await;
+
+            Ok::<_, Error>("Erik".to_string())
+        });
+        let notifying = second.remember_locked("user", Duration::from_secs(60), || async {
+            computed.notify_one();
+
+            Ok::<_, Error>("Erik".to_string())
+        });
+
+        let (first, second) = timeout(Duration::from_secs(1), async {
+            tokio::join!(waiting, notifying)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(first.unwrap(), "Erik");
+        assert_eq!(second.unwrap(), "Erik");
+    }
+
+    #[tokio::test]
+    async fn it_can_increment_values() {
+        let cache: &Cacheable = &MemoryCache::new(Duration::from_secs(60));
+
+        assert_eq!(cache.increment("visits", 1).await.unwrap(), 1);
+        assert_eq!(cache.increment("visits", 5).await.unwrap(), 6);
+        assert_eq!(cache.decrement("visits", 2).await.unwrap(), 4);
+        assert_eq!(cache.get("visits").await.unwrap().value(), "4");
+        assert_eq!(cache.pull("visits").await.unwrap().value(), "4");
+        assert!(matches!(
+            cache.pull("visits").await,
+            Err(Error::NotFound(_))
+        ));
+
+        cache
+            .forever("name".to_string(), "Erik".to_string())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            cache.increment("name", 1).await,
+            Err(Error::NotInteger(_))
+        ));
+
+        let increments = (0..50).map(|_| cache.increment("concurrent", 1));
+
+        futures_util::future::join_all(increments).await;
+
+        assert_eq!(cache.get("concurrent").await.unwrap().value(), "50");
+    }
+
+    struct Empty;
+
+    #[async_trait]
+    impl Cache for Empty {
+        async fn get(&self, key: &str) -> Result<Value<Retreived>, Error> {
+            Err(Error::NotFound(key.to_string()))
+        }
+
+        async fn insert(&self, _: String, _: Value<Insertable>) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn delete(&self, _: &str) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn clear(&self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn it_reports_unsupported_operations() {
+        let cache: &Cacheable = &Empty;
+
+        assert!(matches!(
+            cache
+                .add("name".to_string(), Value::new("Erik".to_string()))
+                .await,
+            Err(Error::Unsupported(_))
+        ));
+        assert!(matches!(
+            cache.pull("name").await,
+            Err(Error::Unsupported(_))
+        ));
+        assert!(matches!(
+            cache.increment("visits", 1).await,
+            Err(Error::Unsupported(_))
+        ));
+    }
+}
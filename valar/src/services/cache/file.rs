@@ -0,0 +1,372 @@
+use std::ffi::OsStr;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+use sha2::Digest;
+use sha2::Sha256;
+use tokio::fs;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::services::cache::add;
+use crate::services::cache::Error;
+use crate::services::cache::Insertable;
+use crate::services::cache::Retreived;
+use crate::services::cache::Value;
+use crate::services::Cache;
+
+/// A cache storing each entry as a file in a directory,
+/// so entries outlive restarts of the application. The
+/// first line of a file holds the time it expires at, in
+/// milliseconds since the epoch, or `0` when it never
+/// expires. Expired files are removed when read.
+///
+/// Files are written to a temporary file first and then
+/// renamed over the entry, so readers never see a partial
+/// write. Increments and the conditional writes of locks
+/// are atomic within the process only.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::services::cache::FileCache;
+///
+/// let cache = FileCache::new("storage/cache");
+/// ```
+pub struct FileCache {
+    directory: PathBuf,
+    writes: Mutex<()>,
+}
+
+impl FileCache {
+    /// Creates a cache storing its entries in the given
+    /// directory, which is created on the first write.
+    pub fn new<P>(directory: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            directory: directory.into(),
+            writes: Mutex::new(()),
+        }
+    }
+
+    /// Returns the file of the given key. Keys are hashed
+    /// so any key is a safe file name.
+    fn path(&self, key: &str) -> PathBuf {
+        let hash = Sha256::digest(key.as_bytes());
+        let name: String = hash.iter().map(|byte| format!("{byte:02x}")).collect();
+
+        self.directory.join(name)
+    }
+
+    /// Reads the value of the key and the time it expires
+    /// at, removing the file when it expired.
+    async fn read(&self, key: &str) -> Result<(String, u64), Error> {
+        read(key, self.path(key)).await
+    }
+
+    /// Determines if the key holds the given value.
+    async fn holds(&self, key: &str, value: &str) -> Result<bool, Error> {
+        match self.read(key).await {
+            Ok((current, _)) => Ok(current == value),
+            Err(Error::NotFound(_) | Error::Expired(_)) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Writes the value of the key, replacing the file
+    /// atomically.
+    async fn write(&self, key: &str, value: &str, expires_at: u64) -> Result<(), Error> {
+        let temporary = self.temporary(value, expires_at).await?;
+
+        if let Err(error) = fs::rename(&temporary, self.path(key)).await {
+            remove(temporary).await?;
+
+            return Err(error.into());
+        }
+
+        Ok(())
+    }
+
+    /// Writes the value to a new temporary file, to be
+    /// moved over the file of its key.
+    async fn temporary(&self, value: &str, expires_at: u64) -> Result<PathBuf, Error> {
+        let temporary = self.directory.join(format!(".{}.tmp", Uuid::now_v7()));
+
+        fs::create_dir_all(&self.directory).await?;
+        fs::write(&temporary, format!("{expires_at}\n{value}")).await?;
+
+        Ok(temporary)
+    }
+}
+
+#[async_trait]
+impl<App> Cache<App> for FileCache {
+    async fn get(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        let (value, _) = self.read(key).await?;
+
+        Ok(Value::new(value))
+    }
+
+    async fn insert(&self, key: String, value: Value<Insertable>) -> Result<(), Error> {
+        self.write(&key, value.value(), expires_at(&value)).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Error> {
+        remove(self.path(key)).await
+    }
+
+    /// Removes the files of the entries only, so other
+    /// files in the directory are kept.
+    async fn clear(&self) -> Result<(), Error> {
+        let mut entries = match fs::read_dir(&self.directory).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            if is_entry(&entry.file_name()) && entry.file_type().await?.is_file() {
+                remove(entry.path()).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The file is linked to its key, which fails when it
+    /// exists, so it is atomic across processes too.
+    async fn add(&self, key: String, value: Value<Insertable>) -> Result<bool, Error> {
+        let _lock = self.writes.lock().await;
+
+        match self.read(&key).await {
+            Ok(_) => return Ok(false),
+            Err(Error::NotFound(_) | Error::Expired(_)) => {}
+            Err(error) => return Err(error),
+        }
+
+        let temporary = self.temporary(value.value(), expires_at(&value)).await?;
+        let linked = fs::hard_link(&temporary, self.path(&key)).await;
+
+        remove(temporary).await?;
+
+        match linked {
+            Ok(()) => Ok(true),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => Ok(false),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// The file is moved away before it is read, so only
+    /// one caller can pull it, even across processes.
+    async fn pull(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        let temporary = self.directory.join(format!(".{}.tmp", Uuid::now_v7()));
+
+        match fs::rename(self.path(key), &temporary).await {
+            Ok(()) => {}
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                return Err(Error::NotFound(key.to_string()))
+            }
+            Err(error) => return Err(error.into()),
+        }
+
+        let read = read(key, temporary.clone()).await;
+
+        remove(temporary).await?;
+
+        Ok(Value::new(read?.0))
+    }
+
+    async fn increment(&self, key: &str, by: i64) -> Result<i64, Error> {
+        let _lock = self.writes.lock().await;
+        let (value, expires_at) = match self.read(key).await {
+            Ok((value, expires_at)) => (Some(value), expires_at),
+            Err(Error::NotFound(_) | Error::Expired(_)) => (None, 0),
+            Err(error) => return Err(error),
+        };
+        let value = add(key, value.as_deref(), by)?;
+
+        self.write(key, &value.to_string(), expires_at).await?;
+
+        Ok(value)
+    }
+
+    async fn delete_if(&self, key: &str, value: &str) -> Result<bool, Error> {
+        let _lock = self.writes.lock().await;
+
+        if !self.holds(key, value).await? {
+            return Ok(false);
+        }
+
+        remove(self.path(key)).await?;
+
+        Ok(true)
+    }
+
+    async fn extend_if(&self, key: &str, value: &str, ttl: Duration) -> Result<bool, Error> {
+        let _lock = self.writes.lock().await;
+
+        if !self.holds(key, value).await? {
+            return Ok(false);
+        }
+
+        self.write(key, value, now() + ttl.as_millis() as u64)
+            .await?;
+
+        Ok(true)
+    }
+}
+
+/// Reads the value of the key from the given file and
+/// the time it expires at, removing the file when it
+/// expired.
+async fn read(key: &str, path: PathBuf) -> Result<(String, u64), Error> {
+    let contents = match fs::read_to_string(&path).await {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            return Err(Error::NotFound(key.to_string()))
+        }
+        Err(error) => return Err(error.into()),
+    };
+
+    let (expires_at, value) = contents
+        .split_once('\n')
+        .and_then(|(expires_at, value)| Some((expires_at.parse::<u64>().ok()?, value)))
+        .ok_or_else(|| Error::NotFound(key.to_string()))?;
+
+    if expires_at != 0 && now() >= expires_at {
+        remove(path).await?;
+
+        return Err(Error::Expired(key.to_string()));
+    }
+
+    Ok((value.to_string(), expires_at))
+}
+
+/// Determines if the file name is the one of an entry,
+/// the hex encoded hash of its key.
+fn is_entry(name: &OsStr) -> bool {
+    name.to_str().is_some_and(|name| {
+        name.len() == 64
+            && name
+                .bytes()
+                .all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+    })
+}
+
+/// Removes the file, if it exists.
+async fn remove(path: PathBuf) -> Result<(), Error> {
+    match fs::remove_file(path).await {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the current time in milliseconds since the
+/// epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis() as u64
+}
+
+/// Returns the time the value expires at, in
+/// milliseconds since the epoch, or `0` when it never
+/// expires.
+fn expires_at(value: &Value<Insertable>) -> u64 {
+    match value.expires_at {
+        Some(instant) => {
+            now()
+                + instant
+                    .saturating_duration_since(Instant::now())
+                    .as_millis() as u64
+        }
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::time::Duration;
+
+    use crate::services::cache::Error;
+    use crate::services::cache::FileCache;
+    use crate::services::cache::Value;
+    use crate::services::Cacheable;
+
+    #[tokio::test]
+    async fn it_can_store_entries() {
+        let directory = temp_dir().join(format!("valar-file-cache-{}", std::process::id()));
+        let cache: &Cacheable = &FileCache::new(&directory);
+
+        cache
+            .insert("user:1".to_string(), Value::new("Erik".to_string()))
+            .await
+            .unwrap();
+        cache
+            .insert(
+                "user:2".to_string(),
+                Value::new("Anna".to_string()).expires_in(Duration::ZERO),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(cache.get("user:1").await.unwrap().value(), "Erik");
+        assert!(matches!(cache.get("user:2").await, Err(Error::Expired(_))));
+        assert!(matches!(cache.get("user:2").await, Err(Error::NotFound(_))));
+
+        let reopened: &Cacheable = &FileCache::new(&directory);
+
+        assert_eq!(reopened.get("user:1").await.unwrap().value(), "Erik");
+
+        cache.delete("user:1").await.unwrap();
+
+        assert!(matches!(cache.get("user:1").await, Err(Error::NotFound(_))));
+
+        cache
+            .insert("user:3".to_string(), Value::new("Joan".to_string()))
+            .await
+            .unwrap();
+        std::fs::write(directory.join("README.md"), "Cached entries").unwrap();
+        cache.clear().await.unwrap();
+
+        assert!(matches!(cache.get("user:3").await, Err(Error::NotFound(_))));
+        assert!(directory.join("README.md").exists());
+        assert_eq!(cache.increment("visits", 2).await.unwrap(), 2);
+        assert_eq!(cache.decrement("visits", 1).await.unwrap(), 1);
+        assert!(!cache
+            .add("visits".to_string(), Value::new("5".to_string()))
+            .await
+            .unwrap());
+        assert!(cache
+            .add("user:4".to_string(), Value::new("Marc".to_string()))
+            .await
+            .unwrap());
+        assert_eq!(cache.get("visits").await.unwrap().value(), "1");
+        assert_eq!(cache.pull("user:4").await.unwrap().value(), "Marc");
+        assert!(matches!(
+            cache.pull("user:4").await,
+            Err(Error::NotFound(_))
+        ));
+        assert!(!cache.delete_if("visits", "2").await.unwrap());
+        assert!(cache
+            .extend_if("visits", "1", Duration::from_secs(60))
+            .await
+            .unwrap());
+        assert!(cache.delete_if("visits", "1").await.unwrap());
+        assert!(!cache
+            .extend_if("visits", "1", Duration::from_secs(60))
+            .await
+            .unwrap());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
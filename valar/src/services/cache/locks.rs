@@ -0,0 +1,368 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use log::warn;
+use tokio::runtime::Handle;
+use tokio::spawn;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use uuid::Uuid;
+
+use crate::services::cache::Default;
+use crate::services::cache::Error;
+use crate::services::cache::Value;
+use crate::services::Cache;
+use crate::services::Cacheable;
+
+/// Takes locks stored in the cache, so they are shared by
+/// every instance using the same store. It is implemented
+/// for every cache.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use valar::services::cache::Error;
+/// use valar::services::cache::Locks;
+/// use valar::services::Cacheable;
+///
+/// # async fn example(cache: &Cacheable) -> Result<(), Error> {
+/// if let Some(lock) = cache.lock("reports", Duration::from_secs(60)).await? {
+///     // Only one instance builds the reports.
+///
+///     lock.release().await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait Locks<Store = Default>: Cache<Store> + Sync {
+    /// Takes the lock of the given name, or returns `None`
+    /// when it is held already. The lock is held until it
+    /// is released or the given time passes, even if the
+    /// lease is dropped.
+    async fn lock(
+        &self,
+        name: &str,
+        ttl: Duration,
+    ) -> Result<Option<Lease<'_, Self, Store>>, Error> {
+        let owner = Uuid::now_v7().to_string();
+        let value = Value::new(owner.clone()).expires_in(ttl);
+
+        if !self.add(lock_key(name), value).await? {
+            return Ok(None);
+        }
+
+        Ok(Some(Lease {
+            cache: self,
+            name: name.to_string(),
+            owner,
+            _store: PhantomData,
+        }))
+    }
+}
+
+impl<Store, C> Locks<Store> for C where C: Cache<Store> + Sync + ?Sized {}
+
+/// A lock held in the cache. It is not a guard: dropping
+/// it keeps the lock until it expires, so release it
+/// explicitly. Use a [`Mutex`] to release on drop.
+pub struct Lease<'a, C: ?Sized, Store = Default> {
+    cache: &'a C,
+    name: String,
+    owner: String,
+    _store: PhantomData<fn() -> Store>,
+}
+
+impl<C, Store> Lease<'_, C, Store>
+where
+    C: Cache<Store> + Sync + ?Sized,
+{
+    /// Returns the token identifying the holder of the
+    /// lock.
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// Releases the lock, returning whether it was still
+    /// held, as it could have expired and been taken by
+    /// someone else meanwhile.
+    pub async fn release(self) -> Result<bool, Error> {
+        self.cache
+            .delete_if(&lock_key(&self.name), &self.owner)
+            .await
+    }
+}
+
+/// Creates mutexes shared by every instance using the
+/// same cache. It is implemented for shared caches.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use valar::services::cache::Error;
+/// use valar::services::cache::Mutexes;
+/// use valar::services::Cacheable;
+///
+/// # async fn example(cache: Arc<Cacheable>) -> Result<(), Error> {
+/// let guard = cache
+///     .mutex("deploy:migrate")
+///     .acquire(Duration::from_secs(30))
+///     .await?;
+///
+/// // Only one instance migrates at a time.
+///
+/// guard.release().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait Mutexes<Store = Default> {
+    /// Returns the mutex of the given name. It shares the
+    /// locks taken with [`Locks::lock`].
+    fn mutex<N>(&self, name: N) -> Mutex<Store>
+    where
+        N: Into<String>;
+}
+
+impl<Store> Mutexes<Store> for Arc<Cacheable<Store>> {
+    fn mutex<N>(&self, name: N) -> Mutex<Store>
+    where
+        N: Into<String>,
+    {
+        Mutex {
+            cache: self.clone(),
+            name: name.into(),
+            retry: Duration::from_millis(100),
+        }
+    }
+}
+
+/// A mutex held in the cache.
+pub struct Mutex<Store = Default> {
+    cache: Arc<Cacheable<Store>>,
+    name: String,
+    retry: Duration,
+}
+
+impl<Store> Mutex<Store>
+where
+    Store: 'static,
+{
+    /// Sets how long to wait between attempts to acquire
+    /// the mutex. It is 100ms by default.
+    #[must_use]
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = retry;
+
+        self
+    }
+
+    /// Acquires the mutex, waiting until it is free. The
+    /// guard renews it before the given time passes, so it
+    /// is held until the guard is released or dropped, or
+    /// the process stops.
+    pub async fn acquire(&self, ttl: Duration) -> Result<MutexGuard<Store>, Error> {
+        loop {
+            if let Some(guard) = self.try_acquire(ttl).await? {
+                return Ok(guard);
+            }
+
+            sleep(self.retry).await;
+        }
+    }
+
+    /// Acquires the mutex, or returns `None` when it is
+    /// held already.
+    pub async fn try_acquire(&self, ttl: Duration) -> Result<Option<MutexGuard<Store>>, Error> {
+        let key = lock_key(&self.name);
+        let owner = Uuid::now_v7().to_string();
+        let value = Value::new(owner.clone()).expires_in(ttl);
+
+        if !self.cache.add(key.clone(), value).await? {
+            return Ok(None);
+        }
+
+        let renewal = spawn(renew(self.cache.clone(), key.clone(), owner.clone(), ttl));
+
+        Ok(Some(MutexGuard {
+            cache: self.cache.clone(),
+            key,
+            owner,
+            renewal,
+            released: false,
+        }))
+    }
+}
+
+/// A held mutex. Dropping it releases the mutex in the
+/// background.
+pub struct MutexGuard<Store = Default>
+where
+    Store: 'static,
+{
+    cache: Arc<Cacheable<Store>>,
+    key: String,
+    owner: String,
+    renewal: JoinHandle<()>,
+    released: bool,
+}
+
+impl<Store> MutexGuard<Store>
+where
+    Store: 'static,
+{
+    /// Releases the mutex, returning whether it was still
+    /// held.
+    pub async fn release(mut self) -> Result<bool, Error> {
+        self.renewal.abort();
+        self.released = true;
+
+        self.cache.delete_if(&self.key, &self.owner).await
+    }
+}
+
+impl<Store> Drop for MutexGuard<Store>
+where
+    Store: 'static,
+{
+    fn drop(&mut self) {
+        self.renewal.abort();
+
+        if self.released {
+            return;
+        }
+
+        if let Ok(handle) = Handle::try_current() {
+            let (cache, key, owner) = (self.cache.clone(), self.key.clone(), self.owner.clone());
+
+            handle.spawn(async move { cache.delete_if(&key, &owner).await });
+        }
+    }
+}
+
+/// Extends the mutex every half of its time, as long as
+/// it is held by the given owner.
+async fn renew<Store>(cache: Arc<Cacheable<Store>>, key: String, owner: String, ttl: Duration) {
+    loop {
+        sleep(ttl / 2).await;
+
+        match cache.extend_if(&key, &owner, ttl).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(error) => warn!("Unable to renew the mutex {key}: {error}"),
+        }
+    }
+}
+
+/// The prefix of the keys holding locks.
+const LOCK_PREFIX: &str = "valar:lock:";
+
+/// Returns the key holding the lock of the given name.
+fn lock_key(name: &str) -> String {
+    format!("{LOCK_PREFIX}{name}")
+}
+
+/// Determines if the key holds a lock, even within a
+/// prefixed cache.
+pub(crate) fn is_lock_key(key: &str) -> bool {
+    key.starts_with(LOCK_PREFIX) || key.contains(&format!(":{LOCK_PREFIX}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use tokio::spawn;
+    use tokio::time::sleep;
+
+    use crate::services::cache::locks::is_lock_key;
+    use crate::services::cache::Locks;
+    use crate::services::cache::MemoryCache;
+    use crate::services::cache::Mutexes;
+    use crate::services::Cacheable;
+
+    #[tokio::test]
+    async fn it_can_lock() {
+        let cache: &Cacheable = &MemoryCache::new(Duration::from_secs(60));
+        let lock = cache
+            .lock("reports", Duration::from_secs(60))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(cache
+            .lock("reports", Duration::from_secs(60))
+            .await
+            .unwrap()
+            .is_none());
+        assert!(lock.release().await.unwrap());
+
+        let expired = cache
+            .lock("reports", Duration::ZERO)
+            .await
+            .unwrap()
+            .unwrap();
+        let lock = cache
+            .lock("reports", Duration::from_secs(60))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(!expired.release().await.unwrap());
+        assert!(lock.release().await.unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_can_share_a_mutex() {
+        let cache: Arc<Cacheable> = Arc::new(MemoryCache::new(Duration::from_secs(60)));
+        let mutex = cache.mutex("migrate").retry(Duration::from_millis(5));
+        let guard = mutex.acquire(Duration::from_millis(40)).await.unwrap();
+
+        sleep(Duration::from_millis(100)).await;
+
+        assert!(mutex
+            .try_acquire(Duration::from_secs(1))
+            .await
+            .unwrap()
+            .is_none());
+
+        let waiting = spawn(async move {
+            let guard = mutex.acquire(Duration::from_secs(1)).await.unwrap();
+
+            guard.release().await.unwrap()
+        });
+
+        assert!(guard.release().await.unwrap());
+        assert!(waiting.await.unwrap());
+
+        let guard = cache
+            .mutex("migrate")
+            .acquire(Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        drop(guard);
+        sleep(Duration::from_millis(10)).await;
+
+        assert!(cache
+            .lock("migrate", Duration::from_secs(1))
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn it_can_detect_lock_keys() {
+        assert!(is_lock_key("valar:lock:reports"));
+        assert!(is_lock_key("billing:valar:lock:reports"));
+        assert!(!is_lock_key("lock:reports"));
+        assert!(!is_lock_key("users:valar:tag:reports"));
+    }
+}
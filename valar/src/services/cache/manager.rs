@@ -0,0 +1,277 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::services::cache::Error;
+use crate::services::cache::FileCache;
+use crate::services::cache::MemoryCache;
+use crate::services::cache::PrefixedCache;
+#[cfg(feature = "redis")]
+use crate::services::cache::RedisCache;
+use crate::services::Cacheable;
+
+/// The configuration of the cache of the application.
+///
+/// # Example
+///
+/// ```
+/// use valar::services::cache::manager::CacheConfig;
+/// use valar::services::cache::manager::Driver;
+///
+/// let config: CacheConfig =
+///     serde_json::from_str(r#"{ "driver": "file", "path": "storage/cache" }"#).unwrap();
+///
+/// assert_eq!(config.driver, Driver::File { path: "storage/cache".into() });
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CacheConfig {
+    /// The driver storing the entries.
+    #[serde(flatten)]
+    pub driver: Driver,
+
+    /// The prefix of every key, if any. The file driver
+    /// stores the entries of a prefix in a subdirectory of
+    /// that name instead, so it can be cleared on its own.
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+/// The driver of a cache, and its options.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "driver", rename_all = "lowercase")]
+pub enum Driver {
+    /// A [`MemoryCache`], purging expired entries every
+    /// given number of seconds.
+    Memory {
+        #[serde(default = "purge_interval")]
+        purge_interval: u64,
+        #[serde(default)]
+        max_entries: Option<usize>,
+        #[serde(default)]
+        max_bytes: Option<usize>,
+    },
+
+    /// A [`FileCache`] storing its entries in the given
+    /// directory.
+    File { path: PathBuf },
+
+    /// A [`RedisCache`] on the server at the given URL,
+    /// with the `redis` feature.
+    Redis { url: String },
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            driver: Driver::Memory {
+                purge_interval: purge_interval(),
+                max_entries: None,
+                max_bytes: None,
+            },
+            prefix: None,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Reads the configuration from the environment:
+    ///
+    /// - `VALAR_CACHE_DRIVER`: `memory` (the default),
+    ///   `file` or `redis`.
+    /// - `VALAR_CACHE_PATH`: the directory of the file
+    ///   driver, `storage/cache` by default.
+    /// - `VALAR_CACHE_URL`: the URL of the Redis server.
+    /// - `VALAR_CACHE_MAX_ENTRIES` and
+    ///   `VALAR_CACHE_MAX_BYTES`: the limits of the memory
+    ///   driver.
+    /// - `VALAR_CACHE_PREFIX`: the prefix of every key.
+    pub fn from_env() -> Result<Self, Error> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars<F>(var: F) -> Result<Self, Error>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let number = |name: &str| {
+            var(name)
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| Error::Config(format!("{name} is not a number")))
+                })
+                .transpose()
+        };
+
+        let driver = match var("VALAR_CACHE_DRIVER").as_deref() {
+            None | Some("memory") => Driver::Memory {
+                purge_interval: purge_interval(),
+                max_entries: number("VALAR_CACHE_MAX_ENTRIES")?,
+                max_bytes: number("VALAR_CACHE_MAX_BYTES")?,
+            },
+            Some("file") => Driver::File {
+                path: var("VALAR_CACHE_PATH")
+                    .unwrap_or_else(|| "storage/cache".to_string())
+                    .into(),
+            },
+            Some("redis") => Driver::Redis {
+                url: var("VALAR_CACHE_URL")
+                    .ok_or_else(|| Error::Config("VALAR_CACHE_URL is missing".to_string()))?,
+            },
+            Some(driver) => {
+                return Err(Error::Config(format!("unknown cache driver {driver}")));
+            }
+        };
+
+        Ok(Self {
+            driver,
+            prefix: var("VALAR_CACHE_PREFIX"),
+        })
+    }
+}
+
+/// Builds the cache of the application from its
+/// configuration, so the driver can change between
+/// environments without changing the code. It must be
+/// called within a Tokio runtime.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use valar::services::cache::manager::CacheConfig;
+/// use valar::services::cache::CacheManager;
+/// use valar::services::Cacheable;
+///
+/// pub struct App {
+///     pub cache: Arc<Cacheable>,
+/// }
+///
+/// # async fn example() -> Result<(), valar::services::cache::Error> {
+/// let app = App {
+///     cache: CacheManager::build(&CacheConfig::from_env()?)?,
+/// };
+/// # Ok(())
+/// # }
+/// ```
+pub struct CacheManager;
+
+impl CacheManager {
+    /// Builds the cache described by the configuration.
+    /// The Redis driver needs the `redis` feature.
+    pub fn build(config: &CacheConfig) -> Result<Arc<Cacheable>, Error> {
+        let cache: Arc<Cacheable> = match &config.driver {
+            Driver::Memory {
+                purge_interval,
+                max_entries,
+                max_bytes,
+            } => {
+                let mut cache = MemoryCache::new(Duration::from_secs(*purge_interval));
+
+                if let Some(max_entries) = max_entries {
+                    cache = cache.max_entries(*max_entries);
+                }
+
+                if let Some(max_bytes) = max_bytes {
+                    cache = cache.max_bytes(*max_bytes);
+                }
+
+                Arc::new(cache)
+            }
+            Driver::File { path } => {
+                let path = match &config.prefix {
+                    Some(prefix) => path.join(prefix),
+                    None => path.clone(),
+                };
+
+                return Ok(Arc::new(FileCache::new(path)));
+            }
+            #[cfg(feature = "redis")]
+            Driver::Redis { url } => Arc::new(RedisCache::new(url)?),
+            #[cfg(not(feature = "redis"))]
+            Driver::Redis { .. } => return Err(Error::Unsupported("the redis driver")),
+        };
+
+        Ok(match &config.prefix {
+            Some(prefix) => Arc::new(PrefixedCache::new(cache, prefix.clone())),
+            None => cache,
+        })
+    }
+
+    /// Builds the cache configured by the application.
+    pub fn from_app<A>(app: &A) -> Result<Arc<Cacheable>, Error>
+    where
+        A: Config<CacheConfig>,
+    {
+        Self::build(&app.config())
+    }
+}
+
+fn purge_interval() -> u64 {
+    60
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::services::cache::manager::CacheConfig;
+    use crate::services::cache::manager::Driver;
+    use crate::services::cache::CacheManager;
+    use crate::services::cache::Error;
+
+    #[test]
+    fn it_reads_the_environment() {
+        let vars = HashMap::from([
+            ("VALAR_CACHE_DRIVER", "memory"),
+            ("VALAR_CACHE_MAX_ENTRIES", "100"),
+            ("VALAR_CACHE_PREFIX", "billing"),
+        ]);
+        let config =
+            CacheConfig::from_vars(|name| vars.get(name).map(ToString::to_string)).unwrap();
+
+        assert_eq!(
+            config.driver,
+            Driver::Memory {
+                purge_interval: 60,
+                max_entries: Some(100),
+                max_bytes: None
+            }
+        );
+        assert_eq!(config.prefix.as_deref(), Some("billing"));
+        assert_eq!(
+            CacheConfig::from_vars(|_| None).unwrap(),
+            CacheConfig::default()
+        );
+        assert!(matches!(
+            CacheConfig::from_vars(|_| Some("memcached".to_string())),
+            Err(Error::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_builds_the_configured_cache() {
+        let config: CacheConfig =
+            serde_json::from_str(r#"{ "driver": "memory", "prefix": "billing" }"#).unwrap();
+        let cache = CacheManager::build(&config).unwrap();
+
+        cache
+            .forever("visits".to_string(), "1".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(cache.get("visits").await.unwrap().value(), "1");
+
+        let config: CacheConfig =
+            serde_json::from_str(r#"{ "driver": "redis", "url": "redis://localhost" }"#).unwrap();
+
+        assert_eq!(
+            CacheManager::build(&config).is_ok(),
+            cfg!(feature = "redis")
+        );
+    }
+}
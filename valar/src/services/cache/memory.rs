@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
@@ -7,6 +8,8 @@ use tokio::spawn;
 use tokio::time::interval;
 use tokio::time::Instant;
 
+use crate::services::cache::add;
+use crate::services::cache::locks::is_lock_key;
 use crate::services::cache::Error;
 use crate::services::cache::Insertable;
 use crate::services::cache::Retreived;
@@ -15,78 +18,207 @@ use crate::services::Cache;
 use crate::State;
 
 /// A memory cache implementation that has a passive and
-/// active expiration policy for each entry.
+/// active expiration policy for each entry. It can be
+/// bounded by a number of entries or bytes, evicting the
+/// least recently used entries when it is full. Held
+/// locks are never evicted, so the cache can go over its
+/// limits while they are held.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use valar::services::cache::MemoryCache;
+///
+/// # async fn example() {
+/// let cache = MemoryCache::new(Duration::from_secs(60))
+///     .max_entries(10_000)
+///     .max_bytes(64 * 1024 * 1024);
+/// # }
+/// ```
 pub struct MemoryCache {
-    state: Arc<State<HashMap<String, String>>>,
-    expirations: Arc<State<HashMap<String, Instant>>>,
+    state: Arc<State<Entries>>,
+    max_entries: Option<usize>,
+    max_bytes: Option<usize>,
 }
 
 impl MemoryCache {
     pub fn new(purge_interval: Duration) -> Self {
         let memory = Self {
             state: Arc::default(),
-            expirations: Arc::default(),
+            max_entries: None,
+            max_bytes: None,
         };
-        let state = memory.state.clone();
-        let expirations = memory.expirations.clone();
+        let state = Arc::downgrade(&memory.state);
 
-        // Passive elimination of expired entries.
+        // Passive elimination of expired entries, until
+        // the cache is dropped.
         spawn(async move {
             let mut interval = interval(purge_interval);
 
             loop {
                 interval.tick().await;
 
-                let mut expirations = expirations.get().await;
+                let Some(state) = state.upgrade() else {
+                    return;
+                };
 
-                let expired_keys: Vec<String> = expirations
-                    .iter()
-                    .filter(|(_, expiration)| Instant::now() > **expiration)
-                    .map(|(key, _)| key.clone())
-                    .collect();
-
-                let mut state = state.get().await;
-
-                for key in expired_keys {
-                    state.remove(&key);
-                    expirations.remove(&key);
-                }
-
-                drop(state);
-                drop(expirations);
+                state.get().await.purge();
             }
         });
 
         memory
     }
+
+    /// Sets the maximum number of entries of the cache.
+    #[must_use]
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+
+        self
+    }
+
+    /// Sets the maximum size of the cache, counted as the
+    /// bytes of the keys and values of its entries.
+    #[must_use]
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+
+        self
+    }
+
+    /// Evicts the least recently used entries until the
+    /// cache fits its limits, skipping locks.
+    fn evict(&self, entries: &mut Entries) {
+        let over = |entries: &Entries| {
+            self.max_entries
+                .is_some_and(|max| entries.entries.len() > max)
+                || self.max_bytes.is_some_and(|max| entries.bytes > max)
+        };
+
+        while over(entries) {
+            let Some(key) = entries
+                .recency
+                .values()
+                .find(|key| !is_lock_key(key))
+                .cloned()
+            else {
+                return;
+            };
+
+            entries.remove(&key);
+        }
+    }
 }
 
-#[async_trait]
-impl<App> Cache<App> for MemoryCache {
-    async fn get(&self, key: &str) -> Result<Value<Retreived>, Error> {
-        let mut state = self.state.get().await;
+/// An entry of the cache.
+struct Entry {
+    value: String,
+    expires_at: Option<Instant>,
+    used: u64,
+}
 
-        let value = state
-            .get(key)
-            .cloned()
-            .ok_or_else(|| Error::NotFound(key.to_string()))?;
+impl Entry {
+    fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| Instant::now() > expires_at)
+    }
+}
+
+/// The entries of the cache, with the order they were
+/// last used in.
+#[derive(Default)]
+struct Entries {
+    entries: HashMap<String, Entry>,
+    recency: BTreeMap<u64, String>,
+    uses: u64,
+    bytes: usize,
+}
 
-        let mut expirations = self.expirations.get().await;
+impl Entries {
+    /// Returns the entry of the key, marking it as used.
+    /// Expired entries are removed instead.
+    fn get(&mut self, key: &str) -> Result<&Entry, Error> {
+        match self.entries.get(key) {
+            None => return Err(Error::NotFound(key.to_string())),
+            Some(entry) if entry.is_expired() => {
+                self.remove(key);
 
-        if let Some(expiration) = expirations.get(key) {
-            if Instant::now() > *expiration {
-                state.remove(key);
-                expirations.remove(key);
                 return Err(Error::Expired(key.to_string()));
             }
+            Some(_) => {}
         }
 
-        Ok(Value::new(value))
+        let entry = self
+            .entries
+            .get_mut(key)
+            .ok_or_else(|| Error::NotFound(key.to_string()))?;
+
+        self.uses += 1;
+        self.recency.remove(&entry.used);
+        self.recency.insert(self.uses, key.to_string());
+        entry.used = self.uses;
+
+        Ok(entry)
+    }
+
+    fn insert(&mut self, key: String, value: String, expires_at: Option<Instant>) {
+        self.remove(&key);
+
+        self.uses += 1;
+        self.bytes += key.len() + value.len();
+        self.recency.insert(self.uses, key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                expires_at,
+                used: self.uses,
+            },
+        );
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.bytes -= key.len() + entry.value.len();
+            self.recency.remove(&entry.used);
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Removes the expired entries.
+    fn purge(&mut self) {
+        let expired: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.is_expired())
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in expired {
+            self.remove(&key);
+        }
+    }
+}
+
+#[async_trait]
+impl<App> Cache<App> for MemoryCache {
+    async fn get(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        let mut state = self.state.get().await;
+
+        Ok(Value::new(state.get(key)?.value.clone()))
     }
 
     async fn insert(&self, key: String, value: Value<Insertable>) -> Result<(), Error> {
         let mut state = self.state.get().await;
-        state.insert(key, value.into_value());
+        let expires_at = value.expires_at;
+
+        state.insert(key, value.into_value(), expires_at);
+        self.evict(&mut state);
 
         Ok(())
     }
@@ -106,4 +238,169 @@ impl<App> Cache<App> for MemoryCache {
 
         Ok(())
     }
+
+    async fn add(&self, key: String, value: Value<Insertable>) -> Result<bool, Error> {
+        let mut state = self.state.get().await;
+
+        if state.get(&key).is_ok() {
+            return Ok(false);
+        }
+
+        let expires_at = value.expires_at;
+
+        state.insert(key, value.into_value(), expires_at);
+        self.evict(&mut state);
+
+        Ok(true)
+    }
+
+    async fn pull(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        let mut state = self.state.get().await;
+        let value = state.get(key)?.value.clone();
+
+        state.remove(key);
+
+        Ok(Value::new(value))
+    }
+
+    async fn increment(&self, key: &str, by: i64) -> Result<i64, Error> {
+        let mut state = self.state.get().await;
+        let (value, expires_at) = match state.get(key) {
+            Ok(entry) => (add(key, Some(&entry.value), by)?, entry.expires_at),
+            Err(Error::NotFound(_) | Error::Expired(_)) => (add(key, None, by)?, None),
+            Err(error) => return Err(error),
+        };
+
+        state.insert(key.to_string(), value.to_string(), expires_at);
+        self.evict(&mut state);
+
+        Ok(value)
+    }
+
+    async fn delete_if(&self, key: &str, value: &str) -> Result<bool, Error> {
+        let mut state = self.state.get().await;
+
+        if !state.get(key).is_ok_and(|entry| entry.value == value) {
+            return Ok(false);
+        }
+
+        state.remove(key);
+
+        Ok(true)
+    }
+
+    async fn extend_if(&self, key: &str, value: &str, ttl: Duration) -> Result<bool, Error> {
+        let mut state = self.state.get().await;
+
+        if !state.get(key).is_ok_and(|entry| entry.value == value) {
+            return Ok(false);
+        }
+
+        if let Some(entry) = state.entries.get_mut(key) {
+            entry.expires_at = Some(Instant::now() + ttl);
+        }
+
+        Ok(true)
+    }
+
+    async fn clear_prefix(&self, prefix: &str) -> Result<(), Error> {
+        let mut state = self.state.get().await;
+        let keys: Vec<String> = state
+            .entries
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+
+        for key in keys {
+            state.remove(&key);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::services::cache::Error;
+    use crate::services::cache::Locks;
+    use crate::services::cache::MemoryCache;
+    use crate::services::cache::Value;
+    use crate::services::Cacheable;
+
+    #[tokio::test]
+    async fn it_evicts_the_least_recently_used_entries() {
+        let cache: &Cacheable = &MemoryCache::new(Duration::from_secs(60)).max_entries(2);
+
+        for key in ["a", "b"] {
+            cache
+                .insert(key.to_string(), Value::new(key.to_string()))
+                .await
+                .unwrap();
+        }
+
+        cache.get("a").await.unwrap();
+        cache
+            .insert("c".to_string(), Value::new("c".to_string()))
+            .await
+            .unwrap();
+
+        assert!(cache.get("a").await.is_ok());
+        assert!(matches!(cache.get("b").await, Err(Error::NotFound(_))));
+        assert!(cache.get("c").await.is_ok());
+
+        let cache: &Cacheable = &MemoryCache::new(Duration::from_secs(60)).max_bytes(10);
+
+        for key in ["a", "b", "c"] {
+            cache
+                .insert(key.to_string(), Value::new("1234".to_string()))
+                .await
+                .unwrap();
+        }
+
+        assert!(matches!(cache.get("a").await, Err(Error::NotFound(_))));
+        assert!(cache.get("b").await.is_ok());
+        assert!(cache.get("c").await.is_ok());
+
+        let cache: &Cacheable = &MemoryCache::new(Duration::from_secs(60)).max_entries(2);
+        let lock = cache
+            .lock("reports", Duration::from_secs(60))
+            .await
+            .unwrap()
+            .unwrap();
+
+        for key in ["a", "b", "c"] {
+            cache
+                .insert(key.to_string(), Value::new(key.to_string()))
+                .await
+                .unwrap();
+        }
+
+        assert!(matches!(cache.get("b").await, Err(Error::NotFound(_))));
+        assert!(cache.get("c").await.is_ok());
+        assert!(cache
+            .lock("reports", Duration::from_secs(60))
+            .await
+            .unwrap()
+            .is_none());
+        assert!(lock.release().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn it_expires_entries() {
+        let cache: &Cacheable = &MemoryCache::new(Duration::from_secs(60));
+
+        cache
+            .insert(
+                "a".to_string(),
+                Value::new("a".to_string()).expires_in(Duration::ZERO),
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(cache.get("a").await, Err(Error::Expired(_))));
+        assert!(matches!(cache.get("a").await, Err(Error::NotFound(_))));
+    }
 }
@@ -0,0 +1,177 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::services::cache::Default;
+use crate::services::cache::Error;
+use crate::services::cache::Insertable;
+use crate::services::cache::Retreived;
+use crate::services::cache::Value;
+use crate::services::Cache;
+
+/// A cache prefixing every key with `<prefix>:`, so
+/// several applications or tenants can share the same
+/// store without their keys colliding.
+///
+/// Clearing it only deletes the keys of its prefix, which
+/// needs a cache supporting [`Cache::clear_prefix`], like
+/// the memory and Redis ones.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use valar::services::cache::MemoryCache;
+/// use valar::services::cache::Namespaces;
+/// use valar::services::cache::PrefixedCache;
+///
+/// # async fn example() {
+/// let cache: PrefixedCache<_> =
+///     PrefixedCache::new(MemoryCache::new(Duration::from_secs(60)), "billing");
+/// let sessions = cache.namespace("sessions");
+/// # }
+/// ```
+pub struct PrefixedCache<C, Store = Default> {
+    cache: C,
+    prefix: String,
+    _store: PhantomData<fn() -> Store>,
+}
+
+impl<C, Store> PrefixedCache<C, Store> {
+    /// Creates a cache prefixing the keys of the given
+    /// one.
+    pub fn new<P>(cache: C, prefix: P) -> Self
+    where
+        P: Into<String>,
+    {
+        Self {
+            cache,
+            prefix: prefix.into(),
+            _store: PhantomData,
+        }
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}:{key}", self.prefix)
+    }
+}
+
+#[async_trait]
+impl<C, Store> Cache<Store> for PrefixedCache<C, Store>
+where
+    C: Cache<Store> + Send + Sync,
+{
+    async fn get(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        self.cache.get(&self.key(key)).await
+    }
+
+    async fn insert(&self, key: String, value: Value<Insertable>) -> Result<(), Error> {
+        self.cache.insert(self.key(&key), value).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Error> {
+        self.cache.delete(&self.key(key)).await
+    }
+
+    async fn clear(&self) -> Result<(), Error> {
+        self.cache.clear_prefix(&self.key("")).await
+    }
+
+    async fn add(&self, key: String, value: Value<Insertable>) -> Result<bool, Error> {
+        self.cache.add(self.key(&key), value).await
+    }
+
+    async fn pull(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        self.cache.pull(&self.key(key)).await
+    }
+
+    async fn increment(&self, key: &str, by: i64) -> Result<i64, Error> {
+        self.cache.increment(&self.key(key), by).await
+    }
+
+    async fn delete_if(&self, key: &str, value: &str) -> Result<bool, Error> {
+        self.cache.delete_if(&self.key(key), value).await
+    }
+
+    async fn extend_if(&self, key: &str, value: &str, ttl: Duration) -> Result<bool, Error> {
+        self.cache.extend_if(&self.key(key), value, ttl).await
+    }
+
+    async fn clear_prefix(&self, prefix: &str) -> Result<(), Error> {
+        self.cache.clear_prefix(&self.key(prefix)).await
+    }
+
+    fn identity(&self) -> usize {
+        self.cache.identity()
+    }
+}
+
+/// Creates namespaced views of a cache. It is
+/// implemented for every cache.
+pub trait Namespaces<Store = Default>: Cache<Store> {
+    /// Returns a view of the cache prefixing every key
+    /// with `<name>:`.
+    fn namespace<N>(&self, name: N) -> PrefixedCache<&Self, Store>
+    where
+        N: Into<String>,
+    {
+        PrefixedCache::new(self, name)
+    }
+}
+
+impl<Store, C> Namespaces<Store> for C where C: Cache<Store> + ?Sized {}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::services::cache::Error;
+    use crate::services::cache::MemoryCache;
+    use crate::services::cache::Namespaces;
+    use crate::services::cache::PrefixedCache;
+    use crate::services::cache::Value;
+    use crate::services::Cache;
+    use crate::services::Cacheable;
+
+    #[tokio::test]
+    async fn it_can_prefix_keys() {
+        let memory: &Cacheable = &MemoryCache::new(Duration::from_secs(60));
+        let billing: PrefixedCache<_> = PrefixedCache::new(memory, "billing");
+        let sessions = billing.namespace("sessions");
+
+        sessions
+            .insert("abc".to_string(), Value::new("Erik".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(sessions.get("abc").await.unwrap().value(), "Erik");
+        assert_eq!(
+            memory.get("billing:sessions:abc").await.unwrap().value(),
+            "Erik"
+        );
+        assert!(matches!(billing.get("abc").await, Err(Error::NotFound(_))));
+
+        memory
+            .insert("billing-old".to_string(), Value::new("1".to_string()))
+            .await
+            .unwrap();
+        billing
+            .insert("visits".to_string(), Value::new("2".to_string()))
+            .await
+            .unwrap();
+        sessions.clear().await.unwrap();
+
+        assert!(matches!(sessions.get("abc").await, Err(Error::NotFound(_))));
+        assert_eq!(billing.get("visits").await.unwrap().value(), "2");
+
+        billing.clear().await.unwrap();
+
+        assert!(matches!(
+            billing.get("visits").await,
+            Err(Error::NotFound(_))
+        ));
+        assert_eq!(memory.get("billing-old").await.unwrap().value(), "1");
+    }
+}
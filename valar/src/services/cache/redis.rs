@@ -0,0 +1,293 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::aio::ConnectionManagerConfig;
+use redis::Client;
+use redis::Cmd;
+use redis::FromRedisValue;
+use redis::RedisError;
+use redis::Script;
+use tokio::sync::OnceCell;
+use tokio::time::Instant;
+
+use crate::services::cache::Error;
+use crate::services::cache::Insertable;
+use crate::services::cache::Retreived;
+use crate::services::cache::Value;
+use crate::services::Cache;
+
+/// Increments the key, unless it holds something other
+/// than an integer.
+const INCREMENT: &str = r"
+local value = redis.call('GET', KEYS[1])
+if value and not string.match(value, '^%-?%d+$') then
+    return false
+end
+return redis.call('INCRBY', KEYS[1], ARGV[1])
+";
+
+/// Deletes the key if it holds the given value.
+const DELETE_IF: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+";
+
+/// Expires the key after the given milliseconds if it
+/// holds the given value.
+const EXTEND_IF: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+";
+
+/// A cache storing its entries in a Redis server, so they
+/// are shared by every instance of the application. The
+/// conditional writes and increments run on the server,
+/// so they are atomic across processes too.
+///
+/// The connection is opened on first use and reopened
+/// whenever it is lost.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::services::cache::RedisCache;
+///
+/// # fn example() -> Result<(), valar::services::cache::Error> {
+/// let cache = RedisCache::new("redis://localhost:6379/0")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RedisCache {
+    client: Client,
+    connection: OnceCell<ConnectionManager>,
+}
+
+impl RedisCache {
+    /// Creates a cache using the Redis server at the given
+    /// URL, without connecting to it yet.
+    pub fn new(url: &str) -> Result<Self, Error> {
+        Ok(Self {
+            client: Client::open(url).map_err(backend)?,
+            connection: OnceCell::new(),
+        })
+    }
+
+    /// Runs the command and returns its reply.
+    async fn query<T>(&self, command: &Cmd) -> Result<T, Error>
+    where
+        T: FromRedisValue,
+    {
+        let mut connection = self.connection().await?;
+
+        command.query_async(&mut connection).await.map_err(backend)
+    }
+
+    /// Runs the script with the given key and arguments.
+    async fn eval<T>(&self, script: &str, key: &str, arguments: &[&str]) -> Result<T, Error>
+    where
+        T: FromRedisValue,
+    {
+        let mut connection = self.connection().await?;
+        let script = Script::new(script);
+        let mut invocation = script.key(key);
+
+        for argument in arguments {
+            invocation.arg(*argument);
+        }
+
+        invocation
+            .invoke_async(&mut connection)
+            .await
+            .map_err(backend)
+    }
+
+    async fn connection(&self) -> Result<ConnectionManager, Error> {
+        self.connection
+            .get_or_try_init(|| {
+                // Fail fast instead of retrying, so callers
+                // can fall back while the server is down.
+                let config = ConnectionManagerConfig::new().set_number_of_retries(1);
+
+                self.client.get_connection_manager_with_config(config)
+            })
+            .await
+            .cloned()
+            .map_err(backend)
+    }
+}
+
+#[async_trait]
+impl<App> Cache<App> for RedisCache {
+    async fn get(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        let value: Option<String> = self.query(Cmd::new().arg("GET").arg(key)).await?;
+
+        value
+            .map(Value::new)
+            .ok_or_else(|| Error::NotFound(key.to_string()))
+    }
+
+    async fn insert(&self, key: String, value: Value<Insertable>) -> Result<(), Error> {
+        let mut command = Cmd::new();
+
+        command.arg("SET").arg(&key).arg(value.value());
+
+        if let Some(expires_at) = value.expires_at {
+            command.arg("PX").arg(milliseconds(expires_at));
+        }
+
+        self.query(&command).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Error> {
+        self.query(Cmd::new().arg("DEL").arg(key)).await
+    }
+
+    /// Deletes every key of the Redis database, including
+    /// the ones not written by the cache.
+    async fn clear(&self) -> Result<(), Error> {
+        self.query(Cmd::new().arg("FLUSHDB")).await
+    }
+
+    async fn add(&self, key: String, value: Value<Insertable>) -> Result<bool, Error> {
+        let mut command = Cmd::new();
+
+        command.arg("SET").arg(&key).arg(value.value()).arg("NX");
+
+        if let Some(expires_at) = value.expires_at {
+            command.arg("PX").arg(milliseconds(expires_at));
+        }
+
+        let stored: Option<String> = self.query(&command).await?;
+
+        Ok(stored.is_some())
+    }
+
+    async fn pull(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        let value: Option<String> = self.query(Cmd::new().arg("GETDEL").arg(key)).await?;
+
+        value
+            .map(Value::new)
+            .ok_or_else(|| Error::NotFound(key.to_string()))
+    }
+
+    async fn increment(&self, key: &str, by: i64) -> Result<i64, Error> {
+        let value: Option<i64> = self.eval(INCREMENT, key, &[&by.to_string()]).await?;
+
+        value.ok_or_else(|| Error::NotInteger(key.to_string()))
+    }
+
+    async fn delete_if(&self, key: &str, value: &str) -> Result<bool, Error> {
+        let deleted: i64 = self.eval(DELETE_IF, key, &[value]).await?;
+
+        Ok(deleted == 1)
+    }
+
+    async fn extend_if(&self, key: &str, value: &str, ttl: Duration) -> Result<bool, Error> {
+        let ttl = ttl.as_millis().max(1).to_string();
+        let extended: i64 = self.eval(EXTEND_IF, key, &[value, &ttl]).await?;
+
+        Ok(extended == 1)
+    }
+
+    async fn clear_prefix(&self, prefix: &str) -> Result<(), Error> {
+        let pattern = format!("{}*", escape(prefix));
+        let mut cursor = 0u64;
+
+        loop {
+            let (next, keys): (u64, Vec<String>) = self
+                .query(
+                    Cmd::new()
+                        .arg("SCAN")
+                        .arg(cursor)
+                        .arg("MATCH")
+                        .arg(&pattern)
+                        .arg("COUNT")
+                        .arg(1000),
+                )
+                .await?;
+
+            if !keys.is_empty() {
+                self.query::<()>(Cmd::new().arg("UNLINK").arg(keys)).await?;
+            }
+
+            if next == 0 {
+                return Ok(());
+            }
+
+            cursor = next;
+        }
+    }
+}
+
+/// Escapes the characters `SCAN` patterns treat as
+/// wildcards.
+fn escape(prefix: &str) -> String {
+    let mut escaped = String::with_capacity(prefix.len());
+
+    for character in prefix.chars() {
+        if matches!(character, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+
+        escaped.push(character);
+    }
+
+    escaped
+}
+
+/// Returns the milliseconds until the given instant.
+/// Redis rejects an expiration of zero, so an entry that
+/// already expired lives for a millisecond instead.
+fn milliseconds(expires_at: Instant) -> u64 {
+    let remaining = expires_at.saturating_duration_since(Instant::now());
+
+    (remaining.as_millis() as u64).max(1)
+}
+
+fn backend(error: RedisError) -> Error {
+    Error::Backend(Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use crate::services::cache::redis::escape;
+    use crate::services::cache::redis::milliseconds;
+    use crate::services::cache::Error;
+    use crate::services::cache::RedisCache;
+    use crate::services::Cacheable;
+
+    #[test]
+    fn it_converts_expirations_to_milliseconds() {
+        let expires_at = Instant::now() + Duration::from_secs(60);
+
+        assert!((59_000..=60_000).contains(&milliseconds(expires_at)));
+        assert_eq!(milliseconds(Instant::now() - Duration::from_secs(1)), 1);
+    }
+
+    #[test]
+    fn it_escapes_patterns() {
+        assert_eq!(escape("billing:"), "billing:");
+        assert_eq!(escape("a*b?[c]\\"), "a\\*b\\?\\[c\\]\\\\");
+    }
+
+    #[tokio::test]
+    async fn it_reports_unreachable_servers() {
+        assert!(matches!(
+            RedisCache::new("not a url"),
+            Err(Error::Backend(_))
+        ));
+
+        let cache: &Cacheable = &RedisCache::new("redis://127.0.0.1:1").unwrap();
+
+        assert!(matches!(cache.get("visits").await, Err(Error::Backend(_))));
+    }
+}
@@ -0,0 +1,233 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sha2::Digest;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::services::cache::Default;
+use crate::services::cache::Error;
+use crate::services::cache::Insertable;
+use crate::services::cache::Remember;
+use crate::services::cache::Retreived;
+use crate::services::cache::Value;
+use crate::services::Cache;
+
+/// Groups cache entries under tags, so they can be
+/// flushed together. It is implemented for every cache.
+///
+/// Each tag has a version stored under
+/// `valar:tag:<name>`, and tagged keys are namespaced by
+/// the versions of their tags. Flushing a tag changes its
+/// version, so the entries stored under it are no longer
+/// found and are left to expire.
+///
+/// # Example
+///
+/// ```no_run
+/// use valar::services::cache::Error;
+/// use valar::services::cache::Tags;
+/// use valar::services::cache::Value;
+/// use valar::services::Cache;
+/// use valar::services::Cacheable;
+///
+/// # async fn example(cache: &Cacheable) -> Result<(), Error> {
+/// cache
+///     .tags(["users", "tenant:5"])
+///     .insert("users:active".to_string(), Value::new("42".to_string()))
+///     .await?;
+///
+/// cache.flush_tag("users").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait Tags<Store = Default>: Cache<Store> + Sync {
+    /// Returns a cache storing its entries under the
+    /// given tags. Entries are only found through the
+    /// same tags.
+    fn tags<T, I>(&self, tags: I) -> TaggedCache<'_, Self, Store>
+    where
+        T: Into<String>,
+        I: IntoIterator<Item = T>,
+    {
+        TaggedCache {
+            cache: self,
+            tags: tags.into_iter().map(Into::into).collect(),
+            _store: PhantomData,
+        }
+    }
+
+    /// Discards every entry stored under the given tag.
+    async fn flush_tag(&self, tag: &str) -> Result<(), Error> {
+        self.insert(tag_key(tag), Value::new(Uuid::now_v7().to_string()))
+            .await
+    }
+}
+
+impl<Store, C> Tags<Store> for C where C: Cache<Store> + Sync + ?Sized {}
+
+/// A cache storing its entries under a set of tags.
+pub struct TaggedCache<'a, C: ?Sized, Store = Default> {
+    cache: &'a C,
+    tags: Vec<String>,
+    _store: PhantomData<fn() -> Store>,
+}
+
+impl<C, Store> TaggedCache<'_, C, Store>
+where
+    C: Cache<Store> + Sync + ?Sized,
+{
+    /// Returns the key namespaced by the current versions
+    /// of the tags.
+    async fn key(&self, key: &str) -> Result<String, Error> {
+        let mut hash = Sha256::new();
+
+        for tag in &self.tags {
+            hash.update(format!("{tag}={};", self.version(tag).await?));
+        }
+
+        let namespace: String = hash
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        Ok(format!("valar:tagged:{namespace}:{key}"))
+    }
+
+    /// Returns the version of the tag, starting one when
+    /// it has none. The version is only stored when the
+    /// tag still has none, so concurrent callers agree on
+    /// it.
+    async fn version(&self, tag: &str) -> Result<String, Error> {
+        let key = tag_key(tag);
+
+        if let Some(version) = self.cache.find(&key).await? {
+            return Ok(version);
+        }
+
+        let version = Uuid::now_v7().to_string();
+
+        if self
+            .cache
+            .add(key.clone(), Value::new(version.clone()))
+            .await?
+        {
+            return Ok(version);
+        }
+
+        self.cache.find(&key).await?.ok_or(Error::NotFound(key))
+    }
+}
+
+#[async_trait]
+impl<C, Store> Cache<Store> for TaggedCache<'_, C, Store>
+where
+    C: Cache<Store> + Sync + ?Sized,
+{
+    async fn get(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        self.cache.get(&self.key(key).await?).await
+    }
+
+    async fn insert(&self, key: String, value: Value<Insertable>) -> Result<(), Error> {
+        self.cache.insert(self.key(&key).await?, value).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Error> {
+        self.cache.delete(&self.key(key).await?).await
+    }
+
+    /// Flushes every tag of the cache.
+    async fn clear(&self) -> Result<(), Error> {
+        for tag in &self.tags {
+            self.cache.flush_tag(tag).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn add(&self, key: String, value: Value<Insertable>) -> Result<bool, Error> {
+        self.cache.add(self.key(&key).await?, value).await
+    }
+
+    async fn pull(&self, key: &str) -> Result<Value<Retreived>, Error> {
+        self.cache.pull(&self.key(key).await?).await
+    }
+
+    async fn increment(&self, key: &str, by: i64) -> Result<i64, Error> {
+        self.cache.increment(&self.key(key).await?, by).await
+    }
+
+    async fn delete_if(&self, key: &str, value: &str) -> Result<bool, Error> {
+        self.cache.delete_if(&self.key(key).await?, value).await
+    }
+
+    async fn extend_if(&self, key: &str, value: &str, ttl: Duration) -> Result<bool, Error> {
+        self.cache
+            .extend_if(&self.key(key).await?, value, ttl)
+            .await
+    }
+
+    fn identity(&self) -> usize {
+        self.cache.identity()
+    }
+}
+
+/// Returns the key holding the version of the tag.
+fn tag_key(tag: &str) -> String {
+    format!("valar:tag:{tag}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::services::cache::Error;
+    use crate::services::cache::MemoryCache;
+    use crate::services::cache::Tags;
+    use crate::services::cache::Value;
+    use crate::services::Cache;
+    use crate::services::Cacheable;
+
+    #[tokio::test]
+    async fn it_can_flush_tags() {
+        let cache: &Cacheable = &MemoryCache::new(Duration::from_secs(60));
+        let users = cache.tags(["users", "tenant:5"]);
+        let posts = cache.tags(["posts"]);
+
+        users
+            .insert("active".to_string(), Value::new("42".to_string()))
+            .await
+            .unwrap();
+        posts
+            .insert("active".to_string(), Value::new("7".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(users.get("active").await.unwrap().value(), "42");
+        assert_eq!(posts.get("active").await.unwrap().value(), "7");
+        assert!(matches!(
+            cache.tags(["users"]).get("active").await,
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(cache.get("active").await, Err(Error::NotFound(_))));
+
+        let version = cache.get("valar:tag:users").await.unwrap();
+
+        assert_eq!(
+            cache.tags(["users"]).version("users").await.unwrap(),
+            version.value()
+        );
+
+        cache.flush_tag("tenant:5").await.unwrap();
+
+        assert!(matches!(users.get("active").await, Err(Error::NotFound(_))));
+        assert_eq!(posts.get("active").await.unwrap().value(), "7");
+
+        posts.clear().await.unwrap();
+
+        assert!(matches!(posts.get("active").await, Err(Error::NotFound(_))));
+    }
+}
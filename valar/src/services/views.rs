@@ -0,0 +1,113 @@
+use std::path::PathBuf;
+
+use minijinja::path_loader;
+use minijinja::Environment;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Template(#[from] minijinja::Error),
+}
+
+/// The views service renders templates stored in a
+/// directory. Templates are referenced by their path
+/// relative to that directory and without the extension,
+/// so `users/show` renders `users/show.html`.
+///
+/// HTML templates are automatically escaped.
+pub struct Views {
+    environment: Environment<'static>,
+    extension: String,
+}
+
+impl Views {
+    /// Creates a new views service that loads the templates
+    /// from the given directory.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use valar::services::Views;
+    ///
+    /// let views = Views::new("resources/views");
+    /// ```
+    pub fn new<P>(directory: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        let mut environment = Environment::new();
+        environment.set_loader(path_loader(directory.into()));
+
+        Self {
+            environment,
+            extension: "html".to_string(),
+        }
+    }
+
+    /// Changes the extension of the template files.
+    pub fn extension<E>(mut self, extension: E) -> Self
+    where
+        E: Into<String>,
+    {
+        self.extension = extension.into();
+
+        self
+    }
+
+    /// Returns a mutable reference to the underlying
+    /// template environment to register filters, functions
+    /// or globals.
+    pub fn environment_mut(&mut self) -> &mut Environment<'static> {
+        &mut self.environment
+    }
+
+    /// Renders the given template using the given context.
+    pub fn render<C>(&self, name: &str, context: C) -> Result<String, Error>
+    where
+        C: Serialize,
+    {
+        let name = format!("{name}.{}", self.extension);
+        let template = self.environment.get_template(&name)?;
+
+        Ok(template.render(context)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::fs::create_dir_all;
+    use std::fs::remove_dir_all;
+    use std::fs::write;
+
+    use serde::Serialize;
+
+    use crate::services::Views;
+
+    #[derive(Serialize)]
+    struct User {
+        name: &'static str,
+    }
+
+    #[test]
+    fn it_can_render_views() {
+        let directory = temp_dir().join("valar-it-can-render-views");
+        create_dir_all(directory.join("users")).unwrap();
+        write(
+            directory.join("users/show.html"),
+            "<h1>{{ user.name }}</h1>",
+        )
+        .unwrap();
+
+        let views = Views::new(&directory);
+        let user = User { name: "<Erik>" };
+        let html = views.render("users/show", minijinja::context! { user });
+
+        remove_dir_all(&directory).unwrap();
+
+        assert_eq!(html.unwrap(), "<h1>&lt;Erik&gt;</h1>");
+        assert!(views.render("users/missing", ()).is_err());
+    }
+}
@@ -38,7 +38,7 @@ impl<T> State<T> {
     ///     assert_eq!(*value, 0);
     /// });
     /// ```
-    pub async fn get(&self) -> MutexGuard<T> {
+    pub async fn get(&self) -> MutexGuard<'_, T> {
         self.0.lock().await
     }
 
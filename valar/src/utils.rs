@@ -21,6 +21,19 @@ impl TruncatableToFit for &str {
     }
 }
 
+/// Compares the given strings in constant time, so the
+/// time taken doesn't leak how much of a secret matched.
+pub(crate) fn constant_time_eq(left: &str, right: &str) -> bool {
+    let (left, right) = (left.as_bytes(), right.as_bytes());
+
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0, |difference, (left, right)| difference | (left ^ right))
+            == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;